@@ -0,0 +1,259 @@
+/*
+ * Timings for the completion hot path. Run with `cargo bench`, or `cargo bench --features
+ * parallel` for the last one.
+ *
+ * Completion of the group axioms, on the original symbols and on interned ones.
+ *
+ * Normalization of group words with the completed rules: by repeated rewrite_step, which copies
+ * each subword it tries and allocates a new word for every step, then building reducts in a
+ * TermArena, then again with a cache of the normal forms already found.
+ *
+ * Normalization in a system of 300 rules with the same root, beside the cost of indexing them,
+ * which every call paid before Trs kept its DiscriminationTree.
+ *
+ * The Knuth-Bendix ordering on deep group words, walked once, beside the comparison it replaced,
+ * which counted the variables of both words again at each level; then on ground words.
+ *
+ * Completion of the ring axioms simplifying the pending equations after each rule, on one thread
+ * and, with the parallel feature, on several.
+ */
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use knuth_bendix::kbo::{self, KboParams};
+use knuth_bendix::prod::{inv, one, var, Prod, Word};
+use knuth_bendix::{
+    knuth_bendix, knuth_bendix_interned, theories, Completion, DiscriminationTree, Op, Rule,
+    Signature, TermArena, Trs, Var, WordRef,
+};
+
+// Words with a lot of rewriting to do: nested products of elements and their inverses.
+fn workload() -> Vec<Word> {
+    let (a, b, c) = (var("a"), var("b"), var("c"));
+    let mut words = Vec::new();
+    let mut w = &a * &b;
+    for k in 0..12 {
+        let u = if k % 2 == 0 { &c } else { &a };
+        w = inv(&(&w * u)) * (inv(&inv(&w)) * (one() * u));
+        words.push(w.clone());
+    }
+    words
+}
+
+// A system of many rules with the same root, f(c_i, x) → x for 300 constants c_i, and a word
+// f(d, f(d, ... f(c_0, e))) where only the innermost f can be rewritten, so that finding the rule
+// that applies dominates.
+fn many_rules() -> (Trs<String, knuth_bendix::DynOp>, knuth_bendix::DynWord) {
+    let mut sig = Signature::new();
+    sig.declare("f", 2, 1).unwrap();
+    sig.declare("d", 0, 1).unwrap();
+    sig.declare("e", 0, 1).unwrap();
+    let x = sig.var("x");
+    let mut rules = Vec::new();
+    for i in 0..300 {
+        let c = format!("c{}", i);
+        sig.declare(&c, 0, 1).unwrap();
+        rules.push(Rule {
+            left: sig
+                .app("f", &[sig.app(&c, &[]).unwrap(), x.clone()])
+                .unwrap(),
+            right: x.clone(),
+        });
+    }
+    let (c0, d, e) = (sig.app("c0", &[]), sig.app("d", &[]), sig.app("e", &[]));
+    let d = d.unwrap();
+    let mut w = sig.app("f", &[c0.unwrap(), e.unwrap()]).unwrap();
+    for _ in 0..300 {
+        w = sig.app("f", &[d.clone(), w]).unwrap();
+    }
+    (Trs::new(rules), w)
+}
+
+// A deep group word, inv(... inv(inv(a * b * a) * b) ...), and the same word with b * a at the
+// bottom, which only differ there, so the comparison goes all the way down.
+fn deep_pair() -> (Word, Word) {
+    let (a, b, c) = (var("a"), var("b"), var("c"));
+    let (mut s, mut t) = (&a * &b, &b * &a);
+    for k in 0..300 {
+        let u = [&a, &b, &c][k % 3];
+        s = inv(&(&s * u));
+        t = inv(&(&t * u));
+    }
+    (s, t)
+}
+
+// deep_pair with the identity for each variable, so both words are ground.
+fn deep_ground_pair() -> (Word, Word) {
+    let e = one();
+    let (mut s, mut t) = (&e * inv(&e), inv(&e) * &e);
+    for _ in 0..300 {
+        s = inv(&(&s * &e));
+        t = inv(&(&t * &e));
+    }
+    (s, t)
+}
+
+// The comparison before it was linear, counting each word's weight and variables at every level.
+fn compare_by_counting(
+    s: WordRef<'_, String, Prod>,
+    t: WordRef<'_, String, Prod>,
+    params: &KboParams<Prod>,
+) -> Option<Ordering> {
+    let counts = |w: WordRef<'_, String, Prod>| {
+        let mut counts = BTreeMap::new();
+        for v in w.syms.iter().filter_map(|s| s.var()) {
+            *counts.entry(v.clone()).or_insert(0) += 1;
+        }
+        counts
+    };
+    let (sc, tc): (BTreeMap<String, usize>, _) = (counts(s), counts(t));
+    let covers = |a: &BTreeMap<String, usize>, b: &BTreeMap<String, usize>| {
+        b.iter().all(|(v, n)| a.get(v).is_some_and(|m| m >= n))
+    };
+    match params.weight(s).cmp(&params.weight(t)) {
+        Ordering::Greater => covers(&sc, &tc).then_some(Ordering::Greater),
+        Ordering::Less => covers(&tc, &sc).then_some(Ordering::Less),
+        Ordering::Equal if sc != tc => None,
+        Ordering::Equal => match (&s.syms[0], &t.syms[0]) {
+            (Op(_), Var(_)) => Some(Ordering::Greater),
+            (Var(_), Op(_)) => Some(Ordering::Less),
+            (Var(_), Var(_)) => Some(Ordering::Equal),
+            (Op(f), Op(g)) if f == g => {
+                for (a, b) in s.subwords().zip(t.subwords()) {
+                    match compare_by_counting(a, b, params) {
+                        Some(Ordering::Equal) => {}
+                        o => return o,
+                    }
+                }
+                Some(Ordering::Equal)
+            }
+            (Op(f), Op(g)) => Some(params.precedence(f, g)),
+        },
+    }
+}
+
+fn time<F: FnMut()>(name: &str, iters: u32, mut f: F) -> Duration {
+    f();
+    let start = Instant::now();
+    for _ in 0..iters {
+        f();
+    }
+    let per = start.elapsed() / iters;
+    println!("{:<32} {:>12.3?}/iter", name, per);
+    per
+}
+
+fn main() {
+    time("knuth_bendix groups", 20, || {
+        black_box(knuth_bendix(&theories::group::axioms()));
+    });
+    time("knuth_bendix_interned groups", 20, || {
+        black_box(knuth_bendix_interned(&theories::group::axioms()));
+    });
+
+    let trs = Trs::new(knuth_bendix(&theories::group::axioms()).unwrap());
+    let words = workload();
+    let stepwise = time("normalize by rewrite_step", 20, || {
+        for w in &words {
+            let mut w = w.clone();
+            while let Some(v) = trs.rewrite_step(&w) {
+                w = v;
+            }
+            black_box(w);
+        }
+    });
+    let mut arena = TermArena::new();
+    let arena_time = time("normalize_in with TermArena", 20, || {
+        for w in &words {
+            black_box(trs.normalize_in(w, &mut arena));
+        }
+    });
+    println!(
+        "arena speedup: {:.1}x",
+        stepwise.as_secs_f64() / arena_time.as_secs_f64()
+    );
+    // Normalizing the same words again, which a cache holds from the first time.
+    let cached = trs.clone().with_cache();
+    for w in &words {
+        cached.normalize(w);
+    }
+    let cached = time("normalize again with a cache", 20, || {
+        for w in &words {
+            black_box(cached.normalize(w));
+        }
+    });
+    println!(
+        "cache speedup: {:.1}x",
+        arena_time.as_secs_f64() / cached.as_secs_f64()
+    );
+
+    let (trs, w) = many_rules();
+    let mut arena = TermArena::new();
+    time("normalize_in with 300 rules", 20, || {
+        black_box(trs.normalize_in(&w, &mut arena));
+    });
+    // What each call cost when the index was built every time.
+    time("index 300 rules", 20, || {
+        black_box(DiscriminationTree::new(trs.rules()));
+    });
+
+    let (s, t) = deep_pair();
+    let params = KboParams::default();
+    assert_eq!(
+        kbo::compare(&s, &s, &params),
+        compare_by_counting(s.view(), s.view(), &params)
+    );
+    let counting = time("kbo by counting, deep words", 20, || {
+        black_box(compare_by_counting(s.view(), t.view(), &params));
+        black_box(compare_by_counting(s.view(), s.view(), &params));
+    });
+    let linear = time("kbo in one pass, deep words", 20, || {
+        black_box(kbo::compare(&s, &t, &params));
+        black_box(kbo::compare(&s, &s, &params));
+    });
+    println!(
+        "one pass speedup: {:.1}x",
+        counting.as_secs_f64() / linear.as_secs_f64()
+    );
+    // Ground words take the ground path, which walks them together too.
+    let (s, t) = deep_ground_pair();
+    time("kbo deep ground words", 20, || {
+        black_box(kbo::compare(&s, &t, &params));
+        black_box(kbo::compare(&s, &s, &params));
+    });
+    let e = one();
+    let small = [
+        e.clone(),
+        inv(&e),
+        &e * &e,
+        inv(&e) * &e,
+        &e * inv(&e),
+        (&e * &e) * &e,
+        &e * (&e * &e),
+    ];
+    time("kbo small ground words", 2000, || {
+        for a in &small {
+            for b in &small {
+                black_box(kbo::compare(a, b, &params));
+            }
+        }
+    });
+
+    let ring = || {
+        let mut c = Completion::new(&theories::ring::axioms());
+        c.max_steps = Some(400);
+        c.simplify_pending = true;
+        c
+    };
+    time("simplify_pending ring, 400 steps", 5, || {
+        black_box(ring().run());
+    });
+    #[cfg(feature = "parallel")]
+    time("simplify_pending_in_parallel ring", 5, || {
+        let mut c = ring();
+        c.simplify_pending_in_parallel();
+        black_box(c.run());
+    });
+}
@@ -0,0 +1,35 @@
+/*
+ * The standard timings of bench::run_standard, compared with those of the previous run, which are
+ * kept in target/standard-bench.txt. Whatever got more than 10% slower or counted differently is
+ * listed after the timings. Run with `cargo bench --features bench --bench standard`; timings of
+ * a few milliseconds vary by more than that from run to run, so rerun before believing one.
+ * This is a plain harness, not Criterion; see the notes in knuth_bendix::bench.
+ */
+use std::path::Path;
+
+use knuth_bendix::bench::{run_standard, Report};
+
+const BASELINE: &str = "target/standard-bench.txt";
+
+fn main() {
+    let report = run_standard();
+    print!("{}", report);
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(BASELINE);
+    if let Ok(text) = std::fs::read_to_string(&path) {
+        match Report::load(&text) {
+            Ok(baseline) => {
+                let differences = report.compare(&baseline, 0.1);
+                if differences.is_empty() {
+                    println!("no changes from the previous run");
+                }
+                for d in differences {
+                    println!("changed: {}", d);
+                }
+            }
+            Err(e) => eprintln!("{}: {}", path.display(), e),
+        }
+    }
+    if let Err(e) = std::fs::write(&path, report.save()) {
+        eprintln!("{}: {}", path.display(), e);
+    }
+}
@@ -0,0 +1,207 @@
+/*
+ * #[derive(Operator)] for fieldless enums, implementing knuth_bendix::Operator and Display:
+ *
+ *     #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Operator)]
+ *     #[op(min_weight = 1)]
+ *     enum Sum {
+ *         #[op(arity = 0, weight = 1, name = "0")]
+ *         Zero,
+ *         #[op(arity = 2, weight = 1, name = " + ", commutative, associative, infix, precedence = 1)]
+ *         Add,
+ *         #[op(arity = 1, weight = 0, name = "−", prefix)]
+ *         Negate,
+ *     }
+ *
+ * A variant's arity defaults to 0, its weight to 1, and its name to the variant's own name, and
+ * min_weight defaults to 1. It's written as a function unless it's marked prefix, postfix, or
+ * infix, with a precedence and assoc = left or right for infix. This has no dependencies, so it
+ * reads the enum straight from the token stream.
+ */
+use proc_macro::{Delimiter, Group, TokenStream, TokenTree};
+
+#[derive(Default)]
+struct Variant {
+    ident: String,
+    arity: Option<String>,
+    weight: Option<String>,
+    name: Option<String>,
+    commutative: bool,
+    associative: bool,
+    fixity: Option<&'static str>,
+    precedence: Option<String>,
+    assoc: Option<&'static str>,
+}
+
+#[proc_macro_derive(Operator, attributes(op))]
+pub fn derive_operator(input: TokenStream) -> TokenStream {
+    let code = match expand(input) {
+        Ok(code) => code,
+        Err(e) => format!("compile_error!({:?});", e),
+    };
+    code.parse().expect("generated code doesn't parse")
+}
+
+// The key = value pairs of an attribute, with no value for a bare flag.
+type Args = Vec<(String, Option<String>)>;
+
+// The arguments of an #[op(...)] attribute, or None if the bracketed attribute is some other
+// attribute.
+fn op_args(attr: &Group) -> Result<Option<Args>, String> {
+    let tokens: Vec<TokenTree> = attr.stream().into_iter().collect();
+    let args = match tokens.as_slice() {
+        [TokenTree::Ident(i), TokenTree::Group(g)]
+            if i.to_string() == "op" && g.delimiter() == Delimiter::Parenthesis =>
+        {
+            g.stream()
+        }
+        _ => return Ok(None),
+    };
+    let mut out = Vec::new();
+    let tokens: Vec<TokenTree> = args.into_iter().collect();
+    for arg in tokens.split(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == ',')) {
+        match arg {
+            [] => {}
+            [TokenTree::Ident(k)] => out.push((k.to_string(), None)),
+            [TokenTree::Ident(k), TokenTree::Punct(eq), v] if eq.as_char() == '=' => {
+                out.push((k.to_string(), Some(v.to_string())))
+            }
+            _ => return Err("expected key = value in #[op(...)]".to_string()),
+        }
+    }
+    Ok(Some(out))
+}
+
+fn number(key: &str, value: Option<String>) -> Result<String, String> {
+    match value {
+        Some(v) if v.parse::<u64>().is_ok() => Ok(v),
+        _ => Err(format!("#[op({} = ...)] must be a number", key)),
+    }
+}
+
+fn expand(input: TokenStream) -> Result<String, String> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut min_weight = "1".to_string();
+    let mut i = 0;
+    // Outer attributes, then the visibility, up to `enum Name { ... }`.
+    while i < tokens.len() {
+        match &tokens[i] {
+            TokenTree::Punct(p) if p.as_char() == '#' => {
+                if let Some(TokenTree::Group(g)) = tokens.get(i + 1) {
+                    for (k, v) in op_args(g)?.unwrap_or_default() {
+                        match k.as_str() {
+                            "min_weight" => min_weight = number(&k, v)?,
+                            _ => return Err(format!("unknown enum attribute #[op({})]", k)),
+                        }
+                    }
+                }
+                i += 2;
+            }
+            TokenTree::Ident(id) if id.to_string() == "enum" => break,
+            TokenTree::Ident(id) if id.to_string() == "struct" || id.to_string() == "union" => {
+                return Err("#[derive(Operator)] only supports enums".to_string())
+            }
+            _ => i += 1,
+        }
+    }
+    let name = match tokens.get(i + 1) {
+        Some(TokenTree::Ident(n)) => n.to_string(),
+        _ => return Err("#[derive(Operator)] only supports enums".to_string()),
+    };
+    let body = match tokens.get(i + 2) {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => g.stream(),
+        _ => return Err("#[derive(Operator)] doesn't support generic enums".to_string()),
+    };
+
+    let mut variants = Vec::new();
+    let mut v = Variant::default();
+    let body: Vec<TokenTree> = body.into_iter().collect();
+    let mut j = 0;
+    while j < body.len() {
+        match &body[j] {
+            TokenTree::Punct(p) if p.as_char() == '#' => {
+                if let Some(TokenTree::Group(g)) = body.get(j + 1) {
+                    for (k, val) in op_args(g)?.unwrap_or_default() {
+                        match k.as_str() {
+                            "arity" => v.arity = Some(number(&k, val)?),
+                            "weight" => v.weight = Some(number(&k, val)?),
+                            "name" => v.name = val,
+                            "commutative" => v.commutative = true,
+                            "associative" => v.associative = true,
+                            "prefix" => v.fixity = Some("Prefix"),
+                            "postfix" => v.fixity = Some("Postfix"),
+                            "infix" => v.fixity = Some("Infix"),
+                            "precedence" => v.precedence = Some(number(&k, val)?),
+                            "assoc" => {
+                                v.assoc = Some(match val.as_deref() {
+                                    Some("left") => "Left",
+                                    Some("right") => "Right",
+                                    _ => {
+                                        return Err(
+                                            "#[op(assoc = ...)] must be left or right".to_string()
+                                        )
+                                    }
+                                })
+                            }
+                            _ => return Err(format!("unknown variant attribute #[op({})]", k)),
+                        }
+                    }
+                }
+                j += 2;
+            }
+            TokenTree::Ident(id) if v.ident.is_empty() => {
+                v.ident = id.to_string();
+                j += 1;
+            }
+            TokenTree::Group(_) => {
+                return Err(format!(
+                    "#[derive(Operator)] only supports variants without fields, not {}",
+                    v.ident
+                ))
+            }
+            TokenTree::Punct(p) if p.as_char() == ',' => {
+                variants.push(std::mem::take(&mut v));
+                j += 1;
+            }
+            // An explicit discriminant.
+            _ => j += 1,
+        }
+    }
+    if !v.ident.is_empty() {
+        variants.push(v);
+    }
+
+    let arms = |f: &dyn Fn(&Variant) -> String| -> String {
+        variants
+            .iter()
+            .map(|v| format!("{}::{} => {},", name, v.ident, f(v)))
+            .collect()
+    };
+    let arity = arms(&|v| v.arity.clone().unwrap_or_else(|| "0".to_string()));
+    let weight = arms(&|v| v.weight.clone().unwrap_or_else(|| "1".to_string()));
+    let commutative = arms(&|v| v.commutative.to_string());
+    let associative = arms(&|v| v.associative.to_string());
+    let notation = arms(&|v| {
+        format!(
+            "::knuth_bendix::Notation {{ fixity: ::knuth_bendix::Fixity::{}, precedence: {}, assoc: ::knuth_bendix::Assoc::{} }}",
+            v.fixity.unwrap_or("Function"),
+            v.precedence.as_deref().unwrap_or("0"),
+            v.assoc.unwrap_or("None"),
+        )
+    });
+    let display = arms(&|v| v.name.clone().unwrap_or_else(|| format!("{:?}", v.ident)));
+    Ok(format!(
+        "impl ::knuth_bendix::Operator for {name} {{
+            fn min_weight() -> u64 {{ {min_weight} }}
+            fn arity(&self) -> usize {{ match self {{ {arity} }} }}
+            fn weight(&self) -> u64 {{ match self {{ {weight} }} }}
+            fn is_commutative(&self) -> bool {{ match self {{ {commutative} }} }}
+            fn is_associative(&self) -> bool {{ match self {{ {associative} }} }}
+            fn notation(&self) -> ::knuth_bendix::Notation {{ match self {{ {notation} }} }}
+        }}
+        impl ::std::fmt::Display for {name} {{
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {{
+                f.write_str(match self {{ {display} }})
+            }}
+        }}"
+    ))
+}
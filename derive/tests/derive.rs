@@ -0,0 +1,77 @@
+use knuth_bendix::{knuth_bendix, Axiom, Operator, Word};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Operator)]
+#[op(min_weight = 1)]
+enum Group {
+    #[op(name = "e")]
+    One,
+    /// Multiplication.
+    #[op(arity = 2, weight = 0, name = " * ", infix, precedence = 1, assoc = left)]
+    Mul,
+    #[op(arity = 1, weight = 0, name = "⁻¹", postfix)]
+    Inv,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Operator)]
+enum Lattice {
+    #[op(arity = 2, commutative)]
+    Meet,
+    #[op(arity = 2, commutative, associative)]
+    Join,
+}
+
+#[test]
+fn derived() {
+    assert_eq!(Group::min_weight(), 1);
+    assert_eq!(
+        [Group::One, Group::Mul, Group::Inv].map(|f| (f.arity(), f.weight())),
+        [(0, 1), (2, 0), (1, 0)]
+    );
+    assert_eq!(Group::Inv.to_string(), "⁻¹");
+    assert!(!Group::Mul.is_commutative());
+    assert!(Lattice::Join.is_commutative());
+    assert!(Lattice::Join.is_associative() && !Lattice::Meet.is_associative());
+    assert_eq!(Lattice::Meet.to_string(), "Meet");
+
+    type W = Word<String, Group>;
+    let (x, y) = (W::var("x"), W::var("y"));
+    let xy = W::op(Group::Mul, &[x.clone(), y.clone()]);
+    let w = W::op(
+        Group::Mul,
+        &[W::op(Group::Inv, std::slice::from_ref(&xy)), xy],
+    );
+    assert_eq!(w.to_string(), "(x * y)⁻¹ * (x * y)");
+    let w = W::op(Group::Mul, &[w, x.clone()]);
+    assert_eq!(w.to_string(), "(x * y)⁻¹ * (x * y) * x");
+
+    // Without a notation, operators are written as functions.
+    type L = Word<String, Lattice>;
+    let (a, b) = (L::var("a"), L::var("b"));
+    let join = L::op(Lattice::Join, &[a.clone(), b]);
+    assert_eq!(
+        L::op(Lattice::Meet, &[a, join]).to_string(),
+        "Meet(a, Join(a, b))"
+    );
+}
+
+#[test]
+fn completes_groups() {
+    type W = Word<String, Group>;
+    let (x, y, z) = (W::var("x"), W::var("y"), W::var("z"));
+    let mul = |a: &W, b: &W| W::op(Group::Mul, &[a.clone(), b.clone()]);
+    let axioms = [
+        Axiom {
+            left: mul(&W::op(Group::One, &[]), &x),
+            right: x.clone(),
+        },
+        Axiom {
+            left: mul(&W::op(Group::Inv, std::slice::from_ref(&x)), &x),
+            right: W::op(Group::One, &[]),
+        },
+        Axiom {
+            left: mul(&mul(&x, &y), &z),
+            right: mul(&x, &mul(&y, &z)),
+        },
+    ];
+    assert_eq!(knuth_bendix(&axioms).unwrap().len(), 10);
+}
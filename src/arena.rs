@@ -0,0 +1,155 @@
+use std::ops::Range;
+
+use crate::flatterm::Flatterm;
+use crate::word::{match_syms, Operator, Symbol, Variable, Word, WordRef};
+
+/*
+ * Scratch space for building words without a fresh allocation for each one. Words built in the
+ * arena are runs of one shared buffer, named by ArenaWord handles and borrowed back as slices,
+ * and matching a pattern records each variable's binding as a range of the word matched rather
+ * than as a copy of it. Clearing the arena keeps its capacity, so a loop that clears it after
+ * every use, like normalization, soon stops allocating at all.
+ *
+ * There are no scopes for discarding only the words built since some point: normalization clears
+ * the arena after every rewrite step, and completion builds critical pairs and pending equations
+ * as owned words, so a pair found trivial is already freed when it's dropped. Scopes would pay
+ * off once overlaps are built in the arena.
+ */
+#[derive(Clone, Debug)]
+pub struct TermArena<V: Variable, O: Operator> {
+    syms: Vec<Symbol<V, O>>,
+    // The bindings of the last successful match_at, as ranges of the word matched.
+    bindings: Vec<(V, Range<usize>)>,
+}
+
+/// A word built in a TermArena, valid until the arena is cleared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArenaWord {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl<V: Variable, O: Operator> Default for TermArena<V, O> {
+    fn default() -> Self {
+        TermArena {
+            syms: Vec::new(),
+            bindings: Vec::new(),
+        }
+    }
+}
+
+impl<V: Variable, O: Operator> TermArena<V, O> {
+    pub fn new() -> TermArena<V, O> {
+        TermArena::default()
+    }
+
+    /// Forget every word built so far, keeping the space they used.
+    pub fn clear(&mut self) {
+        self.syms.clear();
+        self.bindings.clear();
+    }
+
+    pub fn get(&self, w: ArenaWord) -> &[Symbol<V, O>] {
+        &self.syms[w.start..w.end]
+    }
+
+    pub fn to_word(&self, w: ArenaWord) -> Word<V, O> {
+        Word {
+            syms: self.get(w).to_vec(),
+        }
+    }
+
+    pub fn alloc(&mut self, syms: &[Symbol<V, O>]) -> ArenaWord {
+        let start = self.syms.len();
+        self.syms.extend_from_slice(syms);
+        ArenaWord {
+            start,
+            end: self.syms.len(),
+        }
+    }
+
+    /*
+     * Match pattern against the word w one way, as Word::unify does, and remember the bindings
+     * for the next subst. Nothing is copied: each binding is a range of w.
+     */
+    pub fn match_at(&mut self, pattern: &[Symbol<V, O>], w: &[Symbol<V, O>]) -> bool {
+        let word = WordRef { syms: w };
+        self.match_from(pattern, w, 0, |j| word.subword_end(j))
+    }
+
+    /*
+     * match_at against the subword of w starting at i, skipping each bound subword by its offset
+     * instead of counting its arities. Bindings are ranges of w.syms, so pass those to subst.
+     */
+    pub fn match_flat(&mut self, pattern: &[Symbol<V, O>], w: &Flatterm<V, O>, i: usize) -> bool {
+        self.match_from(pattern, &w.syms, i, |j| w.end(j))
+    }
+
+    // Match pattern against syms at i with match_syms, keeping each binding as a range of syms.
+    fn match_from(
+        &mut self,
+        pattern: &[Symbol<V, O>],
+        syms: &[Symbol<V, O>],
+        i: usize,
+        end: impl Fn(usize) -> usize,
+    ) -> bool {
+        let bindings = &mut self.bindings;
+        bindings.clear();
+        match_syms(pattern, syms, i, end, |v, r| {
+            match bindings.iter().find(|(u, _)| u == v) {
+                Some((_, old)) => syms[old.clone()] == syms[r],
+                None => {
+                    bindings.push((v.clone(), r));
+                    true
+                }
+            }
+        })
+    }
+
+    /// Build w with each variable bound by the last match_at replaced by its binding in matched,
+    /// which must be the word that was matched.
+    pub fn subst(&mut self, w: &[Symbol<V, O>], matched: &[Symbol<V, O>]) -> ArenaWord {
+        let start = self.syms.len();
+        for s in w {
+            match s
+                .var()
+                .and_then(|v| self.bindings.iter().find(|(u, _)| u == v))
+            {
+                Some((_, r)) => self.syms.extend_from_slice(&matched[r.clone()]),
+                None => self.syms.push(s.clone()),
+            }
+        }
+        ArenaWord {
+            start,
+            end: self.syms.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::{inv, one, var};
+
+    #[test]
+    fn match_and_subst() {
+        let (x, y, a, b) = (var("x"), var("y"), var("a"), var("b"));
+        let mut arena = TermArena::new();
+        let pattern = &x * (&y * &x);
+        let w = inv(&a) * (&b * inv(&a));
+        assert!(arena.match_at(&pattern.syms, &w.syms));
+        let r = arena.subst(&(&y * inv(&x)).syms, &w.syms);
+        assert_eq!(arena.to_word(r), &b * inv(&inv(&a)));
+        assert_eq!(
+            arena.to_word(r),
+            (&y * inv(&x)).subst(&pattern.unify(&w).unwrap())
+        );
+
+        assert!(!arena.match_at(&pattern.syms, &(&a * (&b * &b)).syms));
+        assert!(!arena.match_at(&inv(&x).syms, &one().syms));
+        let c = arena.alloc(&one().syms);
+        assert_eq!(arena.get(c), one().syms.as_slice());
+        arena.clear();
+        assert!(arena.syms.is_empty());
+    }
+}
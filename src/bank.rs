@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use crate::word::{Operator, Variable, Word};
+
+/// A word stored in a TermBank. Words interned in the same bank are equal exactly when they are
+/// the same allocation, so equality and hashing only look at the pointer.
+pub struct Interned<V: Variable, O: Operator>(Rc<Word<V, O>>);
+
+impl<V: Variable, O: Operator> Interned<V, O> {
+    pub fn word(&self) -> &Word<V, O> {
+        &self.0
+    }
+}
+
+impl<V: Variable, O: Operator> Clone for Interned<V, O> {
+    fn clone(&self) -> Self {
+        Interned(Rc::clone(&self.0))
+    }
+}
+
+impl<V: Variable, O: Operator> fmt::Debug for Interned<V, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<V: Variable, O: Operator> PartialEq for Interned<V, O> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<V: Variable, O: Operator> Eq for Interned<V, O> {}
+
+impl<V: Variable, O: Operator> Hash for Interned<V, O> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Rc::as_ptr(&self.0).hash(state);
+    }
+}
+
+/// A hash-consing store of words: interning a word equal to one already in the bank returns the
+/// stored copy, so equal words share storage.
+#[derive(Debug)]
+pub struct TermBank<V: Variable + Hash, O: Operator + Hash> {
+    words: HashSet<Rc<Word<V, O>>>,
+}
+
+impl<V: Variable + Hash, O: Operator + Hash> Default for TermBank<V, O> {
+    fn default() -> Self {
+        TermBank {
+            words: HashSet::new(),
+        }
+    }
+}
+
+impl<V: Variable + Hash, O: Operator + Hash> TermBank<V, O> {
+    pub fn new() -> TermBank<V, O> {
+        TermBank::default()
+    }
+
+    pub fn intern(&mut self, w: Word<V, O>) -> Interned<V, O> {
+        if let Some(rc) = self.words.get(&w) {
+            return Interned(Rc::clone(rc));
+        }
+        let rc = Rc::new(w);
+        self.words.insert(Rc::clone(&rc));
+        Interned(rc)
+    }
+
+    /// The number of distinct words in the bank.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::prod::{inv, one, var, Word};
+
+    #[test]
+    fn hash_consing() {
+        let (x, y) = (var("x"), var("y"));
+        let mut bank = TermBank::new();
+        let a = bank.intern(&x * inv(&y));
+        let b = bank.intern(&x * inv(&y));
+        let c = bank.intern(&y * inv(&x));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(bank.len(), 2);
+        assert_eq!(a.word(), &(&x * inv(&y)));
+
+        let mut counts: HashMap<Word, usize> = HashMap::new();
+        for w in [&x * &y, &x * &y, one()] {
+            *counts.entry(w).or_insert(0) += 1;
+        }
+        assert_eq!(counts[&(&x * &y)], 2);
+    }
+}
@@ -0,0 +1,353 @@
+/*
+ * A standard set of timings on the built-in theories, for comparing one build with another, such
+ * as a fork with the original or a change with the commit before it. run_standard completes the
+ * group axioms, and the ring and Boolean algebra axioms for a fixed number of steps, since neither
+ * finishes; normalizes words in the completed group system and with the rules of a bounded
+ * Boolean run; and finds critical terms and applies substitutions, which both copy words. Each
+ * measurement counts something too, such as the critical pairs completion found, so a change in
+ * behavior shows up beside a change in speed.
+ *
+ * A Report saves to and loads from a plain text baseline, and compare lists what got slower or
+ * counted differently. benches/standard.rs runs it against the previous run's results:
+ *
+ *     cargo bench --features bench --bench standard
+ *
+ * This isn't a Criterion suite. measure is a plain timing loop, with no statistics beyond the
+ * mean, because criterion can't be a dependency here yet. Once it can, benches/standard.rs should
+ * run these measurements as Criterion benchmarks. run_standard and Report would stay as they
+ * are, a thin library API for forks that compare builds without Criterion.
+ */
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::completion::Completion;
+use crate::prod;
+use crate::relation::Axiom;
+use crate::theories::{boolean, group, ring};
+use crate::trs::Trs;
+use crate::word::{critical_term, Operator, Variable, Word};
+
+/// One timing: the time an iteration took on average, and what it counted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Measurement {
+    pub name: String,
+    pub time: Duration,
+    pub count: usize,
+    /// What count counts, such as critical pairs.
+    pub unit: String,
+}
+
+impl Measurement {
+    /// How many of the unit an iteration gets through per second.
+    pub fn per_second(&self) -> f64 {
+        self.count as f64 / self.time.as_secs_f64()
+    }
+}
+
+impl Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = format!("{:<36}", self.name);
+        let time = format!("{:>12.3?}", self.time);
+        write!(
+            f,
+            "{} {}/iter  {} {}, {:.0}/s",
+            name,
+            time,
+            self.count,
+            self.unit,
+            self.per_second()
+        )
+    }
+}
+
+/// A measurement that got slower or counted something different between two reports.
+#[derive(Clone, Debug)]
+pub struct Difference {
+    pub before: Measurement,
+    pub after: Measurement,
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let change = self.after.time.as_secs_f64() / self.before.time.as_secs_f64() - 1.0;
+        write!(
+            f,
+            "{}: {:.3?} → {:.3?} ({:+.0}%)",
+            self.after.name,
+            self.before.time,
+            self.after.time,
+            change * 100.0
+        )?;
+        if self.before.count != self.after.count {
+            write!(
+                f,
+                ", {} → {} {}",
+                self.before.count, self.after.count, self.after.unit
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub measurements: Vec<Measurement>,
+}
+
+impl Report {
+    /// The report as a baseline file: a line for each measurement of its name, nanoseconds,
+    /// count, and unit, separated by tabs.
+    pub fn save(&self) -> String {
+        let mut out = String::from("# knuth-bendix standard bench\n");
+        for m in &self.measurements {
+            out += &format!(
+                "{}\t{}\t{}\t{}\n",
+                m.name,
+                m.time.as_nanos(),
+                m.count,
+                m.unit
+            );
+        }
+        out
+    }
+
+    pub fn load(text: &str) -> Result<Report, String> {
+        let mut measurements = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = || {
+                format!(
+                    "line {}: expected name, nanoseconds, count, and unit",
+                    i + 1
+                )
+            };
+            let [name, nanos, count, unit] = line.split('\t').collect::<Vec<_>>()[..] else {
+                return Err(err());
+            };
+            measurements.push(Measurement {
+                name: name.to_string(),
+                time: Duration::from_nanos(nanos.parse().map_err(|_| err())?),
+                count: count.parse().map_err(|_| err())?,
+                unit: unit.to_string(),
+            });
+        }
+        Ok(Report { measurements })
+    }
+
+    /*
+     * The measurements that are slower than in baseline by more than tolerance, as a fraction
+     * such as 0.1 for 10%, or whose counts differ. Measurements only one of the reports has are
+     * left out.
+     */
+    pub fn compare(&self, baseline: &Report, tolerance: f64) -> Vec<Difference> {
+        let before: BTreeMap<&str, &Measurement> = baseline
+            .measurements
+            .iter()
+            .map(|m| (m.name.as_str(), m))
+            .collect();
+        self.measurements
+            .iter()
+            .filter_map(|after| {
+                let before = before.get(after.name.as_str())?;
+                let slower =
+                    after.time.as_secs_f64() > before.time.as_secs_f64() * (1.0 + tolerance);
+                (slower || after.count != before.count).then(|| Difference {
+                    before: (*before).clone(),
+                    after: after.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for m in &self.measurements {
+            writeln!(f, "{}", m)?;
+        }
+        Ok(())
+    }
+}
+
+// The least time to spend on each measurement, running it as many times as that takes.
+const MIN_TIME: Duration = Duration::from_millis(200);
+
+// Time f, which returns what it counted, once to warm up and then repeatedly, doubling the
+// number of iterations until they take at least MIN_TIME.
+fn measure(name: &str, unit: &str, mut f: impl FnMut() -> usize) -> Measurement {
+    let count = f();
+    let mut iters: u32 = 1;
+    loop {
+        let start = Instant::now();
+        for _ in 0..iters {
+            black_box(f());
+        }
+        let elapsed = start.elapsed();
+        if elapsed >= MIN_TIME {
+            return Measurement {
+                name: name.to_string(),
+                time: elapsed / iters,
+                count,
+                unit: unit.to_string(),
+            };
+        }
+        iters *= 2;
+    }
+}
+
+// The critical pairs of a completion run of at most max_steps steps.
+fn complete<V: Variable, O: Operator>(axioms: &[Axiom<V, O>], max_steps: usize) -> usize {
+    let mut c = Completion::new(axioms);
+    c.max_steps = Some(max_steps);
+    c.run();
+    c.stats().critical_pairs
+}
+
+// Normalize words, returning how many symbols they had.
+fn normalize<V: Variable, O: Operator>(trs: &Trs<V, O>, words: &[Word<V, O>]) -> usize {
+    for w in words {
+        black_box(trs.normalize(w));
+    }
+    words.iter().map(|w| w.size()).sum()
+}
+
+// Nested group words with a lot of rewriting to do.
+fn group_words() -> Vec<prod::Word> {
+    use prod::{inv, one, var};
+    let (a, b, c) = (var("a"), var("b"), var("c"));
+    let mut words = Vec::new();
+    let mut w = &a * &b;
+    for k in 0..10 {
+        let u = if k % 2 == 0 { &c } else { &a };
+        w = inv(&(&w * u)) * (inv(&inv(&w)) * (one() * u));
+        words.push(w.clone());
+    }
+    words
+}
+
+// Boolean formulas of growing depth, each the negated disjunction of the one before.
+fn boolean_words() -> Vec<boolean::Word> {
+    let mut words = vec!["x ∧ (y ⊕ 1)".parse::<boolean::Word>().unwrap()];
+    for k in 0..6 {
+        let prev = words.last().unwrap().to_string();
+        let v = ["x", "y", "z"][k % 3];
+        words.push(format!("¬({}) ∨ {}", prev, v).parse().unwrap());
+    }
+    words
+}
+
+/// Run the standard measurements, which take a few seconds.
+pub fn run_standard() -> Report {
+    let mut measurements = vec![
+        measure("complete group", "critical pairs", || {
+            complete(&group::axioms(), usize::MAX)
+        }),
+        measure("complete ring, 400 steps", "critical pairs", || {
+            complete(&ring::axioms(), 400)
+        }),
+        measure("complete boolean, 400 steps", "critical pairs", || {
+            complete(&boolean::axioms(), 400)
+        }),
+    ];
+
+    let groups = Trs::new(group::rules());
+    let words = group_words();
+    measurements.push(measure("normalize group words", "symbols", || {
+        normalize(&groups, &words)
+    }));
+    // The rules of a bounded run, as completion never finishes.
+    let mut c = Completion::new(&boolean::axioms());
+    c.max_steps = Some(200);
+    c.run();
+    let booleans = c.trs();
+    let formulas = boolean_words();
+    measurements.push(measure("normalize boolean formulas", "symbols", || {
+        normalize(&booleans, &formulas)
+    }));
+
+    // Every pair of left sides of the group rules, and the ring rules a bounded run finds.
+    let mut c = Completion::new(&ring::axioms());
+    c.max_steps = Some(200);
+    c.run();
+    let ring_rules = c.rules().to_vec();
+    measurements.push(measure(
+        "critical_term, group rules",
+        "critical terms",
+        || {
+            let rules = group::rules();
+            let lefts: Vec<_> = rules.iter().map(|r| &r.left).collect();
+            pairs(&lefts, |s, t| critical_term(s, t).is_some())
+        },
+    ));
+    measurements.push(measure(
+        "critical_term, ring rules",
+        "critical terms",
+        || {
+            let lefts: Vec<_> = ring_rules.iter().map(|r| &r.left).collect();
+            pairs(&lefts, |s, t| critical_term(s, t).is_some())
+        },
+    ));
+
+    // Substituting each of the smaller group words for the variables of each other one.
+    measurements.push(measure("subst, group words", "symbols", || {
+        let mut size = 0;
+        for s in &words[..6] {
+            for t in &words[..6] {
+                let vars = s.vars().into_iter().map(|v| (v, t.clone())).collect();
+                size += s.subst(&vars).size();
+            }
+        }
+        size
+    }));
+    Report { measurements }
+}
+
+// How many ordered pairs of words f holds for.
+fn pairs<V: Variable, O: Operator>(
+    words: &[&Word<V, O>],
+    f: impl Fn(&Word<V, O>, &Word<V, O>) -> bool,
+) -> usize {
+    words
+        .iter()
+        .flat_map(|s| words.iter().map(move |t| (s, t)))
+        .filter(|(s, t)| f(s, t))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn baseline() {
+        let m = |name: &str, millis, count| Measurement {
+            name: name.to_string(),
+            time: Duration::from_millis(millis),
+            count,
+            unit: "critical pairs".to_string(),
+        };
+        let before = Report {
+            measurements: vec![m("a", 10, 5), m("b", 10, 5), m("c", 10, 5)],
+        };
+        assert_eq!(Report::load(&before.save()).unwrap(), before);
+        assert!(Report::load("a\t1\t2").is_err());
+
+        let after = Report {
+            measurements: vec![m("a", 10, 5), m("b", 20, 5), m("c", 9, 6), m("d", 1, 1)],
+        };
+        let names: Vec<_> = after
+            .compare(&before, 0.1)
+            .into_iter()
+            .map(|d| d.after.name)
+            .collect();
+        assert_eq!(names, ["b", "c"]);
+        assert_eq!(
+            after.compare(&before, 0.1)[0].to_string(),
+            "b: 10.000ms → 20.000ms (+100%)"
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use knuth_bendix::{tpdb, Completion, DynOp, Equation, Outcome, Spec};
+
+use knuth_bendix::json::{obj, Json};
+
+// A duration such as 10s, 500ms, or 2m; a bare number is in seconds.
+fn parse_duration(s: &str) -> Option<Duration> {
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (n, unit) = s.split_at(split);
+    let n: f64 = n.parse().ok()?;
+    let secs = match unit {
+        "" | "s" => n,
+        "ms" => n / 1000.0,
+        "m" => n * 60.0,
+        _ => return None,
+    };
+    Some(Duration::from_secs_f64(secs))
+}
+
+// The axioms in a problem file: a spec, or a .trs system whose rules are taken as equations.
+fn axioms(path: &Path) -> Result<Vec<Equation<String, DynOp>>, String> {
+    if path.extension().is_some_and(|e| e == "trs") {
+        let (_, trs) = tpdb::from_file(path).map_err(|e| e.to_string())?;
+        Ok(trs.into_rules().into_iter().map(Equation::from).collect())
+    } else {
+        Ok(Spec::from_file(path).map_err(|e| e.to_string())?.axioms)
+    }
+}
+
+struct Row {
+    name: String,
+    status: &'static str,
+    detail: String,
+    rules: Option<usize>,
+    time: Duration,
+}
+
+fn bench(path: &Path, timeout: Duration) -> Row {
+    let name = path.file_name().unwrap().to_string_lossy().into_owned();
+    let start = Instant::now();
+    let axioms = match axioms(path) {
+        Ok(axioms) => axioms,
+        Err(e) => {
+            return Row {
+                name,
+                status: "error",
+                detail: e,
+                rules: None,
+                time: start.elapsed(),
+            }
+        }
+    };
+    let mut completion = Completion::new(&axioms);
+    let outcome = completion.run_for(timeout);
+    let time = start.elapsed();
+    let (status, detail) = match outcome {
+        Some(Outcome::Convergent) => ("completed", String::new()),
+        Some(Outcome::Trivial(_)) => ("completed", "trivial".to_string()),
+        Some(Outcome::Unorientable(eqs)) => ("failed", format!("{} unorientable", eqs.len())),
+        Some(Outcome::Limit) => ("failed", "limit".to_string()),
+        None => ("timeout", String::new()),
+    };
+    Row {
+        name,
+        status,
+        detail,
+        rules: Some(completion.rules().len()),
+        time,
+    }
+}
+
+/*
+ * kb bench DIR [--timeout 10s]: run completion on every .trs and spec (.eq or .spec) file in DIR,
+ * in order of name, each for at most the timeout, and print a table of how each ended, the rules
+ * it had, and the wall time. The timeout is only checked between completion steps.
+ */
+pub fn run(args: &[String], json: bool) -> Result<ExitCode, String> {
+    let mut timeout = Duration::from_secs(10);
+    let mut dirs = Vec::new();
+    let mut args = args.iter();
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--timeout" => {
+                timeout = args
+                    .next()
+                    .and_then(|t| parse_duration(t))
+                    .ok_or("--timeout needs a duration such as 10s")?;
+            }
+            s if s.starts_with("--") => return Err(format!("unknown option {}", s)),
+            _ => dirs.push(a),
+        }
+    }
+    let [dir] = dirs[..] else {
+        return Err(crate::USAGE.to_string());
+    };
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .map_err(|e| format!("{}: {}", dir, e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|e| e == "trs" || e == "eq" || e == "spec")
+        })
+        .collect();
+    paths.sort();
+
+    let width = paths
+        .iter()
+        .map(|p| p.file_name().unwrap().len())
+        .max()
+        .unwrap_or(0)
+        .max(7);
+    if !json {
+        println!(
+            "{:width$}  {:9}  {:>5}  {:>9}  note",
+            "problem", "result", "rules", "time"
+        );
+    }
+    let mut rows = Vec::new();
+    for p in &paths {
+        let row = bench(p, timeout);
+        if !json {
+            let rules = row.rules.map_or("-".to_string(), |n| n.to_string());
+            let line = format!(
+                "{:width$}  {:9}  {:>5}  {:>8.3}s  {}",
+                row.name,
+                row.status,
+                rules,
+                row.time.as_secs_f64(),
+                row.detail
+            );
+            println!("{}", line.trim_end());
+        }
+        rows.push(row);
+    }
+    let count = |s: &str| rows.iter().filter(|r| r.status == s).count();
+    let (completed, failed, timed_out) = (count("completed"), count("failed"), count("timeout"));
+    if json {
+        let results = rows.iter().map(|r| {
+            obj([
+                ("problem", r.name.as_str().into()),
+                ("result", r.status.into()),
+                ("note", r.detail.as_str().into()),
+                ("rules", r.rules.into()),
+                ("millis", (r.time.as_millis() as u64).into()),
+            ])
+        });
+        println!(
+            "{}",
+            obj([
+                ("completed", completed.into()),
+                ("failed", failed.into()),
+                ("timeout", timed_out.into()),
+                ("results", Json::Arr(results.collect())),
+            ])
+        );
+    } else {
+        println!(
+            "{} completed, {} failed, {} timed out, of {}",
+            completed,
+            failed,
+            timed_out,
+            rows.len()
+        );
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,176 @@
+use std::process::ExitCode;
+
+use knuth_bendix::dependency_pairs::{prove_termination, DpResult};
+use knuth_bendix::kbo::KboParams;
+use knuth_bendix::{tpdb, DynOp, LpoParams, PolyOrder};
+
+use knuth_bendix::json::{obj, Json};
+
+// Interpretations are searched with coefficients up to this.
+const MAX_COEFF: u64 = 2;
+
+/*
+ * kb check RULES.trs: report what can be shown about the system. Termination is tried with a
+ * Knuth-Bendix ordering whose precedence is searched for, then with a lexicographic path
+ * ordering whose precedence is searched for, then with linear polynomial interpretations, then
+ * with dependency pairs. Critical pairs are only checked for joinability once the system is
+ * known to terminate, since the check may not finish otherwise; without termination, a system
+ * Trs::analysis finds orthogonal is still confluent. Exits with status 2 unless the system is
+ * shown to be convergent.
+ */
+pub fn run(args: &[String], json: bool) -> Result<ExitCode, String> {
+    let [path] = args else {
+        return Err(crate::USAGE.to_string());
+    };
+    let (_, trs) = tpdb::from_file(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut out: Vec<(&'static str, Json)> = vec![("n_rules", trs.rules().len().into())];
+    if !json {
+        println!("{} rules", trs.rules().len());
+    }
+
+    let analysis = trs.analysis();
+    let nonlinear = analysis.left_nonlinear.first().copied();
+    out.push(("left_linear", analysis.is_left_linear().into()));
+    out.push(("nonlinear_rule", nonlinear.into()));
+    if !json {
+        match nonlinear {
+            None => println!("left-linear: yes"),
+            Some(i) => println!(
+                "left-linear: no, rule {} ({}) repeats a variable",
+                i,
+                trs.rules()[i]
+            ),
+        }
+    }
+
+    let cps = trs.critical_pairs();
+    out.push(("critical_pairs", cps.len().into()));
+    if !json {
+        println!("critical pairs: {}", cps.len());
+    }
+
+    // How termination was shown, as a method name and a description.
+    let names = |precedence: &[DynOp]| {
+        let names: Vec<String> = precedence
+            .iter()
+            .map(|f| f.to_string().trim().to_string())
+            .collect();
+        names.join(" < ")
+    };
+    let termination = if let Some(params) = KboParams::search(trs.rules()) {
+        Some((
+            "kbo",
+            format!("KBO with precedence {}", names(&params.precedence)),
+        ))
+    } else if let Some(params) = LpoParams::search(trs.rules()) {
+        Some((
+            "lpo",
+            format!("LPO with precedence {}", names(&params.precedence)),
+        ))
+    } else if let Some(order) = PolyOrder::search(trs.rules(), MAX_COEFF) {
+        let interpretations: Vec<String> = order
+            .coeffs
+            .iter()
+            .map(|(f, cs)| {
+                let terms: Vec<String> = std::iter::once(cs[0].to_string())
+                    .chain(
+                        cs[1..]
+                            .iter()
+                            .enumerate()
+                            .map(|(i, c)| format!("{}x{}", c, i + 1)),
+                    )
+                    .collect();
+                format!("[{}] = {}", f.to_string().trim(), terms.join(" + "))
+            })
+            .collect();
+        Some((
+            "polynomial",
+            format!("the linear interpretation {}", interpretations.join(", ")),
+        ))
+    } else if let DpResult::Terminating(proof) = prove_termination(&trs) {
+        Some((
+            "dependency_pairs",
+            format!("dependency pairs in {} steps", proof.len()),
+        ))
+    } else {
+        None
+    };
+    let terminates = termination.is_some();
+    // A rule whose right side has a variable its left side lacks never terminates.
+    let fresh = trs
+        .rules()
+        .iter()
+        .find(|r| !r.right.vars().is_subset(&r.left.vars()));
+    out.push((
+        "termination",
+        obj([
+            ("proved", terminates.into()),
+            ("method", termination.as_ref().map(|t| t.0).into()),
+            ("proof", termination.as_ref().map(|t| t.1.clone()).into()),
+            ("counterexample", fresh.map(|r| r.to_string()).into()),
+        ]),
+    ));
+    if !json {
+        match (&termination, fresh) {
+            (Some((_, how)), _) => println!("termination: yes, by {}", how),
+            (None, Some(r)) => println!("termination: no, {} introduces a variable", r),
+            (None, None) => println!("termination: unknown"),
+        }
+    }
+
+    let confluent = if terminates {
+        let counterexamples = trs.counterexamples();
+        let json_counterexamples = counterexamples.iter().map(|c| {
+            obj([
+                ("peak", c.pair.peak.to_string().into()),
+                ("left", c.pair.left.to_string().into()),
+                ("right", c.pair.right.to_string().into()),
+                ("outer_rule", c.outer.into()),
+                ("inner_rule", c.inner.into()),
+                ("left_normal", c.left_normal.to_string().into()),
+                ("right_normal", c.right_normal.to_string().into()),
+            ])
+        });
+        out.push(("joinable", counterexamples.is_empty().into()));
+        out.push(("counterexamples", Json::Arr(json_counterexamples.collect())));
+        if !json {
+            match counterexamples.first() {
+                None => println!("joinable: all"),
+                Some(c) => println!(
+                    "joinable: no, {} of them, such as {}",
+                    counterexamples.len(),
+                    c
+                ),
+            }
+        }
+        counterexamples.is_empty()
+    } else {
+        out.push(("joinable", Json::Null));
+        out.push(("counterexamples", Json::Arr(Vec::new())));
+        if !json {
+            println!("joinable: not checked");
+        }
+        false
+    };
+
+    let (status, summary) = match (terminates, confluent) {
+        (true, true) => ("convergent", "convergent: confluent and terminating"),
+        (true, false) => ("not_confluent", "not confluent"),
+        (false, _) if analysis.is_orthogonal() => (
+            "orthogonal",
+            "confluent, being orthogonal, but not known to terminate",
+        ),
+        (false, _) => ("unknown", "confluence unknown"),
+    };
+    out.push(("status", status.into()));
+    if json {
+        println!("{}", obj(out));
+    } else {
+        println!("{}", summary);
+    }
+    Ok(if terminates && confluent {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(2)
+    })
+}
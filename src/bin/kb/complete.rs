@@ -0,0 +1,212 @@
+use std::process::ExitCode;
+
+use knuth_bendix::json::{self, Json};
+use knuth_bendix::{
+    detect_properties, tpdb, Completion, Detected, DynOp, Equation, Outcome, Property, Spec, Stats,
+    Trs,
+};
+
+use crate::orient::{self, Choice, Orientations};
+
+// The rules a completion run ended with, and how it ended.
+pub struct Summary {
+    pub outcome: Outcome<String, DynOp>,
+    pub trs: Trs<String, DynOp>,
+    pub stats: Stats,
+    pub max_steps: usize,
+    /// The operators the axioms make commutative, associative, or idempotent, with the axiom.
+    pub properties: Vec<(Detected<DynOp>, Equation<String, DynOp>)>,
+    /// Whether an orientation was asked for and the answer was to abort.
+    pub aborted: bool,
+}
+
+impl Summary {
+    pub fn run(spec: &Spec, max_steps: usize) -> Summary {
+        Summary::run_with(spec, max_steps, &mut |_| Choice::Postpone)
+    }
+
+    /*
+     * Run completion, and each time it stops with equations it can't orient, ask choose about
+     * them in turn until one is oriented, then carry on. The run ends if every one is postponed
+     * or one is aborted.
+     */
+    pub fn run_with(
+        spec: &Spec,
+        max_steps: usize,
+        choose: &mut dyn FnMut(&Equation<String, DynOp>) -> Choice,
+    ) -> Summary {
+        let mut completion = Completion::new(&spec.axioms);
+        completion.max_steps = Some(max_steps);
+        let mut aborted = false;
+        let outcome = loop {
+            let outcome = completion.run();
+            let Outcome::Unorientable(eqs) = &outcome else {
+                break outcome;
+            };
+            let mut oriented = false;
+            for (i, eq) in eqs.iter().enumerate() {
+                match choose(eq) {
+                    Choice::Postpone => continue,
+                    Choice::Abort => aborted = true,
+                    c => {
+                        completion.orient_unorientable(i, c == Choice::LeftToRight);
+                        oriented = true;
+                    }
+                }
+                break;
+            }
+            if !oriented {
+                break outcome;
+            }
+        };
+        Summary {
+            outcome,
+            trs: completion.trs(),
+            stats: completion.stats().clone(),
+            max_steps,
+            properties: detect_properties(&spec.axioms)
+                .into_iter()
+                .map(|d| {
+                    let axiom = spec.axioms[d.axiom].clone();
+                    (d, axiom)
+                })
+                .collect(),
+            aborted,
+        }
+    }
+
+    pub fn print(&self) {
+        let n = self.trs.rules().len();
+        if self.aborted {
+            println!("aborted");
+        }
+        match &self.outcome {
+            Outcome::Convergent => println!("convergent, {} rules", n),
+            Outcome::Unorientable(eqs) => {
+                println!("stopped with {} unorientable equations:", eqs.len());
+                for eq in eqs {
+                    println!("  {}", eq);
+                }
+            }
+            Outcome::Trivial(eq) => println!("trivial: the axioms imply {}", eq),
+            Outcome::Limit => println!("stopped after {} steps with {} rules", self.max_steps, n),
+        }
+        if !matches!(self.outcome, Outcome::Convergent) {
+            for (d, axiom) in &self.properties {
+                if d.property == Property::Commutative {
+                    println!(
+                        "note: {} makes {} commutative, which no ordering can orient, and \
+                         completion modulo commutativity isn't supported",
+                        axiom, d.op.name
+                    );
+                }
+            }
+        }
+        let s = &self.stats;
+        println!(
+            "{} equations, {} critical pairs, {} pruned, {} rules added, {} deleted, \
+             largest term {}",
+            s.equations, s.critical_pairs, s.pruned, s.rules_added, s.rules_deleted, s.max_size
+        );
+        println!(
+            "time: {:.3}s normalizing, {:.3}s orienting, {:.3}s adding rules",
+            s.normalize_time.as_secs_f64(),
+            s.orient_time.as_secs_f64(),
+            s.add_time.as_secs_f64()
+        );
+    }
+
+    /// The summary as kb complete --json prints it, with the file the rules were written to.
+    pub fn json(&self, output: Option<&str>) -> Json {
+        json::completion(
+            &self.outcome,
+            self.trs.rules(),
+            self.aborted,
+            &self.stats,
+            &self.properties,
+            output,
+        )
+    }
+}
+
+/*
+ * kb complete AXIOMS.spec [--max-steps N] [--output FILE] [--interactive] [--orientations FILE]
+ * [--summary FILE]: run completion on the axioms and print how it ended with the rules it found,
+ * or write the rules to FILE in the .trs format. With --summary, the JSON object --json prints is
+ * also written to that file, whatever is printed, for scripts to read while a person reads the
+ * text. Equations the ordering can't orient are oriented as recorded
+ * in the orientations file, and with --interactive the others are asked about and the answers
+ * added to the file. Rules oriented by hand aren't checked to terminate.
+ */
+pub fn run(args: &[String], json: bool) -> Result<ExitCode, String> {
+    let mut max_steps = 10_000;
+    let mut output = None;
+    let mut interactive = false;
+    let mut orientations_path = None;
+    let mut summary_path = None;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--max-steps" => {
+                max_steps = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or("--max-steps needs a number")?;
+            }
+            "--output" => output = Some(args.next().ok_or("--output needs a file")?),
+            "--interactive" => interactive = true,
+            "--orientations" => {
+                orientations_path = Some(args.next().ok_or("--orientations needs a file")?)
+            }
+            "--summary" => summary_path = Some(args.next().ok_or("--summary needs a file")?),
+            s if s.starts_with("--") => return Err(format!("unknown option {}", s)),
+            _ => paths.push(a),
+        }
+    }
+    let [path] = paths[..] else {
+        return Err(crate::USAGE.to_string());
+    };
+    let spec = Spec::from_file(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut orientations = match orientations_path {
+        Some(p) => Orientations::load(p, &spec.signature)?,
+        None => Orientations::default(),
+    };
+    let summary = Summary::run_with(&spec, max_steps, &mut |eq| {
+        if let Some(c) = orientations.lookup(eq) {
+            return c;
+        }
+        if !interactive {
+            return Choice::Postpone;
+        }
+        let c = orient::ask(eq);
+        orientations.record(c, eq);
+        c
+    });
+    if let (true, Some(p)) = (interactive, orientations_path) {
+        orientations.save(p)?;
+    }
+    if let Some(out) = output {
+        std::fs::write(out, tpdb::to_string(&summary.trs))
+            .map_err(|e| format!("{}: {}", out, e))?;
+    }
+
+    let summary_json = summary.json(output.map(String::as_str));
+    if let Some(p) = summary_path {
+        std::fs::write(p, format!("{}\n", summary_json)).map_err(|e| format!("{}: {}", p, e))?;
+    }
+    if json {
+        println!("{}", summary_json);
+    } else {
+        summary.print();
+        if output.is_none() {
+            for r in summary.trs.rules() {
+                println!("  {}", r);
+            }
+        }
+    }
+    Ok(match summary.outcome {
+        Outcome::Convergent if !summary.aborted => ExitCode::SUCCESS,
+        _ => ExitCode::from(2),
+    })
+}
@@ -0,0 +1,88 @@
+/*
+ * kb, the command line front end to the crate. Each subcommand is a module with a run function
+ * taking the arguments after the subcommand's name:
+ *
+ *     kb complete axioms.spec [--max-steps N] [--output rules.trs] [--interactive]
+ *                 [--orientations orient.txt] [--summary summary.json]
+ *     kb normalize rules.trs "term" [--steps]
+ *     kb prove axioms.spec ["s = t"] [--max-steps N]
+ *     kb check rules.trs
+ *     kb repl
+ *     kb bench dir/ [--timeout 10s]
+ *
+ * Rule systems are read in the TPDB .trs format and terms are parsed with the system's
+ * signature; axioms are read as a Spec. Errors are printed as kb: message with exit status 1,
+ * and kb complete, kb prove, and kb check exit with status 2 when they can't show the system is
+ * convergent or the goal holds.
+ *
+ * With --json, anywhere in the arguments, each subcommand prints its result as one JSON object
+ * instead, and an error as {"error": message}. kb repl then answers each command with a line of
+ * JSON.
+ */
+use std::fmt::Display;
+use std::process::ExitCode;
+
+use knuth_bendix::json::obj;
+use knuth_bendix::{Operator, Trs, Variable, Word};
+
+mod bench;
+mod check;
+mod complete;
+mod normalize;
+mod orient;
+mod prove;
+mod repl;
+
+const USAGE: &str = "usage: kb complete AXIOMS.spec [--max-steps N] [--output FILE] [--interactive]
+                   [--orientations FILE] [--summary FILE] [--json]
+       kb normalize RULES.trs TERM [--steps] [--json]
+       kb prove AXIOMS.spec [GOAL] [--max-steps N] [--json]
+       kb check RULES.trs [--json]
+       kb repl [--json]
+       kb bench DIR [--timeout DURATION] [--json]";
+
+/// Print the leftmost outermost derivation of w to normal form with trs, one step per line with
+/// the rule and position used.
+fn print_derivation<V: Variable + Display, O: Operator + Display>(trs: &Trs<V, O>, w: &Word<V, O>) {
+    println!("  {}", w);
+    for s in trs.derivation(w) {
+        println!(
+            "→ {}    by rule {} ({}) at {}",
+            s.word,
+            s.rule,
+            trs.rules()[s.rule],
+            s.position
+        );
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let json = args.iter().any(|a| a == "--json");
+    args.retain(|a| a != "--json");
+    let result = match args.first().map(String::as_str) {
+        Some("complete") => complete::run(&args[1..], json),
+        Some("normalize") => normalize::run(&args[1..], json),
+        Some("prove") => prove::run(&args[1..], json),
+        Some("check") => check::run(&args[1..], json),
+        Some("repl") => repl::run(&args[1..], json),
+        Some("bench") => bench::run(&args[1..], json),
+        Some("-h" | "--help") => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Some(cmd) => Err(format!("unknown command {}\n{}", cmd, USAGE)),
+        None => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) if json => {
+            println!("{}", obj([("error", e.into())]));
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("kb: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
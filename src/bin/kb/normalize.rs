@@ -0,0 +1,34 @@
+use std::process::ExitCode;
+
+use knuth_bendix::{json, parse_term, tpdb};
+
+// kb normalize RULES.trs TERM [--steps]: rewrite TERM to normal form, leftmost outermost, and
+// print it. With --steps, print each intermediate word with the rule and position used. The JSON
+// output always has the steps.
+pub fn run(args: &[String], json: bool) -> Result<ExitCode, String> {
+    let mut steps = false;
+    let mut paths = Vec::new();
+    for a in args {
+        match a.as_str() {
+            "--steps" => steps = true,
+            s if s.starts_with("--") => return Err(format!("unknown option {}", s)),
+            _ => paths.push(a),
+        }
+    }
+    let [rules, term] = paths[..] else {
+        return Err(crate::USAGE.to_string());
+    };
+    let (sig, trs) = tpdb::from_file(rules).map_err(|e| format!("{}: {}", rules, e))?;
+    let w = parse_term(term, &sig).map_err(|e| format!("{}: {}", term, e))?;
+
+    let derivation = trs.derivation(&w);
+    let nf = derivation.last().map_or(&w, |s| &s.word);
+    if json {
+        println!("{}", json::normalization(&w, &derivation));
+    } else if steps {
+        crate::print_derivation(&trs, &w);
+    } else {
+        println!("{}", nf);
+    }
+    Ok(ExitCode::SUCCESS)
+}
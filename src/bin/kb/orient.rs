@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+
+use knuth_bendix::{parse_equation, DynOp, Equation, Op, Signature, Symbol, Var, Word};
+
+/*
+ * Orientations chosen by hand for equations the ordering can't orient, as kb complete
+ * --interactive asks for them. They're kept in a file, one per line, to replay in later runs:
+ *
+ *     # kb orientations
+ *     ltr f(x, x, y) = g(x, y, y)
+ *     rtl x * y = y * x
+ *     postpone x + y = y + x
+ *
+ * An entry applies to any equation that is the same up to renaming variables, with its sides
+ * either way round.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Choice {
+    LeftToRight,
+    RightToLeft,
+    Postpone,
+    Abort,
+}
+
+type Key = (Vec<Symbol<String, DynOp>>, Vec<Symbol<String, DynOp>>);
+
+// The sides of eq with their variables renamed by first occurrence.
+fn key(left: &Word<String, DynOp>, right: &Word<String, DynOp>) -> Key {
+    let mut names = BTreeMap::new();
+    let mut rename = |w: &Word<String, DynOp>| -> Vec<Symbol<String, DynOp>> {
+        w.syms
+            .iter()
+            .map(|s| match s {
+                Var(v) => {
+                    let n = names.len();
+                    Var(names.entry(v.clone()).or_insert(n.to_string()).clone())
+                }
+                Op(f) => Op(f.clone()),
+            })
+            .collect()
+    };
+    (rename(left), rename(right))
+}
+
+// Whether the rule l → r is allowed at all: l isn't a variable, and r has no variables l lacks.
+fn valid(l: &Word<String, DynOp>, r: &Word<String, DynOp>) -> bool {
+    !matches!(l.syms[..], [Var(_)]) && r.vars().is_subset(&l.vars())
+}
+
+#[derive(Default)]
+pub struct Orientations {
+    entries: Vec<(Choice, Equation<String, DynOp>)>,
+}
+
+impl Orientations {
+    /// Read the entries in path, if it exists.
+    pub fn load(path: &str, sig: &Signature) -> Result<Orientations, String> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Orientations::default()),
+            Err(e) => return Err(format!("{}: {}", path, e)),
+        };
+        let mut entries = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |m: String| format!("{}: line {}: {}", path, i + 1, m);
+            let (word, eq) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let choice = match word {
+                "ltr" => Choice::LeftToRight,
+                "rtl" => Choice::RightToLeft,
+                "postpone" => Choice::Postpone,
+                w => return Err(err(format!("expected ltr, rtl, or postpone, found {}", w))),
+            };
+            let eq = parse_equation(eq, sig).map_err(|e| err(e.to_string()))?;
+            entries.push((choice, eq));
+        }
+        Ok(Orientations { entries })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut out = String::from("# kb orientations\n");
+        for (choice, eq) in &self.entries {
+            let word = match choice {
+                Choice::LeftToRight => "ltr",
+                Choice::RightToLeft => "rtl",
+                _ => "postpone",
+            };
+            writeln!(out, "{} {}", word, eq).unwrap();
+        }
+        std::fs::write(path, out).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// The recorded choice for eq, turned round if the entry has its sides the other way.
+    pub fn lookup(&self, eq: &Equation<String, DynOp>) -> Option<Choice> {
+        let k = key(&eq.left, &eq.right);
+        self.entries.iter().find_map(|(choice, e)| {
+            if key(&e.left, &e.right) == k {
+                Some(*choice)
+            } else if key(&e.right, &e.left) == k {
+                Some(match choice {
+                    Choice::LeftToRight => Choice::RightToLeft,
+                    Choice::RightToLeft => Choice::LeftToRight,
+                    c => *c,
+                })
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn record(&mut self, choice: Choice, eq: &Equation<String, DynOp>) {
+        if choice != Choice::Abort {
+            self.entries.push((choice, eq.clone()));
+        }
+    }
+}
+
+/// Ask on standard error how to orient eq, offering only the directions that make a valid rule.
+/// The end of input aborts.
+pub fn ask(eq: &Equation<String, DynOp>) -> Choice {
+    let ltr = valid(&eq.left, &eq.right);
+    let rtl = valid(&eq.right, &eq.left);
+    eprintln!("can't orient {}", eq);
+    if ltr {
+        eprintln!("  l: {} → {}", eq.left, eq.right);
+    }
+    if rtl {
+        eprintln!("  r: {} → {}", eq.right, eq.left);
+    }
+    eprintln!("  p: postpone\n  a: abort");
+    let stdin = io::stdin();
+    loop {
+        eprint!("orient? ");
+        let _ = io::stderr().flush();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            return Choice::Abort;
+        }
+        match line.trim() {
+            "l" if ltr => return Choice::LeftToRight,
+            "r" if rtl => return Choice::RightToLeft,
+            "p" => return Choice::Postpone,
+            "a" => return Choice::Abort,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::process::ExitCode;
+
+use knuth_bendix::{parse_equation, Completion, Outcome, Spec};
+
+use knuth_bendix::json::{self, obj, Json};
+
+// kb prove AXIOMS.spec [GOAL] [--max-steps N]: decide whether GOAL, or the spec's goal if it's
+// left out, holds in the theory of the axioms. Completion is stopped as soon as its rules join
+// the two sides, and the proof is the derivation of each side to their common normal form.
+pub fn run(args: &[String], json: bool) -> Result<ExitCode, String> {
+    let mut max_steps = 10_000;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--max-steps" => {
+                max_steps = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or("--max-steps needs a number")?;
+            }
+            s if s.starts_with("--") => return Err(format!("unknown option {}", s)),
+            _ => paths.push(a),
+        }
+    }
+    let (path, goal) = match paths[..] {
+        [path] => (path, None),
+        [path, goal] => (path, Some(goal)),
+        _ => return Err(crate::USAGE.to_string()),
+    };
+    let spec = Spec::from_file(path).map_err(|e| format!("{}: {}", path, e))?;
+    let goal = match goal {
+        Some(g) => {
+            let eq = parse_equation(g, &spec.signature).map_err(|e| format!("{}: {}", g, e))?;
+            if let Some(name) = spec.undeclared_var(&eq) {
+                return Err(format!("{}: {} is not an operator or a variable", g, name));
+            }
+            eq
+        }
+        None => spec.goal.ok_or(format!("{}: no goal given", path))?,
+    };
+
+    let mut completion = Completion::new(&spec.axioms);
+    completion.max_steps = Some(max_steps);
+    let outcome = loop {
+        if completion.normalize(&goal.left) == completion.normalize(&goal.right) {
+            let trs = completion.trs();
+            if json {
+                let proof = obj([
+                    ("rules", json::rules(trs.rules())),
+                    ("left", json::steps(&trs.derivation(&goal.left))),
+                    ("right", json::steps(&trs.derivation(&goal.right))),
+                ]);
+                println!(
+                    "{}",
+                    obj([
+                        ("goal", goal.to_string().into()),
+                        ("status", "holds".into()),
+                        ("proof", proof),
+                    ])
+                );
+            } else {
+                println!("{} holds:", goal);
+                crate::print_derivation(&trs, &goal.left);
+                println!();
+                crate::print_derivation(&trs, &goal.right);
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        if let Some(outcome) = completion.step() {
+            break outcome;
+        }
+    };
+
+    let n_rules = completion.rules().len();
+    let (status, reason) = match &outcome {
+        Outcome::Trivial(eq) => (
+            "holds",
+            format!("the axioms imply {}, so every pair of words is equal", eq),
+        ),
+        Outcome::Convergent => (
+            "does not hold",
+            format!(
+                "the sides have different normal forms in the completed system of {} rules",
+                n_rules
+            ),
+        ),
+        Outcome::Unorientable(eqs) => {
+            let eqs: Vec<String> = eqs.iter().map(|eq| eq.to_string()).collect();
+            (
+                "unknown",
+                format!("completion couldn't orient {}", eqs.join(", ")),
+            )
+        }
+        Outcome::Limit => (
+            "unknown",
+            format!("completion stopped after {} steps", max_steps),
+        ),
+    };
+    if json {
+        println!(
+            "{}",
+            obj([
+                ("goal", goal.to_string().into()),
+                ("status", status.into()),
+                ("reason", reason.into()),
+                ("proof", Json::Null),
+            ])
+        );
+    } else if status == "unknown" {
+        println!("unknown: {}", reason);
+    } else {
+        println!("{} {}: {}", goal, status, reason);
+    }
+    Ok(match outcome {
+        Outcome::Trivial(_) => ExitCode::SUCCESS,
+        _ => ExitCode::from(2),
+    })
+}
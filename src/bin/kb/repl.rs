@@ -0,0 +1,213 @@
+#[cfg(not(feature = "repl"))]
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+
+use knuth_bendix::{parse_term, DynOp, Spec, Trs};
+
+use crate::complete::Summary;
+use knuth_bendix::json::{self, obj, Json};
+
+const HELP: &str = "\
+op NAME ARITY [WEIGHT] [NOTATION]  declare an operator, as in a spec file
+vars NAME...                       declare variables
+axiom S = T                        add an axiom
+load FILE                          start over with the declarations in a spec file
+ops, axioms, rules                 list the operators, axioms, or current rules
+complete [MAX-STEPS]               run completion on the axioms
+nf TERM                            the normal form of TERM under the current rules
+steps TERM                         the derivation of TERM to normal form
+help, quit";
+
+// Where lines come from: an editor with history when built with the repl feature, standard input
+// otherwise.
+#[cfg(feature = "repl")]
+struct Input {
+    editor: rustyline::DefaultEditor,
+    history: Option<std::path::PathBuf>,
+}
+
+#[cfg(feature = "repl")]
+impl Input {
+    fn new(_json: bool) -> Result<Input, String> {
+        let mut editor = rustyline::DefaultEditor::new().map_err(|e| e.to_string())?;
+        let history =
+            std::env::var_os("HOME").map(|h| std::path::Path::new(&h).join(".kb_history"));
+        if let Some(h) = &history {
+            let _ = editor.load_history(h);
+        }
+        Ok(Input { editor, history })
+    }
+
+    fn line(&mut self) -> Option<String> {
+        let line = self.editor.readline("kb> ").ok()?;
+        let _ = self.editor.add_history_entry(line.as_str());
+        Some(line)
+    }
+}
+
+#[cfg(feature = "repl")]
+impl Drop for Input {
+    fn drop(&mut self) {
+        if let Some(h) = &self.history {
+            let _ = self.editor.save_history(h);
+        }
+    }
+}
+
+// Without an editor the prompt is left out of JSON output, which is meant for another program.
+#[cfg(not(feature = "repl"))]
+struct Input {
+    stdin: io::StdinLock<'static>,
+    prompt: bool,
+}
+
+#[cfg(not(feature = "repl"))]
+impl Input {
+    fn new(json: bool) -> Result<Input, String> {
+        Ok(Input {
+            stdin: io::stdin().lock(),
+            prompt: !json,
+        })
+    }
+
+    fn line(&mut self) -> Option<String> {
+        if self.prompt {
+            print!("kb> ");
+            io::stdout().flush().ok()?;
+        }
+        let mut line = String::new();
+        match self.stdin.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line),
+        }
+    }
+}
+
+struct Session {
+    spec: Spec,
+    trs: Trs<String, DynOp>,
+    // The number of lines read, for the line numbers in errors from the spec.
+    n_lines: usize,
+    json: bool,
+}
+
+impl Session {
+    // Run one command, printing its result, as a line of JSON in json mode.
+    fn eval(&mut self, line: &str) -> Result<(), String> {
+        let (cmd, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        let strings = |items: Vec<String>| -> Json { items.into() };
+        let reply = match cmd {
+            "op" | "vars" | "axiom" => {
+                self.spec
+                    .add_line(self.n_lines, line)
+                    .map_err(|e| e.to_string())?;
+                obj([("ok", true.into())])
+            }
+            "load" => {
+                self.spec = Spec::from_file(rest).map_err(|e| format!("{}: {}", rest, e))?;
+                self.trs = Trs::new(Vec::new());
+                let (n_ops, n_axioms) = (self.spec.signature.ops().len(), self.spec.axioms.len());
+                if !self.json {
+                    println!("{} operators, {} axioms", n_ops, n_axioms);
+                }
+                obj([("n_ops", n_ops.into()), ("n_axioms", n_axioms.into())])
+            }
+            "ops" => {
+                let ops = self.spec.signature.ops().iter();
+                let ops: Vec<String> = ops
+                    .map(|o| format!("{} {} {}", o.name, o.arity, o.weight))
+                    .collect();
+                if !self.json {
+                    ops.iter().for_each(|o| println!("{}", o));
+                }
+                obj([("ops", strings(ops))])
+            }
+            "axioms" => {
+                let axioms: Vec<String> = self.spec.axioms.iter().map(|a| a.to_string()).collect();
+                if !self.json {
+                    axioms.iter().for_each(|a| println!("{}", a));
+                }
+                obj([("axioms", strings(axioms))])
+            }
+            "rules" => {
+                if !self.json {
+                    for (i, r) in self.trs.rules().iter().enumerate() {
+                        println!("{}: {}", i, r);
+                    }
+                }
+                obj([("rules", json::rules(self.trs.rules()))])
+            }
+            "complete" => {
+                let max_steps = match rest {
+                    "" => 10_000,
+                    n => n
+                        .parse()
+                        .map_err(|_| format!("expected a number, found {}", n))?,
+                };
+                let summary = Summary::run(&self.spec, max_steps);
+                if !self.json {
+                    summary.print();
+                }
+                let reply = summary.json(None);
+                self.trs = summary.trs;
+                reply
+            }
+            "nf" | "steps" => {
+                let w = parse_term(rest, &self.spec.signature).map_err(|e| e.to_string())?;
+                let derivation = self.trs.derivation(&w);
+                let nf = derivation.last().map_or(&w, |s| &s.word);
+                match (self.json, cmd) {
+                    (true, _) => {}
+                    (false, "steps") => crate::print_derivation(&self.trs, &w),
+                    (false, _) => println!("{}", nf),
+                }
+                obj([
+                    ("normal_form", nf.to_string().into()),
+                    ("steps", json::steps(&derivation)),
+                ])
+            }
+            "help" => {
+                if !self.json {
+                    println!("{}", HELP);
+                }
+                obj([("help", HELP.into())])
+            }
+            c => return Err(format!("unknown command {}; try help", c)),
+        };
+        if self.json {
+            println!("{}", reply);
+        }
+        Ok(())
+    }
+}
+
+// kb repl: read commands a line at a time until quit or the end of input.
+pub fn run(args: &[String], json: bool) -> Result<ExitCode, String> {
+    if !args.is_empty() {
+        return Err(crate::USAGE.to_string());
+    }
+    let mut input = Input::new(json)?;
+    let mut session = Session {
+        spec: Spec::default(),
+        trs: Trs::new(Vec::new()),
+        n_lines: 0,
+        json,
+    };
+    while let Some(line) = input.line() {
+        session.n_lines += 1;
+        let line = line.trim();
+        if line == "quit" || line == "exit" {
+            break;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match session.eval(line) {
+            Ok(()) => {}
+            Err(e) if json => println!("{}", obj([("error", e.into())])),
+            Err(e) => println!("error: {}", e),
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,303 @@
+/*
+ * Words with binders, using de Bruijn indices.
+ *
+ * A signature O is extended with binder symbols and bound-variable indices by Scoped<O>. Abs(b)
+ * applies the binder b (e.g. a lambda or a quantifier) to a single body word, and Index(i) refers
+ * to the variable bound by the i-th enclosing Abs, counting from zero. Ordinary variables of the
+ * word remain free variables, and are what substitution and matching bind. The functions here
+ * keep words well scoped: substituting under a binder shifts the indices of the substituted
+ * word so that they can't be captured, and matching refuses to bind a free variable to a word
+ * that refers to a binder inside the pattern.
+ */
+use std::collections::BTreeMap;
+
+use crate::word::{self, Op, Operator, Var, Variable};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Scoped<O: Operator> {
+    Op(O),
+    Abs(O),
+    Index(usize),
+}
+
+impl<O: Operator> Operator for Scoped<O> {
+    fn min_weight() -> u64 {
+        O::min_weight()
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Scoped::Op(f) => f.arity(),
+            Scoped::Abs(_) => 1,
+            Scoped::Index(_) => 0,
+        }
+    }
+
+    fn weight(&self) -> u64 {
+        match self {
+            Scoped::Op(f) | Scoped::Abs(f) => f.weight(),
+            Scoped::Index(_) => O::min_weight(),
+        }
+    }
+}
+
+pub type Word<V, O> = word::Word<V, Scoped<O>>;
+
+pub fn op<V: Variable, O: Operator>(f: O, args: &[Word<V, O>]) -> Word<V, O> {
+    Word::op(Scoped::Op(f), args)
+}
+
+pub fn abs<V: Variable, O: Operator>(b: O, body: &Word<V, O>) -> Word<V, O> {
+    Word::op(Scoped::Abs(b), std::slice::from_ref(body))
+}
+
+pub fn index<V: Variable, O: Operator>(i: usize) -> Word<V, O> {
+    Word::op(Scoped::Index(i), &[])
+}
+
+/*
+ * Return the number of binders enclosing each symbol of w.
+ */
+fn depths<V: Variable, O: Operator>(w: &Word<V, O>) -> Vec<usize> {
+    let mut out = Vec::with_capacity(w.syms.len());
+    // Arguments remaining for each enclosing operator, and whether it is a binder.
+    let mut stack: Vec<(usize, bool)> = Vec::new();
+    let mut depth = 0;
+    for s in w.syms.iter() {
+        out.push(depth);
+        if s.arity() > 0 {
+            let binder = matches!(s, Op(Scoped::Abs(_)));
+            if binder {
+                depth += 1;
+            }
+            stack.push((s.arity(), binder));
+            continue;
+        }
+        // s completes a subword; close every operator whose last argument this was.
+        while let Some(top) = stack.last_mut() {
+            top.0 -= 1;
+            if top.0 > 0 {
+                break;
+            }
+            if let Some((_, true)) = stack.pop() {
+                depth -= 1;
+            }
+        }
+    }
+    out
+}
+
+/*
+ * Add d to every index of w that refers past the cutoff-th enclosing binder, i.e. to a binder
+ * outside of w.
+ */
+fn shift<V: Variable, O: Operator>(w: &Word<V, O>, d: isize, cutoff: usize) -> Word<V, O> {
+    let syms = w
+        .syms
+        .iter()
+        .zip(depths(w))
+        .map(|(s, depth)| match s {
+            Op(Scoped::Index(i)) if *i >= cutoff + depth => {
+                Op(Scoped::Index((*i as isize + d) as usize))
+            }
+            _ => s.clone(),
+        })
+        .collect();
+    Word { syms }
+}
+
+/// Check that every index in w refers to a binder within w, so w is a closed word up to its free
+/// variables.
+pub fn is_well_scoped<V: Variable, O: Operator>(w: &Word<V, O>) -> bool {
+    w.syms.iter().zip(depths(w)).all(|(s, depth)| match s {
+        Op(Scoped::Index(i)) => *i < depth,
+        _ => true,
+    })
+}
+
+/// Substitute words for the free variables of w, shifting each substituted word as it moves
+/// under binders so that none of its indices are captured.
+pub fn subst<V: Variable, O: Operator>(
+    w: &Word<V, O>,
+    vars: &BTreeMap<V, Word<V, O>>,
+) -> Word<V, O> {
+    let mut syms = Vec::with_capacity(w.syms.len());
+    for (s, depth) in w.syms.iter().zip(depths(w)) {
+        match s.var().and_then(|v| vars.get(v)) {
+            Some(u) => syms.extend(shift(u, depth as isize, 0).syms),
+            None => syms.push(s.clone()),
+        }
+    }
+    Word { syms }
+}
+
+/// Abstract the free variable v of body, giving the word b(body) in which v is bound by b.
+pub fn bind<V: Variable, O: Operator>(b: O, v: &V, body: &Word<V, O>) -> Word<V, O> {
+    let body = shift(body, 1, 0);
+    let syms = body
+        .syms
+        .iter()
+        .zip(depths(&body))
+        .map(|(s, depth)| match s {
+            Var(u) if u == v => Op(Scoped::Index(depth)),
+            _ => s.clone(),
+        })
+        .collect();
+    abs(b, &Word { syms })
+}
+
+/// Instantiate the bound variable of an abstraction with u, giving the abstraction's body with u
+/// in place of the variable. Returns None if w is not an abstraction.
+pub fn instantiate<V: Variable, O: Operator>(w: &Word<V, O>, u: &Word<V, O>) -> Option<Word<V, O>> {
+    match w.syms.first() {
+        Some(Op(Scoped::Abs(_))) => {}
+        _ => return None,
+    }
+    let body = w.subwords().next()?.to_word();
+    let mut syms = Vec::with_capacity(body.syms.len());
+    for (s, depth) in body.syms.iter().zip(depths(&body)) {
+        match s {
+            Op(Scoped::Index(i)) if *i == depth => syms.extend(shift(u, depth as isize, 0).syms),
+            Op(Scoped::Index(i)) if *i > depth => syms.push(Op(Scoped::Index(i - 1))),
+            _ => syms.push(s.clone()),
+        }
+    }
+    Some(Word { syms })
+}
+
+/// Compute the substitution of the free variables of pattern that makes it equal to w, as
+/// Word::unify does, but with scope checks: a variable under binders of the pattern can only be
+/// bound to a word that doesn't refer to those binders, since the binding would otherwise move
+/// the bound variable out of its scope. Bindings are stored relative to the root of the pattern.
+pub fn matches<V: Variable, O: Operator>(
+    pattern: &Word<V, O>,
+    w: &Word<V, O>,
+) -> Option<BTreeMap<V, Word<V, O>>> {
+    let mut vmap: BTreeMap<V, Word<V, O>> = BTreeMap::new();
+    let pdepths = depths(pattern);
+    let mut i = 0;
+    let mut j = 0;
+    while i < pattern.syms.len() {
+        let s = w.syms.get(j)?;
+        match &pattern.syms[i] {
+            Var(v) => {
+                let end = w.subword_end(j);
+                let t = Word {
+                    syms: w.syms[j..end].to_vec(),
+                };
+                let depth = pdepths[i];
+                // Indices below depth refer to binders inside the pattern.
+                let refers_inside = t.syms.iter().zip(depths(&t)).any(|(s, d)| match s {
+                    Op(Scoped::Index(k)) => *k >= d && *k < d + depth,
+                    _ => false,
+                });
+                if refers_inside {
+                    return None;
+                }
+                let t = shift(&t, -(depth as isize), 0);
+                if let Some(ow) = vmap.insert(v.clone(), t.clone()) {
+                    if ow.syms != t.syms {
+                        return None;
+                    }
+                }
+                j = end;
+            }
+            p => {
+                if p != s {
+                    return None;
+                }
+                j += 1;
+            }
+        }
+        i += 1;
+    }
+    if j == w.syms.len() {
+        Some(vmap)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The untyped lambda calculus: application, and lambda as the only binder.
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    enum Lam {
+        App,
+        Lambda,
+    }
+    use Lam::*;
+
+    impl Operator for Lam {
+        fn min_weight() -> u64 {
+            1
+        }
+
+        fn arity(&self) -> usize {
+            match self {
+                App => 2,
+                Lambda => 1,
+            }
+        }
+
+        fn weight(&self) -> u64 {
+            1
+        }
+    }
+
+    type Term = Word<String, Lam>;
+
+    fn var(v: &str) -> Term {
+        Term::var(v)
+    }
+
+    fn app(s: &Term, t: &Term) -> Term {
+        op(App, &[s.clone(), t.clone()])
+    }
+
+    #[test]
+    fn bind_and_instantiate() {
+        // λx. x y
+        let l = bind(Lambda, &"x".to_string(), &app(&var("x"), &var("y")));
+        assert_eq!(l, abs(Lambda, &app(&index(0), &var("y"))));
+        assert!(is_well_scoped(&l));
+        assert!(!is_well_scoped(&app(&index(0), &var("y"))));
+        let z = var("z");
+        assert_eq!(instantiate(&l, &z), Some(app(&z, &var("y"))));
+        assert_eq!(instantiate(&z, &z), None);
+    }
+
+    #[test]
+    fn capture_avoiding_subst() {
+        // Substituting a word with a loose index under a binder shifts the index past it.
+        let l = abs(Lambda, &app(&index(0), &var("y")));
+        let vars = BTreeMap::from([("y".to_string(), index(0))]);
+        assert_eq!(subst(&l, &vars), abs(Lambda, &app(&index(0), &index(1))));
+        // Instantiating under a binder shifts the instantiated word too.
+        let k: Term = abs(Lambda, &abs(Lambda, &index(1)));
+        let inner = instantiate(&k, &index(5)).unwrap();
+        assert_eq!(inner, abs(Lambda, &index(6)));
+    }
+
+    #[test]
+    fn scoped_matching() {
+        // λ. #0 y matches λ. #0 #1, binding y to the loose index #0 outside the binder.
+        let pat = abs(Lambda, &app(&index(0), &var("y")));
+        let w = abs(Lambda, &app(&index(0), &index(1)));
+        let vmap = matches(&pat, &w).unwrap();
+        assert_eq!(vmap.get("y"), Some(&index(0)));
+        assert_eq!(subst(&pat, &vmap), w);
+
+        // But not λ. #0 #0, where y would have to capture the bound variable.
+        let w = abs(Lambda, &app(&index(0), &index(0)));
+        assert!(matches(&pat, &w).is_none());
+
+        // Unscoped parts bind as usual, and nonlinear patterns are respected.
+        let pat = app(&var("x"), &var("x"));
+        let l = abs(Lambda, &index(0));
+        assert!(matches(&pat, &app(&l, &l)).is_some());
+        assert!(matches(&pat, &app(&l, &var("z"))).is_none());
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use crate::word::{Op, Operator, Symbol, Var, Variable, Word};
+
+/// How often a NormalFormCache has been asked for a normal form and had it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    /// The number of normal forms held.
+    pub entries: usize,
+    /// How many times it was emptied, such as because the rules changed.
+    pub clears: usize,
+}
+
+impl CacheStats {
+    /// The fraction of lookups that were hits, or zero if there were none.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            n => self.hits as f64 / n as f64,
+        }
+    }
+}
+
+type Syms<V, O> = Vec<Symbol<V, O>>;
+
+/*
+ * Normal forms already found, for Trs::normalize to reuse. Words are keyed with their variables
+ * renamed by first occurrence, as canonicalize_vars does, since renaming the variables of a word
+ * renames those of its normal form the same way, so a word and its renamings share one entry.
+ * The cache doesn't know the rules; whoever owns it empties it when they change, as
+ * Trs::rules_mut does.
+ */
+#[derive(Clone, Debug)]
+pub struct NormalFormCache<V: Variable, O: Operator> {
+    forms: BTreeMap<Syms<V, O>, Syms<V, O>>,
+    stats: CacheStats,
+}
+
+impl<V: Variable, O: Operator> Default for NormalFormCache<V, O> {
+    fn default() -> Self {
+        NormalFormCache {
+            forms: BTreeMap::new(),
+            stats: CacheStats::default(),
+        }
+    }
+}
+
+impl<V: Variable, O: Operator> NormalFormCache<V, O> {
+    pub fn new() -> Self {
+        NormalFormCache::default()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.forms.len(),
+            ..self.stats
+        }
+    }
+
+    pub fn clear(&mut self) {
+        if !self.forms.is_empty() {
+            self.stats.clears += 1;
+        }
+        self.forms.clear();
+    }
+
+    /// The normal form of w if it's held; a miss is counted otherwise.
+    pub fn get(&mut self, w: &Word<V, O>) -> Option<Word<V, O>> {
+        let (key, names) = canonical(w);
+        let Some(form) = self.forms.get(&key) else {
+            self.stats.misses += 1;
+            return None;
+        };
+        self.stats.hits += 1;
+        // Put back the names of w's variables.
+        let names: BTreeMap<V, &V> = names
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (V::nth(i), v))
+            .collect();
+        let syms = form
+            .iter()
+            .map(|s| match s {
+                Var(v) => Var(names.get(v).map_or(v, |n| *n).clone()),
+                Op(f) => Op(f.clone()),
+            })
+            .collect();
+        Some(Word { syms })
+    }
+
+    /// Record that normal is the normal form of w.
+    pub fn insert(&mut self, w: &Word<V, O>, normal: &Word<V, O>) {
+        let (key, names) = canonical(w);
+        let renamed: BTreeMap<&V, V> = names
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v, V::nth(i)))
+            .collect();
+        let form = normal.map_vars(|v| renamed.get(v).unwrap_or(v).clone());
+        self.forms.insert(key, form.syms);
+    }
+}
+
+// The symbols of w with its variables renamed by Word::canonicalize_vars, and the variables in
+// order of first occurrence, so the i-th was renamed to Variable::nth(i).
+fn canonical<V: Variable, O: Operator>(w: &Word<V, O>) -> (Syms<V, O>, Vec<V>) {
+    let mut names: Vec<V> = Vec::new();
+    for v in w.syms.iter().filter_map(Symbol::var) {
+        if !names.contains(v) {
+            names.push(v.clone());
+        }
+    }
+    (w.canonicalize_vars().syms, names)
+}
@@ -0,0 +1,943 @@
+use std::cell::OnceCell;
+use std::time::{Duration, Instant};
+
+use crate::arena::TermArena;
+use crate::discrimination::DiscriminationTree;
+use crate::fingerprint::{Fingerprint, FingerprintIndex};
+use crate::order::{Kbo, ReductionOrder};
+use crate::relation::{Axiom, Equation, Rule};
+use crate::trs::{normalize_steps, overlaps_at, Trs};
+use crate::word::{Operator, Var, Variable, Word};
+
+// An event for the tracing feature, which compiles to nothing without it.
+macro_rules! event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::event!($($arg)*);
+    };
+}
+
+/// How a completion run ended.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome<V: Variable, O: Operator> {
+    /// The rules form a convergent system for the axioms.
+    Convergent,
+    /// These equations, normalized by the rules, could not be oriented by the ordering.
+    Unorientable(Vec<Axiom<V, O>>),
+    /// The axioms imply this equation, which equates a variable with a word not containing it.
+    /// Every pair of words is then equal, so the theory is trivial.
+    Trivial(Axiom<V, O>),
+    /// A limit on the run was reached before it finished.
+    Limit,
+}
+
+/// Whether two words are equal in the theory of a completion run, and whether that answer is
+/// certain. Only a claim that the words are not equal can fail to be definitive, when it comes
+/// from a search that found no proof rather than from a convergent system.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Equivalence {
+    pub equal: bool,
+    pub definitive: bool,
+}
+
+/// Counts and timings accumulated over a completion run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stats {
+    /// Equations taken from the pending ones and processed.
+    pub equations: usize,
+    /// Critical pairs added as pending equations.
+    pub critical_pairs: usize,
+    /// Equations dropped because the rules joined their sides.
+    pub pruned: usize,
+    /// Pending equations dropped by simplify_pending because a new rule joined their sides.
+    pub pruned_pending: usize,
+    pub rules_added: usize,
+    /// Rules turned back into equations because a new rule simplified their left side.
+    pub rules_deleted: usize,
+    /// The size of the largest side of an equation processed, after normalizing.
+    pub max_size: usize,
+    /// Time spent normalizing the equations processed.
+    pub normalize_time: Duration,
+    /// Time spent orienting them.
+    pub orient_time: Duration,
+    /// Time spent adding rules: simplifying the others and finding critical pairs.
+    pub add_time: Duration,
+    /// How many times simplify_pending_in_parallel split the pending equations between threads.
+    #[cfg(feature = "parallel")]
+    pub parallel_splits: usize,
+}
+
+/*
+ * The state of a run of the Knuth-Bendix completion procedure, which can be advanced a step at a
+ * time:
+ *
+ * Pick the smallest pending equation and normalize both sides with the current rules. If the
+ * sides are equal, drop it. Otherwise orient it with the ordering into a new rule, use the new
+ * rule to simplify the existing ones (a rule whose left side becomes reducible turns back into a
+ * pending equation), and add the critical pairs of the new rule with every rule as pending
+ * equations. Equations that can't be oriented are set aside and retried once other rules have
+ * been added, since those may make them joinable or orientable.
+ *
+ * When no equations remain, the rules are a convergent system equivalent to the axioms. In
+ * particular, no axioms (or only axioms of the form s = s) give the empty system. If an equation
+ * normalizes to x = t where t doesn't contain x, then substituting for x makes every word equal
+ * to t, and the run stops with Outcome::Trivial instead of reporting the equation unorientable.
+ */
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Completion<V: Variable, O: Operator, R: ReductionOrder<V, O> = Kbo> {
+    pub order: R,
+    /// Stop with Outcome::Limit if there would be more than this many rules.
+    pub max_rules: Option<usize>,
+    /// Stop with Outcome::Limit after processing this many equations.
+    pub max_steps: Option<usize>,
+    /*
+     * Normalize every pending equation each time a rule is added, dropping those whose sides
+     * join, rather than each one only when it's taken. The queue stays smaller and is always
+     * normal, which pays off on runs that converge, but runs stopped by a limit normalize many
+     * equations they would never have taken.
+     */
+    pub simplify_pending: bool,
+    /// How simplify_pending_in_parallel splits the pending equations between threads.
+    #[cfg(feature = "parallel")]
+    pub parallelism: Parallelism,
+    rules: Vec<Rule<V, O>>,
+    equations: Vec<Axiom<V, O>>,
+    deferred: Vec<Axiom<V, O>>,
+    // The number of rules added so far, and its value when the deferred equations were last
+    // retried.
+    n_added: usize,
+    n_added_at_retry: usize,
+    stats: Stats,
+    outcome: Option<Outcome<V, O>>,
+    // An index of the rules for normalizing, built when it's first needed after they change.
+    #[cfg_attr(feature = "serde", serde(skip, default = "OnceCell::new"))]
+    index: OnceCell<DiscriminationTree<O>>,
+    // Set by simplify_pending_in_parallel, as only there are V and O known to be Send and Sync.
+    #[cfg(feature = "parallel")]
+    #[cfg_attr(feature = "serde", serde(skip, default = "Option::default"))]
+    normalize_in_parallel: Option<NormalizeInParallel<V, O>>,
+}
+
+impl<V: Variable, O: Operator> Completion<V, O> {
+    pub fn new(axioms: &[Axiom<V, O>]) -> Completion<V, O> {
+        Completion::with_order(axioms, Kbo)
+    }
+}
+
+impl<V: Variable, O: Operator, R: ReductionOrder<V, O>> Completion<V, O, R> {
+    pub fn with_order(axioms: &[Axiom<V, O>], order: R) -> Completion<V, O, R> {
+        Completion {
+            order,
+            max_rules: None,
+            max_steps: None,
+            simplify_pending: false,
+            #[cfg(feature = "parallel")]
+            parallelism: Parallelism::default(),
+            rules: Vec::new(),
+            equations: axioms.to_vec(),
+            deferred: Vec::new(),
+            n_added: 0,
+            n_added_at_retry: 0,
+            stats: Stats::default(),
+            outcome: None,
+            index: OnceCell::new(),
+            #[cfg(feature = "parallel")]
+            normalize_in_parallel: None,
+        }
+    }
+
+    /// The current rules. Once the run is convergent these are the completed system.
+    pub fn rules(&self) -> &[Rule<V, O>] {
+        &self.rules
+    }
+
+    /// The equations still waiting to be processed, including those set aside as unorientable.
+    pub fn pending(&self) -> impl Iterator<Item = &Axiom<V, O>> {
+        self.equations.iter().chain(self.deferred.iter())
+    }
+
+    pub fn trs(&self) -> Trs<V, O> {
+        Trs::new(self.rules.clone())
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// The outcome of the run, if it has finished.
+    pub fn outcome(&self) -> Option<&Outcome<V, O>> {
+        self.outcome.as_ref()
+    }
+
+    /// The normal form of w with the current rules.
+    pub fn normalize(&self, w: &Word<V, O>) -> Word<V, O> {
+        let index = self
+            .index
+            .get_or_init(|| DiscriminationTree::new(&self.rules));
+        normalize_steps(&self.rules, index, w, &mut TermArena::new(), None).unwrap_or_else(|w| w)
+    }
+
+    /*
+     * Take the next equation to process: the smallest pending one, or once there are none, the
+     * deferred ones if rules have been added since they were last tried.
+     */
+    fn next_equation(&mut self) -> Option<Axiom<V, O>> {
+        if self.equations.is_empty() && self.n_added > self.n_added_at_retry {
+            self.n_added_at_retry = self.n_added;
+            self.equations.append(&mut self.deferred);
+        }
+        let i = (0..self.equations.len())
+            .min_by_key(|&i| self.equations[i].left.size() + self.equations[i].right.size())?;
+        Some(self.equations.swap_remove(i))
+    }
+
+    fn add_rule(&mut self, rule: Rule<V, O>) {
+        let start = Instant::now();
+        let new = Trs::new(vec![rule.clone()]);
+        let mut kept = Vec::with_capacity(self.rules.len() + 1);
+        for old in self.rules.drain(..) {
+            if new.rewrite_step(&old.left).is_some() {
+                event!(
+                    tracing::Level::DEBUG,
+                    left_size = old.left.size(),
+                    right_size = old.right.size(),
+                    "rule removed"
+                );
+                self.stats.rules_deleted += 1;
+                self.equations.push(Axiom {
+                    left: old.left,
+                    right: old.right,
+                });
+            } else {
+                kept.push(old);
+            }
+        }
+        event!(
+            tracing::Level::DEBUG,
+            id = kept.len(),
+            left_size = rule.left.size(),
+            right_size = rule.right.size(),
+            "rule added"
+        );
+        kept.push(rule);
+        self.rules = kept;
+        let trs = self.trs();
+        let mut arena = TermArena::new();
+        for old in self.rules.iter_mut() {
+            old.right = trs.normalize_in(&old.right, &mut arena);
+        }
+        // Normalizing right sides leaves the left sides, and so the index, as they are.
+        self.index = OnceCell::from(trs.into_index());
+        let rule = self.rules.last().unwrap();
+        let new_id = self.rules.len() - 1;
+        /*
+         * Only subwords whose fingerprints are unifiable can overlap, so index the fingerprint of
+         * every left side, and of every subword of the other left sides that isn't a variable,
+         * and look up the new rule's left side and its subwords in them.
+         */
+        let mut roots = FingerprintIndex::new();
+        let mut subwords = FingerprintIndex::new();
+        for (id, other) in self.rules.iter().enumerate() {
+            roots.insert(&Fingerprint::of(&other.left, 0), id);
+            if id == new_id {
+                continue;
+            }
+            for (p, s) in other.left.syms.iter().enumerate() {
+                if s.op().is_some() {
+                    subwords.insert(&Fingerprint::of(&other.left, p), (id, p));
+                }
+            }
+        }
+        // The subwords of the new rule's left side that each rule's left side might unify with,
+        // and the subwords of each rule's left side that the new rule's left side might.
+        let mut into_new: Vec<Vec<usize>> = vec![Vec::new(); self.rules.len()];
+        let mut from_new: Vec<Vec<usize>> = vec![Vec::new(); self.rules.len()];
+        for (p, s) in rule.left.syms.iter().enumerate() {
+            if s.op().is_some() {
+                for id in roots.unifiable(&Fingerprint::of(&rule.left, p)) {
+                    into_new[id].push(p);
+                }
+            }
+        }
+        for (id, p) in subwords.unifiable(&Fingerprint::of(&rule.left, 0)) {
+            from_new[id].push(p);
+        }
+        for (id, other) in self.rules.iter().enumerate() {
+            let same = id == new_id;
+            into_new[id].sort_unstable();
+            from_new[id].sort_unstable();
+            let mut cps = overlaps_at(rule, other, same, into_new[id].iter().copied());
+            cps.extend(overlaps_at(
+                other,
+                rule,
+                false,
+                from_new[id].iter().copied(),
+            ));
+            #[cfg(feature = "tracing")]
+            for cp in &cps {
+                tracing::event!(
+                    tracing::Level::TRACE,
+                    with = id,
+                    left_size = cp.left.size(),
+                    right_size = cp.right.size(),
+                    "critical pair"
+                );
+            }
+            self.stats.critical_pairs += cps.len();
+            self.equations.extend(cps.into_iter().map(|cp| Axiom {
+                left: cp.left,
+                right: cp.right,
+            }));
+        }
+        if self.simplify_pending {
+            let index = self.index.get().unwrap();
+            #[cfg(feature = "parallel")]
+            if let Some(f) = self.normalize_in_parallel {
+                if f(&self.rules, index, &mut self.equations, self.parallelism) {
+                    self.stats.parallel_splits += 1;
+                }
+            } else {
+                normalize_each(&self.rules, index, &mut self.equations);
+            }
+            #[cfg(not(feature = "parallel"))]
+            normalize_each(&self.rules, index, &mut self.equations);
+            let before = self.equations.len();
+            self.equations.retain(|eq| eq.left.syms != eq.right.syms);
+            self.stats.pruned_pending += before - self.equations.len();
+        }
+        self.n_added += 1;
+        self.stats.rules_added += 1;
+        self.stats.add_time += start.elapsed();
+    }
+
+    /*
+     * Add a rule oriented as given rather than by the ordering, such as a definition of an
+     * operator. It simplifies and overlaps with the other rules like any rule, and if a later
+     * rule simplifies its left side, it goes back to being an ordinary equation.
+     */
+    pub fn add_fixed_rule(&mut self, rule: Rule<V, O>) {
+        self.add_rule(rule);
+    }
+
+    /*
+     * Resume a run that stopped with Outcome::Unorientable by orienting the i-th of the
+     * equations it gave, left to right or right to left, as with add_fixed_rule. The other
+     * equations are retried once the rule is added. The caller is responsible for the rules
+     * still terminating.
+     *
+     * # Panics
+     *
+     * If the run hasn't stopped with Outcome::Unorientable since it was last resumed, which
+     * includes calling this twice without running in between, or if i isn't the index of one of
+     * the equations it gave.
+     */
+    pub fn orient_unorientable(&mut self, i: usize, left_to_right: bool) {
+        assert!(
+            matches!(self.outcome, Some(Outcome::Unorientable(_))),
+            "orient_unorientable on a run that didn't stop with unorientable equations"
+        );
+        assert!(
+            i < self.deferred.len(),
+            "orient_unorientable({}) with only {} unorientable equations",
+            i,
+            self.deferred.len()
+        );
+        let eq = self.deferred.remove(i);
+        let rule = if left_to_right {
+            Rule {
+                left: eq.left,
+                right: eq.right,
+            }
+        } else {
+            Rule {
+                left: eq.right,
+                right: eq.left,
+            }
+        };
+        self.outcome = None;
+        self.add_rule(rule);
+    }
+
+    /// Process a single equation. Returns the outcome once the run has finished.
+    pub fn step(&mut self) -> Option<Outcome<V, O>> {
+        if let Some(outcome) = &self.outcome {
+            return Some(outcome.clone());
+        }
+        if self
+            .max_steps
+            .is_some_and(|max| self.stats.equations >= max)
+        {
+            self.outcome = Some(Outcome::Limit);
+            return self.outcome.clone();
+        }
+        let Some(eq) = self.next_equation() else {
+            self.outcome = Some(if self.deferred.is_empty() {
+                Outcome::Convergent
+            } else {
+                let deferred = std::mem::take(&mut self.deferred);
+                let eqs: Vec<Axiom<V, O>> = deferred
+                    .into_iter()
+                    .map(|eq| Axiom {
+                        left: self.normalize(&eq.left),
+                        right: self.normalize(&eq.right),
+                    })
+                    .collect();
+                self.deferred = eqs.clone();
+                Outcome::Unorientable(eqs)
+            });
+            return self.outcome.clone();
+        };
+        self.stats.equations += 1;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "step",
+            n = self.stats.equations,
+            rules = self.rules.len(),
+            pending = self.equations.len()
+        )
+        .entered();
+        let start = Instant::now();
+        let s = self.normalize(&eq.left);
+        let t = self.normalize(&eq.right);
+        self.stats.normalize_time += start.elapsed();
+        self.stats.max_size = self.stats.max_size.max(s.size()).max(t.size());
+        if s.syms == t.syms {
+            self.stats.pruned += 1;
+            return None;
+        }
+        if is_collapsing(&s, &t) || is_collapsing(&t, &s) {
+            event!(
+                tracing::Level::DEBUG,
+                left_size = s.size(),
+                right_size = t.size(),
+                "trivial"
+            );
+            self.outcome = Some(Outcome::Trivial(Axiom { left: s, right: t }));
+            return self.outcome.clone();
+        }
+        let eq = Equation { left: s, right: t };
+        let start = Instant::now();
+        let oriented = eq.orient(&self.order);
+        self.stats.orient_time += start.elapsed();
+        let rule = match oriented {
+            Ok(rule) => rule,
+            Err(_) => {
+                event!(
+                    tracing::Level::DEBUG,
+                    left_size = eq.left.size(),
+                    right_size = eq.right.size(),
+                    "deferred"
+                );
+                self.deferred.push(eq);
+                return None;
+            }
+        };
+        if self.max_rules.is_some_and(|max| self.rules.len() >= max) {
+            self.equations.push(rule.into());
+            self.outcome = Some(Outcome::Limit);
+            return self.outcome.clone();
+        }
+        self.add_rule(rule);
+        None
+    }
+
+    /// Run until the run finishes, which may never happen if completion diverges and no limits
+    /// are set.
+    pub fn run(&mut self) -> Outcome<V, O> {
+        loop {
+            if let Some(outcome) = self.step() {
+                return outcome;
+            }
+        }
+    }
+
+    /// Run for approximately the given time, then return control. Returns None if the run has not
+    /// finished, in which case calling run_for again continues where it left off.
+    pub fn run_for(&mut self, budget: Duration) -> Option<Outcome<V, O>> {
+        let start = Instant::now();
+        loop {
+            if let Some(outcome) = self.step() {
+                return Some(outcome);
+            }
+            if start.elapsed() >= budget {
+                return None;
+            }
+        }
+    }
+
+    /*
+     * Decide whether s and t are equal in the theory of the axioms, as far as the run has got.
+     * If the rules join s and t they are equal, and once the run is convergent, they are equal
+     * only if the rules join them. If the run hasn't converged, the rules alone can't show that
+     * s and t differ, so this returns None unless search_steps is given. In that case it falls
+     * back to a breadth-first search of at most that many steps between the normal forms of s
+     * and t, using the rules and the pending equations in both directions. A path found is a
+     * proof that they are equal; otherwise the answer is a best-effort not equal.
+     *
+     * The search doesn't use an equation right to left when the right side is a variable, since
+     * that would apply at every position of every word.
+     */
+    pub fn equivalent(
+        &self,
+        s: &Word<V, O>,
+        t: &Word<V, O>,
+        search_steps: Option<usize>,
+    ) -> Option<Equivalence> {
+        let definitive = |equal| {
+            Some(Equivalence {
+                equal,
+                definitive: true,
+            })
+        };
+        if let Some(Outcome::Trivial(_)) = self.outcome {
+            return definitive(true);
+        }
+        let s = self.normalize(s);
+        let t = self.normalize(t);
+        if s.syms == t.syms {
+            return definitive(true);
+        }
+        if let Some(Outcome::Convergent) = self.outcome {
+            return definitive(false);
+        }
+        let mut rules = Vec::new();
+        let eqs = self
+            .rules
+            .iter()
+            .map(|r| (&r.left, &r.right))
+            .chain(self.pending().map(|a| (&a.left, &a.right)));
+        for (l, r) in eqs {
+            for (l, r) in [(l, r), (r, l)] {
+                if l.size() > 1 && r.vars().is_subset(&l.vars()) {
+                    rules.push(Rule {
+                        left: l.clone(),
+                        right: r.clone(),
+                    });
+                }
+            }
+        }
+        let equal = Trs::new(rules).reachable(&s, &t, search_steps?).is_some();
+        Some(Equivalence {
+            equal,
+            definitive: equal,
+        })
+    }
+}
+
+// normalize_in_parallel, which splits normalizing the equations between threads and says
+// whether it did.
+#[cfg(feature = "parallel")]
+type NormalizeInParallel<V, O> =
+    fn(&[Rule<V, O>], &DiscriminationTree<O>, &mut [Axiom<V, O>], Parallelism) -> bool;
+
+// Normalize each side of each equation with the rules and their index.
+fn normalize_each<V: Variable, O: Operator>(
+    rules: &[Rule<V, O>],
+    index: &DiscriminationTree<O>,
+    eqs: &mut [Axiom<V, O>],
+) {
+    let mut arena = TermArena::new();
+    for eq in eqs {
+        for side in [&mut eq.left, &mut eq.right] {
+            *side = normalize_steps(rules, index, side, &mut arena, None).unwrap_or_else(|w| w);
+        }
+    }
+}
+
+/// How simplify_pending_in_parallel splits the pending equations between threads.
+#[cfg(feature = "parallel")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Parallelism {
+    /// The number of threads, by default as many as the machine runs at once.
+    pub threads: usize,
+    /// Fewer pending equations than this are normalized on one thread, which is quicker than
+    /// starting others. 256 by default.
+    pub min_equations: usize,
+}
+
+#[cfg(feature = "parallel")]
+impl Default for Parallelism {
+    fn default() -> Parallelism {
+        Parallelism {
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            min_equations: 256,
+        }
+    }
+}
+
+// normalize_each with the equations split between threads as parallelism says, returning
+// whether they were.
+#[cfg(feature = "parallel")]
+fn normalize_in_parallel<V: Variable + Send + Sync, O: Operator + Send + Sync>(
+    rules: &[Rule<V, O>],
+    index: &DiscriminationTree<O>,
+    eqs: &mut [Axiom<V, O>],
+    parallelism: Parallelism,
+) -> bool {
+    if parallelism.threads <= 1 || eqs.len() < parallelism.min_equations {
+        normalize_each(rules, index, eqs);
+        return false;
+    }
+    let chunk = eqs.len().div_ceil(parallelism.threads);
+    std::thread::scope(|scope| {
+        for part in eqs.chunks_mut(chunk) {
+            scope.spawn(|| normalize_each(rules, index, part));
+        }
+    });
+    true
+}
+
+#[cfg(feature = "parallel")]
+impl<V, O, R> Completion<V, O, R>
+where
+    V: Variable + Send + Sync,
+    O: Operator + Send + Sync,
+    R: ReductionOrder<V, O>,
+{
+    /// Turn on simplify_pending, and split the normalizing between threads when there are many
+    /// pending equations, as parallelism says. Each is normalized on its own, so the run is the
+    /// same as on one thread.
+    pub fn simplify_pending_in_parallel(&mut self) {
+        self.simplify_pending = true;
+        self.normalize_in_parallel = Some(normalize_in_parallel);
+    }
+}
+
+/*
+ * Check whether s is a variable that doesn't occur in t.
+ */
+fn is_collapsing<V: Variable, O: Operator>(s: &Word<V, O>, t: &Word<V, O>) -> bool {
+    match s.syms.first() {
+        Some(Var(v)) => s.syms.len() == 1 && !t.vars().contains(v),
+        _ => false,
+    }
+}
+
+/// Run Knuth-Bendix completion on the axioms with the default ordering, returning the completed
+/// rules if it succeeds. No axioms give the empty system.
+pub fn knuth_bendix<V: Variable, O: Operator>(
+    axioms: &[Equation<V, O>],
+) -> Option<Vec<Rule<V, O>>> {
+    let mut c = Completion::new(axioms);
+    match c.run() {
+        Outcome::Convergent => Some(c.rules),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::prod::*;
+    use crate::theories::group;
+    use crate::{knuth_bendix, Axiom, Completion, Equivalence, Outcome};
+
+    #[test]
+    fn group() {
+        let rules = knuth_bendix(&group::axioms()).unwrap();
+        let trs = crate::Trs::new(rules);
+        assert!(trs.is_locally_confluent().is_locally_confluent());
+        let a = var("a");
+        let b = var("b");
+        let w = inv(&(&a * &b)) * (&a * (&b * inv(&inv(&one()))));
+        assert_eq!(trs.normalize(&w), one());
+        assert_eq!(trs.normalize(&(&a * inv(&a))), one());
+    }
+
+    #[test]
+    fn run_for() {
+        let mut c = Completion::new(&group::axioms());
+        let mut slices = 0;
+        let outcome = loop {
+            slices += 1;
+            if let Some(outcome) = c.run_for(Duration::ZERO) {
+                break outcome;
+            }
+        };
+        assert!(matches!(outcome, Outcome::Convergent));
+        assert!(slices > 1);
+        let rules = knuth_bendix(&group::axioms()).unwrap();
+        assert_eq!(c.rules().len(), rules.len());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn trace() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        // A subscriber that keeps the message of every event.
+        struct Messages(Arc<Mutex<Vec<String>>>);
+        struct Message(Option<String>);
+        impl Visit for Message {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0 = Some(format!("{:?}", value));
+                }
+            }
+        }
+        impl Subscriber for Messages {
+            fn enabled(&self, _: &Metadata) -> bool {
+                true
+            }
+            fn new_span(&self, _: &Attributes) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event) {
+                let mut m = Message(None);
+                event.record(&mut m);
+                self.0.lock().unwrap().extend(m.0);
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        tracing::subscriber::with_default(Messages(messages.clone()), || {
+            knuth_bendix(&group::axioms()).unwrap();
+        });
+        let messages = messages.lock().unwrap();
+        let count = |m: &str| messages.iter().filter(|s| *s == m).count();
+        assert!(count("rule added") >= 10);
+        assert!(count("rule removed") > 0);
+        assert!(count("critical pair") > 0);
+        assert_eq!(count("deferred"), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn checkpoint() {
+        let mut c = Completion::new(&group::axioms());
+        for _ in 0..10 {
+            assert!(c.step().is_none());
+        }
+        let json = serde_json::to_string(&c).unwrap();
+        let mut resumed: Completion<String, Prod> = serde_json::from_str(&json).unwrap();
+        assert_eq!(resumed.rules(), c.rules());
+        assert!(matches!(resumed.run(), Outcome::Convergent));
+        c.run();
+        assert_eq!(resumed.rules(), c.rules());
+
+        let trs: crate::Trs<String, Prod> =
+            serde_json::from_str(&serde_json::to_string(&c.trs()).unwrap()).unwrap();
+        assert_eq!(trs.rules(), c.rules());
+        // A word must be well formed.
+        let bad = r#"{"syms":[{"Op":"Mul"},{"Var":"x"}]}"#;
+        assert!(serde_json::from_str::<Word>(bad).is_err());
+    }
+
+    #[test]
+    fn stats() {
+        let mut c = Completion::new(&group::axioms());
+        assert!(matches!(c.run(), Outcome::Convergent));
+        let s = c.stats();
+        assert_eq!(s.rules_added - s.rules_deleted, c.rules().len());
+        assert!(s.critical_pairs > 0 && s.pruned > 0);
+        assert!(s.equations >= s.pruned + s.rules_added);
+        assert!(s.max_size >= 5);
+    }
+
+    #[test]
+    fn simplify_pending() {
+        let mut lazy = Completion::new(&group::axioms());
+        assert!(matches!(lazy.run(), Outcome::Convergent));
+        let mut eager = Completion::new(&group::axioms());
+        eager.simplify_pending = true;
+        assert!(matches!(eager.run(), Outcome::Convergent));
+        assert!(eager
+            .trs()
+            .equivalent_to(&eager.order, &lazy.trs(), &lazy.order)
+            .is_equivalent());
+        assert_eq!(lazy.stats().pruned_pending, 0);
+        assert!(eager.stats().pruned_pending > 0);
+
+        #[cfg(feature = "parallel")]
+        {
+            // Few enough equations are pending that the default never splits them.
+            let mut parallel = Completion::new(&group::axioms());
+            parallel.simplify_pending_in_parallel();
+            parallel.parallelism = crate::Parallelism {
+                threads: 4,
+                min_equations: 8,
+            };
+            assert!(matches!(parallel.run(), Outcome::Convergent));
+            assert!(parallel.stats().parallel_splits > 0);
+            assert_eq!(parallel.rules(), eager.rules());
+            assert_eq!(
+                parallel.stats().pruned_pending,
+                eager.stats().pruned_pending
+            );
+        }
+    }
+
+    #[test]
+    fn limits() {
+        let mut c = Completion::new(&group::axioms());
+        c.max_rules = Some(2);
+        assert!(matches!(c.run(), Outcome::Limit));
+        assert!(c.rules().len() <= 2);
+        let mut c = Completion::new(&group::axioms());
+        c.max_steps = Some(1);
+        assert!(matches!(c.run(), Outcome::Limit));
+    }
+
+    #[test]
+    fn unorientable() {
+        let x = var("x");
+        let y = var("y");
+        let comm = Axiom {
+            left: &x * &y,
+            right: &y * &x,
+        };
+        let mut c = Completion::new(&[comm]);
+        match c.run() {
+            Outcome::Unorientable(eqs) => assert_eq!(eqs.len(), 1),
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        }
+    }
+
+    #[test]
+    fn equivalent() {
+        let (x, y) = (var("x"), var("y"));
+        let (a, b) = (var("a"), var("b"));
+        let mut c = Completion::new(&[
+            Axiom {
+                left: &x * &y,
+                right: &y * &x,
+            },
+            Axiom {
+                left: one() * &x,
+                right: x.clone(),
+            },
+        ]);
+        assert!(matches!(c.run(), Outcome::Unorientable(_)));
+        let yes = Equivalence {
+            equal: true,
+            definitive: true,
+        };
+        assert_eq!(c.equivalent(&(one() * &a), &a, None), Some(yes));
+        assert_eq!(c.equivalent(&(&a * &b), &(&b * &a), None), None);
+        assert_eq!(c.equivalent(&(&a * one()), &a, Some(2)), Some(yes));
+        assert_eq!(
+            c.equivalent(&(&a * &b), &(&a * &a), Some(3)),
+            Some(Equivalence {
+                equal: false,
+                definitive: false
+            })
+        );
+
+        let mut c = Completion::new(&[Axiom {
+            left: one() * &x,
+            right: x.clone(),
+        }]);
+        assert!(matches!(c.run(), Outcome::Convergent));
+        assert_eq!(
+            c.equivalent(&(&a * one()), &a, None),
+            Some(Equivalence {
+                equal: false,
+                definitive: true
+            })
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let mut c = Completion::<String, Prod>::new(&[]);
+        assert!(matches!(c.run(), Outcome::Convergent));
+        assert!(c.rules().is_empty());
+        assert_eq!(knuth_bendix::<String, Prod>(&[]).map(|r| r.len()), Some(0));
+    }
+
+    #[test]
+    fn identity() {
+        let x = var("x");
+        let id = Axiom {
+            left: &x * one(),
+            right: &x * one(),
+        };
+        assert_eq!(knuth_bendix(&[id]).map(|r| r.len()), Some(0));
+    }
+
+    #[test]
+    fn single_ground() {
+        let a = var("a");
+        let ground = Axiom {
+            left: one(),
+            right: inv(&one()) * one(),
+        };
+        let rules = knuth_bendix(&[ground]).unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].left, inv(&one()) * one());
+        assert_eq!(rules[0].right, one());
+        let trs = crate::Trs::new(rules);
+        assert_eq!(trs.normalize(&(&a * (inv(&one()) * one()))), &a * one());
+    }
+
+    #[test]
+    fn orient_by_hand() {
+        // Each side has a variable more often than the other, so Kbo can't orient it, but
+        // f(x, x, y) → g(x, y, y) terminates.
+        let mut sig = crate::Signature::new();
+        sig.declare("f", 3, 1).unwrap();
+        sig.declare("g", 3, 1).unwrap();
+        let mut c = Completion::new(&[Axiom {
+            left: crate::term!(sig, g(x, y, y)),
+            right: crate::term!(sig, f(x, x, y)),
+        }]);
+        assert!(matches!(c.run(), Outcome::Unorientable(eqs) if eqs.len() == 1));
+        c.orient_unorientable(0, false);
+        assert!(matches!(c.run(), Outcome::Convergent));
+        assert_eq!(c.rules()[0].left, crate::term!(sig, f(x, x, y)));
+        assert!(c.pending().next().is_none());
+    }
+
+    #[test]
+    fn trivial() {
+        let x = var("x");
+        let y = var("y");
+        for eq in [
+            Axiom {
+                left: x.clone(),
+                right: one(),
+            },
+            Axiom {
+                left: x.clone(),
+                right: y.clone(),
+            },
+            Axiom {
+                left: &x * &x,
+                right: y.clone(),
+            },
+        ] {
+            let mut c = Completion::new(&[eq]);
+            assert!(matches!(c.run(), Outcome::Trivial(_)));
+        }
+        // A trivial theory can also be implied rather than stated, e.g. by a left identity that
+        // is also a constant left zero.
+        let mut c = Completion::new(&[
+            Axiom {
+                left: one() * &x,
+                right: x.clone(),
+            },
+            Axiom {
+                left: one() * &x,
+                right: one(),
+            },
+        ]);
+        match c.run() {
+            Outcome::Trivial(eq) => {
+                assert!(eq.left.syms.len() == 1 || eq.right.syms.len() == 1)
+            }
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::word::{Operator, Position, Symbol, Variable, Word};
+
+/*
+ * A word with a single hole in it, into which another word can be placed. Since subwords are
+ * contiguous in the flat encoding, a context is just the symbols before the hole and the symbols
+ * after it.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Context<V: Variable, O: Operator> {
+    pub before: Vec<Symbol<V, O>>,
+    pub after: Vec<Symbol<V, O>>,
+}
+
+impl<V: Variable, O: Operator> Context<V, O> {
+    /// The context with the hole at the root, which applies to a word as the word itself.
+    pub fn hole() -> Context<V, O> {
+        Context {
+            before: Vec::new(),
+            after: Vec::new(),
+        }
+    }
+
+    /// The context of the subword of w at position p, or None if there is no subword at p.
+    pub fn new(w: &Word<V, O>, p: &Position) -> Option<Context<V, O>> {
+        let i = w.index_at(p)?;
+        Some(Context {
+            before: w.syms[..i].to_vec(),
+            after: w.syms[w.subword_end(i)..].to_vec(),
+        })
+    }
+
+    pub fn is_hole(&self) -> bool {
+        self.before.is_empty() && self.after.is_empty()
+    }
+
+    /// Fill the hole with w.
+    pub fn apply(&self, w: Word<V, O>) -> Word<V, O> {
+        let mut syms = Vec::with_capacity(self.before.len() + w.syms.len() + self.after.len());
+        syms.extend_from_slice(&self.before);
+        syms.extend(w.syms);
+        syms.extend_from_slice(&self.after);
+        Word { syms }
+    }
+
+    /// The context that fills this one's hole with inner.
+    pub fn compose(&self, inner: &Context<V, O>) -> Context<V, O> {
+        Context {
+            before: [self.before.as_slice(), inner.before.as_slice()].concat(),
+            after: [inner.after.as_slice(), self.after.as_slice()].concat(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::*;
+
+    #[test]
+    fn apply() {
+        let (a, b, c) = (var("a"), var("b"), var("c"));
+        let w = (&a * inv(&b)) * &c;
+        for (p, sub) in w.positions() {
+            let ctx = Context::new(&w, &p).unwrap();
+            assert_eq!(ctx.apply(sub), w);
+            assert_eq!(ctx.apply(one()), w.replace_at(&p, one()).unwrap());
+        }
+        assert!(Context::new(&w, &Position(vec![0, 1, 1])).is_none());
+        assert!(Context::new(&w, &Position::root()).unwrap().is_hole());
+
+        let outer = Context::new(&w, &Position(vec![0])).unwrap();
+        let inner = Context::new(&(&a * inv(&b)), &Position(vec![1, 0])).unwrap();
+        assert_eq!(
+            outer.compose(&inner),
+            Context::new(&w, &Position(vec![0, 1, 0])).unwrap()
+        );
+        assert_eq!(Context::hole().apply(c.clone()), c);
+    }
+}
@@ -0,0 +1,208 @@
+/*
+ * Certificates in CPF, the Certification Problem Format, which CeTA checks independently of the
+ * tool that made them. A certificate says a system is confluent because it's terminating and
+ * locally confluent: termination is shown by removing every rule with the Knuth-Bendix ordering
+ * Kbo uses, given by the operators' weights and the precedence derived from their Ord, and local
+ * confluence is left to CeTA to confirm by joining the critical pairs itself. The certificate is
+ * only made once the crate has checked both, so a failure is reported here rather than by CeTA.
+ */
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt::{self, Display, Write};
+
+use crate::kbo;
+use crate::order::Kbo;
+use crate::relation::Rule;
+use crate::trs::{CriticalPair, Trs};
+use crate::word::{Op, Operator, Var, Variable, WordRef};
+
+/// Why no certificate could be made.
+#[derive(Clone, Debug)]
+pub enum CertificateError<V: Variable, O: Operator> {
+    /// Kbo doesn't make this rule decreasing.
+    NotDecreasing(Rule<V, O>),
+    /// This critical pair isn't joinable.
+    NotJoinable(CriticalPair<V, O>),
+}
+
+impl<V: Variable, O: Operator> Display for CertificateError<V, O>
+where
+    Rule<V, O>: Display,
+    crate::word::Word<V, O>: Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CertificateError::NotDecreasing(r) => {
+                write!(f, "the ordering doesn't orient {}", r)
+            }
+            CertificateError::NotJoinable(cp) => write!(
+                f,
+                "the critical pair {} = {} isn't joinable",
+                cp.left, cp.right
+            ),
+        }
+    }
+}
+
+impl<V: Variable, O: Operator> Error for CertificateError<V, O>
+where
+    Rule<V, O>: Display,
+    crate::word::Word<V, O>: Display,
+{
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn write_term<V: Variable + Display, O: Operator + Display>(
+    out: &mut String,
+    w: WordRef<'_, V, O>,
+) {
+    match &w.syms[0] {
+        Var(v) => write!(out, "<var>{}</var>", escape(&v.to_string())).unwrap(),
+        Op(o) => {
+            write!(out, "<funapp><name>{}</name>", escape(o.to_string().trim())).unwrap();
+            for a in w.subwords() {
+                out.push_str("<arg>");
+                write_term(out, a);
+                out.push_str("</arg>");
+            }
+            out.push_str("</funapp>");
+        }
+    }
+}
+
+/// A CPF certificate that trs is confluent, if the crate can show it terminates by Kbo and its
+/// critical pairs are joinable.
+pub fn certificate<V: Variable + Display, O: Operator + Display>(
+    trs: &Trs<V, O>,
+) -> Result<String, CertificateError<V, O>> {
+    trs.terminates_by(&Kbo)
+        .map_err(CertificateError::NotDecreasing)?;
+    if let Some(cp) = trs
+        .is_locally_confluent()
+        .counterexamples
+        .into_iter()
+        .next()
+    {
+        return Err(CertificateError::NotJoinable(cp));
+    }
+
+    let mut out = String::from(
+        "<?xml version=\"1.0\"?>\n\
+         <certificationProblem xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:noNamespaceSchemaLocation=\"cpf.xsd\">\n\
+         <input><trsInput><trs><rules>\n",
+    );
+    for r in trs.rules() {
+        out.push_str("<rule><lhs>");
+        write_term(&mut out, r.left.view());
+        out.push_str("</lhs><rhs>");
+        write_term(&mut out, r.right.view());
+        out.push_str("</rhs></rule>\n");
+    }
+    out.push_str(
+        "</rules></trs></trsInput></input>\n\
+         <cpfVersion>2.1</cpfVersion>\n\
+         <proof><crProof><wcrAndSN>\n\
+         <trsTerminationProof><ruleRemoval><orderingConstraintProof><redPair>\
+         <knuthBendixOrder>",
+    );
+    writeln!(out, "<w0>{}</w0><precedenceWeight>", O::min_weight()).unwrap();
+    // The operators in increasing precedence.
+    let ops: BTreeSet<&O> = trs
+        .rules()
+        .iter()
+        .flat_map(|r| r.left.syms.iter().chain(&r.right.syms))
+        .filter_map(|s| match s {
+            Op(o) => Some(o),
+            Var(_) => None,
+        })
+        .collect();
+    let mut ops: Vec<&O> = ops.into_iter().collect();
+    ops.sort_by(|f, g| kbo::derived_precedence(*f, *g, |h| h.weight() == 0));
+    for (i, o) in ops.into_iter().enumerate() {
+        writeln!(
+            out,
+            "<precedenceWeightEntry><name>{}</name><arity>{}</arity><precedence>{}</precedence>\
+             <weight>{}</weight></precedenceWeightEntry>",
+            escape(o.to_string().trim()),
+            o.arity(),
+            i,
+            o.weight()
+        )
+        .unwrap();
+    }
+    out.push_str(
+        "</precedenceWeight></knuthBendixOrder></redPair></orderingConstraintProof>\
+         <trs><rules/></trs><trsTerminationProof><rIsEmpty/></trsTerminationProof>\
+         </ruleRemoval></trsTerminationProof>\n\
+         <wcrProof><joinableCriticalPairsAuto/></wcrProof>\n\
+         </wcrAndSN></crProof></proof>\n",
+    );
+    writeln!(
+        out,
+        "<origin><proofOrigin><tool><name>knuth-bendix</name><version>{}</version></tool>\
+         </proofOrigin></origin>\n</certificationProblem>",
+        env!("CARGO_PKG_VERSION")
+    )
+    .unwrap();
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::knuth_bendix;
+    use crate::prod::{inv, one, var};
+    use crate::relation::Equation;
+
+    #[test]
+    fn groups() {
+        let (x, y, z) = (var("x"), var("y"), var("z"));
+        let axioms = [
+            Equation {
+                left: one() * &x,
+                right: x.clone(),
+            },
+            Equation {
+                left: inv(&x) * &x,
+                right: one(),
+            },
+            Equation {
+                left: (&x * &y) * &z,
+                right: &x * (&y * &z),
+            },
+        ];
+        let trs = Trs::new(knuth_bendix(&axioms).unwrap());
+        let cpf = certificate(&trs).unwrap();
+        assert_eq!(cpf.matches("<rule>").count(), 10);
+        assert!(cpf.contains(
+            "<rule><lhs><funapp><name>*</name><arg><funapp><name>1</name></funapp></arg>\
+             <arg><var>x</var></arg></funapp></lhs><rhs><var>x</var></rhs></rule>\n"
+        ));
+        assert!(cpf.contains(
+            "<precedenceWeightEntry><name>⁻¹</name><arity>1</arity><precedence>2</precedence>\
+             <weight>0</weight></precedenceWeightEntry>\n"
+        ));
+
+        // The axioms themselves aren't confluent.
+        let partial = Trs::new(axioms.iter().map(|eq| eq.orient(&Kbo).unwrap()).collect());
+        assert!(matches!(
+            certificate(&partial),
+            Err(CertificateError::NotJoinable(_))
+        ));
+        let backwards = Trs::new(vec![Rule {
+            left: x.clone(),
+            right: one() * &x,
+        }]);
+        assert_eq!(
+            certificate(&backwards).unwrap_err().to_string(),
+            "the ordering doesn't orient x → 1 * x"
+        );
+    }
+}
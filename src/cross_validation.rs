@@ -0,0 +1,331 @@
+/*
+ * Cross-validation of the flat word engine against a reference implementation on boxed trees.
+ *
+ * The flat encoding makes rewriting a matter of index arithmetic on the symbol vector, which is
+ * easy to get subtly wrong (positions shift when a substitution grows a word, subwords end where
+ * argument counting says they do). The tree implementation below does the same operations the
+ * obvious recursive way, and the tests run both on the same rules and generated words and
+ * require identical one-step reducts, normal forms, and critical pairs.
+ */
+use std::collections::BTreeMap;
+
+use crate::prod::*;
+use crate::{knuth_bendix, Axiom, Op, Operator, Rule, Trs, Var, Variable};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Tree<V: Variable, O: Operator> {
+    Var(V),
+    App(O, Vec<Tree<V, O>>),
+}
+
+type T = Tree<String, Prod>;
+type Subst = BTreeMap<String, T>;
+
+fn from_word(w: &Word) -> T {
+    match &w.syms[0] {
+        Var(v) => Tree::Var(v.clone()),
+        Op(f) => Tree::App(
+            f.clone(),
+            w.subwords().map(|a| from_word(&a.to_word())).collect(),
+        ),
+    }
+}
+
+fn to_word(t: &T) -> Word {
+    match t {
+        Tree::Var(v) => Word::var(v.clone()),
+        Tree::App(f, args) => {
+            let args: Vec<Word> = args.iter().map(to_word).collect();
+            Word::op(f.clone(), &args)
+        }
+    }
+}
+
+fn subst(t: &T, s: &Subst) -> T {
+    match t {
+        Tree::Var(v) => s.get(v).cloned().unwrap_or_else(|| t.clone()),
+        Tree::App(f, args) => Tree::App(f.clone(), args.iter().map(|a| subst(a, s)).collect()),
+    }
+}
+
+fn matches(pat: &T, t: &T, s: &mut Subst) -> bool {
+    match (pat, t) {
+        (Tree::Var(v), _) => match s.get(v) {
+            Some(b) => b == t,
+            None => {
+                s.insert(v.clone(), t.clone());
+                true
+            }
+        },
+        (Tree::App(f, ps), Tree::App(g, ts)) => {
+            f == g && ps.iter().zip(ts.iter()).all(|(p, t)| matches(p, t, s))
+        }
+        _ => false,
+    }
+}
+
+fn occurs(v: &String, t: &T) -> bool {
+    match t {
+        Tree::Var(u) => u == v,
+        Tree::App(_, args) => args.iter().any(|a| occurs(v, a)),
+    }
+}
+
+fn unify(a: &T, b: &T, s: &mut Subst) -> bool {
+    let a = subst(a, s);
+    let b = subst(b, s);
+    match (&a, &b) {
+        (Tree::Var(v), Tree::Var(u)) if u == v => true,
+        (Tree::Var(v), t) | (t, Tree::Var(v)) => {
+            if occurs(v, t) {
+                return false;
+            }
+            let bind = Subst::from([(v.clone(), t.clone())]);
+            for b in s.values_mut() {
+                *b = subst(b, &bind);
+            }
+            s.insert(v.clone(), t.clone());
+            true
+        }
+        (Tree::App(f, xs), Tree::App(g, ys)) => {
+            f == g && xs.iter().zip(ys.iter()).all(|(x, y)| unify(x, y, s))
+        }
+    }
+}
+
+// Positions in preorder, which is the order of the flat symbol vector.
+fn positions(t: &T) -> Vec<Vec<usize>> {
+    let mut out = vec![vec![]];
+    if let Tree::App(_, args) = t {
+        for (i, a) in args.iter().enumerate() {
+            for mut p in positions(a) {
+                p.insert(0, i);
+                out.push(p);
+            }
+        }
+    }
+    out
+}
+
+fn at<'a>(t: &'a T, p: &[usize]) -> &'a T {
+    match (t, p.split_first()) {
+        (Tree::App(_, args), Some((i, rest))) => at(&args[*i], rest),
+        _ => t,
+    }
+}
+
+fn replace(t: &T, p: &[usize], u: &T) -> T {
+    match (t, p.split_first()) {
+        (Tree::App(f, args), Some((i, rest))) => {
+            let mut args = args.clone();
+            args[*i] = replace(&args[*i], rest, u);
+            Tree::App(f.clone(), args)
+        }
+        _ => u.clone(),
+    }
+}
+
+fn rewrites(rules: &[(T, T)], t: &T) -> Vec<T> {
+    let mut out = Vec::new();
+    for p in positions(t) {
+        for (l, r) in rules {
+            let mut s = Subst::new();
+            if matches(l, at(t, &p), &mut s) {
+                out.push(replace(t, &p, &subst(r, &s)));
+            }
+        }
+    }
+    out
+}
+
+fn normalize(rules: &[(T, T)], t: &T) -> T {
+    let mut t = t.clone();
+    while let Some(r) = rewrites(rules, &t).into_iter().next() {
+        t = r;
+    }
+    t
+}
+
+fn vars(t: &T, out: &mut Vec<String>) {
+    match t {
+        Tree::Var(v) => {
+            if !out.contains(v) {
+                out.push(v.clone())
+            }
+        }
+        Tree::App(_, args) => args.iter().for_each(|a| vars(a, out)),
+    }
+}
+
+fn critical_pairs(rules: &[(T, T)]) -> Vec<(T, T)> {
+    let mut out = Vec::new();
+    for (i, (l1, r1)) in rules.iter().enumerate() {
+        let mut avoid = Vec::new();
+        vars(l1, &mut avoid);
+        vars(r1, &mut avoid);
+        for (j, (l2, r2)) in rules.iter().enumerate() {
+            let mut inner = Vec::new();
+            vars(l2, &mut inner);
+            vars(r2, &mut inner);
+            let mut taken = avoid.clone();
+            taken.extend(inner.iter().cloned());
+            let mut ren = Subst::new();
+            for v in inner.iter().filter(|v| avoid.contains(v)) {
+                let mut nv = v.prime();
+                while taken.contains(&nv) {
+                    nv = nv.prime();
+                }
+                taken.push(nv.clone());
+                ren.insert(v.clone(), Tree::Var(nv));
+            }
+            let (l2, r2) = (subst(l2, &ren), subst(r2, &ren));
+            for p in positions(l1) {
+                if matches!(at(l1, &p), Tree::Var(_)) || (i == j && p.is_empty()) {
+                    continue;
+                }
+                let mut s = Subst::new();
+                if unify(at(l1, &p), &l2, &mut s) {
+                    let peak = subst(l1, &s);
+                    out.push((subst(r1, &s), replace(&peak, &p, &subst(&r2, &s))));
+                }
+            }
+        }
+    }
+    out
+}
+
+// Rename the variables of a pair by first occurrence, so pairs can be compared up to renaming.
+fn canonical(l: &Word, r: &Word) -> (Vec<Symbol>, Vec<Symbol>) {
+    let mut names = BTreeMap::new();
+    let mut rename = |w: &Word| -> Vec<Symbol> {
+        w.syms
+            .iter()
+            .map(|s| match s {
+                Var(v) => {
+                    let n = names.len();
+                    Var(names.entry(v.clone()).or_insert(format!("_{}", n)).clone())
+                }
+                Op(f) => Op(f.clone()),
+            })
+            .collect()
+    };
+    (rename(l), rename(r))
+}
+
+// A small deterministic generator, so failures are reproducible.
+struct Gen(u64);
+
+impl Gen {
+    fn next(&mut self, n: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % n
+    }
+
+    fn word(&mut self, depth: usize) -> Word {
+        let choices = if depth == 0 { 2 } else { 5 };
+        match self.next(choices) {
+            0 => var(["a", "b", "c"][self.next(3) as usize]),
+            1 => one(),
+            2 => inv(&self.word(depth - 1)),
+            _ => self.word(depth - 1) * self.word(depth - 1),
+        }
+    }
+}
+
+fn group_rules() -> Vec<Rule<String, Prod>> {
+    let x = var("x");
+    let y = var("y");
+    let z = var("z");
+    knuth_bendix(&[
+        Axiom {
+            left: one() * &x,
+            right: x.clone(),
+        },
+        Axiom {
+            left: inv(&x) * &x,
+            right: one(),
+        },
+        Axiom {
+            left: (&x * &y) * &z,
+            right: &x * (&y * &z),
+        },
+    ])
+    .unwrap()
+}
+
+fn tree_rules(rules: &[Rule<String, Prod>]) -> Vec<(T, T)> {
+    rules
+        .iter()
+        .map(|r| (from_word(&r.left), from_word(&r.right)))
+        .collect()
+}
+
+#[test]
+fn conversion_round_trip() {
+    let mut g = Gen(0x2545f4914f6cdd1d);
+    for _ in 0..200 {
+        let w = g.word(5);
+        assert_eq!(to_word(&from_word(&w)).syms, w.syms);
+    }
+}
+
+#[test]
+fn rewrites_and_normal_forms() {
+    let rules = group_rules();
+    let trs = Trs::new(rules.clone());
+    let trees = tree_rules(&rules);
+    let mut g = Gen(0x9e3779b97f4a7c15);
+    for _ in 0..200 {
+        let w = g.word(5);
+        let t = from_word(&w);
+        let flat: Vec<_> = trs.rewrites(&w).into_iter().map(|r| r.syms).collect();
+        let tree: Vec<_> = rewrites(&trees, &t)
+            .iter()
+            .map(|r| to_word(r).syms)
+            .collect();
+        assert_eq!(flat, tree, "one-step reducts of {}", w);
+        assert_eq!(
+            trs.normalize(&w).syms,
+            to_word(&normalize(&trees, &t)).syms,
+            "normal form of {}",
+            w
+        );
+    }
+}
+
+#[test]
+fn critical_pairs_agree() {
+    let x = var("x");
+    let y = var("y");
+    let z = var("z");
+    let axioms = vec![
+        Rule {
+            left: one() * &x,
+            right: x.clone(),
+        },
+        Rule {
+            left: inv(&x) * &x,
+            right: one(),
+        },
+        Rule {
+            left: (&x * &y) * &z,
+            right: &x * (&y * &z),
+        },
+    ];
+    for rules in [axioms, group_rules()] {
+        let mut flat: Vec<_> = Trs::new(rules.clone())
+            .critical_pairs()
+            .iter()
+            .map(|cp| canonical(&cp.left, &cp.right))
+            .collect();
+        let mut tree: Vec<_> = critical_pairs(&tree_rules(&rules))
+            .iter()
+            .map(|(l, r)| canonical(&to_word(l), &to_word(r)))
+            .collect();
+        flat.sort();
+        tree.sort();
+        assert_eq!(flat, tree);
+    }
+}
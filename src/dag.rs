@@ -0,0 +1,360 @@
+/*
+ * Words as nodes of a shared DAG. A Dag is an arena of nodes, each a variable or an operator
+ * applied to earlier nodes, and every node is stored once: building a node equal to an existing
+ * one returns the existing one. A word with a lot of repeated structure, like the ones
+ * completion builds by substituting the same subword for several variables, then takes space
+ * proportional to its distinct subwords rather than its length, and equal subwords have equal
+ * handles.
+ *
+ * Rewriting works bottom up, normalizing arguments before trying rules at the root, and caches
+ * the normal form of every node it visits, so a shared subword is normalized once.
+ */
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::relation::Rule;
+use crate::word::{Op, Operator, Var, Variable, Word};
+
+/// A handle to a node of a Dag, standing for the word rooted there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DagWord(pub usize);
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Node<V: Variable, O: Operator> {
+    Var(V),
+    App(O, Vec<DagWord>),
+}
+
+/// A rule whose sides are nodes of a Dag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DagRule {
+    pub left: DagWord,
+    pub right: DagWord,
+}
+
+pub type DagSubst<V> = BTreeMap<V, DagWord>;
+
+#[derive(Clone, Debug)]
+pub struct Dag<V: Variable, O: Operator> {
+    nodes: Vec<Node<V, O>>,
+    ids: BTreeMap<Node<V, O>, DagWord>,
+}
+
+impl<V: Variable, O: Operator> Default for Dag<V, O> {
+    fn default() -> Self {
+        Dag {
+            nodes: Vec::new(),
+            ids: BTreeMap::new(),
+        }
+    }
+}
+
+impl<V: Variable, O: Operator> Dag<V, O> {
+    pub fn new() -> Dag<V, O> {
+        Dag::default()
+    }
+
+    /// The number of distinct nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn node(&self, w: DagWord) -> &Node<V, O> {
+        &self.nodes[w.0]
+    }
+
+    fn insert(&mut self, node: Node<V, O>) -> DagWord {
+        if let Some(&w) = self.ids.get(&node) {
+            return w;
+        }
+        let w = DagWord(self.nodes.len());
+        self.nodes.push(node.clone());
+        self.ids.insert(node, w);
+        w
+    }
+
+    pub fn var(&mut self, v: V) -> DagWord {
+        self.insert(Node::Var(v))
+    }
+
+    pub fn app(&mut self, f: O, args: Vec<DagWord>) -> DagWord {
+        self.insert(Node::App(f, args))
+    }
+
+    pub fn from_word(&mut self, w: &Word<V, O>) -> DagWord {
+        // Build from the end, so the arguments of each operator are already on the stack.
+        let mut stack: Vec<DagWord> = Vec::new();
+        for s in w.syms.iter().rev() {
+            let node = match s {
+                Var(v) => Node::Var(v.clone()),
+                Op(f) => {
+                    let args = stack.drain(stack.len() - f.arity()..).rev().collect();
+                    Node::App(f.clone(), args)
+                }
+            };
+            stack.push(self.insert(node));
+        }
+        stack.pop().expect("empty word")
+    }
+
+    pub fn rule(&mut self, rule: &Rule<V, O>) -> DagRule {
+        DagRule {
+            left: self.from_word(&rule.left),
+            right: self.from_word(&rule.right),
+        }
+    }
+
+    /// Unfold a node back into a flat word.
+    pub fn to_word(&self, w: DagWord) -> Word<V, O> {
+        let mut syms = Vec::new();
+        let mut todo = vec![w];
+        while let Some(w) = todo.pop() {
+            match self.node(w) {
+                Node::Var(v) => syms.push(Var(v.clone())),
+                Node::App(f, args) => {
+                    syms.push(Op(f.clone()));
+                    todo.extend(args.iter().rev());
+                }
+            }
+        }
+        Word { syms }
+    }
+
+    fn occurs(&self, v: &V, w: DagWord) -> bool {
+        let mut seen = BTreeSet::new();
+        self.occurs_memo(v, w, &mut seen)
+    }
+
+    // occurs, skipping the nodes in seen, which have already been found not to contain v, so
+    // that a shared node is only visited once.
+    fn occurs_memo(&self, v: &V, w: DagWord, seen: &mut BTreeSet<DagWord>) -> bool {
+        if !seen.insert(w) {
+            return false;
+        }
+        match self.node(w) {
+            Node::Var(u) => u == v,
+            Node::App(_, args) => args.iter().any(|&a| self.occurs_memo(v, a, seen)),
+        }
+    }
+
+    pub fn subst(&mut self, w: DagWord, s: &DagSubst<V>) -> DagWord {
+        let mut memo = BTreeMap::new();
+        self.subst_memo(w, s, &mut memo)
+    }
+
+    fn subst_memo(
+        &mut self,
+        w: DagWord,
+        s: &DagSubst<V>,
+        memo: &mut BTreeMap<DagWord, DagWord>,
+    ) -> DagWord {
+        if let Some(&r) = memo.get(&w) {
+            return r;
+        }
+        let r = match self.node(w).clone() {
+            Node::Var(v) => s.get(&v).copied().unwrap_or(w),
+            Node::App(f, args) => {
+                let args = args
+                    .into_iter()
+                    .map(|a| self.subst_memo(a, s, memo))
+                    .collect();
+                self.app(f, args)
+            }
+        };
+        memo.insert(w, r);
+        r
+    }
+
+    /// Match pattern against w, one way, extending s. Returns false if they don't match, in which
+    /// case s may have been partly extended.
+    pub fn matches(&self, pattern: DagWord, w: DagWord, s: &mut DagSubst<V>) -> bool {
+        match (self.node(pattern), self.node(w)) {
+            (Node::Var(v), _) => match s.get(v) {
+                Some(&b) => b == w,
+                None => {
+                    s.insert(v.clone(), w);
+                    true
+                }
+            },
+            (Node::App(f, ps), Node::App(g, ws)) => {
+                f == g
+                    && ps.len() == ws.len()
+                    && ps
+                        .iter()
+                        .zip(ws.iter())
+                        .all(|(&p, &w)| self.matches(p, w, s))
+            }
+            _ => false,
+        }
+    }
+
+    /// A most general unifier of a and b, with no variable bound to a word containing it.
+    pub fn unify(&mut self, a: DagWord, b: DagWord) -> Option<DagSubst<V>> {
+        let mut s = DagSubst::new();
+        self.unify_into(a, b, &mut s).then_some(s)
+    }
+
+    fn unify_into(&mut self, a: DagWord, b: DagWord, s: &mut DagSubst<V>) -> bool {
+        let a = self.subst(a, s);
+        let b = self.subst(b, s);
+        if a == b {
+            return true;
+        }
+        match (self.node(a).clone(), self.node(b).clone()) {
+            (Node::Var(v), _) => self.bind(v, b, s),
+            (_, Node::Var(v)) => self.bind(v, a, s),
+            (Node::App(f, xs), Node::App(g, ys)) => {
+                f == g
+                    && xs.len() == ys.len()
+                    && xs
+                        .into_iter()
+                        .zip(ys)
+                        .all(|(x, y)| self.unify_into(x, y, s))
+            }
+        }
+    }
+
+    fn bind(&mut self, v: V, w: DagWord, s: &mut DagSubst<V>) -> bool {
+        if self.occurs(&v, w) {
+            return false;
+        }
+        let one = DagSubst::from([(v.clone(), w)]);
+        for b in s.values_mut() {
+            *b = self.subst(*b, &one);
+        }
+        s.insert(v, w);
+        true
+    }
+
+    /*
+     * Normalize w with the rules, innermost first. Normal forms are cached in nf, which can be
+     * kept across calls with the same rules. As with Trs::normalize, this only terminates if the
+     * rules do, and the normal form is only unique if they are confluent.
+     */
+    pub fn normalize(
+        &mut self,
+        rules: &[DagRule],
+        w: DagWord,
+        nf: &mut BTreeMap<DagWord, DagWord>,
+    ) -> DagWord {
+        if let Some(&r) = nf.get(&w) {
+            return r;
+        }
+        let t = match self.node(w).clone() {
+            Node::Var(_) => w,
+            Node::App(f, args) => {
+                let args = args
+                    .into_iter()
+                    .map(|a| self.normalize(rules, a, nf))
+                    .collect();
+                self.app(f, args)
+            }
+        };
+        let mut r = t;
+        for rule in rules {
+            let mut s = DagSubst::new();
+            if self.matches(rule.left, t, &mut s) {
+                let reduct = self.subst(rule.right, &s);
+                r = self.normalize(rules, reduct, nf);
+                break;
+            }
+        }
+        nf.insert(w, r);
+        nf.insert(t, r);
+        r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::knuth_bendix;
+    use crate::prod::*;
+    use crate::{Axiom, Trs};
+
+    #[test]
+    fn sharing() {
+        let x = var("x");
+        let mut w = &x * &x;
+        for _ in 0..20 {
+            w = &w * &w;
+        }
+        let mut dag = Dag::new();
+        let d = dag.from_word(&w);
+        // The word has millions of symbols but only 22 distinct subwords.
+        assert_eq!(w.size(), (1 << 22) - 1);
+        assert_eq!(dag.len(), 22);
+        assert_eq!(dag.to_word(d), w);
+        assert_eq!(dag.from_word(&w), d);
+    }
+
+    #[test]
+    fn unify() {
+        let (x, y, z) = (var("x"), var("y"), var("z"));
+        let mut dag = Dag::new();
+        let a = dag.from_word(&(&x * inv(&y)));
+        let b = dag.from_word(&(inv(&z) * &x));
+        let s = dag.unify(a, b).unwrap();
+        let u = dag.subst(a, &s);
+        assert_eq!(u, dag.subst(b, &s));
+        assert_eq!(
+            dag.to_word(u).canonicalize_vars(),
+            (inv(&y) * inv(&y)).canonicalize_vars()
+        );
+        let c = dag.from_word(&inv(&x));
+        let vx = dag.var("x".to_string());
+        assert!(dag.unify(vx, c).is_none());
+
+        // The occurs check visits each shared subword once, not once per path to it, of which
+        // there are 2^60 here.
+        let mut w = vx;
+        for _ in 0..60 {
+            w = dag.app(Prod::Mul, vec![w, w]);
+        }
+        let vy = dag.var("y".to_string());
+        assert_eq!(
+            dag.unify(vy, w),
+            Some(DagSubst::from([("y".to_string(), w)]))
+        );
+        assert!(dag.unify(vx, w).is_none());
+    }
+
+    #[test]
+    fn agrees_with_trs() {
+        let (x, y, z) = (var("x"), var("y"), var("z"));
+        let rules = knuth_bendix(&[
+            Axiom {
+                left: one() * &x,
+                right: x.clone(),
+            },
+            Axiom {
+                left: inv(&x) * &x,
+                right: one(),
+            },
+            Axiom {
+                left: (&x * &y) * &z,
+                right: &x * (&y * &z),
+            },
+        ])
+        .unwrap();
+        let trs = Trs::new(rules.clone());
+        let mut dag = Dag::new();
+        let dag_rules: Vec<DagRule> = rules.iter().map(|r| dag.rule(r)).collect();
+        let mut nf = BTreeMap::new();
+        let (a, b) = (var("a"), var("b"));
+        let words = [
+            inv(&(&a * &b)) * (&a * &b),
+            inv(&inv(&a)) * (inv(&b) * &b),
+            (&a * inv(&a)) * ((&b * one()) * inv(&(inv(&b) * one()))),
+            inv(&(inv(&a) * inv(&one()))),
+        ];
+        for w in words {
+            let d = dag.from_word(&w);
+            let n = dag.normalize(&dag_rules, d, &mut nf);
+            assert_eq!(dag.to_word(n), trs.normalize(&w), "{}", w);
+        }
+    }
+}
@@ -0,0 +1,526 @@
+/*
+ * Termination analysis with dependency pairs.
+ *
+ * For a rule l → r and a subword t of r whose root is a defined symbol (one that is the root of
+ * some left side), l# → t# is a dependency pair, where # marks the root symbol as a distinct
+ * tuple symbol. A system terminates if there is no infinite chain of dependency pairs, which is
+ * checked one strongly connected component of the estimated dependency graph at a time. Each
+ * component is handled by searching for a simple argument filtering under which every rule is
+ * weakly decreasing and every pair in the component is weakly decreasing in the Knuth-Bendix
+ * ordering, with at least one strictly decreasing. The strictly decreasing pairs are removed
+ * and the rest of the component is analysed again.
+ */
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::trs::Trs;
+use crate::word::{Op, Operator, Symbol, Var, Variable, Word, WordRef};
+
+/// A dependency pair l# → t#, stored without the marks on the root symbols.
+#[derive(Clone, Debug)]
+pub struct DependencyPair<V: Variable, O: Operator> {
+    pub left: Word<V, O>,
+    pub right: Word<V, O>,
+    /// The index of the rule that this pair comes from.
+    pub rule: usize,
+}
+
+/// An operator of a filtered, marked word. Its weight is never below one, so the Knuth-Bendix
+/// ordering on these words is well founded whatever the weights of the original signature.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DpOp<O: Operator> {
+    pub op: O,
+    pub marked: bool,
+    pub arity: usize,
+}
+
+impl<O: Operator> Operator for DpOp<O> {
+    fn min_weight() -> u64 {
+        1
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn weight(&self) -> u64 {
+        self.op.weight().max(1)
+    }
+}
+
+/// How an argument filtering treats one symbol.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Filter {
+    /// Keep the arguments at these indices, in order.
+    Keep(Vec<usize>),
+    /// Replace the word by its argument at this index.
+    Collapse(usize),
+}
+
+/// An argument filtering, keyed by each operator and whether it is marked. Symbols without an
+/// entry keep all of their arguments.
+pub type ArgumentFiltering<O> = BTreeMap<(O, bool), Filter>;
+
+/// The outcome of a dependency pair analysis.
+#[derive(Clone, Debug)]
+pub enum DpResult<V: Variable, O: Operator> {
+    /// The system terminates. Each entry gives the pairs that were removed together and the
+    /// filtering that removed them.
+    Terminating(Vec<(Vec<usize>, ArgumentFiltering<O>)>),
+    /// The analysis gave up on a component with these pairs.
+    Unknown(Vec<DependencyPair<V, O>>),
+}
+
+/*
+ * A variable of a word whose variables are being renamed apart, either an original variable or a
+ * fresh one.
+ */
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum DpVar<V: Variable> {
+    Orig(V),
+    Fresh(usize),
+}
+
+impl<V: Variable> Variable for DpVar<V> {
+    fn prime(&self) -> DpVar<V> {
+        match self {
+            DpVar::Orig(v) => DpVar::Orig(v.prime()),
+            DpVar::Fresh(n) => DpVar::Fresh(n + 1),
+        }
+    }
+
+    fn nth(n: usize) -> DpVar<V> {
+        DpVar::Orig(V::nth(n))
+    }
+}
+
+fn defined_symbols<V: Variable, O: Operator>(trs: &Trs<V, O>) -> BTreeSet<O> {
+    trs.rules()
+        .iter()
+        .filter_map(|rule| rule.left.syms.first().and_then(Symbol::op))
+        .cloned()
+        .collect()
+}
+
+fn subword<V: Variable, O: Operator>(w: &Word<V, O>, i: usize) -> Word<V, O> {
+    Word {
+        syms: w.syms[i..w.subword_end(i)].to_vec(),
+    }
+}
+
+/// Compute the dependency pairs of a system. Subwords of a right side that are also proper
+/// subwords of the left side are skipped, since they can't start an infinite chain.
+pub fn dependency_pairs<V: Variable, O: Operator>(trs: &Trs<V, O>) -> Vec<DependencyPair<V, O>> {
+    let defined = defined_symbols(trs);
+    let mut out = Vec::new();
+    for (n, rule) in trs.rules().iter().enumerate() {
+        let lsubs: Vec<Word<V, O>> = (1..rule.left.syms.len())
+            .map(|i| subword(&rule.left, i))
+            .collect();
+        for (i, s) in rule.right.syms.iter().enumerate() {
+            if !s.op().is_some_and(|f| defined.contains(f)) {
+                continue;
+            }
+            let t = subword(&rule.right, i);
+            if lsubs.iter().any(|l| l.syms == t.syms) {
+                continue;
+            }
+            out.push(DependencyPair {
+                left: rule.left.clone(),
+                right: t,
+                rule: n,
+            });
+        }
+    }
+    out
+}
+
+/*
+ * Rename every variable occurrence of t apart, and replace every proper subword with a defined
+ * root by a fresh variable, giving ren(cap(t)).
+ */
+fn ren_cap<V: Variable, O: Operator>(t: &Word<V, O>, defined: &BTreeSet<O>) -> Word<DpVar<V>, O> {
+    let mut syms = Vec::with_capacity(t.syms.len());
+    let mut fresh = 0;
+    let mut i = 0;
+    while i < t.syms.len() {
+        match &t.syms[i] {
+            Op(f) if i == 0 || !defined.contains(f) => {
+                syms.push(Op(f.clone()));
+                i += 1;
+                continue;
+            }
+            _ => {
+                syms.push(Var(DpVar::Fresh(fresh)));
+                fresh += 1;
+            }
+        }
+        i = t.subword_end(i);
+    }
+    Word { syms }
+}
+
+fn orig_vars<V: Variable, O: Operator>(w: &Word<V, O>) -> Word<DpVar<V>, O> {
+    Word {
+        syms: w
+            .syms
+            .iter()
+            .map(|s| match s {
+                Var(v) => Var(DpVar::Orig(v.clone())),
+                Op(f) => Op(f.clone()),
+            })
+            .collect(),
+    }
+}
+
+/// Compute the estimated dependency graph: there is an edge from pair i to pair j if
+/// ren(cap(t)) unifies with the left side of j, where t is the right side of i. Returns the
+/// successors of each pair.
+pub fn dependency_graph<V: Variable, O: Operator>(
+    trs: &Trs<V, O>,
+    dps: &[DependencyPair<V, O>],
+) -> Vec<Vec<usize>> {
+    let defined = defined_symbols(trs);
+    dps.iter()
+        .map(|from| {
+            let t = ren_cap(&from.right, &defined);
+            (0..dps.len())
+                .filter(|&j| t.mgu(&orig_vars(&dps[j].left)).is_some())
+                .collect()
+        })
+        .collect()
+}
+
+/*
+ * Tarjan's algorithm, restricted to the nodes in nodes. Only components containing a cycle are
+ * returned, since the others can't take part in an infinite chain.
+ */
+fn cyclic_sccs(graph: &[Vec<usize>], nodes: &BTreeSet<usize>) -> Vec<Vec<usize>> {
+    struct State {
+        index: BTreeMap<usize, usize>,
+        low: BTreeMap<usize, usize>,
+        stack: Vec<usize>,
+        on_stack: BTreeSet<usize>,
+        out: Vec<Vec<usize>>,
+    }
+
+    fn visit(v: usize, graph: &[Vec<usize>], nodes: &BTreeSet<usize>, st: &mut State) {
+        let n = st.index.len();
+        st.index.insert(v, n);
+        st.low.insert(v, n);
+        st.stack.push(v);
+        st.on_stack.insert(v);
+        for &w in graph[v].iter().filter(|w| nodes.contains(w)) {
+            if !st.index.contains_key(&w) {
+                visit(w, graph, nodes, st);
+                let lw = st.low[&w];
+                let lv = st.low.get_mut(&v).unwrap();
+                *lv = (*lv).min(lw);
+            } else if st.on_stack.contains(&w) {
+                let iw = st.index[&w];
+                let lv = st.low.get_mut(&v).unwrap();
+                *lv = (*lv).min(iw);
+            }
+        }
+        if st.low[&v] == st.index[&v] {
+            let mut scc = Vec::new();
+            while let Some(w) = st.stack.pop() {
+                st.on_stack.remove(&w);
+                scc.push(w);
+                if w == v {
+                    break;
+                }
+            }
+            let cyclic = scc.len() > 1 || graph[v].contains(&v);
+            if cyclic {
+                scc.sort();
+                st.out.push(scc);
+            }
+        }
+    }
+
+    let mut st = State {
+        index: BTreeMap::new(),
+        low: BTreeMap::new(),
+        stack: Vec::new(),
+        on_stack: BTreeSet::new(),
+        out: Vec::new(),
+    };
+    for &v in nodes.iter() {
+        if !st.index.contains_key(&v) {
+            visit(v, graph, nodes, &mut st);
+        }
+    }
+    st.out
+}
+
+/*
+ * Apply an argument filtering to w, marking its root symbol if marked is set.
+ */
+fn filter<V: Variable, O: Operator>(
+    w: &Word<V, O>,
+    marked: bool,
+    af: &ArgumentFiltering<O>,
+) -> Word<V, DpOp<O>> {
+    match w.syms.first() {
+        Some(Op(f)) => {
+            let args: Vec<Word<V, O>> = w.subwords().map(WordRef::to_word).collect();
+            match af.get(&(f.clone(), marked)) {
+                Some(Filter::Collapse(i)) => filter(&args[*i], false, af),
+                Some(Filter::Keep(keep)) => {
+                    let op = DpOp {
+                        op: f.clone(),
+                        marked,
+                        arity: keep.len(),
+                    };
+                    let args: Vec<_> = keep.iter().map(|&i| filter(&args[i], false, af)).collect();
+                    Word::op(op, &args)
+                }
+                None => {
+                    let op = DpOp {
+                        op: f.clone(),
+                        marked,
+                        arity: args.len(),
+                    };
+                    let args: Vec<_> = args.iter().map(|a| filter(a, false, af)).collect();
+                    Word::op(op, &args)
+                }
+            }
+        }
+        Some(Var(v)) => Word::var(v.clone()),
+        None => Word { syms: Vec::new() },
+    }
+}
+
+fn weakly_greater<V: Variable, O: Operator>(s: &Word<V, DpOp<O>>, t: &Word<V, DpOp<O>>) -> bool {
+    s.syms == t.syms || s.partial_cmp(t) == Some(Ordering::Greater)
+}
+
+/// The largest number of argument filterings tried for each component.
+const MAX_FILTERINGS: usize = 1 << 12;
+
+/*
+ * Search for an argument filtering under which every rule is weakly decreasing, every pair in
+ * scc is weakly decreasing, and at least one of them strictly. Returns the filtering and the
+ * strictly decreasing pairs.
+ */
+fn orient_scc<V: Variable, O: Operator>(
+    trs: &Trs<V, O>,
+    dps: &[DependencyPair<V, O>],
+    scc: &[usize],
+) -> Option<(ArgumentFiltering<O>, Vec<usize>)> {
+    let mut symbols: BTreeMap<(O, bool), usize> = BTreeMap::new();
+    for rule in trs.rules().iter() {
+        for s in rule.left.syms.iter().chain(rule.right.syms.iter()) {
+            if let Op(f) = s {
+                symbols.insert((f.clone(), false), f.arity());
+            }
+        }
+    }
+    for &i in scc {
+        for w in [&dps[i].left, &dps[i].right] {
+            if let Some(Op(f)) = w.syms.first() {
+                symbols.insert((f.clone(), true), f.arity());
+            }
+        }
+    }
+    // Each symbol keeps all of its arguments or collapses to one of them.
+    let choices: Vec<((O, bool), Vec<Filter>)> = symbols
+        .into_iter()
+        .map(|(key, arity)| {
+            let mut fs = vec![Filter::Keep((0..arity).collect())];
+            fs.extend((0..arity).map(Filter::Collapse));
+            (key, fs)
+        })
+        .collect();
+    let mut counters = vec![0; choices.len()];
+    for _ in 0..MAX_FILTERINGS {
+        let af: ArgumentFiltering<O> = choices
+            .iter()
+            .zip(counters.iter())
+            .map(|((key, fs), &c)| (key.clone(), fs[c].clone()))
+            .collect();
+        let rules_ok = trs.rules().iter().all(|rule| {
+            weakly_greater(
+                &filter(&rule.left, false, &af),
+                &filter(&rule.right, false, &af),
+            )
+        });
+        if rules_ok {
+            let mut strict = Vec::new();
+            let mut pairs_ok = true;
+            for &i in scc {
+                let s = filter(&dps[i].left, true, &af);
+                let t = filter(&dps[i].right, true, &af);
+                if s.partial_cmp(&t) == Some(Ordering::Greater) {
+                    strict.push(i);
+                } else if s.syms != t.syms {
+                    pairs_ok = false;
+                    break;
+                }
+            }
+            if pairs_ok && !strict.is_empty() {
+                return Some((af, strict));
+            }
+        }
+        // Advance to the next combination of choices.
+        let mut k = 0;
+        loop {
+            if k == counters.len() {
+                return None;
+            }
+            counters[k] += 1;
+            if counters[k] < choices[k].1.len() {
+                break;
+            }
+            counters[k] = 0;
+            k += 1;
+        }
+    }
+    None
+}
+
+/// Try to prove that a system terminates with dependency pairs.
+pub fn prove_termination<V: Variable, O: Operator>(trs: &Trs<V, O>) -> DpResult<V, O> {
+    // The dependency pair method assumes left sides aren't variables and right sides don't
+    // introduce variables; such a rule makes the system non-terminating anyway.
+    for rule in trs.rules().iter() {
+        let lvars = rule.left.vars();
+        if rule.left.syms.first().and_then(Symbol::var).is_some()
+            || !rule.right.vars().is_subset(&lvars)
+        {
+            return DpResult::Unknown(Vec::new());
+        }
+    }
+    let dps = dependency_pairs(trs);
+    let graph = dependency_graph(trs, &dps);
+    let mut proof = Vec::new();
+    let mut work = cyclic_sccs(&graph, &(0..dps.len()).collect());
+    while let Some(scc) = work.pop() {
+        match orient_scc(trs, &dps, &scc) {
+            Some((af, strict)) => {
+                let rest: BTreeSet<usize> = scc
+                    .iter()
+                    .filter(|i| !strict.contains(i))
+                    .cloned()
+                    .collect();
+                work.extend(cyclic_sccs(&graph, &rest));
+                proof.push((strict, af));
+            }
+            None => {
+                return DpResult::Unknown(scc.iter().map(|&i| dps[i].clone()).collect());
+            }
+        }
+    }
+    DpResult::Terminating(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Kbo, Rule};
+
+    // Natural number subtraction and division.
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    enum Nat {
+        Zero,
+        Succ,
+        Minus,
+        Quot,
+    }
+    use Nat::*;
+
+    impl Operator for Nat {
+        fn min_weight() -> u64 {
+            1
+        }
+
+        fn arity(&self) -> usize {
+            match self {
+                Zero => 0,
+                Succ => 1,
+                Minus | Quot => 2,
+            }
+        }
+
+        fn weight(&self) -> u64 {
+            1
+        }
+    }
+
+    type W = Word<String, Nat>;
+
+    fn op(f: Nat, args: &[&W]) -> W {
+        let args: Vec<W> = args.iter().map(|&a| a.clone()).collect();
+        Word::op(f, &args)
+    }
+
+    fn rule(left: W, right: W) -> Rule<String, Nat> {
+        Rule { left, right }
+    }
+
+    fn quot_trs() -> Trs<String, Nat> {
+        let x = Word::var("x");
+        let y = Word::var("y");
+        let zero = op(Zero, &[]);
+        let s = |w: &W| op(Succ, &[w]);
+        Trs::new(vec![
+            rule(op(Minus, &[&x, &zero]), x.clone()),
+            rule(op(Minus, &[&s(&x), &s(&y)]), op(Minus, &[&x, &y])),
+            rule(op(Quot, &[&zero, &s(&y)]), zero.clone()),
+            rule(
+                op(Quot, &[&s(&x), &s(&y)]),
+                s(&op(Quot, &[&op(Minus, &[&x, &y]), &s(&y)])),
+            ),
+        ])
+    }
+
+    #[test]
+    fn pairs_and_graph() {
+        let trs = quot_trs();
+        let dps = dependency_pairs(&trs);
+        // minus# → minus# from the second rule, and quot# → quot# and quot# → minus# from the
+        // fourth.
+        assert_eq!(dps.len(), 3);
+        assert_eq!(
+            dps.iter().map(|dp| dp.rule).collect::<Vec<_>>(),
+            vec![1, 3, 3]
+        );
+        let graph = dependency_graph(&trs, &dps);
+        let quot = dps
+            .iter()
+            .position(|dp| dp.right.syms[0] == Op(Quot))
+            .unwrap();
+        let minus = dps
+            .iter()
+            .position(|dp| dp.right.syms[0] == Op(Minus) && dp.rule == 3)
+            .unwrap();
+        assert!(graph[quot].contains(&quot));
+        assert!(graph[quot].contains(&minus));
+        // The pair for the recursive call to minus leads into the minus# component, which never
+        // leads back to quot#.
+        assert_eq!(graph[minus], vec![0]);
+        assert_eq!(graph[0], vec![0]);
+    }
+
+    #[test]
+    fn quot_terminates() {
+        // The division rule duplicates y, so KBO alone can't orient the system.
+        let trs = quot_trs();
+        assert!(trs.terminates_by(&Kbo).is_err());
+        match prove_termination(&trs) {
+            DpResult::Terminating(proof) => assert_eq!(proof.len(), 2),
+            DpResult::Unknown(dps) => panic!("not proved: {:?}", dps),
+        }
+    }
+
+    #[test]
+    fn loop_unknown() {
+        let x = Word::var("x");
+        let trs = Trs::new(vec![rule(op(Succ, &[&x]), op(Succ, &[&x]))]);
+        match prove_termination(&trs) {
+            DpResult::Terminating(_) => panic!("proved a looping system terminating"),
+            DpResult::Unknown(dps) => assert_eq!(dps.len(), 1),
+        }
+    }
+}
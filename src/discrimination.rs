@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+
+use crate::flatterm::Flatterm;
+use crate::relation::Rule;
+use crate::trs::RuleId;
+use crate::word::{Op, Operator, Symbol, Var, Variable, Word};
+
+#[derive(Clone, Debug)]
+struct Node<O: Operator> {
+    ops: BTreeMap<O, usize>,
+    /// The child for a variable of a left side, which stands for a whole subword of the query.
+    var: Option<usize>,
+    /// The rules whose left sides end here.
+    rules: Vec<RuleId>,
+}
+
+impl<O: Operator> Default for Node<O> {
+    fn default() -> Node<O> {
+        Node {
+            ops: BTreeMap::new(),
+            var: None,
+            rules: Vec::new(),
+        }
+    }
+}
+
+/*
+ * A discrimination tree over the left sides of rules: a trie of their symbols in order, with
+ * every variable the same, so that the rules that might match a word are found by walking down
+ * it along the word's symbols instead of trying every rule. At each node the walk follows the
+ * child for the word's next operator, and also the variable child, skipping the whole subword
+ * there. The candidates are exactly the rules whose left sides match once repeated variables are
+ * treated as distinct, so a rule with a nonlinear left side must still be matched to be sure.
+ */
+#[derive(Clone, Debug)]
+pub struct DiscriminationTree<O: Operator> {
+    nodes: Vec<Node<O>>,
+    /// Whether each rule's left side is ground, so it can be matched by comparing symbols.
+    ground: Vec<bool>,
+}
+
+impl<O: Operator> Default for DiscriminationTree<O> {
+    fn default() -> DiscriminationTree<O> {
+        DiscriminationTree {
+            nodes: vec![Node::default()],
+            ground: Vec::new(),
+        }
+    }
+}
+
+impl<O: Operator> DiscriminationTree<O> {
+    pub fn new<V: Variable>(rules: &[Rule<V, O>]) -> DiscriminationTree<O> {
+        let mut tree = DiscriminationTree::default();
+        for (id, rule) in rules.iter().enumerate() {
+            tree.insert(id, &rule.left);
+        }
+        tree
+    }
+
+    /// Add a rule with left side left. Rules should be added in increasing order.
+    pub fn insert<V: Variable>(&mut self, id: RuleId, left: &Word<V, O>) {
+        let mut node = 0;
+        for s in &left.syms {
+            let child = match s {
+                Op(f) => self.nodes[node].ops.get(f).copied(),
+                Var(_) => self.nodes[node].var,
+            };
+            node = match child {
+                Some(child) => child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node::default());
+                    match s {
+                        Op(f) => {
+                            self.nodes[node].ops.insert(f.clone(), child);
+                        }
+                        Var(_) => self.nodes[node].var = Some(child),
+                    }
+                    child
+                }
+            };
+        }
+        self.nodes[node].rules.push(id);
+        if self.ground.len() <= id {
+            self.ground.resize(id + 1, false);
+        }
+        self.ground[id] = left.is_ground();
+    }
+
+    pub(crate) fn is_ground(&self, id: RuleId) -> bool {
+        self.ground[id]
+    }
+
+    /// The rules that might match the subword of w at index i, in increasing order.
+    pub fn candidates<V: Variable>(&self, w: &Word<V, O>, i: usize) -> Vec<RuleId> {
+        let mut out = Vec::new();
+        self.retrieve(&w.syms, i, |j| w.subword_end(j), &mut Vec::new(), &mut out);
+        out
+    }
+
+    /// The same as candidates, for a Flatterm, into out, with stack to walk the tree with.
+    pub(crate) fn candidates_flat<V: Variable>(
+        &self,
+        w: &Flatterm<V, O>,
+        i: usize,
+        stack: &mut Vec<(usize, usize)>,
+        out: &mut Vec<RuleId>,
+    ) {
+        self.retrieve(&w.syms, i, |j| w.end(j), stack, out);
+    }
+
+    fn retrieve<V: Variable>(
+        &self,
+        syms: &[Symbol<V, O>],
+        i: usize,
+        end: impl Fn(usize) -> usize,
+        stack: &mut Vec<(usize, usize)>,
+        out: &mut Vec<RuleId>,
+    ) {
+        out.clear();
+        let last = end(i);
+        stack.clear();
+        stack.push((0, i));
+        while let Some((node, j)) = stack.pop() {
+            let node = &self.nodes[node];
+            if j == last {
+                out.extend(&node.rules);
+                continue;
+            }
+            if let Some(child) = node.var {
+                stack.push((child, end(j)));
+            }
+            if let Op(f) = &syms[j] {
+                if let Some(&child) = node.ops.get(f) {
+                    stack.push((child, j + 1));
+                }
+            }
+        }
+        if out.len() > 1 {
+            out.sort_unstable();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::*;
+
+    #[test]
+    fn candidates() {
+        let (x, y) = (var("x"), var("y"));
+        let rules = [
+            (one() * &x, x.clone()),
+            (inv(&x) * &x, one()),
+            (inv(&one()), one()),
+            ((&x * &y) * var("z"), &x * (&y * var("z"))),
+            (x.clone(), x.clone()),
+        ]
+        .map(|(left, right)| Rule { left, right });
+        let tree = DiscriminationTree::new(&rules);
+        let (a, b) = (var("a"), var("b"));
+        assert_eq!(tree.candidates(&(one() * &a), 0), [0, 4]);
+        // A nonlinear left side is a candidate even where its variables differ.
+        assert_eq!(tree.candidates(&(inv(&a) * &b), 0), [1, 4]);
+        assert_eq!(tree.candidates(&(inv(&one()) * one()), 0), [1, 4]);
+        assert_eq!(tree.candidates(&(inv(&one()) * one()), 1), [2, 4]);
+        let w = (inv(&a) * &b) * one();
+        assert_eq!(tree.candidates(&w, 0), [3, 4]);
+        assert_eq!(tree.candidates(&w, 1), [1, 4]);
+        assert_eq!(tree.candidates(&a, 0), [4]);
+    }
+}
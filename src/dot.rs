@@ -0,0 +1,232 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt::{self, Display, Write};
+
+use crate::trs::Trs;
+use crate::word::{Op, Operator, Var, Variable, Word, WordRef};
+
+/*
+ * Graphviz dot output, drawing words as trees: an operator is a node with an edge to each of
+ * its arguments, in order, and a variable is a plain label. A word's nodes are named by the
+ * indexes of their symbols in syms, so a node can be traced back to a position in the word.
+ * Trs::to_dot draws each rule as a box with its left and right sides side by side, and
+ * Trs::rewrite_graph the words a word rewrites to. Render with dot -Tsvg.
+ */
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Write the nodes and edges of w, naming its nodes by prefix and the symbol index.
+fn write_tree<V: Variable + Display, O: Operator + Display>(
+    out: &mut String,
+    w: WordRef<'_, V, O>,
+    prefix: &str,
+    indent: &str,
+) {
+    for (i, s) in w.syms.iter().enumerate() {
+        match s {
+            Var(v) => writeln!(
+                out,
+                "{}{}{} [label={}, shape=plaintext];",
+                indent,
+                prefix,
+                i,
+                quote(&v.to_string())
+            ),
+            Op(o) => writeln!(
+                out,
+                "{}{}{} [label={}];",
+                indent,
+                prefix,
+                i,
+                quote(o.to_string().trim())
+            ),
+        }
+        .unwrap();
+    }
+    for i in 0..w.syms.len() {
+        if let Op(_) = w.syms[i] {
+            let mut j = i + 1;
+            let end = w.subword_end(i);
+            while j < end {
+                writeln!(out, "{}{}{} -> {}{};", indent, prefix, i, prefix, j).unwrap();
+                j = w.subword_end(j);
+            }
+        }
+    }
+}
+
+impl<V: Variable + Display, O: Operator + Display> Word<V, O> {
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph {\n  ordering=out;\n");
+        write_tree(&mut out, self.view(), "n", "  ");
+        out.push_str("}\n");
+        out
+    }
+}
+
+impl<V: Variable + Display, O: Operator + Display> Trs<V, O> {
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph {\n  ordering=out;\n");
+        for (k, r) in self.rules().iter().enumerate() {
+            writeln!(out, "  subgraph cluster_{} {{", k).unwrap();
+            writeln!(out, "    label={};", quote(&format!("rule {}", k))).unwrap();
+            let (l, rhs) = (format!("l{}_", k), format!("r{}_", k));
+            write_tree(&mut out, r.left.view(), &l, "    ");
+            write_tree(&mut out, r.right.view(), &rhs, "    ");
+            writeln!(
+                out,
+                "    {}0 -> {}0 [label=\"→\", style=dashed];\n    {{ rank=same; {}0; {}0; }}",
+                l, rhs, l, rhs
+            )
+            .unwrap();
+            out.push_str("  }\n");
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// The words reachable from a seed by rewriting, with an edge for each rewrite step, as found by
+/// Trs::rewrite_graph. Its Display is a dot graph with the normal forms filled in.
+#[derive(Clone, Debug)]
+pub struct DotGraph<V: Variable, O: Operator> {
+    /// The words found, starting with the seed, in breadth-first order.
+    pub nodes: Vec<Word<V, O>>,
+    /// Each step from one word to another, by their indexes in nodes.
+    pub edges: Vec<(usize, usize)>,
+    /// The words that don't rewrite.
+    pub normal_forms: BTreeSet<usize>,
+    /// The words that rewrite to words left out because of the limit on nodes.
+    pub incomplete: BTreeSet<usize>,
+}
+
+impl<V: Variable, O: Operator> Trs<V, O> {
+    /// Explore the words reachable from seed breadth first, keeping at most max_nodes of them.
+    pub fn rewrite_graph(&self, seed: &Word<V, O>, max_nodes: usize) -> DotGraph<V, O> {
+        let mut graph = DotGraph {
+            nodes: vec![seed.clone()],
+            edges: Vec::new(),
+            normal_forms: BTreeSet::new(),
+            incomplete: BTreeSet::new(),
+        };
+        let mut index = BTreeMap::from([(seed.syms.clone(), 0)]);
+        let mut queue = VecDeque::from([0]);
+        while let Some(i) = queue.pop_front() {
+            let rewrites = self.rewrites(&graph.nodes[i]);
+            if rewrites.is_empty() {
+                graph.normal_forms.insert(i);
+            }
+            for r in rewrites {
+                let j = match index.get(&r.syms) {
+                    Some(&j) => j,
+                    None if graph.nodes.len() < max_nodes => {
+                        let j = graph.nodes.len();
+                        index.insert(r.syms.clone(), j);
+                        graph.nodes.push(r);
+                        queue.push_back(j);
+                        j
+                    }
+                    None => {
+                        graph.incomplete.insert(i);
+                        continue;
+                    }
+                };
+                if !graph.edges.contains(&(i, j)) {
+                    graph.edges.push((i, j));
+                }
+            }
+        }
+        graph
+    }
+}
+
+impl<V: Variable + Display, O: Operator + Display> Display for DotGraph<V, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "digraph {{\n  node [shape=box];")?;
+        for (i, w) in self.nodes.iter().enumerate() {
+            write!(f, "  n{} [label={}", i, quote(&w.to_string()))?;
+            if i == 0 {
+                f.write_str(", penwidth=2")?;
+            }
+            if self.normal_forms.contains(&i) {
+                f.write_str(", style=filled, fillcolor=palegreen")?;
+            }
+            if self.incomplete.contains(&i) {
+                f.write_str(", style=dashed")?;
+            }
+            f.write_str("];\n")?;
+        }
+        for (i, j) in &self.edges {
+            writeln!(f, "  n{} -> n{};", i, j)?;
+        }
+        f.write_str("}\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prod::{inv, one, var};
+    use crate::{Rule, Trs};
+
+    #[test]
+    fn rewrite_graph() {
+        // Two ways to rewrite f(a), to b and to c, and c rewrites on to b.
+        let mut sig = crate::Signature::new();
+        for (name, arity) in [("a", 0), ("b", 0), ("c", 0), ("f", 1)] {
+            sig.declare(name, arity, 1).unwrap();
+        }
+        let rule = |l, r| Rule { left: l, right: r };
+        let trs = Trs::new(vec![
+            rule(crate::term!(sig, f(x)), crate::term!(sig, b)),
+            rule(crate::term!(sig, a), crate::term!(sig, c)),
+            rule(crate::term!(sig, f(c)), crate::term!(sig, b)),
+        ]);
+        let graph = trs.rewrite_graph(&crate::term!(sig, f(a)), 10);
+        let names: Vec<String> = graph.nodes.iter().map(|w| w.to_string()).collect();
+        assert_eq!(names, ["f(a)", "b", "f(c)"]);
+        assert_eq!(graph.edges, [(0, 1), (0, 2), (2, 1)]);
+        assert_eq!(graph.normal_forms, [1].into());
+        assert!(graph.incomplete.is_empty());
+        assert_eq!(
+            graph.to_string(),
+            "digraph {\n  node [shape=box];\n  \
+               n0 [label=\"f(a)\", penwidth=2];\n  \
+               n1 [label=\"b\", style=filled, fillcolor=palegreen];\n  \
+               n2 [label=\"f(c)\"];\n  \
+               n0 -> n1;\n  n0 -> n2;\n  n2 -> n1;\n\
+             }\n"
+        );
+
+        let graph = trs.rewrite_graph(&crate::term!(sig, f(a)), 2);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.incomplete, [0].into());
+    }
+
+    #[test]
+    fn trees() {
+        let x = var("x");
+        let w = inv(&x) * &x;
+        assert_eq!(
+            w.to_dot(),
+            "digraph {\n  ordering=out;\n  \
+               n0 [label=\"*\"];\n  \
+               n1 [label=\"⁻¹\"];\n  \
+               n2 [label=\"x\", shape=plaintext];\n  \
+               n3 [label=\"x\", shape=plaintext];\n  \
+               n0 -> n1;\n  \
+               n0 -> n3;\n  \
+               n1 -> n2;\n\
+             }\n"
+        );
+
+        let trs = Trs::new(vec![Rule {
+            left: w,
+            right: one(),
+        }]);
+        let dot = trs.to_dot();
+        assert!(dot
+            .contains("  subgraph cluster_0 {\n    label=\"rule 0\";\n    l0_0 [label=\"*\"];\n"));
+        assert!(dot
+            .contains("    r0_0 [label=\"1\"];\n    l0_0 -> r0_0 [label=\"→\", style=dashed];\n"));
+    }
+}
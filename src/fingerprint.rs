@@ -0,0 +1,203 @@
+use crate::word::{Operator, Symbol, Var, Variable, Word};
+
+/// The symbol found at a position of a word, as a fingerprint records it.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Feature<O: Operator> {
+    Op(O),
+    /// A variable, which could be instantiated to anything.
+    Var,
+    /// The position is below a variable, so an instance may or may not have it.
+    BelowVar,
+    /// The position isn't in the word and can't be in any instance of it.
+    Absent,
+}
+
+impl<O: Operator> Feature<O> {
+    /// Whether some instances of two words with these features at a position could be equal
+    /// there.
+    pub fn unifiable(&self, other: &Feature<O>) -> bool {
+        use Feature::*;
+        match (self, other) {
+            (Op(f), Op(g)) => f == g,
+            (Op(_) | Var, Absent) | (Absent, Op(_) | Var) => false,
+            _ => true,
+        }
+    }
+}
+
+/// The positions a fingerprint samples: the root, its first two arguments, and theirs.
+pub const FINGERPRINT_POSITIONS: [&[usize]; 7] =
+    [&[], &[0], &[1], &[0, 0], &[0, 1], &[1, 0], &[1, 1]];
+
+/*
+ * A word's features at a few positions near its root, after Schulz's fingerprint indexing. Two
+ * words can only unify if their features agree at every position, which is much cheaper to check
+ * than unifying them, so fingerprints rule out most pairs before unification is tried.
+ */
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fingerprint<O: Operator>(pub Vec<Feature<O>>);
+
+impl<O: Operator> Fingerprint<O> {
+    /// The fingerprint of the subword of w at index i.
+    pub fn of<V: Variable>(w: &Word<V, O>, i: usize) -> Fingerprint<O> {
+        let features = FINGERPRINT_POSITIONS.iter().map(|path| feature(w, i, path));
+        Fingerprint(features.collect())
+    }
+
+    pub fn unifiable(&self, other: &Fingerprint<O>) -> bool {
+        self.0.iter().zip(&other.0).all(|(a, b)| a.unifiable(b))
+    }
+}
+
+// The feature at path below the subword of w at index i.
+fn feature<V: Variable, O: Operator>(w: &Word<V, O>, i: usize, path: &[usize]) -> Feature<O> {
+    let mut j = i;
+    for &k in path {
+        match &w.syms[j] {
+            Var(_) => return Feature::BelowVar,
+            s if k >= s.arity() => return Feature::Absent,
+            _ => {}
+        }
+        j += 1;
+        for _ in 0..k {
+            j = w.subword_end(j);
+        }
+    }
+    match &w.syms[j] {
+        Var(_) => Feature::Var,
+        Symbol::Op(f) => Feature::Op(f.clone()),
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Node<O: Operator, T> {
+    children: Vec<(Feature<O>, usize)>,
+    values: Vec<T>,
+}
+
+/// A trie of fingerprints with a value for each, to find the values whose fingerprints are
+/// unifiable with a given one without comparing against each in turn.
+#[derive(Clone, Debug)]
+pub struct FingerprintIndex<O: Operator, T> {
+    nodes: Vec<Node<O, T>>,
+}
+
+impl<O: Operator, T: Clone> Default for FingerprintIndex<O, T> {
+    fn default() -> FingerprintIndex<O, T> {
+        FingerprintIndex {
+            nodes: vec![Node {
+                children: Vec::new(),
+                values: Vec::new(),
+            }],
+        }
+    }
+}
+
+impl<O: Operator, T: Clone> FingerprintIndex<O, T> {
+    pub fn new() -> FingerprintIndex<O, T> {
+        FingerprintIndex::default()
+    }
+
+    pub fn insert(&mut self, fp: &Fingerprint<O>, value: T) {
+        let mut node = 0;
+        for f in &fp.0 {
+            let found = self.nodes[node]
+                .children
+                .iter()
+                .find(|(g, _)| g == f)
+                .map(|&(_, child)| child);
+            node = found.unwrap_or_else(|| {
+                let child = self.nodes.len();
+                self.nodes.push(Node {
+                    children: Vec::new(),
+                    values: Vec::new(),
+                });
+                self.nodes[node].children.push((f.clone(), child));
+                child
+            });
+        }
+        self.nodes[node].values.push(value);
+    }
+
+    /// The values of every fingerprint unifiable with fp, in no particular order.
+    pub fn unifiable(&self, fp: &Fingerprint<O>) -> Vec<T> {
+        let mut out = Vec::new();
+        let mut stack = vec![(0, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            let node = &self.nodes[node];
+            match fp.0.get(depth) {
+                None => out.extend(node.values.iter().cloned()),
+                Some(f) => stack.extend(
+                    node.children
+                        .iter()
+                        .filter(|(g, _)| f.unifiable(g))
+                        .map(|&(_, child)| (child, depth + 1)),
+                ),
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::*;
+
+    #[test]
+    fn fingerprints() {
+        let (x, y) = (var("x"), var("y"));
+        let w = inv(&x) * (&y * one());
+        let fp = Fingerprint::of(&w, 0);
+        assert_eq!(
+            fp.0,
+            [
+                Feature::Op(Prod::Mul),
+                Feature::Op(Prod::Inv),
+                Feature::Op(Prod::Mul),
+                Feature::Var,
+                Feature::Absent,
+                Feature::Var,
+                Feature::Op(Prod::One),
+            ]
+        );
+        // The subword at index 1 is x⁻¹.
+        assert_eq!(Fingerprint::of(&w, 1).0[1], Feature::Var);
+        assert_eq!(Fingerprint::of(&w, 1).0[3], Feature::BelowVar);
+
+        let words = [
+            &x * &y,
+            inv(&x) * &x,
+            one() * &x,
+            inv(&one()),
+            (&x * &y) * var("z"),
+            x.clone(),
+        ];
+        let mut index = FingerprintIndex::new();
+        for (i, w) in words.iter().enumerate() {
+            index.insert(&Fingerprint::of(w, 0), i);
+        }
+        for query in [
+            inv(&one()) * one(),
+            one() * one(),
+            inv(&x),
+            (&x * &y) * one(),
+        ] {
+            let fp = Fingerprint::of(&query, 0);
+            let mut found = index.unifiable(&fp);
+            found.sort();
+            // Fingerprints only rule out words that don't unify.
+            let unifies: Vec<usize> = (0..words.len())
+                .filter(|&i| words[i].mgu(&query).is_some())
+                .collect();
+            assert!(unifies.iter().all(|i| found.contains(i)), "{}", query);
+            assert!(found
+                .iter()
+                .all(|&i| fp.unifiable(&Fingerprint::of(&words[i], 0))));
+        }
+        let fp = Fingerprint::of(&(one() * one()), 0);
+        let mut found = index.unifiable(&fp);
+        found.sort();
+        assert_eq!(found, [0, 2, 5]);
+    }
+}
@@ -0,0 +1,78 @@
+use std::ops::Range;
+
+use crate::word::{Operator, Symbol, Variable, Word};
+
+/*
+ * A word with a skip offset for every symbol: ends[i] is one past the end of the subword starting
+ * at i. Word finds the end of a subword by counting arities along it, which a matching loop does
+ * for every variable it binds and every position it tries, so normalizing a word of n symbols
+ * costs O(n²) in finding ends alone. With the offsets, skipping a subword is a lookup, and
+ * keeping them up to date costs one O(n) pass per rewrite step.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Flatterm<V: Variable, O: Operator> {
+    pub syms: Vec<Symbol<V, O>>,
+    pub ends: Vec<usize>,
+}
+
+// Fill ends with the skip offsets of syms, right to left: the first argument of the symbol at i
+// starts at i + 1 and each later one where the one before it ends. A malformed word's missing
+// arguments are taken to run to the end.
+fn fill_ends<V: Variable, O: Operator>(syms: &[Symbol<V, O>], ends: &mut Vec<usize>) {
+    ends.clear();
+    ends.resize(syms.len(), 0);
+    for i in (0..syms.len()).rev() {
+        let mut end = i + 1;
+        for _ in 0..syms[i].arity() {
+            end = ends.get(end).copied().unwrap_or(syms.len());
+        }
+        ends[i] = end;
+    }
+}
+
+impl<V: Variable, O: Operator> Flatterm<V, O> {
+    pub fn new(w: &Word<V, O>) -> Flatterm<V, O> {
+        let mut ends = Vec::new();
+        fill_ends(&w.syms, &mut ends);
+        Flatterm {
+            syms: w.syms.clone(),
+            ends,
+        }
+    }
+
+    pub fn to_word(&self) -> Word<V, O> {
+        Word {
+            syms: self.syms.clone(),
+        }
+    }
+
+    /// One past the end of the subword starting at i.
+    pub fn end(&self, i: usize) -> usize {
+        self.ends[i]
+    }
+
+    /// Replace the subword at range with syms, updating the offsets.
+    pub fn splice(&mut self, range: Range<usize>, syms: &[Symbol<V, O>]) {
+        self.syms.splice(range, syms.iter().cloned());
+        fill_ends(&self.syms, &mut self.ends);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::{inv, one, var};
+
+    #[test]
+    fn offsets() {
+        let (a, b) = (var("a"), var("b"));
+        let w = inv(&(&a * &b)) * one();
+        let mut flat = Flatterm::new(&w);
+        for i in 0..w.syms.len() {
+            assert_eq!(flat.end(i), w.subword_end(i));
+        }
+        flat.splice(1..5, &one().syms);
+        assert_eq!(flat.to_word(), one() * one());
+        assert_eq!(flat.ends, vec![3, 2, 3]);
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+use crate::completion::knuth_bendix;
+use crate::relation::{Axiom, Rule};
+use crate::word::{used_signature, Op, Operator, Symbol, Var, Variable, Word};
+
+/*
+ * Variables and operators replaced by small numbers. Comparing two interned symbols compares a
+ * couple of integers where the originals might compare strings, which is most of the work of
+ * matching and counting variables. A SymbolTable maps each name to its number and back.
+ */
+
+/// An interned variable. Variables made by priming or by Variable::nth, which completion uses to
+/// rename rules apart, are interned too: primes counts the primes on top of the variable id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarId {
+    pub id: u32,
+    pub primes: u32,
+}
+
+impl Variable for VarId {
+    fn prime(&self) -> VarId {
+        VarId {
+            id: self.id,
+            primes: self.primes + 1,
+        }
+    }
+
+    // Counted down from the top, so these never collide with variables from a table.
+    fn nth(n: usize) -> VarId {
+        VarId {
+            id: u32::MAX - n as u32,
+            primes: 0,
+        }
+    }
+}
+
+/// An interned operator of type O. It carries the arity and weight of the original, so it works
+/// as an Operator without the table, and its Ord is its id.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OpId<O: Operator> {
+    pub id: u32,
+    pub arity: usize,
+    pub weight: u64,
+    pub commutative: bool,
+    op: PhantomData<O>,
+}
+
+impl<O: Operator> Operator for OpId<O> {
+    fn min_weight() -> u64 {
+        O::min_weight()
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn weight(&self) -> u64 {
+        self.weight
+    }
+
+    fn is_commutative(&self) -> bool {
+        self.commutative
+    }
+}
+
+pub type IdWord<O> = Word<VarId, OpId<O>>;
+
+/*
+ * The names behind interned symbols. Ids are handed out in the order symbols are interned, so a
+ * table made by from_axioms, which interns the whole signature in order, gives ids that order the
+ * same way as the originals, and completion orients interned equations exactly as it would the
+ * originals.
+ */
+#[derive(Clone, Debug)]
+pub struct SymbolTable<V: Variable, O: Operator> {
+    vars: Vec<V>,
+    var_ids: BTreeMap<V, u32>,
+    ops: Vec<O>,
+    op_ids: BTreeMap<O, u32>,
+}
+
+impl<V: Variable, O: Operator> Default for SymbolTable<V, O> {
+    fn default() -> Self {
+        SymbolTable {
+            vars: Vec::new(),
+            var_ids: BTreeMap::new(),
+            ops: Vec::new(),
+            op_ids: BTreeMap::new(),
+        }
+    }
+}
+
+impl<V: Variable, O: Operator> SymbolTable<V, O> {
+    pub fn new() -> SymbolTable<V, O> {
+        SymbolTable::default()
+    }
+
+    /// A table holding the signature of the axioms, in order.
+    pub fn from_axioms(axioms: &[Axiom<V, O>]) -> SymbolTable<V, O> {
+        let mut table = SymbolTable::new();
+        let (ops, vars) = used_signature(axioms);
+        ops.iter().for_each(|f| {
+            table.intern_op(f);
+        });
+        vars.iter().for_each(|v| {
+            table.intern_var(v);
+        });
+        table
+    }
+
+    pub fn intern_var(&mut self, v: &V) -> VarId {
+        let id = match self.var_ids.get(v) {
+            Some(&id) => id,
+            None => {
+                let id = self.vars.len() as u32;
+                self.vars.push(v.clone());
+                self.var_ids.insert(v.clone(), id);
+                id
+            }
+        };
+        VarId { id, primes: 0 }
+    }
+
+    pub fn intern_op(&mut self, f: &O) -> OpId<O> {
+        let id = match self.op_ids.get(f) {
+            Some(&id) => id,
+            None => {
+                let id = self.ops.len() as u32;
+                self.ops.push(f.clone());
+                self.op_ids.insert(f.clone(), id);
+                id
+            }
+        };
+        OpId {
+            id,
+            arity: f.arity(),
+            weight: f.weight(),
+            commutative: f.is_commutative(),
+            op: PhantomData,
+        }
+    }
+
+    pub fn intern_word(&mut self, w: &Word<V, O>) -> IdWord<O> {
+        Word {
+            syms: w
+                .syms
+                .iter()
+                .map(|s| match s {
+                    Var(v) => Var(self.intern_var(v)),
+                    Op(f) => Op(self.intern_op(f)),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn intern_axiom(&mut self, a: &Axiom<V, O>) -> Axiom<VarId, OpId<O>> {
+        Axiom {
+            left: self.intern_word(&a.left),
+            right: self.intern_word(&a.right),
+        }
+    }
+
+    /// The variable behind an id. Variables that weren't interned are V::nth of their number,
+    /// and primes are applied on top.
+    pub fn var(&self, v: &VarId) -> V {
+        let base = match self.vars.get(v.id as usize) {
+            Some(name) => name.clone(),
+            None => V::nth((u32::MAX - v.id) as usize),
+        };
+        (0..v.primes).fold(base, |v, _| v.prime())
+    }
+
+    /// The operator behind an id, if it is in this table.
+    pub fn op(&self, f: &OpId<O>) -> Option<&O> {
+        self.ops.get(f.id as usize)
+    }
+
+    /// Convert an interned word back to names. Panics if it has an operator not in this table.
+    pub fn word(&self, w: &IdWord<O>) -> Word<V, O> {
+        Word {
+            syms: w
+                .syms
+                .iter()
+                .map(|s| -> Symbol<V, O> {
+                    match s {
+                        Var(v) => Var(self.var(v)),
+                        Op(f) => Op(self.op(f).expect("operator not in table").clone()),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    pub fn rule(&self, r: &Rule<VarId, OpId<O>>) -> Rule<V, O> {
+        Rule {
+            left: self.word(&r.left),
+            right: self.word(&r.right),
+        }
+    }
+}
+
+/// knuth_bendix on interned symbols, converting the completed rules back to the original names.
+/// It returns the same rules, up to the names of generated variables, but compares numbers where
+/// the original symbols may be expensive to compare.
+pub fn knuth_bendix_interned<V: Variable, O: Operator>(
+    axioms: &[Axiom<V, O>],
+) -> Option<Vec<Rule<V, O>>> {
+    let mut table = SymbolTable::from_axioms(axioms);
+    let interned: Vec<_> = axioms.iter().map(|a| table.intern_axiom(a)).collect();
+    let rules = knuth_bendix(&interned)?;
+    Some(rules.iter().map(|r| table.rule(r)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::{inv, one, var, Prod};
+    use crate::theories::group;
+
+    #[test]
+    fn round_trip() {
+        let axioms = group::axioms();
+        let mut table = SymbolTable::from_axioms(&axioms);
+        let w = inv(&(var("x") * var("q"))) * one();
+        let i = table.intern_word(&w);
+        assert_eq!(table.word(&i), w);
+        // q was interned after the axioms' variables, and primed and generated variables come
+        // back as the originals would have made them.
+        assert_eq!(table.intern_var(&"q".to_string()).id, 3);
+        let x = table.intern_var(&"x".to_string());
+        assert_eq!(table.var(&x.prime().prime()), "x''");
+        assert_eq!(table.var(&VarId::nth(4)), "x4");
+    }
+
+    #[test]
+    fn same_completion() {
+        let axioms = group::axioms();
+        let canonical = |rules: Vec<Rule<String, Prod>>| {
+            let mut rules: Vec<_> = rules
+                .iter()
+                .map(|r| {
+                    let w = Word::op(Prod::Mul, &[r.left.clone(), r.right.clone()]);
+                    w.canonicalize_vars().syms
+                })
+                .collect();
+            rules.sort();
+            rules
+        };
+        assert_eq!(
+            canonical(knuth_bendix_interned(&axioms).unwrap()),
+            canonical(knuth_bendix(&axioms).unwrap())
+        );
+    }
+}
@@ -0,0 +1,216 @@
+/*
+ * JSON output, as the kb command line tool prints it with --json and the playground returns it.
+ * Json is a value to build and display, with objects keeping their fields in the order given,
+ * and the functions below build the shapes that both print, so they can't drift apart:
+ *
+ *     rules(&rules)                   ["x * 1 → x", ...]
+ *     steps(&derivation)              [{"rule": 0, "position": [1], "word": "..."}, ...]
+ *     normalization(&w, &derivation)  {"term", "normal_form", "steps"}
+ *     completion(...)                 {"status", "aborted", "n_rules", "rules", ...}
+ */
+use std::fmt::{self, Display, Write};
+
+use crate::completion::{Outcome, Stats};
+use crate::properties::Detected;
+use crate::relation::{Equation, Rule};
+use crate::trs::RewriteStep;
+use crate::word::{Operator, Variable, Word};
+
+/// A JSON value. Objects keep their fields in the order given.
+pub enum Json {
+    Null,
+    Bool(bool),
+    Num(u64),
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(&'static str, Json)>),
+}
+
+pub fn obj(fields: impl IntoIterator<Item = (&'static str, Json)>) -> Json {
+    Json::Obj(fields.into_iter().collect())
+}
+
+fn write_str(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => b.fmt(f),
+            Json::Num(n) => n.fmt(f),
+            Json::Str(s) => write_str(f, s),
+            Json::Arr(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    item.fmt(f)?;
+                }
+                f.write_char(']')
+            }
+            Json::Obj(fields) => {
+                f.write_char('{')?;
+                for (i, (k, v)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_str(f, k)?;
+                    f.write_char(':')?;
+                    v.fmt(f)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Json {
+        Json::Bool(b)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(n: usize) -> Json {
+        Json::Num(n as u64)
+    }
+}
+
+impl From<u64> for Json {
+    fn from(n: u64) -> Json {
+        Json::Num(n)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Json {
+        Json::Str(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Json {
+        Json::Str(s)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(items: Vec<T>) -> Json {
+        Json::Arr(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(x: Option<T>) -> Json {
+        x.map_or(Json::Null, Into::into)
+    }
+}
+
+/// The rules as an array of strings.
+pub fn rules<V: Variable + Display, O: Operator + Display>(rules: &[Rule<V, O>]) -> Json {
+    Json::Arr(rules.iter().map(|r| r.to_string().into()).collect())
+}
+
+/// The steps of a derivation as an array of objects.
+pub fn steps<V: Variable + Display, O: Operator + Display>(steps: &[RewriteStep<V, O>]) -> Json {
+    let steps = steps.iter().map(|s| {
+        obj([
+            ("rule", s.rule.into()),
+            ("position", s.position.0.clone().into()),
+            ("word", s.word.to_string().into()),
+        ])
+    });
+    Json::Arr(steps.collect())
+}
+
+/// A word, its normal form, and the derivation that reached it, as kb normalize prints them.
+pub fn normalization<V: Variable + Display, O: Operator + Display>(
+    w: &Word<V, O>,
+    derivation: &[RewriteStep<V, O>],
+) -> Json {
+    let normal = derivation.last().map_or(w, |s| &s.word);
+    obj([
+        ("term", w.to_string().into()),
+        ("normal_form", normal.to_string().into()),
+        ("steps", steps(derivation)),
+    ])
+}
+
+/*
+ * How a completion run ended, as kb complete prints it: its outcome and rules, whether an
+ * orientation was asked for and the answer was to abort, its statistics, the properties the
+ * axioms give operators with the axiom for each, and the file the rules were written to, if any.
+ * The status is convergent, unorientable, trivial, or limit, for a run that stopped after its
+ * maximum number of steps, by the outcome, except that it's partial for an aborted run, whose
+ * rules are the ones found before the abort and whose unorientable equations are still listed.
+ */
+pub fn completion<V: Variable + Display, O: Operator + Display>(
+    outcome: &Outcome<V, O>,
+    rules: &[Rule<V, O>],
+    aborted: bool,
+    stats: &Stats,
+    properties: &[(Detected<O>, Equation<V, O>)],
+    output: Option<&str>,
+) -> Json {
+    let status = match outcome {
+        _ if aborted => "partial",
+        Outcome::Convergent => "convergent",
+        Outcome::Unorientable(_) => "unorientable",
+        Outcome::Trivial(_) => "trivial",
+        Outcome::Limit => "limit",
+    };
+    let unorientable = match outcome {
+        Outcome::Unorientable(eqs) => eqs.iter().map(|eq| eq.to_string()).collect(),
+        _ => Vec::new(),
+    };
+    let trivial = match outcome {
+        Outcome::Trivial(eq) => Some(eq.to_string()),
+        _ => None,
+    };
+    let properties = properties.iter().map(|(d, axiom)| {
+        obj([
+            ("op", d.op.to_string().trim().into()),
+            ("property", d.property.to_string().into()),
+            ("axiom", axiom.to_string().into()),
+        ])
+    });
+    obj([
+        ("status", status.into()),
+        ("aborted", aborted.into()),
+        ("n_rules", rules.len().into()),
+        ("rules", self::rules(rules)),
+        ("unorientable", unorientable.into()),
+        ("trivial", trivial.into()),
+        ("stats", stats_json(stats)),
+        ("properties", Json::Arr(properties.collect())),
+        ("output", output.into()),
+    ])
+}
+
+fn stats_json(s: &Stats) -> Json {
+    let millis = |d: std::time::Duration| Json::from(d.as_millis() as u64);
+    obj([
+        ("equations", s.equations.into()),
+        ("critical_pairs", s.critical_pairs.into()),
+        ("pruned", s.pruned.into()),
+        ("rules_added", s.rules_added.into()),
+        ("rules_deleted", s.rules_deleted.into()),
+        ("max_size", s.max_size.into()),
+        ("normalize_millis", millis(s.normalize_time)),
+        ("orient_millis", millis(s.orient_time)),
+        ("add_millis", millis(s.add_time)),
+    ])
+}
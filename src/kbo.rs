@@ -0,0 +1,499 @@
+/*
+ * The Knuth-Bendix ordering on words, usable on its own.
+ *
+ * Each operator has a weight, and variables all have the same positive weight. The weight of a
+ * word is the sum of the weights of its symbols. Operators are also totally ordered by a
+ * precedence. compare(s, t, params) is then
+ *
+ * - Greater if s is heavier than t and every variable occurs at least as often in s as in t,
+ * - otherwise, if they have the same weight and every variable occurs equally often in both:
+ *   - Greater if s is f(...) and t is a variable (so s is f^k(t) for a unary f of weight zero),
+ *   - the precedence of their head operators if those differ,
+ *   - and if the heads are the same, the comparison of the first arguments that differ,
+ * - the reverse of the above with s and t swapped,
+ * - and None if neither word is greater.
+ *
+ * Equal weights with differing variable counts are incomparable, which is stricter than the
+ * textbook definition but never orients a rule the textbook ordering wouldn't. The ordering is a
+ * reduction ordering when the weights are admissible: every constant weighs at least as much as
+ * a variable, and a unary operator of weight zero has the highest precedence.
+ */
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::order::ReductionOrder;
+use crate::relation::Rule;
+use crate::word::{Op, Operator, Symbol, Var, Variable, Word, WordRef};
+
+/// The weights and precedence of a Knuth-Bendix ordering. The default uses each operator's own
+/// weight and the precedence derived from their Ord, which is the ordering of Word's PartialOrd.
+#[derive(Clone, Debug)]
+pub struct KboParams<O: Operator> {
+    /// The weight of each operator. Operators without an entry have their Operator::weight.
+    pub weights: BTreeMap<O, u64>,
+    /// The weight of every variable.
+    pub var_weight: u64,
+    /// Operators from lowest to highest precedence. Listed operators are greater than unlisted
+    /// ones, which are compared by derived_precedence.
+    pub precedence: Vec<O>,
+}
+
+impl<O: Operator> Default for KboParams<O> {
+    fn default() -> KboParams<O> {
+        KboParams {
+            weights: BTreeMap::new(),
+            var_weight: O::min_weight(),
+            precedence: Vec::new(),
+        }
+    }
+}
+
+impl<O: Operator> KboParams<O> {
+    pub fn op_weight(&self, f: &O) -> u64 {
+        self.weights.get(f).copied().unwrap_or_else(|| f.weight())
+    }
+
+    pub fn weight<V: Variable>(&self, w: WordRef<'_, V, O>) -> u64 {
+        w.syms
+            .iter()
+            .map(|s| match s {
+                Var(_) => self.var_weight,
+                Op(f) => self.op_weight(f),
+            })
+            .sum()
+    }
+
+    /// Compare operators by precedence.
+    pub fn precedence(&self, f: &O, g: &O) -> Ordering {
+        if self.precedence.is_empty() {
+            return derived_precedence(f, g, |h| self.op_weight(h) == 0);
+        }
+        let rank = |h: &O| self.precedence.iter().position(|p| p == h);
+        match (rank(f), rank(g)) {
+            (Some(i), Some(j)) => i.cmp(&j),
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (None, None) => derived_precedence(f, g, |h| self.op_weight(h) == 0),
+        }
+    }
+}
+
+/// The precedence of operators that aren't listed: a unary operator of weight zero is greater
+/// than the rest, as admissibility requires, and otherwise it's the operators' own Ord.
+pub fn derived_precedence<O: Operator>(f: &O, g: &O, weightless: impl Fn(&O) -> bool) -> Ordering {
+    let top = |h: &O| h.arity() == 1 && weightless(h);
+    top(f).cmp(&top(g)).then_with(|| f.cmp(g))
+}
+
+/// The largest number of precedences tried by KboParams::search and LpoParams::search.
+pub(crate) const MAX_PRECEDENCES: usize = 40_320;
+
+impl<O: Operator> KboParams<O> {
+    /*
+     * Search for a precedence on the operators of the rules under which the ordering, with the
+     * operators' own weights, makes each rule's left side greater than its right side. The
+     * precedences are tried in lexicographic order starting from the operators' Ord, skipping
+     * those where a unary operator of weight zero isn't the greatest, which would make the
+     * ordering inadmissible.
+     */
+    pub fn search<V: Variable>(rules: &[Rule<V, O>]) -> Option<KboParams<O>> {
+        let ops = operators(rules);
+        let mut perm: Vec<usize> = (0..ops.len()).collect();
+        for _ in 0..MAX_PRECEDENCES {
+            let precedence: Vec<O> = perm.iter().map(|&i| ops[i].clone()).collect();
+            let admissible = precedence[..precedence.len().saturating_sub(1)]
+                .iter()
+                .all(|f| f.arity() != 1 || f.weight() > 0);
+            let params = KboParams {
+                precedence,
+                ..KboParams::default()
+            };
+            if admissible
+                && rules
+                    .iter()
+                    .all(|r| compare(&r.left, &r.right, &params) == Some(Ordering::Greater))
+            {
+                return Some(params);
+            }
+            if !next_permutation(&mut perm) {
+                return None;
+            }
+        }
+        None
+    }
+}
+
+// The operators of rules, in their Ord.
+pub(crate) fn operators<V: Variable, O: Operator>(rules: &[Rule<V, O>]) -> Vec<O> {
+    rules
+        .iter()
+        .flat_map(|r| r.left.syms.iter().chain(&r.right.syms))
+        .filter_map(|s| match s {
+            Op(f) => Some(f.clone()),
+            Var(_) => None,
+        })
+        .collect::<BTreeSet<O>>()
+        .into_iter()
+        .collect()
+}
+
+// Step perm to the next permutation in lexicographic order, or return false if it's the last.
+pub(crate) fn next_permutation(perm: &mut [usize]) -> bool {
+    let Some(i) = (1..perm.len()).rev().find(|&i| perm[i - 1] < perm[i]) else {
+        return false;
+    };
+    let j = (i..perm.len())
+        .rev()
+        .find(|&j| perm[i - 1] < perm[j])
+        .unwrap();
+    perm.swap(i - 1, j);
+    perm[i..].reverse();
+    true
+}
+
+/// Compare two words in the Knuth-Bendix ordering given by params, returning None if they are
+/// incomparable.
+pub fn compare<V: Variable, O: Operator>(
+    s: &Word<V, O>,
+    t: &Word<V, O>,
+    params: &KboParams<O>,
+) -> Option<Ordering> {
+    compare_ref(s.view(), t.view(), params)
+}
+
+/*
+ * compare on borrowed views, so comparing arguments copies nothing. This is the linear-time
+ * algorithm of Löchner's "Things to know when implementing KBO": both words are walked together,
+ * keeping one running difference of their weights and of each variable's occurrences. Where the
+ * heads are the same only the first pair of arguments that differ is compared, and the arguments
+ * after it are just counted, so each symbol of s and t is visited once.
+ */
+pub fn compare_ref<V: Variable, O: Operator>(
+    s: WordRef<'_, V, O>,
+    t: WordRef<'_, V, O>,
+    params: &KboParams<O>,
+) -> Option<Ordering> {
+    if s.is_ground() && t.is_ground() {
+        return compare_ground(s, t, params);
+    }
+    Balance::default().compare(s.syms, 0, t.syms, 0, params).0
+}
+
+/*
+ * How much s outweighs t, and how many more times each variable occurs in s than t, over the
+ * symbols walked so far, with the number of variables whose difference is positive and negative.
+ * Arguments that compared Equal add nothing, so on returning from a pair of arguments these are
+ * the differences between just that pair.
+ */
+struct Balance<'a, V> {
+    weight: i64,
+    vars: BTreeMap<&'a V, i64>,
+    pos: usize,
+    neg: usize,
+}
+
+impl<V> Default for Balance<'_, V> {
+    fn default() -> Self {
+        Balance {
+            weight: 0,
+            vars: BTreeMap::new(),
+            pos: 0,
+            neg: 0,
+        }
+    }
+}
+
+impl<'a, V: Variable> Balance<'a, V> {
+    // Add the subword of syms at i to s's side if sign is 1, or to t's if it's -1, and return
+    // where it ends.
+    fn add<O: Operator>(
+        &mut self,
+        syms: &'a [Symbol<V, O>],
+        mut i: usize,
+        sign: i64,
+        params: &KboParams<O>,
+    ) -> usize {
+        let mut pending = 1;
+        while pending > 0 {
+            let sym = &syms[i];
+            pending = pending - 1 + sym.arity();
+            i += 1;
+            match sym {
+                Var(v) => {
+                    self.weight += sign * params.var_weight as i64;
+                    let n = self.vars.entry(v).or_insert(0);
+                    let before = *n;
+                    *n += sign;
+                    match (before.signum(), n.signum()) {
+                        (0, 1) => self.pos += 1,
+                        (0, -1) => self.neg += 1,
+                        (1, 0) => self.pos -= 1,
+                        (-1, 0) => self.neg -= 1,
+                        _ => {}
+                    }
+                }
+                Op(f) => self.weight += sign * params.op_weight(f) as i64,
+            }
+        }
+        i
+    }
+
+    // Compare the subwords of s at i and of t at j, and return where each ends. The words are
+    // walked as flat symbols rather than with subwords, which would scan each argument to find
+    // its end before comparing it.
+    fn compare<O: Operator>(
+        &mut self,
+        s: &'a [Symbol<V, O>],
+        i: usize,
+        t: &'a [Symbol<V, O>],
+        j: usize,
+        params: &KboParams<O>,
+    ) -> (Option<Ordering>, usize, usize) {
+        // The comparison of the first arguments that differ, when the heads are the same. Their
+        // weights cancel, so only the arguments are counted.
+        let (lex, s_end, t_end) = match (&s[i], &t[j]) {
+            (Op(f), Op(g)) if f == g => {
+                let (mut lex, mut i, mut j) = (Some(Ordering::Equal), i + 1, j + 1);
+                for _ in 0..f.arity() {
+                    if lex == Some(Ordering::Equal) {
+                        (lex, i, j) = self.compare(s, i, t, j, params);
+                    } else {
+                        i = self.add(s, i, 1, params);
+                        j = self.add(t, j, -1, params);
+                    }
+                }
+                (lex, i, j)
+            }
+            _ => (None, self.add(s, i, 1, params), self.add(t, j, -1, params)),
+        };
+        // s can only be greater if every variable occurs at least as often in s as in t.
+        let covers_t = self.neg == 0;
+        let covered_by_t = self.pos == 0;
+        let o = match self.weight.cmp(&0) {
+            Ordering::Greater => covers_t.then_some(Ordering::Greater),
+            Ordering::Less => covered_by_t.then_some(Ordering::Less),
+            // Equal weight words can only be compared if each variable occurs exactly as often
+            // in both.
+            Ordering::Equal if !(covers_t && covered_by_t) => None,
+            Ordering::Equal => match (&s[i], &t[j]) {
+                // This covers the case where s is f^N x and t is x. Each word has exactly the
+                // same variables here, so if one side is just a variable, then the other side
+                // also only has one of that same variable. They also have the same weight, which
+                // means that there are no operators which have positive weight, otherwise the
+                // only side with any operators would have larger weight. Operators with arity 2
+                // or more may have zero weight, but this would ultimately require additional
+                // variables or nullary operators on one side, which would contribute to a larger
+                // weight, and so can't happen here. Therefore the only operator in play here is
+                // a unary operator of zero weight, and the ordering defines this to mean that the
+                // one with an operator is greater than the one without.
+                (Op(_), Var(_)) => Some(Ordering::Greater),
+                (Var(_), Op(_)) => Some(Ordering::Less),
+                // The variables balance, so these are the same variable.
+                (Var(_), Var(_)) => Some(Ordering::Equal),
+                (Op(f), Op(g)) if f == g => lex,
+                (Op(f), Op(g)) => Some(params.precedence(f, g)),
+            },
+        };
+        (o, s_end, t_end)
+    }
+}
+
+/*
+ * compare on ground words, where there are no variable counts to check and the ordering is total
+ * when the precedence is: by weight, then by head precedence, then by the first arguments that
+ * differ. None if either word has a variable. The words are walked together as in compare_ref,
+ * keeping only the difference of their weights.
+ */
+pub fn compare_ground<V: Variable, O: Operator>(
+    s: WordRef<'_, V, O>,
+    t: WordRef<'_, V, O>,
+    params: &KboParams<O>,
+) -> Option<Ordering> {
+    let mut weight = 0;
+    ground_at(s.syms, 0, t.syms, 0, &mut weight, params).map(|(o, _, _)| o)
+}
+
+// Compare the subwords of s at i and of t at j, adding how much s outweighs t to weight, and
+// return where each ends, or None if either has a variable.
+fn ground_at<V: Variable, O: Operator>(
+    s: &[Symbol<V, O>],
+    i: usize,
+    t: &[Symbol<V, O>],
+    j: usize,
+    weight: &mut i64,
+    params: &KboParams<O>,
+) -> Option<(Ordering, usize, usize)> {
+    let (Op(f), Op(g)) = (&s[i], &t[j]) else {
+        return None;
+    };
+    let (lex, s_end, t_end) = if f == g {
+        let (mut lex, mut i, mut j) = (Ordering::Equal, i + 1, j + 1);
+        for _ in 0..f.arity() {
+            if lex.is_eq() {
+                (lex, i, j) = ground_at(s, i, t, j, weight, params)?;
+            } else {
+                i = add_ground(s, i, 1, weight, params)?;
+                j = add_ground(t, j, -1, weight, params)?;
+            }
+        }
+        (lex, i, j)
+    } else {
+        let s_end = add_ground(s, i, 1, weight, params)?;
+        let t_end = add_ground(t, j, -1, weight, params)?;
+        (params.precedence(f, g), s_end, t_end)
+    };
+    Some(((*weight).cmp(&0).then(lex), s_end, t_end))
+}
+
+// Add sign times the weight of the subword of syms at i to weight, and return where it ends, or
+// None if it has a variable.
+fn add_ground<V: Variable, O: Operator>(
+    syms: &[Symbol<V, O>],
+    mut i: usize,
+    sign: i64,
+    weight: &mut i64,
+    params: &KboParams<O>,
+) -> Option<usize> {
+    let mut pending = 1;
+    while pending > 0 {
+        let Op(f) = &syms[i] else {
+            return None;
+        };
+        pending = pending - 1 + f.arity();
+        *weight += sign * params.op_weight(f) as i64;
+        i += 1;
+    }
+    Some(i)
+}
+
+impl<V: Variable, O: Operator> ReductionOrder<V, O> for KboParams<O> {
+    fn compare(&self, s: &Word<V, O>, t: &Word<V, O>) -> Option<Ordering> {
+        compare(s, t, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::*;
+    use crate::Kbo;
+
+    #[test]
+    fn custom_weights() {
+        let x = var("x");
+        let params = KboParams::<Prod>::default();
+        assert_eq!(
+            compare(&(&x * &one()), &x, &params),
+            Some(Ordering::Greater)
+        );
+
+        // The identity outweighs x * y here, but the variable counts still rule out 1 > x * y.
+        let y = var("y");
+        let heavy = KboParams {
+            weights: BTreeMap::from([(Prod::One, 4)]),
+            ..KboParams::default()
+        };
+        assert_eq!(compare(&one(), &(&x * &y), &heavy), None);
+        assert_eq!(
+            compare(&one(), &(&one() * &one()), &heavy),
+            Some(Ordering::Less)
+        );
+        assert_eq!(compare(&(&x * &one()), &(&x * &x), &heavy), None);
+        assert_eq!(
+            compare(&(inv(&one()) * &x), &(&one() * &x), &heavy),
+            Some(Ordering::Greater)
+        );
+
+        // Reversing the precedence of Mul and Inv flips their comparison at equal weight, though
+        // it makes the ordering inadmissible, since Inv has weight zero.
+        let flipped = KboParams {
+            precedence: vec![Prod::One, Prod::Inv, Prod::Mul],
+            ..KboParams::default()
+        };
+        let a = inv(&(&x * &y));
+        let b = inv(&y) * inv(&x);
+        assert_eq!(Kbo.compare(&a, &b), Some(Ordering::Greater));
+        assert_eq!(compare(&a, &b, &flipped), Some(Ordering::Less));
+
+        // Without a list Inv is above the others whatever the order of its declaration.
+        let derived = KboParams::<Prod>::default();
+        assert_eq!(
+            derived.precedence(&Prod::Inv, &Prod::Mul),
+            Ordering::Greater
+        );
+        assert_eq!(derived.precedence(&Prod::One, &Prod::Mul), Ordering::Less);
+    }
+
+    #[test]
+    fn search() {
+        // f(g(x)) → g(f(x)) needs f above g, which their Ord doesn't give.
+        let mut sig = crate::Signature::new();
+        sig.declare("f", 1, 1).unwrap();
+        sig.declare("g", 1, 1).unwrap();
+        let rule = Rule {
+            left: crate::term!(sig, f(g(x))),
+            right: crate::term!(sig, g(f(x))),
+        };
+        assert_eq!(Kbo.compare(&rule.left, &rule.right), Some(Ordering::Less));
+        let params = KboParams::search(std::slice::from_ref(&rule)).unwrap();
+        let names: Vec<String> = params.precedence.iter().map(|f| f.to_string()).collect();
+        assert_eq!(names, ["g", "f"]);
+
+        let x = var("x");
+        let backwards = Rule {
+            left: x.clone(),
+            right: one() * &x,
+        };
+        assert!(KboParams::search(&[backwards]).is_none());
+    }
+
+    // The arguments after the first pair that differ are still counted: inv(x) is greater than x,
+    // but y in place of z leaves the products incomparable.
+    #[test]
+    fn lexicographic() {
+        let (x, y, z) = (var("x"), var("y"), var("z"));
+        let params = KboParams::default();
+        assert_eq!(compare(&inv(&x), &x, &params), Some(Ordering::Greater));
+        assert_eq!(
+            compare(&(inv(&x) * &y), &(&x * &y), &params),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(compare(&(inv(&x) * &y), &(&x * &z), &params), None);
+        assert_eq!(
+            compare(&(&x * (&y * &z)), &(&x * (&y * &z)), &params),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(compare(&(&x * &y), &(&y * &x), &params), None);
+    }
+
+    // On ground words compare takes the ground path; the skolemizing order agrees with it there.
+    #[test]
+    fn ground() {
+        let e = one();
+        let words = [
+            e.clone(),
+            inv(&e),
+            &e * &e,
+            inv(&(&e * &e)),
+            inv(&e) * &e,
+            &e * inv(&e),
+            (&e * &e) * &e,
+            &e * (&e * &e),
+        ];
+        for s in &words {
+            assert!(s.is_ground());
+            for t in &words {
+                assert_eq!(
+                    compare(s, t, &KboParams::default()),
+                    Some(crate::GroundKbo.cmp(s, t))
+                );
+            }
+        }
+        let x = &e * &var("x");
+        assert!(!x.is_ground());
+        assert_eq!(
+            compare_ground(x.view(), e.view(), &KboParams::default()),
+            None
+        );
+    }
+}
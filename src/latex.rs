@@ -0,0 +1,195 @@
+/*
+ * LaTeX math for words, rules, and rule systems, to paste into papers and notes:
+ *
+ *     word(&w)            (x \cdot y)^{-1} \cdot x
+ *     rules(&trs)         \begin{align*} x^{-1} \cdot x &\to 1 \\ ... \end{align*}
+ *     derivation(&steps)  the words of a rewrite sequence, one step per line
+ *
+ * Words are parenthesized by their operators' notations exactly as Display does. An operator is
+ * written from its Display: a few common symbols become their LaTeX commands (* and · are \cdot,
+ * − is -, ∧ ∨ ¬ are \land \lor \neg), a name of superscript characters such as ⁻¹ becomes a
+ * superscript, a name of several letters is set upright with \mathrm, and LaTeX's special
+ * characters are escaped. A variable's trailing digits become a subscript, so x1 is x_{1}.
+ */
+use std::fmt::{self, Display, Write};
+
+use crate::notation::{write_with, Fixity, Render};
+use crate::relation::{Equation, Rule};
+use crate::trs::Trs;
+use crate::word::{Operator, Variable, Word, WordRef};
+
+fn escape(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '#' | '$' | '%' | '&' | '_' | '{' | '}' => write!(out, "\\{}", c).unwrap(),
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '\\' => out.push_str("\\backslash{}"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn superscript(c: char) -> Option<char> {
+    let i = "⁰¹²³⁴⁵⁶⁷⁸⁹".chars().position(|d| d == c);
+    match c {
+        '⁻' => Some('-'),
+        '⁺' => Some('+'),
+        _ => i.map(|i| char::from(b'0' + i as u8)),
+    }
+}
+
+// The LaTeX for an operator displayed as name.
+fn op_latex(name: &str) -> String {
+    let name = name.trim();
+    match name {
+        "*" | "·" | "∙" => return "\\cdot".to_string(),
+        "−" => return "-".to_string(),
+        "∧" => return "\\land".to_string(),
+        "∨" => return "\\lor".to_string(),
+        "¬" => return "\\neg".to_string(),
+        _ => {}
+    }
+    if let Some(sup) = name.chars().map(superscript).collect::<Option<String>>() {
+        if !sup.is_empty() {
+            return format!("^{{{}}}", sup);
+        }
+    }
+    if name.chars().count() > 1 && name.chars().all(char::is_alphabetic) {
+        return format!("\\mathrm{{{}}}", name);
+    }
+    escape(name)
+}
+
+// The LaTeX for a variable displayed as name.
+fn var_latex(name: &str) -> String {
+    let digits = name.len() - name.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let (base, sub) = name.split_at(name.len() - digits);
+    if base.is_empty() || sub.is_empty() {
+        escape(name)
+    } else {
+        format!("{}_{{{}}}", escape(base), sub)
+    }
+}
+
+struct Latex<'a, V: Variable, O: Operator>(WordRef<'a, V, O>);
+
+impl<V: Variable + Display, O: Operator + Display> Display for Latex<'_, V, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let render = Render {
+            var: &|v: &V, f| f.write_str(&var_latex(&v.to_string())),
+            op: &|o: &O, f| {
+                let s = op_latex(&o.to_string());
+                if o.notation().fixity == Fixity::Infix {
+                    write!(f, " {} ", s)
+                } else {
+                    f.write_str(&s)
+                }
+            },
+            group_postfix: true,
+        };
+        write_with(self.0, f, &render)
+    }
+}
+
+pub fn word<V: Variable + Display, O: Operator + Display>(w: &Word<V, O>) -> String {
+    Latex(w.view()).to_string()
+}
+
+pub fn rule<V: Variable + Display, O: Operator + Display>(r: &Rule<V, O>) -> String {
+    format!("{} \\to {}", word(&r.left), word(&r.right))
+}
+
+pub fn equation<V: Variable + Display, O: Operator + Display>(eq: &Equation<V, O>) -> String {
+    format!("{} = {}", word(&eq.left), word(&eq.right))
+}
+
+fn align(lines: &[String]) -> String {
+    let mut out = String::from("\\begin{align*}\n");
+    for (i, line) in lines.iter().enumerate() {
+        out.push_str("  ");
+        out.push_str(line);
+        out.push_str(if i + 1 < lines.len() { " \\\\\n" } else { "\n" });
+    }
+    out.push_str("\\end{align*}\n");
+    out
+}
+
+/// The rules of trs in an align* environment, aligned on their arrows.
+pub fn rules<V: Variable + Display, O: Operator + Display>(trs: &Trs<V, O>) -> String {
+    let lines: Vec<String> = trs
+        .rules()
+        .iter()
+        .map(|r| format!("{} &\\to {}", word(&r.left), word(&r.right)))
+        .collect();
+    align(&lines)
+}
+
+/// A sequence of words, each a rewrite of the one before, such as successive rewrite_step
+/// results, in an align* environment with one step per line.
+pub fn derivation<V: Variable + Display, O: Operator + Display>(steps: &[Word<V, O>]) -> String {
+    let Some((first, rest)) = steps.split_first() else {
+        return align(&[]);
+    };
+    let mut lines: Vec<String> = rest.iter().map(|w| format!("&\\to {}", word(w))).collect();
+    match lines.first_mut() {
+        Some(line) => *line = format!("{} {}", word(first), line),
+        None => lines.push(word(first)),
+    }
+    align(&lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::{inv, one, var};
+    use crate::{knuth_bendix, sum};
+
+    #[test]
+    fn words() {
+        let (x, y) = (var("x1"), var("y"));
+        assert_eq!(
+            word(&(inv(&(&x * &y)) * &x)),
+            "(x_{1} \\cdot y)^{-1} \\cdot x_{1}"
+        );
+        assert_eq!(word(&(&x * inv(&inv(&y)))), "x_{1} \\cdot {y^{-1}}^{-1}");
+        let a = sum::var("a");
+        assert_eq!(word(&-(&a + sum::zero())), "-(a + 0)");
+        assert_eq!(op_latex("succ"), "\\mathrm{succ}");
+        assert_eq!(op_latex("f_1"), "f\\_1");
+        assert_eq!(var_latex("x'"), "x'");
+    }
+
+    #[test]
+    fn systems() {
+        let x = var("x");
+        let axioms = [
+            Equation {
+                left: one() * &x,
+                right: x.clone(),
+            },
+            Equation {
+                left: inv(&x) * &x,
+                right: one(),
+            },
+        ];
+        assert_eq!(equation(&axioms[1]), "x^{-1} \\cdot x = 1");
+        let trs = Trs::new(knuth_bendix(&axioms[..1]).unwrap());
+        assert_eq!(
+            rules(&trs),
+            "\\begin{align*}\n  1 \\cdot x &\\to x\n\\end{align*}\n"
+        );
+        assert_eq!(rule(&trs.rules()[0]), "1 \\cdot x \\to x");
+
+        let mut steps = vec![one() * (one() * var("a"))];
+        while let Some(w) = trs.rewrite_step(steps.last().unwrap()) {
+            steps.push(w);
+        }
+        assert_eq!(
+            derivation(&steps),
+            "\\begin{align*}\n  1 \\cdot (1 \\cdot a) &\\to 1 \\cdot a \\\\\n  &\\to a\n\\end{align*}\n"
+        );
+    }
+}
@@ -1,8 +1,101 @@
 mod word;
 pub use word::*;
 
+#[cfg(feature = "derive")]
+pub use knuth_bendix_derive::Operator;
+
+mod arena;
+pub use arena::*;
+
+mod bank;
+pub use bank::*;
+mod cache;
+pub use cache::*;
+
+mod completion;
+pub use completion::*;
+
+mod context;
+pub use context::*;
+mod discrimination;
+pub use discrimination::*;
+mod dot;
+pub use dot::*;
+mod fingerprint;
+pub use fingerprint::*;
+mod flatterm;
+pub use flatterm::*;
+
+mod intern;
+pub use intern::*;
+mod lpo;
+pub use lpo::*;
+mod notation;
+pub use notation::*;
+
+mod order;
+pub use order::*;
+mod parse;
+pub use parse::*;
+
+mod poly;
+pub use poly::*;
+mod properties;
+pub use properties::*;
+
+mod tkbo;
+pub use tkbo::*;
+
 mod relation;
 pub use relation::*;
 
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod binder;
+pub mod cpf;
+pub mod dag;
+pub mod dependency_pairs;
+pub mod json;
+pub mod kbo;
+pub mod latex;
+pub mod maude;
+pub mod presentations;
+pub mod problems;
 pub mod prod;
+pub mod saturation;
+pub mod smtlib;
+pub mod string_rewriting;
 pub mod sum;
+pub mod theories;
+pub mod tpdb;
+pub mod tptp;
+
+mod sexpr;
+mod signature;
+pub use signature::*;
+
+mod sort;
+pub use sort::*;
+mod spec;
+pub use spec::*;
+mod subst;
+pub use subst::*;
+
+mod term;
+pub use term::*;
+
+mod theory;
+pub use theory::*;
+mod theory_macro;
+
+mod trs;
+pub use trs::*;
+
+mod zipper;
+pub use zipper::*;
+
+#[cfg(feature = "playground")]
+pub mod playground;
+
+#[cfg(test)]
+mod cross_validation;
@@ -0,0 +1,198 @@
+use std::cmp::Ordering;
+
+use crate::kbo::{next_permutation, operators, MAX_PRECEDENCES};
+use crate::order::ReductionOrder;
+use crate::relation::Rule;
+use crate::word::{Op, Operator, Var, Variable, Word, WordRef};
+
+/*
+ * The lexicographic path ordering, with the operators' own Ord as precedence. s = f(s1, ..., sn)
+ * is greater than t if
+ *
+ *     some si is t or greater than t, or
+ *     t = g(t1, ..., tm) with f above g, and s is greater than every tj, or
+ *     t = f(t1, ..., tn), s is greater than every ti, and the arguments of s are greater than
+ *     those of t, compared left to right,
+ *
+ * and s is greater than a variable it contains. It's well founded for any precedence, and unlike
+ * the Knuth-Bendix ordering it doesn't count symbols, so it can orient rules that copy a
+ * variable, such as x * (y + z) → x * y + x * z.
+ */
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lpo;
+
+/// The precedence of a lexicographic path ordering. The default uses the operators' own Ord,
+/// which is the ordering of Lpo.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LpoParams<O: Operator> {
+    /// Operators from lowest to highest precedence. Listed operators are greater than unlisted
+    /// ones, which are compared by their own Ord.
+    pub precedence: Vec<O>,
+}
+
+impl<O: Operator> Default for LpoParams<O> {
+    fn default() -> LpoParams<O> {
+        LpoParams {
+            precedence: Vec::new(),
+        }
+    }
+}
+
+impl<O: Operator> LpoParams<O> {
+    /// Compare operators by precedence.
+    pub fn precedence(&self, f: &O, g: &O) -> Ordering {
+        if self.precedence.is_empty() {
+            return f.cmp(g);
+        }
+        let rank = |h: &O| self.precedence.iter().position(|p| p == h);
+        match (rank(f), rank(g)) {
+            (Some(i), Some(j)) => i.cmp(&j),
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (None, None) => f.cmp(g),
+        }
+    }
+
+    /// Search for a precedence on the operators of the rules under which each rule's left side is
+    /// greater than its right side. The precedences are tried in lexicographic order starting
+    /// from the operators' Ord, as KboParams::search tries them.
+    pub fn search<V: Variable>(rules: &[Rule<V, O>]) -> Option<LpoParams<O>> {
+        let ops = operators(rules);
+        let mut perm: Vec<usize> = (0..ops.len()).collect();
+        for _ in 0..MAX_PRECEDENCES {
+            let params = LpoParams {
+                precedence: perm.iter().map(|&i| ops[i].clone()).collect(),
+            };
+            if rules
+                .iter()
+                .all(|r| greater(r.left.view(), r.right.view(), &params))
+            {
+                return Some(params);
+            }
+            if !next_permutation(&mut perm) {
+                return None;
+            }
+        }
+        None
+    }
+}
+
+fn greater<V: Variable, O: Operator>(
+    s: WordRef<'_, V, O>,
+    t: WordRef<'_, V, O>,
+    params: &LpoParams<O>,
+) -> bool {
+    let Some(Op(f)) = s.syms.first() else {
+        return false;
+    };
+    let g = match t.syms.first() {
+        Some(Var(x)) => return s.syms.iter().any(|y| matches!(y, Var(y) if y == x)),
+        Some(Op(g)) => g,
+        None => return false,
+    };
+    if s.subwords()
+        .any(|a| a.syms == t.syms || greater(a, t, params))
+    {
+        return true;
+    }
+    match params.precedence(f, g) {
+        Ordering::Greater => t.subwords().all(|b| greater(s, b, params)),
+        Ordering::Equal => {
+            t.subwords().all(|b| greater(s, b, params))
+                && s.subwords()
+                    .zip(t.subwords())
+                    .find(|(a, b)| a.syms != b.syms)
+                    .is_some_and(|(a, b)| greater(a, b, params))
+        }
+        Ordering::Less => false,
+    }
+}
+
+fn compare<V: Variable, O: Operator>(
+    s: &Word<V, O>,
+    t: &Word<V, O>,
+    params: &LpoParams<O>,
+) -> Option<Ordering> {
+    if s.syms == t.syms {
+        Some(Ordering::Equal)
+    } else if greater(s.view(), t.view(), params) {
+        Some(Ordering::Greater)
+    } else if greater(t.view(), s.view(), params) {
+        Some(Ordering::Less)
+    } else {
+        None
+    }
+}
+
+impl<V: Variable, O: Operator> ReductionOrder<V, O> for Lpo {
+    fn compare(&self, s: &Word<V, O>, t: &Word<V, O>) -> Option<Ordering> {
+        compare(s, t, &LpoParams::default())
+    }
+}
+
+impl<V: Variable, O: Operator> ReductionOrder<V, O> for LpoParams<O> {
+    fn compare(&self, s: &Word<V, O>, t: &Word<V, O>) -> Option<Ordering> {
+        compare(s, t, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::{inv, one, var};
+    use crate::theories::ring;
+    use crate::Kbo;
+
+    #[test]
+    fn lpo() {
+        let (x, y, z) = (var("x"), var("y"), var("z"));
+        let cmp = |s: &crate::prod::Word, t: &crate::prod::Word| Lpo.compare(s, t);
+        assert_eq!(
+            cmp(&((&x * &y) * &z), &(&x * (&y * &z))),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(cmp(&(inv(&x) * &x), &one()), Some(Ordering::Greater));
+        assert_eq!(cmp(&(&x * &y), &(&y * &x)), None);
+        assert_eq!(cmp(&x, &(&x * &y)), Some(Ordering::Less));
+        assert_eq!(cmp(&x, &y), None);
+        // Mul is above Inv, which leaves an inverse of a product and the product of inverses
+        // incomparable; putting Inv above Mul makes the first greater.
+        let (l, r) = (inv(&(&x * &y)), inv(&y) * inv(&x));
+        assert_eq!(cmp(&l, &r), None);
+        let above = LpoParams {
+            precedence: vec![crate::prod::Prod::Mul, crate::prod::Prod::Inv],
+        };
+        assert_eq!(above.compare(&l, &r), Some(Ordering::Greater));
+
+        let (x, y, z) = (ring::var("x"), ring::var("y"), ring::var("z"));
+        let (l, r) = (&x * &(&y + &z), &(&x * &y) + &(&x * &z));
+        assert_eq!(Lpo.compare(&l, &r), Some(Ordering::Greater));
+        assert_eq!(Kbo.compare(&l, &r), Some(Ordering::Less));
+    }
+
+    #[test]
+    fn search() {
+        use crate::prod::Prod;
+        let x = var("x");
+        // Orienting inv(x * x) → inv(x) * x takes inverse above *, the opposite of their Ord,
+        // which orients it the other way.
+        let rule = Rule {
+            left: inv(&(&x * &x)),
+            right: inv(&x) * &x,
+        };
+        assert_eq!(Lpo.compare(&rule.left, &rule.right), Some(Ordering::Less));
+        let params = LpoParams::search(std::slice::from_ref(&rule)).unwrap();
+        assert_eq!(params.precedence, [Prod::Mul, Prod::Inv]);
+        assert_eq!(
+            params.compare(&rule.left, &rule.right),
+            Some(Ordering::Greater)
+        );
+        let swapped = Rule {
+            left: inv(&x) * &x,
+            right: inv(&(&x * &x)),
+        };
+        assert!(LpoParams::search(&[rule, swapped]).is_none());
+    }
+}
@@ -0,0 +1,267 @@
+/*
+ * Export of a Trs as a Maude module, so a system can be run and compared in Maude. fmod writes
+ * the rules as equations of a functional module and module as rules of a system module:
+ *
+ *     fmod GROUPS is
+ *       sort U .
+ *       op e : -> U .
+ *       op _*_ : U U -> U .
+ *       op _⁻¹ : U -> U .
+ *       eq _*_(e, X0:U) = X0:U .
+ *     endfm
+ *
+ * Operators are declared with the mixfix form of their notation, with comm and assoc attributes
+ * when is_commutative and is_associative say so, but words are written in prefix form, which
+ * Maude accepts for any operator, so nothing depends on Maude's precedences. An operator is named
+ * by its Display without surrounding spaces, with Maude's special characters escaped by
+ * backquotes; one whose Display has spaces or underscores, or that another operator already
+ * took, is renamed f0, f1, .... Unsorted operators are over the sort U and sorted ones keep their
+ * sorts, though not their subsorts, and variables are written on the fly as X0:U, X1:U, ... in
+ * each statement.
+ */
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Write};
+
+use crate::notation::Fixity;
+use crate::relation::Rule;
+use crate::sort::{var_sorts, Sort};
+use crate::trs::Trs;
+use crate::word::{Op, Operator, Var, Variable, WordRef};
+
+// The name s is written as, or None if it can't be a Maude operator name.
+fn token(s: &str) -> Option<String> {
+    if s.is_empty() || s.contains(|c: char| c.is_whitespace() || c == '_' || c == '`') {
+        return None;
+    }
+    let mut t = String::new();
+    for c in s.chars() {
+        if "()[]{},".contains(c) {
+            t.push('`');
+        }
+        t.push(c);
+    }
+    Some(t)
+}
+
+struct Names<O> {
+    // Each operator's name in mixfix form, which is also how it's applied in prefix form.
+    ops: BTreeMap<O, String>,
+    sorts: BTreeMap<Option<Sort>, String>,
+}
+
+impl<O: Operator + Display> Names<O> {
+    fn new<V: Variable>(rules: &[Rule<V, O>]) -> Names<O> {
+        let ops: BTreeSet<&O> = rules
+            .iter()
+            .flat_map(|r| r.left.syms.iter().chain(&r.right.syms))
+            .filter_map(|s| match s {
+                Op(o) => Some(o),
+                Var(_) => None,
+            })
+            .collect();
+        let mut used = BTreeSet::new();
+        let mut written = BTreeMap::new();
+        let mut renamed = Vec::new();
+        for o in ops {
+            match token(o.to_string().trim()) {
+                Some(name) if used.insert(name.clone()) => {
+                    written.insert(o, name);
+                }
+                _ => renamed.push(o),
+            }
+        }
+        let mut fresh = 0;
+        for o in renamed {
+            let name = loop {
+                let name = format!("f{}", fresh);
+                fresh += 1;
+                if used.insert(name.clone()) {
+                    break name;
+                }
+            };
+            written.insert(o, name);
+        }
+        let ops: BTreeMap<O, String> = written
+            .into_iter()
+            .map(|(o, name)| {
+                let mixfix = match (o.notation().fixity, o.arity()) {
+                    (Fixity::Infix, 2) => format!("_{}_", name),
+                    (Fixity::Prefix, 1) => format!("{}_", name),
+                    (Fixity::Postfix, 1) => format!("_{}", name),
+                    _ => name,
+                };
+                (o.clone(), mixfix)
+            })
+            .collect();
+
+        let mut sorts: BTreeSet<Option<Sort>> = ops
+            .keys()
+            .flat_map(|o| {
+                (0..o.arity())
+                    .map(|i| o.arg_sort(i))
+                    .chain([o.result_sort()])
+            })
+            .collect();
+        for r in rules {
+            sorts.extend(
+                var_sorts(r.left.view(), r.right.view())
+                    .into_iter()
+                    .map(|v| v.1),
+            );
+        }
+        let mut fresh = 0;
+        let mut names = BTreeSet::new();
+        let sorts = sorts
+            .into_iter()
+            .map(|sort| {
+                let written = sort.as_ref().map_or("U", |s| &s.0);
+                let name = if written
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-')
+                    && names.insert(written.to_string())
+                {
+                    written.to_string()
+                } else {
+                    loop {
+                        let name = format!("S{}", fresh);
+                        fresh += 1;
+                        if names.insert(name.clone()) {
+                            break name;
+                        }
+                    }
+                };
+                (sort, name)
+            })
+            .collect();
+        Names { ops, sorts }
+    }
+
+    fn write<V: Variable>(
+        &self,
+        out: &mut String,
+        w: WordRef<'_, V, O>,
+        vars: &[(&V, Option<Sort>)],
+    ) {
+        match &w.syms[0] {
+            Var(v) => {
+                let i = vars.iter().position(|u| u.0 == v).unwrap();
+                write!(out, "X{}:{}", i, self.sorts[&vars[i].1]).unwrap();
+            }
+            Op(o) => {
+                out.push_str(&self.ops[o]);
+                let mut args = w.subwords().peekable();
+                if args.peek().is_some() {
+                    out.push('(');
+                    for (i, a) in args.enumerate() {
+                        if i > 0 {
+                            out.push_str(", ");
+                        }
+                        self.write(out, a, vars);
+                    }
+                    out.push(')');
+                }
+            }
+        }
+    }
+}
+
+fn export<V: Variable, O: Operator + Display>(
+    keyword: &str,
+    statement: &str,
+    arrow: &str,
+    end: &str,
+    name: &str,
+    trs: &Trs<V, O>,
+) -> String {
+    let names = Names::new(trs.rules());
+    let mut out = format!("{} {} is\n", keyword, name);
+    let sorts: Vec<&str> = names.sorts.values().map(String::as_str).collect();
+    match &sorts[..] {
+        [] => {}
+        [sort] => writeln!(out, "  sort {} .", sort).unwrap(),
+        sorts => writeln!(out, "  sorts {} .", sorts.join(" ")).unwrap(),
+    }
+    for (o, mixfix) in &names.ops {
+        write!(out, "  op {} :", mixfix).unwrap();
+        for i in 0..o.arity() {
+            write!(out, " {}", names.sorts[&o.arg_sort(i)]).unwrap();
+        }
+        write!(out, " -> {}", names.sorts[&o.result_sort()]).unwrap();
+        let attrs: Vec<&str> = [(o.is_associative(), "assoc"), (o.is_commutative(), "comm")]
+            .into_iter()
+            .filter(|a| a.0 && o.arity() == 2)
+            .map(|a| a.1)
+            .collect();
+        if !attrs.is_empty() {
+            write!(out, " [{}]", attrs.join(" ")).unwrap();
+        }
+        out.push_str(" .\n");
+    }
+    for r in trs.rules() {
+        let vars = var_sorts(r.left.view(), r.right.view());
+        write!(out, "  {} ", statement).unwrap();
+        names.write(&mut out, r.left.view(), &vars);
+        write!(out, " {} ", arrow).unwrap();
+        names.write(&mut out, r.right.view(), &vars);
+        out.push_str(" .\n");
+    }
+    out.push_str(end);
+    out.push('\n');
+    out
+}
+
+/// Write trs as a functional module named name, with its rules as equations.
+pub fn fmod<V: Variable, O: Operator + Display>(name: &str, trs: &Trs<V, O>) -> String {
+    export("fmod", "eq", "=", "endfm", name, trs)
+}
+
+/// Write trs as a system module named name, with its rules as rewrite rules.
+pub fn module<V: Variable, O: Operator + Display>(name: &str, trs: &Trs<V, O>) -> String {
+    export("mod", "rl", "=>", "endm", name, trs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::{inv, one, var};
+    use crate::sum;
+
+    #[test]
+    fn groups() {
+        let x = var("x");
+        let trs = Trs::new(vec![
+            Rule {
+                left: one() * &x,
+                right: x.clone(),
+            },
+            Rule {
+                left: inv(&x) * &x,
+                right: one(),
+            },
+        ]);
+        assert_eq!(
+            fmod("GROUPS", &trs),
+            "fmod GROUPS is\n  \
+               sort U .\n  \
+               op 1 : -> U .\n  \
+               op _⁻¹ : U -> U .\n  \
+               op _*_ : U U -> U .\n  \
+               eq _*_(1, X0:U) = X0:U .\n  \
+               eq _*_(_⁻¹(X0:U), X0:U) = 1 .\n\
+             endfm\n"
+        );
+        assert!(module("GROUPS", &trs).ends_with("  rl _*_(_⁻¹(X0:U), X0:U) => 1 .\nendm\n"));
+    }
+
+    #[test]
+    fn attributes() {
+        let a = sum::var("a");
+        let trs = Trs::new(vec![Rule {
+            left: &a + &(-&a),
+            right: sum::zero(),
+        }]);
+        let m = fmod("SUM", &trs);
+        assert!(m.contains("  op _+_ : U U -> U [assoc comm] .\n"), "{}", m);
+        assert!(m.contains("  op −_ : U -> U .\n"), "{}", m);
+    }
+}
@@ -0,0 +1,288 @@
+use std::fmt::{self, Display};
+
+use crate::word::{Op, Operator, Var, Variable, Word, WordRef};
+
+/*
+ * How an operator is written, which drives the Display of words over any operators that are
+ * Display themselves. By default an operator is written as a function, f(x, y), or just f for a
+ * constant, so a new theory prints without any work; Operator::notation can make it prefix,
+ * postfix, or infix instead. An operator is shown by its own Display, so an infix operator's
+ * should include any spacing around it, as " + " does.
+ *
+ * An infix application is parenthesized where it is the argument of a prefix or postfix operator,
+ * which bind tighter than any infix operator, or of an infix operator of higher precedence, or of
+ * one of equal precedence on the side it doesn't associate to. It isn't at the top of a word or as
+ * a function argument, where the commas delimit it. Postfix binds tighter than prefix, so -a! is
+ * -(a!), and a prefix application under a postfix operator is parenthesized. A notation that
+ * doesn't fit the number of arguments, such as infix with one, falls back to a function.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fixity {
+    Function,
+    Prefix,
+    Postfix,
+    Infix,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Assoc {
+    Left,
+    Right,
+    None,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Notation {
+    pub fixity: Fixity,
+    /// For infix operators, how tightly they bind; higher binds tighter.
+    pub precedence: u32,
+    pub assoc: Assoc,
+}
+
+impl Notation {
+    pub const FUNCTION: Notation = Notation {
+        fixity: Fixity::Function,
+        precedence: 0,
+        assoc: Assoc::None,
+    };
+    pub const PREFIX: Notation = Notation {
+        fixity: Fixity::Prefix,
+        ..Notation::FUNCTION
+    };
+    pub const POSTFIX: Notation = Notation {
+        fixity: Fixity::Postfix,
+        ..Notation::FUNCTION
+    };
+
+    pub fn infix(precedence: u32, assoc: Assoc) -> Notation {
+        Notation {
+            fixity: Fixity::Infix,
+            precedence,
+            assoc,
+        }
+    }
+}
+
+impl Default for Notation {
+    fn default() -> Notation {
+        Notation::FUNCTION
+    }
+}
+
+// Where a subword is written, which decides whether an infix application there is parenthesized.
+#[derive(Clone, Copy)]
+enum Context {
+    // At the top of the word or as a function argument.
+    Delimited,
+    // The argument of a prefix or postfix operator.
+    Operand(Fixity),
+    // An argument of an infix operator of this precedence, and whether it's on the side the
+    // operator associates to.
+    Infix(u32, bool),
+}
+
+// The notation o is written in with n arguments.
+fn notation<O: Operator>(o: &O, n: usize) -> Fixity {
+    match o.notation().fixity {
+        Fixity::Prefix | Fixity::Postfix if n != 1 => Fixity::Function,
+        Fixity::Infix if n < 2 => Fixity::Function,
+        fixity => fixity,
+    }
+}
+
+/// How a rendering like Display writes each variable and operator, for write_with.
+pub(crate) struct Render<'a, V, O> {
+    pub var: &'a dyn Fn(&V, &mut fmt::Formatter) -> fmt::Result,
+    pub op: &'a dyn Fn(&O, &mut fmt::Formatter) -> fmt::Result,
+    /// Whether to brace the operand of a postfix operator when it's a postfix application too, as
+    /// LaTeX needs for {y^{-1}}^{-1}.
+    pub group_postfix: bool,
+}
+
+fn write_word<V: Variable, O: Operator>(
+    w: WordRef<'_, V, O>,
+    context: Context,
+    f: &mut fmt::Formatter,
+    r: &Render<'_, V, O>,
+) -> fmt::Result {
+    let o = match w.syms.first() {
+        Some(Var(v)) => return (r.var)(v, f),
+        Some(Op(o)) => o,
+        None => return Err(fmt::Error),
+    };
+    let args: Vec<WordRef<'_, V, O>> = w.subwords().collect();
+    if args.len() < o.arity() || args.iter().any(|a| a.syms.is_empty()) {
+        return Err(fmt::Error);
+    }
+    let fixity = notation(o, args.len());
+    let prec = o.notation().precedence;
+    let parens = match (fixity, context) {
+        (Fixity::Infix, Context::Operand(_)) => true,
+        (Fixity::Infix, Context::Infix(outer, on_side)) => {
+            prec < outer || (prec == outer && !on_side)
+        }
+        (Fixity::Prefix, Context::Operand(Fixity::Postfix)) => true,
+        _ => false,
+    };
+    if parens {
+        f.write_str("(")?;
+    }
+    match fixity {
+        Fixity::Function => {
+            (r.op)(o, f)?;
+            if !args.is_empty() {
+                f.write_str("(")?;
+                for (i, a) in args.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write_word(*a, Context::Delimited, f, r)?;
+                }
+                f.write_str(")")?;
+            }
+        }
+        Fixity::Prefix => {
+            (r.op)(o, f)?;
+            write_word(args[0], Context::Operand(Fixity::Prefix), f, r)?;
+        }
+        Fixity::Postfix => {
+            let group = r.group_postfix
+                && matches!(&args[0].syms[0], Op(p) if notation(p, args[0].subwords().count()) == Fixity::Postfix);
+            if group {
+                f.write_str("{")?;
+            }
+            write_word(args[0], Context::Operand(Fixity::Postfix), f, r)?;
+            if group {
+                f.write_str("}")?;
+            }
+            (r.op)(o, f)?;
+        }
+        Fixity::Infix => {
+            let assoc = o.notation().assoc;
+            let last = args.len() - 1;
+            for (i, a) in args.iter().enumerate() {
+                if i > 0 {
+                    (r.op)(o, f)?;
+                }
+                let on_side =
+                    (i == 0 && assoc == Assoc::Left) || (i == last && assoc == Assoc::Right);
+                write_word(*a, Context::Infix(prec, on_side), f, r)?;
+            }
+        }
+    }
+    if parens {
+        f.write_str(")")?;
+    }
+    Ok(())
+}
+
+/// Write w as Display does, parenthesized by the operators' notations, but rendered by r.
+pub(crate) fn write_with<V: Variable, O: Operator>(
+    w: WordRef<'_, V, O>,
+    f: &mut fmt::Formatter,
+    r: &Render<'_, V, O>,
+) -> fmt::Result {
+    write_word(w, Context::Delimited, f, r)
+}
+
+impl<V: Variable + Display, O: Operator + Display> Display for WordRef<'_, V, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let render = Render {
+            var: &|v, f| write!(f, "{}", v),
+            op: &|o, f| write!(f, "{}", o),
+            group_postfix: false,
+        };
+        write_with(*self, f, &render)
+    }
+}
+
+impl<V: Variable + Display, O: Operator + Display> Display for Word<V, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.view().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Signature;
+
+    // Arithmetic with + and − left associative below *, and ! postfix.
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    enum Arith {
+        Add,
+        Sub,
+        Mul,
+        Neg,
+        Fact,
+    }
+
+    impl Display for Arith {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(match self {
+                Arith::Add => " + ",
+                Arith::Sub => " - ",
+                Arith::Mul => " * ",
+                Arith::Neg => "-",
+                Arith::Fact => "!",
+            })
+        }
+    }
+
+    impl Operator for Arith {
+        fn min_weight() -> u64 {
+            1
+        }
+
+        fn arity(&self) -> usize {
+            match self {
+                Arith::Neg | Arith::Fact => 1,
+                _ => 2,
+            }
+        }
+
+        fn weight(&self) -> u64 {
+            1
+        }
+
+        fn notation(&self) -> Notation {
+            match self {
+                Arith::Add | Arith::Sub => Notation::infix(1, Assoc::Left),
+                Arith::Mul => Notation::infix(2, Assoc::Left),
+                Arith::Neg => Notation::PREFIX,
+                Arith::Fact => Notation::POSTFIX,
+            }
+        }
+    }
+
+    #[test]
+    fn notation() {
+        type W = Word<String, Arith>;
+        let (a, b, c) = (W::var("a"), W::var("b"), W::var("c"));
+        let op =
+            |o: Arith, args: &[&W]| Word::op_owned(o, args.iter().map(|&w| w.clone()).collect());
+        let ab = op(Arith::Sub, &[&a, &b]);
+        assert_eq!(op(Arith::Sub, &[&ab, &c]).to_string(), "a - b - c");
+        assert_eq!(op(Arith::Sub, &[&c, &ab]).to_string(), "c - (a - b)");
+        assert_eq!(op(Arith::Mul, &[&ab, &c]).to_string(), "(a - b) * c");
+        let bc = op(Arith::Mul, &[&b, &c]);
+        assert_eq!(op(Arith::Add, &[&a, &bc]).to_string(), "a + b * c");
+        assert_eq!(op(Arith::Neg, &[&ab]).to_string(), "-(a - b)");
+        let fact = |w: &W| op(Arith::Fact, &[w]);
+        let neg = |w: &W| op(Arith::Neg, &[w]);
+        assert_eq!(neg(&fact(&a)).to_string(), "-a!");
+        assert_eq!(fact(&neg(&a)).to_string(), "(-a)!");
+        assert_eq!(fact(&bc).to_string(), "(b * c)!");
+
+        // Declared operators are written as functions.
+        let mut sig = Signature::new();
+        sig.declare("0", 0, 1).unwrap();
+        sig.declare("f", 2, 1).unwrap();
+        sig.declare("g", 1, 1).unwrap();
+        let w = crate::term!(sig, f(x, g(0)));
+        assert_eq!(w.to_string(), "f(x, g(0))");
+    }
+}
@@ -0,0 +1,312 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+
+use crate::kbo;
+use crate::word::{Op, Operator, Var, Variable, Word, WordRef};
+
+/// A reduction ordering on words: a well-founded partial order that is closed under
+/// substitution and under placing both words in the same context. Orienting every rule of a
+/// system to be decreasing in such an ordering proves that the system terminates.
+pub trait ReductionOrder<V: Variable, O: Operator> {
+    /// Compare two words, returning None if they are incomparable.
+    fn compare(&self, s: &Word<V, O>, t: &Word<V, O>) -> Option<Ordering>;
+
+    fn greater(&self, s: &Word<V, O>, t: &Word<V, O>) -> bool {
+        self.compare(s, t) == Some(Ordering::Greater)
+    }
+}
+
+/// The Knuth-Bendix ordering given by the operators' weights, with the precedence derived from
+/// the operators' Ord by kbo::derived_precedence. This is the ordering implemented by Word's
+/// PartialOrd, and by kbo::compare with the default KboParams.
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Kbo;
+
+impl<V: Variable, O: Operator> ReductionOrder<V, O> for Kbo {
+    fn compare(&self, s: &Word<V, O>, t: &Word<V, O>) -> Option<Ordering> {
+        s.partial_cmp(t)
+    }
+}
+
+/// The case of the Knuth-Bendix ordering that decided a comparison.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KboCase<V: Variable, O: Operator> {
+    /// The words are the same.
+    Equal,
+    /// One word is heavier and has at least as many occurrences of every variable.
+    Weight,
+    /// One word is heavier, but the variable occurs more often in the lighter one.
+    VarExcess(V),
+    /// The weights are equal, but the variable occurs a different number of times in each word.
+    VarMismatch(V),
+    /// The weights are equal and one word is a variable, so the other is that variable under
+    /// unary operators of weight zero.
+    ZeroWeightUnary,
+    /// The weights are equal and the head operators differ, so they are compared by precedence.
+    Precedence(O, O),
+    /// The weights and head operators are equal, and the argument at this index is the first one
+    /// that differs.
+    Argument(usize, Box<CmpExplanation<V, O>>),
+    /// One of the words is empty.
+    Empty,
+}
+
+/// Why Word's partial_cmp gave the result it did, as computed by explain_cmp.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CmpExplanation<V: Variable, O: Operator> {
+    pub result: Option<Ordering>,
+    pub weights: (u64, u64),
+    /// The number of occurrences of each variable in each word.
+    pub var_counts: BTreeMap<V, (usize, usize)>,
+    pub case: KboCase<V, O>,
+}
+
+/// Compare two words the way Word's partial_cmp does, recording which case of the ordering
+/// applied along with the weights and variable counts it was decided by.
+pub fn explain_cmp<V: Variable, O: Operator>(
+    s: &Word<V, O>,
+    t: &Word<V, O>,
+) -> CmpExplanation<V, O> {
+    let weights = (s.weight(), t.weight());
+    let mut var_counts: BTreeMap<V, (usize, usize)> = BTreeMap::new();
+    for v in s.syms.iter().filter_map(|x| x.var()) {
+        var_counts.entry(v.clone()).or_default().0 += 1;
+    }
+    for v in t.syms.iter().filter_map(|x| x.var()) {
+        var_counts.entry(v.clone()).or_default().1 += 1;
+    }
+    let (result, case) = match weights.0.cmp(&weights.1) {
+        Ordering::Equal => {
+            if let Some((v, _)) = var_counts.iter().find(|(_, (m, n))| m != n) {
+                (None, KboCase::VarMismatch(v.clone()))
+            } else {
+                match (s.syms.first(), t.syms.first()) {
+                    (Some(Op(_)), Some(Var(_))) => {
+                        (Some(Ordering::Greater), KboCase::ZeroWeightUnary)
+                    }
+                    (Some(Var(_)), Some(Op(_))) => (Some(Ordering::Less), KboCase::ZeroWeightUnary),
+                    (Some(Var(_)), Some(Var(_))) => (Some(Ordering::Equal), KboCase::Equal),
+                    (Some(Op(f)), Some(Op(g))) if f != g => (
+                        Some(kbo::derived_precedence(f, g, |h| h.weight() == 0)),
+                        KboCase::Precedence(f.clone(), g.clone()),
+                    ),
+                    (Some(Op(_)), Some(Op(_))) => {
+                        let first = s
+                            .subwords()
+                            .zip(t.subwords())
+                            .map(|(a, b)| explain_cmp(&a.to_word(), &b.to_word()))
+                            .enumerate()
+                            .find(|(_, e)| e.result != Some(Ordering::Equal));
+                        match first {
+                            Some((i, e)) => (e.result, KboCase::Argument(i, Box::new(e))),
+                            None => (Some(Ordering::Equal), KboCase::Equal),
+                        }
+                    }
+                    _ => (None, KboCase::Empty),
+                }
+            }
+        }
+        o => {
+            // The heavier word needs at least as many of every variable.
+            let excess = var_counts.iter().find(|(_, &(m, n))| match o {
+                Ordering::Greater => m < n,
+                _ => m > n,
+            });
+            match excess {
+                Some((v, _)) => (None, KboCase::VarExcess(v.clone())),
+                None => (Some(o), KboCase::Weight),
+            }
+        }
+    };
+    CmpExplanation {
+        result,
+        weights,
+        var_counts,
+        case,
+    }
+}
+
+impl<V: Variable + Display, O: Operator + Display> Display for CmpExplanation<V, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.result {
+            Some(Ordering::Greater) => write!(f, "greater")?,
+            Some(Ordering::Less) => write!(f, "less")?,
+            Some(Ordering::Equal) => write!(f, "equal")?,
+            None => write!(f, "incomparable")?,
+        }
+        write!(f, ": weights {} and {}", self.weights.0, self.weights.1)?;
+        for (v, (m, n)) in self.var_counts.iter() {
+            write!(f, ", {} occurs {} and {} times", v, m, n)?;
+        }
+        match &self.case {
+            KboCase::Equal => write!(f, "; the words are the same"),
+            KboCase::Weight => write!(f, "; decided by weight"),
+            KboCase::VarExcess(v) => write!(f, "; {} occurs more often in the lighter word", v),
+            KboCase::VarMismatch(v) => {
+                write!(
+                    f,
+                    "; equal weights but {} occurs a different number of times",
+                    v
+                )
+            }
+            KboCase::ZeroWeightUnary => write!(f, "; a variable under zero-weight operators"),
+            KboCase::Precedence(g, h) => write!(f, "; decided by precedence of {} and {}", g, h),
+            KboCase::Argument(i, e) => write!(f, "; decided by argument {}, which is {}", i, e),
+            KboCase::Empty => write!(f, "; a word is empty"),
+        }
+    }
+}
+
+/*
+ * The Knuth-Bendix ordering extended to a total order on ground words, as ordered completion
+ * needs. Variables are compared as if they were fresh constants of the minimum weight, below
+ * every operator in precedence and ordered among themselves by their own Ord. On ground words
+ * this agrees with Kbo, which is already total there because the precedence is; on words with
+ * variables it decides whether one word is greater than the other after skolemizing both, which
+ * is how ordered completion compares instances it can't otherwise orient. It is not closed
+ * under substitution on non-ground words, so it shouldn't be used to prove termination.
+ */
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroundKbo;
+
+impl GroundKbo {
+    pub fn cmp<V: Variable, O: Operator>(&self, s: &Word<V, O>, t: &Word<V, O>) -> Ordering {
+        self.cmp_ref(s.view(), t.view())
+    }
+
+    pub fn cmp_ref<V: Variable, O: Operator>(
+        &self,
+        s: WordRef<'_, V, O>,
+        t: WordRef<'_, V, O>,
+    ) -> Ordering {
+        s.weight()
+            .cmp(&t.weight())
+            .then_with(|| match (&s.syms[0], &t.syms[0]) {
+                (Var(u), Var(v)) => u.cmp(v),
+                (Var(_), Op(_)) => Ordering::Less,
+                (Op(_), Var(_)) => Ordering::Greater,
+                (Op(f), Op(g)) => {
+                    kbo::derived_precedence(f, g, |h| h.weight() == 0).then_with(|| {
+                        s.subwords()
+                            .zip(t.subwords())
+                            .map(|(a, b)| self.cmp_ref(a, b))
+                            .find(|o| o.is_ne())
+                            .unwrap_or(Ordering::Equal)
+                    })
+                }
+            })
+    }
+}
+
+impl<V: Variable, O: Operator> ReductionOrder<V, O> for GroundKbo {
+    fn compare(&self, s: &Word<V, O>, t: &Word<V, O>) -> Option<Ordering> {
+        Some(self.cmp(s, t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prod, sum};
+
+    // Every ground word with at most the given number of operators, built from the constant
+    // and the unary and binary operators of the signature.
+    fn ground<O: Operator>(c: O, u: O, b: O, size: usize) -> Vec<Word<String, O>> {
+        let mut by_size: Vec<Vec<Word<String, O>>> = vec![vec![], vec![Word::op(c, &[])]];
+        for n in 2..=size {
+            let mut ws: Vec<_> = by_size[n - 1]
+                .iter()
+                .map(|w| Word::op(u.clone(), std::slice::from_ref(w)))
+                .collect();
+            for i in 1..n - 1 {
+                for l in by_size[i].iter() {
+                    for r in by_size[n - 1 - i].iter() {
+                        ws.push(Word::op(b.clone(), &[l.clone(), r.clone()]));
+                    }
+                }
+            }
+            by_size.push(ws);
+        }
+        by_size.concat()
+    }
+
+    fn check_total<O: Operator>(words: &[Word<String, O>]) {
+        for s in words {
+            for t in words {
+                let o = GroundKbo.cmp(s, t);
+                assert_eq!(o.is_eq(), s.syms == t.syms);
+                assert_eq!(GroundKbo.cmp(t, s), o.reverse());
+                assert_eq!(Kbo.compare(s, t), Some(o));
+            }
+        }
+        let mut sorted = words.to_vec();
+        sorted.sort_by(|s, t| GroundKbo.cmp(s, t));
+        for w in sorted.windows(3) {
+            assert!(GroundKbo.cmp(&w[0], &w[2]).is_lt());
+        }
+    }
+
+    #[test]
+    fn total_on_ground() {
+        use prod::Prod;
+        use sum::Sum;
+        check_total(&ground(Prod::One, Prod::Inv, Prod::Mul, 6));
+        check_total(&ground(Sum::Zero, Sum::Negate, Sum::Add, 6));
+    }
+
+    #[test]
+    fn skolemized() {
+        let x = prod::var("x");
+        let y = prod::var("y");
+        // Kbo can't compare x*y with y*x, but their skolemized instances are ordered.
+        assert_eq!(Kbo.compare(&(&x * &y), &(&y * &x)), None);
+        assert_eq!(GroundKbo.cmp(&(&x * &y), &(&y * &x)), Ordering::Less);
+        assert_eq!(GroundKbo.cmp(&x, &prod::one()), Ordering::Less);
+        assert_eq!(GroundKbo.cmp(&prod::inv(&x), &x), Ordering::Greater);
+    }
+
+    #[test]
+    fn explain() {
+        use prod::{inv, one, var};
+        let x = var("x");
+        let y = var("y");
+        let words = ground(prod::Prod::One, prod::Prod::Inv, prod::Prod::Mul, 4)
+            .into_iter()
+            .chain([
+                x.clone(),
+                y.clone(),
+                inv(&x),
+                &x * &y,
+                &y * &x,
+                &x * &x,
+                inv(&(&x * &y)),
+                inv(&y) * inv(&x),
+                one() * &x,
+            ])
+            .collect::<Vec<_>>();
+        for s in words.iter() {
+            for t in words.iter() {
+                assert_eq!(explain_cmp(s, t).result, s.partial_cmp(t), "{} vs {}", s, t);
+            }
+        }
+
+        let e = explain_cmp(&(&x * &y), &(&x * &x));
+        assert_eq!(e.case, KboCase::VarMismatch("x".to_string()));
+        assert_eq!(e.var_counts["x"], (1, 2));
+        let e = explain_cmp(&(&x * &y), &x);
+        assert_eq!(e.weights, (3, 1));
+        assert_eq!(e.case, KboCase::Weight);
+        let e = explain_cmp(&(inv(&x) * &y), &(&x * &y));
+        assert_eq!(e.result, Some(Ordering::Greater));
+        assert!(matches!(&e.case, KboCase::Argument(0, a) if a.case == KboCase::ZeroWeightUnary));
+        assert_eq!(
+            e.to_string(),
+            "greater: weights 3 and 3, x occurs 1 and 1 times, y occurs 1 and 1 times; \
+             decided by argument 0, which is greater: weights 1 and 1, x occurs 1 and 1 times; \
+             a variable under zero-weight operators"
+        );
+    }
+}
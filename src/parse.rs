@@ -0,0 +1,419 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+
+use crate::notation::{Assoc, Fixity};
+use crate::relation::Equation;
+use crate::term::NamedOps;
+use crate::word::{Operator, Word};
+
+/*
+ * Parsing words and equations from text, against anything implementing NamedOps, such as a
+ * Signature:
+ *
+ *     parse_term("f(x, g(y, 0))", &sig)
+ *     parse_equation("(x * y) * z = x * (y * z)", &sig)
+ *
+ * A name followed by parenthesized arguments is an operator application, and a name on its own
+ * is a constant if there's a nullary operator by that name and a variable otherwise, as with
+ * term!. Names are runs of letters, digits, _ and '. Operators whose Operator::notation is prefix,
+ * postfix, or infix are also parsed that way, with its precedence and associativity and the same
+ * binding rules as Display, so a displayed word parses back to itself. Chaining an infix operator
+ * that associates neither way needs parentheses. Any other run of symbols is read as the longest
+ * operator name it starts with.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// Something the grammar doesn't allow, at this byte offset.
+    Unexpected { pos: usize, found: String },
+    /// The input ended where more was needed.
+    End,
+    /// No operator is named by the symbols at this byte offset.
+    UnknownOp { pos: usize, name: String },
+    /// An operator was applied to a number of arguments it can't take.
+    Arity {
+        pos: usize,
+        name: String,
+        found: usize,
+    },
+    /// An infix operator that associates neither way was chained without parentheses.
+    NonAssociative { pos: usize, name: String },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Unexpected { pos, found } => {
+                write!(f, "unexpected {} at offset {}", found, pos)
+            }
+            ParseError::End => write!(f, "unexpected end of input"),
+            ParseError::UnknownOp { pos, name } => {
+                write!(f, "no operator named {} at offset {}", name, pos)
+            }
+            ParseError::Arity { pos, name, found } => write!(
+                f,
+                "operator {} at offset {} can't take {} arguments",
+                name, pos, found
+            ),
+            ParseError::NonAssociative { pos, name } => write!(
+                f,
+                "operator {} at offset {} needs parentheses to be chained",
+                name, pos
+            ),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Name(String),
+    Symbol(String),
+    Open,
+    Close,
+    Comma,
+    Equals,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Name(s) | Token::Symbol(s) => s.fmt(f),
+            Token::Open => "(".fmt(f),
+            Token::Close => ")".fmt(f),
+            Token::Comma => ",".fmt(f),
+            Token::Equals => "=".fmt(f),
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '\''
+}
+
+// Split s into tokens with their byte offsets.
+fn tokenize<O: Operator>(
+    s: &str,
+    ops: &impl NamedOps<O>,
+) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while let Some(c) = s[pos..].chars().next() {
+        let next = pos + c.len_utf8();
+        let (token, end) = match c {
+            c if c.is_whitespace() => {
+                pos = next;
+                continue;
+            }
+            '(' => (Token::Open, next),
+            ')' => (Token::Close, next),
+            ',' => (Token::Comma, next),
+            c if is_name_char(c) => {
+                let end = s[pos..]
+                    .find(|c| !is_name_char(c))
+                    .map_or(s.len(), |n| pos + n);
+                (Token::Name(s[pos..end].to_string()), end)
+            }
+            _ => {
+                // The longest run of symbols that names an operator, or = on its own.
+                let run = s[pos..]
+                    .find(|c: char| c.is_whitespace() || "(),".contains(c))
+                    .map_or(s.len(), |n| pos + n);
+                let longest = (next..=run)
+                    .rev()
+                    .filter(|&end| s.is_char_boundary(end))
+                    .find(|&end| ops.op_named(&s[pos..end]).is_some());
+                match longest {
+                    Some(end) => (Token::Symbol(s[pos..end].to_string()), end),
+                    None if c == '=' => (Token::Equals, next),
+                    None => {
+                        return Err(ParseError::UnknownOp {
+                            pos,
+                            name: c.to_string(),
+                        })
+                    }
+                }
+            }
+        };
+        tokens.push((pos, token));
+        pos = end;
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a, O: Operator, N: NamedOps<O>> {
+    ops: &'a N,
+    tokens: Vec<(usize, Token)>,
+    i: usize,
+    op: PhantomData<O>,
+}
+
+impl<O: Operator, N: NamedOps<O>> Parser<'_, O, N> {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.i)
+    }
+
+    fn next(&mut self) -> Result<(usize, Token), ParseError> {
+        let t = self.peek().cloned().ok_or(ParseError::End)?;
+        self.i += 1;
+        Ok(t)
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), ParseError> {
+        match self.next()? {
+            (_, t) if t == token => Ok(()),
+            (pos, t) => Err(ParseError::Unexpected {
+                pos,
+                found: t.to_string(),
+            }),
+        }
+    }
+
+    fn end(&self) -> Result<(), ParseError> {
+        match self.peek() {
+            None => Ok(()),
+            Some((pos, t)) => Err(ParseError::Unexpected {
+                pos: *pos,
+                found: t.to_string(),
+            }),
+        }
+    }
+
+    // The next token as an operator written with fixity, unless a name is called as a function.
+    fn peek_op(&self, fixity: Fixity) -> Option<(usize, String, O)> {
+        let (pos, name) = match self.peek()? {
+            (pos, Token::Symbol(n)) => (*pos, n),
+            (pos, Token::Name(n))
+                if self.tokens.get(self.i + 1).map(|t| &t.1) != Some(&Token::Open) =>
+            {
+                (*pos, n)
+            }
+            _ => return None,
+        };
+        let o = self.ops.op_named(name)?;
+        (o.notation().fixity == fixity).then(|| (pos, name.clone(), o))
+    }
+
+    fn apply(
+        &self,
+        pos: usize,
+        name: &str,
+        o: O,
+        args: Vec<Word<String, O>>,
+    ) -> Result<Word<String, O>, ParseError> {
+        let o = o.with_arity(args.len()).ok_or_else(|| ParseError::Arity {
+            pos,
+            name: name.to_string(),
+            found: args.len(),
+        })?;
+        Ok(Word::op_owned(o, args))
+    }
+
+    // A word whose infix operators all have at least precedence min.
+    fn expr(&mut self, min: u32) -> Result<Word<String, O>, ParseError> {
+        let mut left = self.prefix()?;
+        while let Some((pos, name, o)) = self.peek_op(Fixity::Infix) {
+            let n = o.notation();
+            if n.precedence < min {
+                break;
+            }
+            self.i += 1;
+            let right = self.expr(match n.assoc {
+                Assoc::Right => n.precedence,
+                _ => n.precedence + 1,
+            })?;
+            left = self.apply(pos, &name, o, vec![left, right])?;
+            if n.assoc == Assoc::None {
+                if let Some((pos, name, next)) = self.peek_op(Fixity::Infix) {
+                    if next.notation().precedence == n.precedence {
+                        return Err(ParseError::NonAssociative { pos, name });
+                    }
+                }
+            }
+        }
+        Ok(left)
+    }
+
+    // Prefix operators bind looser than postfix ones, as in Display.
+    fn prefix(&mut self) -> Result<Word<String, O>, ParseError> {
+        if let Some((pos, name, o)) = self.peek_op(Fixity::Prefix) {
+            self.i += 1;
+            let arg = self.prefix()?;
+            return self.apply(pos, &name, o, vec![arg]);
+        }
+        let mut w = self.primary()?;
+        while let Some((pos, name, o)) = self.peek_op(Fixity::Postfix) {
+            self.i += 1;
+            w = self.apply(pos, &name, o, vec![w])?;
+        }
+        Ok(w)
+    }
+
+    fn primary(&mut self) -> Result<Word<String, O>, ParseError> {
+        match self.next()? {
+            (_, Token::Open) => {
+                let w = self.expr(0)?;
+                self.expect(Token::Close)?;
+                Ok(w)
+            }
+            (pos, Token::Name(name)) if self.peek().map(|t| &t.1) == Some(&Token::Open) => {
+                self.i += 1;
+                let mut args = Vec::new();
+                if self.peek().map(|t| &t.1) == Some(&Token::Close) {
+                    self.i += 1;
+                } else {
+                    loop {
+                        args.push(self.expr(0)?);
+                        match self.next()? {
+                            (_, Token::Comma) => {}
+                            (_, Token::Close) => break,
+                            (pos, t) => {
+                                return Err(ParseError::Unexpected {
+                                    pos,
+                                    found: t.to_string(),
+                                })
+                            }
+                        }
+                    }
+                }
+                let o = self
+                    .ops
+                    .op_named(&name)
+                    .ok_or_else(|| ParseError::UnknownOp {
+                        pos,
+                        name: name.clone(),
+                    })?;
+                self.apply(pos, &name, o, args)
+            }
+            (pos, Token::Name(name)) => match self.ops.op_named(&name) {
+                Some(o) => self.apply(pos, &name, o, vec![]),
+                None => Ok(Word::var(name)),
+            },
+            (pos, Token::Symbol(name)) => {
+                let o = self
+                    .ops
+                    .op_named(&name)
+                    .expect("symbols are operator names");
+                self.apply(pos, &name, o, vec![])
+            }
+            (pos, t) => Err(ParseError::Unexpected {
+                pos,
+                found: t.to_string(),
+            }),
+        }
+    }
+}
+
+fn parser<'a, O: Operator, N: NamedOps<O>>(
+    s: &str,
+    ops: &'a N,
+) -> Result<Parser<'a, O, N>, ParseError> {
+    Ok(Parser {
+        ops,
+        tokens: tokenize(s, ops)?,
+        i: 0,
+        op: PhantomData,
+    })
+}
+
+/// Parse a word, with the operators named by ops.
+pub fn parse_term<O: Operator>(
+    s: &str,
+    ops: &impl NamedOps<O>,
+) -> Result<Word<String, O>, ParseError> {
+    let mut p = parser(s, ops)?;
+    let w = p.expr(0)?;
+    p.end()?;
+    Ok(w)
+}
+
+/// Parse an equation l = r, with the operators named by ops.
+pub fn parse_equation<O: Operator>(
+    s: &str,
+    ops: &impl NamedOps<O>,
+) -> Result<Equation<String, O>, ParseError> {
+    let mut p = parser(s, ops)?;
+    let left = p.expr(0)?;
+    p.expect(Token::Equals)?;
+    let right = p.expr(0)?;
+    p.end()?;
+    Ok(Equation { left, right })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{knuth_bendix, prod, sum, term, Signature};
+
+    #[test]
+    fn prefix_and_infix() {
+        let mut sig = Signature::new();
+        sig.declare("e", 0, 1).unwrap();
+        sig.declare("f", 2, 1).unwrap();
+        sig.declare("i", 1, 0).unwrap();
+        sig.declare_infix("+", 1, Assoc::Left, 1).unwrap();
+        sig.declare_infix("*", 2, Assoc::Right, 1).unwrap();
+        assert_eq!(parse_term("f(x, i(e))", &sig), Ok(term!(sig, f(x, i(e)))));
+
+        let w = parse_term("a + b * c * d + e", &sig).unwrap();
+        assert_eq!(w.to_string(), "a + b * c * d + e");
+        let add = |a, b| sig.app("+", &[a, b]).unwrap();
+        let mul = |a, b| sig.app("*", &[a, b]).unwrap();
+        let (a, b, c, d) = (sig.var("a"), sig.var("b"), sig.var("c"), sig.var("d"));
+        let e = sig.app("e", &[]).unwrap();
+        assert_eq!(w, add(add(a, mul(b, mul(c, d))), e));
+
+        assert_eq!(
+            parse_term("f(x)", &sig),
+            Err(ParseError::Arity {
+                pos: 0,
+                name: "f".to_string(),
+                found: 1
+            })
+        );
+        assert_eq!(parse_term("x + ", &sig), Err(ParseError::End));
+        assert_eq!(
+            parse_term("x # y", &sig),
+            Err(ParseError::UnknownOp {
+                pos: 2,
+                name: "#".to_string()
+            })
+        );
+        assert_eq!(
+            parse_term("(x y", &sig).unwrap_err().to_string(),
+            "unexpected y at offset 3"
+        );
+    }
+
+    #[test]
+    fn groups() {
+        let mut sig = Signature::new();
+        sig.declare("e", 0, 1).unwrap();
+        sig.declare_infix("*", 1, Assoc::None, 0).unwrap();
+        sig.declare("i", 1, 0).unwrap();
+        let axioms = ["e * x = x", "i(x) * x = e", "(x * y) * z = x * (y * z)"]
+            .map(|s| parse_equation(s, &sig).unwrap());
+        assert_eq!(knuth_bendix(&axioms).unwrap().len(), 10);
+        assert_eq!(
+            parse_term("x * y * z", &sig),
+            Err(ParseError::NonAssociative {
+                pos: 6,
+                name: "*".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn from_str() {
+        let (a, b) = (prod::var("a"), prod::var("b"));
+        let w = prod::inv(&(&a * &b)) * (prod::one() * prod::inv(&b));
+        assert_eq!(w.to_string().parse(), Ok(w));
+
+        let (a, b) = (sum::var("a"), sum::var("b"));
+        let w = (&a + &b) + sum::negate(&(&b + sum::zero()));
+        assert_eq!(w.to_string(), "(a + b) + −(b + 0)");
+        assert_eq!(w.to_string().parse(), Ok(w.clone()));
+        assert_eq!("(a + b) + -(b + 0)".parse(), Ok(w));
+    }
+}
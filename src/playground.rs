@@ -0,0 +1,95 @@
+/*
+ * The entry points of an in-browser playground: complete the axioms of a spec, and normalize a
+ * term with a .trs system. Both take and return text, the result being a JSON object for
+ * JavaScript to JSON.parse, built by the same functions of json as the output of
+ * kb complete --json and kb normalize --json, and {"error": message} if the input doesn't parse.
+ * The crate doesn't build for WebAssembly itself: functions of strings to strings are what
+ * wasm-bindgen exports without conversions, so a page's bindings are a #[wasm_bindgen] wrapper of
+ * each. A page can't interrupt a call, so both stop after MAX_STEPS steps.
+ */
+use crate::completion::Completion;
+use crate::json::{self, obj};
+use crate::parse::parse_term;
+use crate::properties::detect_properties;
+use crate::spec::Spec;
+use crate::tpdb;
+
+/// The most equations complete processes, and the most rewrite steps normalize takes.
+pub const MAX_STEPS: usize = 10_000;
+
+/// Run completion on the axioms of spec, in the format of Spec.
+pub fn complete(spec: &str) -> String {
+    let spec: Spec = match spec.parse() {
+        Ok(spec) => spec,
+        Err(e) => return error(e),
+    };
+    let mut completion = Completion::new(&spec.axioms);
+    completion.max_steps = Some(MAX_STEPS);
+    let outcome = completion.run();
+    let properties: Vec<_> = detect_properties(&spec.axioms)
+        .into_iter()
+        .map(|d| {
+            let axiom = spec.axioms[d.axiom].clone();
+            (d, axiom)
+        })
+        .collect();
+    json::completion(
+        &outcome,
+        completion.rules(),
+        false,
+        completion.stats(),
+        &properties,
+        None,
+    )
+    .to_string()
+}
+
+/// Normalize term, parsed with the signature of rules, a system in the TPDB .trs format, with
+/// the derivation of its normal form.
+pub fn normalize(rules: &str, term: &str) -> String {
+    let (sig, trs) = match tpdb::parse(rules) {
+        Ok(parsed) => parsed,
+        Err(e) => return error(e),
+    };
+    let w = match parse_term(term, &sig) {
+        Ok(w) => w,
+        Err(e) => return error(format!("{}: {}", term, e)),
+    };
+    if trs.normalize_bounded(&w, MAX_STEPS).is_err() {
+        return error(format!("no normal form within {} steps", MAX_STEPS));
+    }
+    json::normalization(&w, &trs.derivation(&w)).to_string()
+}
+
+fn error(e: impl ToString) -> String {
+    obj([("error", e.to_string().into())]).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn playground() {
+        let spec = "op e 0 1\nop * 2 0 infix 1\nop i 1 0\nvars x y z\n\
+                    axiom e * x = x\naxiom i(x) * x = e\naxiom (x * y) * z = x * (y * z)\n";
+        let out: Value = serde_json::from_str(&complete(spec)).unwrap();
+        assert_eq!(out["status"], "convergent");
+        assert_eq!(out["aborted"], false);
+        assert_eq!(out["n_rules"], 10);
+        assert!(out["output"].is_null());
+        assert!(serde_json::from_str::<Value>(&complete("axiom x ="))
+            .unwrap()
+            .get("error")
+            .is_some());
+
+        let rules = "(VAR x y) (RULES f(x, y) -> x g(x) -> f(x, x))";
+        let out: Value = serde_json::from_str(&normalize(rules, "g(g(a))")).unwrap();
+        assert_eq!(out["normal_form"], "a");
+        assert_eq!(out["steps"].as_array().unwrap().len(), 4);
+        let looping = "(VAR x) (RULES f(x) -> g(x) g(x) -> f(x))";
+        let out: Value = serde_json::from_str(&normalize(looping, "f(a)")).unwrap();
+        assert!(out["error"].as_str().unwrap().starts_with("no normal form"));
+    }
+}
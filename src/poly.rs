@@ -0,0 +1,273 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use crate::order::ReductionOrder;
+use crate::relation::Rule;
+use crate::word::{Op, Operator, Var, Variable, Word, WordRef};
+
+/// A linear polynomial over the natural numbers, in the variables of a word.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinearPoly<V: Variable> {
+    pub constant: u64,
+    pub coeffs: BTreeMap<V, u64>,
+}
+
+impl<V: Variable> LinearPoly<V> {
+    /*
+     * Check that self is greater than other for every assignment of natural numbers to the
+     * variables, which for linear polynomials is the case exactly when the constant is greater
+     * and every coefficient is at least as large.
+     */
+    fn greater(&self, other: &LinearPoly<V>) -> bool {
+        self.constant > other.constant
+            && other
+                .coeffs
+                .iter()
+                .all(|(v, c)| self.coeffs.get(v).copied().unwrap_or(0) >= *c)
+    }
+}
+
+/*
+ * The ordering given by interpreting each operator f of arity n as a linear polynomial
+ *
+ *     [f](x1, ..., xn) = c0 + c1 x1 + ... + cn xn
+ *
+ * over the natural numbers, and each word as the polynomial obtained by composing these. One
+ * word is greater than another if its polynomial is greater for every assignment to the
+ * variables. With every ci for i ≥ 1 at least one, interpretations are strictly monotone, which
+ * makes this a reduction ordering. Unlike the Knuth-Bendix ordering, it can orient some rules
+ * that duplicate variables, since the coefficients can outweigh the copies.
+ */
+#[derive(Clone, Debug)]
+pub struct PolyOrder<O: Operator> {
+    /// The coefficients c0, c1, ..., cn of each operator. Operators without an entry are
+    /// interpreted with every coefficient one.
+    pub coeffs: BTreeMap<O, Vec<u64>>,
+}
+
+impl<O: Operator> Default for PolyOrder<O> {
+    fn default() -> PolyOrder<O> {
+        PolyOrder {
+            coeffs: BTreeMap::new(),
+        }
+    }
+}
+
+/// The largest number of interpretations tried by PolyOrder::search.
+const MAX_CANDIDATES: usize = 1 << 16;
+
+impl<O: Operator> PolyOrder<O> {
+    pub fn new(coeffs: BTreeMap<O, Vec<u64>>) -> PolyOrder<O> {
+        PolyOrder { coeffs }
+    }
+
+    pub fn interpret<V: Variable>(&self, w: &Word<V, O>) -> LinearPoly<V> {
+        self.interpret_ref(w.view())
+    }
+
+    fn interpret_ref<V: Variable>(&self, w: WordRef<'_, V, O>) -> LinearPoly<V> {
+        match w.syms.first() {
+            Some(Var(v)) => LinearPoly {
+                constant: 0,
+                coeffs: BTreeMap::from([(v.clone(), 1)]),
+            },
+            Some(Op(f)) => {
+                let cs = self.coeffs.get(f);
+                let c = |i: usize| cs.and_then(|cs| cs.get(i)).copied().unwrap_or(1);
+                let mut p = LinearPoly {
+                    constant: c(0),
+                    coeffs: BTreeMap::new(),
+                };
+                for (i, arg) in w.subwords().enumerate() {
+                    let a = self.interpret_ref(arg);
+                    let ci = c(i + 1);
+                    p.constant += ci * a.constant;
+                    for (v, k) in a.coeffs {
+                        *p.coeffs.entry(v).or_insert(0) += ci * k;
+                    }
+                }
+                p
+            }
+            None => LinearPoly {
+                constant: 0,
+                coeffs: BTreeMap::new(),
+            },
+        }
+    }
+
+    /// Search for an interpretation with every coefficient at most max_coeff under which each
+    /// rule's left side is greater than its right side.
+    pub fn search<V: Variable>(rules: &[Rule<V, O>], max_coeff: u64) -> Option<PolyOrder<O>> {
+        let mut ops: BTreeMap<O, usize> = BTreeMap::new();
+        for rule in rules {
+            for s in rule.left.syms.iter().chain(rule.right.syms.iter()) {
+                if let Op(f) = s {
+                    ops.insert(f.clone(), f.arity());
+                }
+            }
+        }
+        // All of the coefficients in one vector, with the range each one takes: constants from
+        // zero, and argument coefficients from one to keep the interpretation monotone.
+        let slots: Vec<(usize, u64)> = ops
+            .values()
+            .flat_map(|&n| (0..=n).map(|i| (i, if i == 0 { 0 } else { 1 })))
+            .collect();
+        let mut values: Vec<u64> = slots.iter().map(|&(_, min)| min).collect();
+        for _ in 0..MAX_CANDIDATES {
+            let mut coeffs = BTreeMap::new();
+            let mut k = 0;
+            for (f, &n) in ops.iter() {
+                coeffs.insert(f.clone(), values[k..k + n + 1].to_vec());
+                k += n + 1;
+            }
+            let order = PolyOrder::new(coeffs);
+            if rules
+                .iter()
+                .all(|rule| order.greater(&rule.left, &rule.right))
+            {
+                return Some(order);
+            }
+            let mut i = 0;
+            loop {
+                if i == values.len() {
+                    return None;
+                }
+                values[i] += 1;
+                if values[i] <= max_coeff {
+                    break;
+                }
+                values[i] = slots[i].1;
+                i += 1;
+            }
+        }
+        None
+    }
+}
+
+impl<V: Variable, O: Operator> ReductionOrder<V, O> for PolyOrder<O> {
+    fn compare(&self, s: &Word<V, O>, t: &Word<V, O>) -> Option<Ordering> {
+        if s.syms == t.syms {
+            return Some(Ordering::Equal);
+        }
+        let ps = self.interpret(s);
+        let pt = self.interpret(t);
+        if ps.greater(&pt) {
+            Some(Ordering::Greater)
+        } else if pt.greater(&ps) {
+            Some(Ordering::Less)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Kbo, Trs};
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    enum Nat {
+        Zero,
+        Succ,
+        Double,
+    }
+    use Nat::*;
+
+    impl Operator for Nat {
+        fn min_weight() -> u64 {
+            1
+        }
+
+        fn arity(&self) -> usize {
+            match self {
+                Zero => 0,
+                Succ | Double => 1,
+            }
+        }
+
+        fn weight(&self) -> u64 {
+            1
+        }
+    }
+
+    type W = Word<String, Nat>;
+
+    fn double_rules() -> Vec<Rule<String, Nat>> {
+        let x = W::var("x");
+        let zero = W::op(Zero, &[]);
+        let s = |w: &W| W::op(Succ, std::slice::from_ref(w));
+        let d = |w: &W| W::op(Double, std::slice::from_ref(w));
+        vec![
+            Rule {
+                left: d(&zero),
+                right: zero.clone(),
+            },
+            Rule {
+                left: d(&s(&x)),
+                right: s(&s(&d(&x))),
+            },
+        ]
+    }
+
+    #[test]
+    fn interpret() {
+        let order = PolyOrder::new(BTreeMap::from([(Succ, vec![1, 1]), (Double, vec![0, 2])]));
+        let x = W::var("x");
+        let w = W::op(Double, &[W::op(Succ, &[x])]);
+        let p = order.interpret(&w);
+        assert_eq!(p.constant, 2);
+        assert_eq!(p.coeffs, BTreeMap::from([("x".to_string(), 2)]));
+    }
+
+    #[test]
+    fn double() {
+        // The second rule makes the right side heavier in every Knuth-Bendix ordering.
+        let trs = Trs::new(double_rules());
+        assert!(trs.terminates_by(&Kbo).is_err());
+        let order = PolyOrder::search(&double_rules(), 3).unwrap();
+        assert!(trs.terminates_by(&order).is_ok());
+        let rules = trs.rules();
+        assert_eq!(
+            order.compare(&rules[1].right, &rules[1].left),
+            Some(Ordering::Less)
+        );
+    }
+
+    #[test]
+    fn duplicating() {
+        // f(x) → g(x, x) needs a coefficient of at least two on f's argument.
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        enum Fg {
+            F,
+            G,
+        }
+
+        impl Operator for Fg {
+            fn min_weight() -> u64 {
+                1
+            }
+
+            fn arity(&self) -> usize {
+                match self {
+                    Fg::F => 1,
+                    Fg::G => 2,
+                }
+            }
+
+            fn weight(&self) -> u64 {
+                1
+            }
+        }
+
+        let x = Word::<String, Fg>::var("x");
+        let rule = Rule {
+            left: Word::op(Fg::F, std::slice::from_ref(&x)),
+            right: Word::op(Fg::G, &[x.clone(), x.clone()]),
+        };
+        assert!(PolyOrder::search(std::slice::from_ref(&rule), 1).is_none());
+        let order = PolyOrder::search(std::slice::from_ref(&rule), 2).unwrap();
+        assert_eq!(order.coeffs[&Fg::F][1], 2);
+        assert!(order.greater(&rule.left, &rule.right));
+    }
+}
@@ -0,0 +1,359 @@
+/*
+ * Finitely presented groups, by completing their presentations as string rewriting systems.
+ * Each generator a gets a formal inverse a⁻¹, with the free reductions a a⁻¹ = 1 and a⁻¹ a = 1,
+ * and each relator r the equation r = 1. Letters are ordered a < a⁻¹ < b < b⁻¹ < ... by the
+ * order the generators are given in, for shortlex. When completion succeeds, two words are equal
+ * in the group exactly when they have the same normal form.
+ *
+ * Words are written as generator names, each optionally raised to a power: a^3, b^-1, or b⁻¹.
+ * Spaces and * between them are ignored, and 1 is the empty word.
+ */
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use crate::string_rewriting::{self, Srs, StringRule};
+
+/// A generator, by its index among the group's generators, or its inverse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Letter {
+    pub generator: usize,
+    pub inverse: bool,
+}
+
+impl Letter {
+    pub fn inverse(self) -> Letter {
+        Letter {
+            generator: self.generator,
+            inverse: !self.inverse,
+        }
+    }
+}
+
+/// The inverse of a word: its letters inverted, in reverse order.
+pub fn inverse(w: &[Letter]) -> Vec<Letter> {
+    w.iter().rev().map(|a| a.inverse()).collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PresentationError {
+    /// No generator is named by the text at this byte offset.
+    UnknownGenerator { pos: usize, found: String },
+    /// An exponent at this byte offset isn't a number.
+    Exponent { pos: usize },
+    /// Completion would need more than this many rules.
+    Incomplete { max_rules: usize },
+}
+
+impl Display for PresentationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PresentationError::UnknownGenerator { pos, found } => {
+                write!(f, "no generator named {} at offset {}", found, pos)
+            }
+            PresentationError::Exponent { pos } => write!(f, "bad exponent at offset {}", pos),
+            PresentationError::Incomplete { max_rules } => {
+                write!(f, "completion needs more than {} rules", max_rules)
+            }
+        }
+    }
+}
+
+impl Error for PresentationError {}
+
+/// A finitely presented group, with the convergent system its presentation completes to.
+#[derive(Clone, Debug)]
+pub struct Group {
+    pub generators: Vec<String>,
+    pub relators: Vec<Vec<Letter>>,
+    pub srs: Srs<Letter>,
+}
+
+impl Group {
+    pub const MAX_RULES: usize = 200;
+
+    /// The group with the given generators and relators, if completion succeeds with at most
+    /// MAX_RULES rules.
+    pub fn new(generators: &[&str], relators: &[&str]) -> Result<Group, PresentationError> {
+        Group::with_max_rules(generators, relators, Group::MAX_RULES)
+    }
+
+    pub fn with_max_rules(
+        generators: &[&str],
+        relators: &[&str],
+        max_rules: usize,
+    ) -> Result<Group, PresentationError> {
+        let mut group = Group {
+            generators: generators.iter().map(|g| g.to_string()).collect(),
+            relators: Vec::new(),
+            srs: Srs::new(Vec::new()),
+        };
+        for r in relators {
+            let r = group.word(r)?;
+            group.relators.push(r);
+        }
+        let mut equations = Vec::new();
+        for generator in 0..generators.len() {
+            let a = Letter {
+                generator,
+                inverse: false,
+            };
+            equations.push((vec![a, a.inverse()], Vec::new()));
+            equations.push((vec![a.inverse(), a], Vec::new()));
+        }
+        equations.extend(group.relators.iter().map(|r| (r.clone(), Vec::new())));
+        group.srs = string_rewriting::knuth_bendix(&equations, max_rules)
+            .ok_or(PresentationError::Incomplete { max_rules })?;
+        Ok(group)
+    }
+
+    /// Parse a word over the generators.
+    pub fn word(&self, s: &str) -> Result<Vec<Letter>, PresentationError> {
+        let mut w = Vec::new();
+        let mut pos = 0;
+        while pos < s.len() {
+            let rest = &s[pos..];
+            let c = rest.chars().next().unwrap();
+            if c.is_whitespace() || c == '*' || c == '1' {
+                pos += c.len_utf8();
+                continue;
+            }
+            // The longest generator name the text starts with.
+            let Some((generator, name)) = self
+                .generators
+                .iter()
+                .enumerate()
+                .filter(|(_, g)| rest.starts_with(g.as_str()))
+                .max_by_key(|(_, g)| g.len())
+            else {
+                let found = rest.split(|c: char| c.is_whitespace()).next().unwrap();
+                return Err(PresentationError::UnknownGenerator {
+                    pos,
+                    found: found.to_string(),
+                });
+            };
+            pos += name.len();
+            let rest = &s[pos..];
+            let power: i64 = if let Some(e) = rest.strip_prefix("⁻¹") {
+                pos += rest.len() - e.len();
+                -1
+            } else if let Some(e) = rest.strip_prefix('^') {
+                let sign = usize::from(e.starts_with('-'));
+                let digits = sign + e[sign..].len()
+                    - e[sign..]
+                        .trim_start_matches(|c: char| c.is_ascii_digit())
+                        .len();
+                let n = e[..digits]
+                    .parse()
+                    .map_err(|_| PresentationError::Exponent { pos })?;
+                pos += 1 + digits;
+                n
+            } else {
+                1
+            };
+            let a = Letter {
+                generator,
+                inverse: power < 0,
+            };
+            w.extend(std::iter::repeat_n(a, power.unsigned_abs() as usize));
+        }
+        Ok(w)
+    }
+
+    /// Write a word with its generators' names, as word parses it.
+    pub fn format(&self, w: &[Letter]) -> String {
+        if w.is_empty() {
+            return "1".to_string();
+        }
+        let letters: Vec<String> = w
+            .iter()
+            .map(|a| {
+                let name = &self.generators[a.generator];
+                if a.inverse {
+                    format!("{}⁻¹", name)
+                } else {
+                    name.clone()
+                }
+            })
+            .collect();
+        letters.join(" ")
+    }
+
+    pub fn normal_form(&self, w: &[Letter]) -> Vec<Letter> {
+        self.srs.normalize(w)
+    }
+
+    /// Whether w is the identity of the group.
+    pub fn is_trivial(&self, w: &[Letter]) -> bool {
+        self.normal_form(w).is_empty()
+    }
+
+    pub fn equal(&self, u: &[Letter], v: &[Letter]) -> bool {
+        self.normal_form(u) == self.normal_form(v)
+    }
+
+    /// The rules of the completed system.
+    pub fn rules(&self) -> &[StringRule<Letter>] {
+        &self.srs.rules
+    }
+
+    /*
+     * The group's elements as their normal forms, the irreducible words, in shortlex order with
+     * the identity first, or None if there are more than limit of them. A prefix of an irreducible
+     * word is irreducible, so they're found a length at a time by extending the last length's by a
+     * letter; the group is finite exactly when some length has none.
+     */
+    pub fn enumerate_elements(&self, limit: usize) -> Option<Vec<Vec<Letter>>> {
+        let letters: Vec<Letter> = (0..self.generators.len())
+            .flat_map(|generator| {
+                let a = Letter {
+                    generator,
+                    inverse: false,
+                };
+                [a, a.inverse()]
+            })
+            .collect();
+        let mut elements = vec![Vec::new()];
+        let mut last = 0;
+        while last < elements.len() {
+            let end = elements.len();
+            for i in last..end {
+                for &a in &letters {
+                    let mut w = elements[i].clone();
+                    w.push(a);
+                    if self.srs.rewrite_step(&w).is_none() {
+                        if elements.len() == limit {
+                            return None;
+                        }
+                        elements.push(w);
+                    }
+                }
+            }
+            last = end;
+        }
+        Some(elements)
+    }
+
+    /// The Cayley table of the elements, as enumerate_elements lists them: entry i, j is the index
+    /// of the product of elements i and j.
+    pub fn multiplication_table(&self, elements: &[Vec<Letter>]) -> Vec<Vec<usize>> {
+        let index: HashMap<&[Letter], usize> = elements
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (&w[..], i))
+            .collect();
+        elements
+            .iter()
+            .map(|u| {
+                elements
+                    .iter()
+                    .map(|v| index[&self.normal_form(&[&u[..], v].concat())[..]])
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The Cayley table written out with the elements' names, a row for each, with a header row
+    /// and column and the columns lined up.
+    pub fn format_table(&self, elements: &[Vec<Letter>]) -> String {
+        let names: Vec<String> = elements.iter().map(|w| self.format(w)).collect();
+        let width = names.iter().map(|n| n.chars().count()).max().unwrap_or(1);
+        let mut out = String::new();
+        let mut row = |first: &str, cells: &mut dyn Iterator<Item = &String>| {
+            let mut line = format!("{:width$} |", first);
+            for c in cells {
+                line.push_str(&format!(" {:width$}", c));
+            }
+            out.push_str(line.trim_end());
+            out.push('\n');
+        };
+        row("", &mut names.iter());
+        for (i, products) in self.multiplication_table(elements).iter().enumerate() {
+            row(&names[i], &mut products.iter().map(|&j| &names[j]));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words() {
+        let g = Group::new(&["a", "b"], &[]).unwrap();
+        let w = g.word("a^2 b⁻¹ * a^-1").unwrap();
+        assert_eq!(g.format(&w), "a a b⁻¹ a⁻¹");
+        assert_eq!(g.word(&g.format(&w)).unwrap(), w);
+        assert_eq!(g.format(&inverse(&w)), "a b a⁻¹ a⁻¹");
+        assert!(g.is_trivial(&[w.clone(), inverse(&w)].concat()));
+        assert!(!g.is_trivial(&g.word("a b a⁻¹ b⁻¹").unwrap()));
+        assert_eq!(g.word("1"), Ok(vec![]));
+        assert_eq!(g.rules().len(), 4);
+        assert_eq!(
+            g.word("a c"),
+            Err(PresentationError::UnknownGenerator {
+                pos: 2,
+                found: "c".to_string()
+            })
+        );
+        assert_eq!(g.word("a^x"), Err(PresentationError::Exponent { pos: 1 }));
+    }
+
+    #[test]
+    fn elements() {
+        let s3 = Group::new(&["s", "t"], &["s^2", "t^2", "s t s t s t"]).unwrap();
+        let elements = s3.enumerate_elements(100).unwrap();
+        let names: Vec<String> = elements.iter().map(|w| s3.format(w)).collect();
+        assert_eq!(names, ["1", "s", "t", "s t", "t s", "s t s"]);
+        assert_eq!(s3.enumerate_elements(6).map(|e| e.len()), Some(6));
+        assert_eq!(s3.enumerate_elements(5), None);
+        let table = s3.multiplication_table(&elements);
+        for (i, row) in table.iter().enumerate() {
+            assert_eq!(row[0], i);
+            assert_eq!(table[0][i], i);
+            let mut sorted = row.clone();
+            sorted.sort();
+            assert_eq!(sorted, (0..6).collect::<Vec<_>>());
+        }
+        assert_eq!(table[1][2], 3);
+        assert_eq!(table[3][3], 4);
+        let text = s3.format_table(&elements);
+        assert_eq!(
+            text.lines().nth(2),
+            Some("s     | s     1     s t   t     s t s t s")
+        );
+
+        let z5 = Group::new(&["a"], &["a^5"]).unwrap();
+        let names: Vec<String> = z5
+            .enumerate_elements(10)
+            .unwrap()
+            .iter()
+            .map(|w| z5.format(w))
+            .collect();
+        assert_eq!(names, ["1", "a", "a⁻¹", "a a", "a⁻¹ a⁻¹"]);
+
+        let z2 = Group::new(&["a", "b"], &["a b a⁻¹ b⁻¹"]).unwrap();
+        assert_eq!(z2.enumerate_elements(1000), None);
+    }
+
+    #[test]
+    fn word_problems() {
+        let s3 = Group::new(&["s", "t"], &["s^2", "t^2", "s t s t s t"]).unwrap();
+        let w = |s: &str| s3.word(s).unwrap();
+        assert!(s3.is_trivial(&w("s t s t⁻¹ s⁻¹ t")));
+        assert!(s3.equal(&w("s t s"), &w("t s t")));
+        assert_eq!(s3.format(&s3.normal_form(&w("s⁻¹"))), "s");
+
+        let z2 = Group::new(&["a", "b"], &["a b a⁻¹ b⁻¹"]).unwrap();
+        let w = |s: &str| z2.word(s).unwrap();
+        assert_eq!(z2.format(&z2.normal_form(&w("b^2 a⁻¹ b⁻¹ a^3"))), "a a b");
+        assert!(!z2.is_trivial(&w("a b")));
+
+        assert_eq!(
+            Group::with_max_rules(&["s", "t"], &["s^2", "t^2", "s t s t s t"], 3).unwrap_err(),
+            PresentationError::Incomplete { max_rules: 3 }
+        );
+    }
+}
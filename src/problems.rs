@@ -0,0 +1,276 @@
+/*
+ * Word problems from Knuth and Bendix, "Simple word problems in universal algebras" (1970), each
+ * with a weight assignment and precedence under which completion succeeds, and the outcome it
+ * reaches: the group examples use the paper's weights, with the inverse weighing zero and having
+ * the highest precedence. A problem on which completion diverges is included for contrast, as are
+ * groups of exponent two, where it diverges around an equation it can't orient. They double as
+ * regression tests for completion and as a set of benchmarks.
+ *
+ * Every problem has its own signature, so the operators are given by Sym values that carry their
+ * own arity, weight, and precedence rather than by a fixed enum.
+ */
+use std::fmt::{self, Display};
+
+use crate::relation::Axiom;
+use crate::word::{self, Operator};
+
+/// An operator of one of the problems. Sym's Ord compares precedence first, so it can be used
+/// directly as the Knuth-Bendix precedence.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sym {
+    pub precedence: usize,
+    pub name: &'static str,
+    pub arity: usize,
+    pub weight: u64,
+}
+
+impl Display for Sym {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.name.fmt(f)
+    }
+}
+
+impl Operator for Sym {
+    fn min_weight() -> u64 {
+        1
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn weight(&self) -> u64 {
+        self.weight
+    }
+}
+
+pub type Word = word::Word<String, Sym>;
+
+/// What completion does with a problem.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Expected {
+    /// Completion converges to a system with this many rules.
+    Convergent(usize),
+    /// Completion never finishes, generating an infinite family of rules.
+    Divergent,
+}
+
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub name: &'static str,
+    pub axioms: Vec<Axiom<String, Sym>>,
+    pub expected: Expected,
+}
+
+fn sym(precedence: usize, name: &'static str, arity: usize, weight: u64) -> Sym {
+    Sym {
+        precedence,
+        name,
+        arity,
+        weight,
+    }
+}
+
+fn var(v: &str) -> Word {
+    Word::var(v)
+}
+
+fn app(f: &Sym, args: &[Word]) -> Word {
+    Word::op(f.clone(), args)
+}
+
+fn eq(left: Word, right: Word) -> Axiom<String, Sym> {
+    Axiom { left, right }
+}
+
+// The signature of the group-like problems: e, ·, and ⁻ with weights 1, 0, 0 and ⁻ highest.
+fn group_sig() -> (Sym, Sym, Sym) {
+    (sym(0, "e", 0, 1), sym(1, "·", 2, 0), sym(2, "⁻", 1, 0))
+}
+
+fn assoc(mul: &Sym) -> Axiom<String, Sym> {
+    let (x, y, z) = (var("x"), var("y"), var("z"));
+    eq(
+        app(mul, &[app(mul, &[x.clone(), y.clone()]), z.clone()]),
+        app(mul, &[x, app(mul, &[y, z])]),
+    )
+}
+
+/// Groups, axiomatized by a left identity and left inverses.
+pub fn groups() -> Problem {
+    let (e, mul, inv) = group_sig();
+    let x = var("x");
+    Problem {
+        name: "groups",
+        axioms: vec![
+            eq(app(&mul, &[app(&e, &[]), x.clone()]), x.clone()),
+            eq(
+                app(&mul, &[app(&inv, std::slice::from_ref(&x)), x]),
+                app(&e, &[]),
+            ),
+            assoc(&mul),
+        ],
+        expected: Expected::Convergent(10),
+    }
+}
+
+/// The (l, r) system: a left identity and right inverses, which doesn't force a group.
+pub fn lr_system() -> Problem {
+    let (e, mul, inv) = group_sig();
+    let x = var("x");
+    Problem {
+        name: "(l, r) system",
+        axioms: vec![
+            eq(app(&mul, &[app(&e, &[]), x.clone()]), x.clone()),
+            eq(app(&mul, &[x.clone(), app(&inv, &[x])]), app(&e, &[])),
+            assoc(&mul),
+        ],
+        expected: Expected::Convergent(10),
+    }
+}
+
+/// The (r, l) system: a right identity and left inverses.
+pub fn rl_system() -> Problem {
+    let (e, mul, inv) = group_sig();
+    let x = var("x");
+    Problem {
+        name: "(r, l) system",
+        axioms: vec![
+            eq(app(&mul, &[x.clone(), app(&e, &[])]), x.clone()),
+            eq(
+                app(&mul, &[app(&inv, std::slice::from_ref(&x)), x]),
+                app(&e, &[]),
+            ),
+            assoc(&mul),
+        ],
+        expected: Expected::Convergent(12),
+    }
+}
+
+/// The (r, r) system: a right identity and right inverses, the mirror image of groups.
+pub fn rr_system() -> Problem {
+    let (e, mul, inv) = group_sig();
+    let x = var("x");
+    Problem {
+        name: "(r, r) system",
+        axioms: vec![
+            eq(app(&mul, &[x.clone(), app(&e, &[])]), x.clone()),
+            eq(app(&mul, &[x.clone(), app(&inv, &[x])]), app(&e, &[])),
+            assoc(&mul),
+        ],
+        expected: Expected::Convergent(10),
+    }
+}
+
+/// Groups in which x·x = e. These are abelian, and completion derives x·y = y·x, which it can't
+/// orient, then diverges generating rules that permute ever longer products.
+pub fn exponent_two() -> Problem {
+    let mut p = groups();
+    let x = var("x");
+    let (e, mul, _) = group_sig();
+    p.name = "groups of exponent two";
+    p.axioms.push(eq(app(&mul, &[x.clone(), x]), app(&e, &[])));
+    p.expected = Expected::Divergent;
+    p
+}
+
+/// Central groupoids: a single binary operation with (x·y)·(y·z) = y.
+pub fn central_groupoids() -> Problem {
+    let mul = sym(0, "·", 2, 1);
+    let (x, y, z) = (var("x"), var("y"), var("z"));
+    Problem {
+        name: "central groupoids",
+        axioms: vec![eq(
+            app(
+                &mul,
+                &[app(&mul, &[x, y.clone()]), app(&mul, &[y.clone(), z])],
+            ),
+            y,
+        )],
+        expected: Expected::Convergent(3),
+    }
+}
+
+/// f(g(f(x))) = g(f(x)), which completes to the infinite family f(gⁿ(f(x))) → gⁿ(f(x)).
+pub fn divergent() -> Problem {
+    let g = sym(0, "g", 1, 1);
+    let f = sym(1, "f", 1, 1);
+    let gf = app(&g, &[app(&f, &[var("x")])]);
+    Problem {
+        name: "divergent",
+        axioms: vec![eq(app(&f, std::slice::from_ref(&gf)), gf)],
+        expected: Expected::Divergent,
+    }
+}
+
+/// All of the problems.
+pub fn all() -> Vec<Problem> {
+    vec![
+        groups(),
+        lr_system(),
+        rl_system(),
+        rr_system(),
+        exponent_two(),
+        central_groupoids(),
+        divergent(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Completion, Outcome};
+
+    #[test]
+    fn expected_outcomes() {
+        for p in all() {
+            let mut c = Completion::new(&p.axioms);
+            c.max_rules = Some(40);
+            c.max_steps = Some(2000);
+            match (c.run(), p.expected) {
+                (Outcome::Convergent, Expected::Convergent(n)) => {
+                    assert_eq!(c.rules().len(), n, "{}", p.name)
+                }
+                (Outcome::Limit, Expected::Divergent) => {}
+                (o, e) => panic!("{}: expected {:?}, got {:?}", p.name, e, o),
+            }
+        }
+    }
+
+    #[test]
+    fn exponent_two_is_abelian() {
+        let p = exponent_two();
+        let mut c = Completion::new(&p.axioms);
+        c.max_steps = Some(100);
+        assert!(matches!(c.run(), Outcome::Limit));
+        let (x, y) = (var("x"), var("y"));
+        let (_, mul, _) = group_sig();
+        let comm = eq(app(&mul, &[x.clone(), y.clone()]), app(&mul, &[y, x]));
+        assert!(c
+            .pending()
+            .any(|e| e.left.is_variant(&comm.left) && e.right.is_variant(&comm.right)));
+    }
+
+    #[test]
+    fn central_groupoid_rules() {
+        // The completed system adds x·((x·y)·z) → x·y and (x·(y·z))·z → y·z.
+        let p = central_groupoids();
+        let rules = crate::knuth_bendix(&p.axioms).unwrap();
+        let mul = sym(0, "·", 2, 1);
+        let (x, y, z) = (var("x"), var("y"), var("z"));
+        let xy = app(&mul, &[x.clone(), y.clone()]);
+        let yz = app(&mul, &[y.clone(), z.clone()]);
+        let trs = crate::Trs::new(rules);
+        assert_eq!(
+            trs.normalize(&app(
+                &mul,
+                &[x.clone(), app(&mul, &[xy.clone(), z.clone()])]
+            )),
+            xy
+        );
+        assert_eq!(
+            trs.normalize(&app(&mul, &[app(&mul, &[x, yz.clone()]), z])),
+            yz
+        );
+    }
+}
@@ -1,78 +1,58 @@
-use std::fmt::{self, Display};
 use std::ops;
 use std::slice;
+use std::str::FromStr;
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub enum Prod {
-    One,
-    Inv,
-    Mul,
-}
-use Prod::*;
+use crate::parse::ParseError;
+use crate::word;
 
-impl Display for Prod {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            One => "1".fmt(f),
-            Inv => "⁻¹".fmt(f),
-            Mul => " * ".fmt(f),
-        }
+crate::theory! {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum Prod {
+        One = "1",
+        Inv(1) = "⁻¹" [weight(0), postfix],
+        Mul(2) = " * " [infix(1)],
     }
 }
-
-
-use crate::word::{self, Op, Var};
+use Prod::*;
 
 pub type Symbol = word::Symbol<String, Prod>;
 pub type Word = word::Word<String, Prod>;
-
-impl word::Operator for Prod {
-    fn min_weight() -> u64 {
-        1
-    }
-
-    fn arity(&self) -> usize {
-        match self {
-            One => 0,
-            Inv => 1,
-            Mul => 2,
-        }
-    }
-
-    fn weight(&self) -> u64 {
-        match self {
-            One => 1,
-            Inv => 0,
-            Mul => 1,
-        }
-    }
-}
+pub type WordRef<'a> = word::WordRef<'a, String, Prod>;
 
 impl ops::Mul for &Word {
     type Output = Word;
     fn mul(self, rhs: &Word) -> Word {
-        Word::op(Mul, &[self.clone(), rhs.clone()])
+        Word::op_owned(Mul, vec![self.clone(), rhs.clone()])
     }
 }
 
 impl ops::Mul<Word> for &Word {
     type Output = Word;
     fn mul(self, rhs: Word) -> Word {
-        Word::op(Mul, &[self.clone(), rhs])
+        Word::op_owned(Mul, vec![self.clone(), rhs])
     }
 }
 
 impl ops::Mul<&Word> for Word {
     type Output = Word;
     fn mul(self, rhs: &Word) -> Word {
-        Word::op(Mul, &[self, rhs.clone()])
+        Word::op_owned(Mul, vec![self, rhs.clone()])
     }
 }
 
 impl ops::Mul for Word {
     type Output = Word;
     fn mul(self, rhs: Word) -> Word {
-        Word::op(Mul, &[self, rhs])
+        Word::op_owned(Mul, vec![self, rhs])
+    }
+}
+
+/// Parse a product as it's displayed, such as (a * b)⁻¹ * 1.
+impl FromStr for Word {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Word, ParseError> {
+        Prod::parse_word(s)
     }
 }
 
@@ -88,55 +68,6 @@ pub fn inv(w: &Word) -> Word {
     Word::op(Inv, slice::from_ref(w))
 }
 
-fn fmt_with_parens(w: &Word, f: &mut fmt::Formatter) -> fmt::Result {
-    match w.syms.first() {
-        Some(Var(v)) => v.fmt(f),
-        Some(Op(One)) => One.fmt(f),
-        Some(Op(Inv)) => {
-            if let Some(arg) = w.subwords().next() {
-                fmt_with_parens(&arg, f).and(Inv.fmt(f))
-            } else {
-                fmt::Result::Err(fmt::Error::default())
-            }
-        }
-        Some(Op(Mul)) => {
-            let mut sw = w.subwords();
-            if let (Some(left), Some(right)) = (sw.next(), sw.next()) {
-                "(".fmt(f)
-                    .and(fmt_with_parens(&left, f))
-                    .and(Mul.fmt(f))
-                    .and(fmt_with_parens(&right, f))
-                    .and(")".fmt(f))
-            } else {
-                fmt::Result::Err(fmt::Error::default())
-            }
-        }
-        None => fmt::Result::Err(fmt::Error::default()),
-    }
-}
-
-impl Display for Word {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.syms.first() {
-            /*
-             * Format a top-level Mul without surrounding parentheses; otherwise, call into
-             * fmt_with_parens.
-             */
-            Some(Op(Mul)) => {
-                let mut sw = self.subwords();
-                if let (Some(left), Some(right)) = (sw.next(), sw.next()) {
-                    fmt_with_parens(&left, f)
-                        .and(Mul.fmt(f))
-                        .and(fmt_with_parens(&right, f))
-                } else {
-                    fmt::Result::Err(fmt::Error::default())
-                }
-            },
-            _ => fmt_with_parens(self, f),
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use crate::print_subs;
@@ -165,6 +96,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn word_ref() {
+        let (x, y, a, b) = (var("x"), var("y"), var("a"), var("b"));
+        let w = inv(&(&a * &b)) * &b;
+        let args: Vec<WordRef> = w.subwords().collect();
+        assert_eq!(args[0].to_string(), "(a * b)⁻¹");
+        assert_eq!(args[1].to_word(), b);
+        assert!(args[0] > args[1]);
+
+        // Matching binds variables to views into the matched word.
+        let vmap = (inv(&x) * &y).view().matches(w.view()).unwrap();
+        assert_eq!(vmap["x"].syms, &w.syms[2..5]);
+        assert_eq!(vmap["y"], b.view());
+        assert!((&x * &x).view().matches(w.view()).is_none());
+    }
+
     #[test]
     fn display() {
         let a = var("a");
@@ -242,4 +189,29 @@ mod tests {
             print_subs(&subs);
         }
     }
+
+    #[test]
+    fn mgu() {
+        let x = var("x");
+        let y = var("y");
+        let a = var("a");
+        let s = &x * inv(&y);
+        let t = inv(&a) * &x;
+        let vmap = s.mgu(&t).unwrap();
+        assert_eq!(s.subst(&vmap), t.subst(&vmap));
+        assert_eq!(x.subst(&vmap), inv(&a));
+        // Occurs check.
+        assert!(x.mgu(&inv(&x)).is_none());
+        assert!(one().mgu(&inv(&x)).is_none());
+
+        // Each binding mentions the next variable while unifying, and comes out resolved.
+        let z = var("z");
+        let s = &x * (&y * &z);
+        let t = inv(&y) * (inv(&z) * one());
+        let vmap = s.mgu(&t).unwrap();
+        assert_eq!(x.subst(&vmap), inv(&inv(&one())));
+        assert_eq!(s.subst(&vmap), t.subst(&vmap));
+        // The occurs check sees through bindings: y is bound to x before x meets inv(y).
+        assert!((&x * &y).mgu(&(&y * inv(&y))).is_none());
+    }
 }
@@ -0,0 +1,127 @@
+use std::fmt::{self, Display};
+
+use crate::relation::{Axiom, Rule};
+use crate::word::{Operator, Variable, Word};
+
+/// An algebraic property an axiom can state of a binary operator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Property {
+    /// f(x, y) = f(y, x), which no reduction ordering can orient.
+    Commutative,
+    /// f(f(x, y), z) = f(x, f(y, z)).
+    Associative,
+    /// f(x, x) = x.
+    Idempotent,
+}
+
+impl Display for Property {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Property::Commutative => "commutative".fmt(f),
+            Property::Associative => "associative".fmt(f),
+            Property::Idempotent => "idempotent".fmt(f),
+        }
+    }
+}
+
+/// An operator that one of the axioms, the one at index axiom, states has a property.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Detected<O: Operator> {
+    pub op: O,
+    pub property: Property,
+    pub axiom: usize,
+}
+
+// The equation stating the property of the binary operator f, as a rule to compare by variant.
+fn statement<V: Variable, O: Operator>(f: &O, property: Property) -> Rule<V, O> {
+    let [x, y, z] = [0, 1, 2].map(|i| Word::var(V::nth(i)));
+    let app = |a: &Word<V, O>, b: &Word<V, O>| Word::op(f.clone(), &[a.clone(), b.clone()]);
+    let (left, right) = match property {
+        Property::Commutative => (app(&x, &y), app(&y, &x)),
+        Property::Associative => (app(&app(&x, &y), &z), app(&x, &app(&y, &z))),
+        Property::Idempotent => (app(&x, &x), x),
+    };
+    Rule { left, right }
+}
+
+/*
+ * Find the axioms that are, up to renaming variables and swapping sides, the commutativity,
+ * associativity, or idempotence of a binary operator, in the order of the axioms. Completion
+ * treats these as it would any other equation, and commutativity in particular can never be
+ * oriented, so these are worth reporting before a run that can't finish.
+ */
+pub fn detect_properties<V: Variable, O: Operator>(axioms: &[Axiom<V, O>]) -> Vec<Detected<O>> {
+    let mut out = Vec::new();
+    for (i, ax) in axioms.iter().enumerate() {
+        let Some(f) = ax.left.syms.first().and_then(|s| s.op()).cloned() else {
+            continue;
+        };
+        if f.arity() != 2 {
+            continue;
+        }
+        let rule = Rule {
+            left: ax.left.clone(),
+            right: ax.right.clone(),
+        };
+        let flipped = Rule {
+            left: ax.right.clone(),
+            right: ax.left.clone(),
+        };
+        for property in [
+            Property::Commutative,
+            Property::Associative,
+            Property::Idempotent,
+        ] {
+            let s = statement(&f, property);
+            if rule.is_variant(&s) || flipped.is_variant(&s) {
+                out.push(Detected {
+                    op: f.clone(),
+                    property,
+                    axiom: i,
+                });
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theories::lattice::{self, Lattice};
+
+    #[test]
+    fn detect() {
+        let found: Vec<(Lattice, Property, usize)> = detect_properties(&lattice::axioms())
+            .into_iter()
+            .map(|d| (d.op, d.property, d.axiom))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (Lattice::Meet, Property::Associative, 0),
+                (Lattice::Meet, Property::Commutative, 1),
+                (Lattice::Meet, Property::Idempotent, 2),
+                (Lattice::Join, Property::Associative, 3),
+                (Lattice::Join, Property::Commutative, 4),
+                (Lattice::Join, Property::Idempotent, 5),
+            ]
+        );
+
+        // Stated the other way round, with other variables.
+        let axioms: [Axiom<String, Lattice>; 1] = [Axiom {
+            left: "b ∨ (c ∨ a)".parse().unwrap(),
+            right: "(b ∨ c) ∨ a".parse().unwrap(),
+        }];
+        assert_eq!(
+            detect_properties(&axioms)[0].property,
+            Property::Associative
+        );
+        // Absorption and group axioms aren't any of these.
+        assert!(detect_properties(&lattice::axioms()[6..]).is_empty());
+        assert_eq!(
+            detect_properties(&crate::theories::group::axioms()).len(),
+            1
+        );
+    }
+}
@@ -1,17 +1,140 @@
-/*
-use std::fmt;
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::{self, Display};
 
-use crate::{Term};
+use crate::order::ReductionOrder;
+use crate::word::{Operator, Variable, Word};
 
-#[derive(Clone)]
-pub struct Relation<V, O: Op<V>> {
-    left: Term<V, O>,
-    right: Term<V, O>,
+/// An equation between two words. It's unordered: l = r and r = l are equal.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Equation<V: Variable, O: Operator> {
+    pub left: Word<V, O>,
+    pub right: Word<V, O>,
 }
 
-impl<V: fmt::Display, O: Op<V> + fmt::Display> fmt::Display for Relation<V, O> {
+/// The equations a theory starts from.
+pub type Axiom<V, O> = Equation<V, O>;
+
+/// An equation oriented to be used left to right.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rule<V: Variable, O: Operator> {
+    pub left: Word<V, O>,
+    pub right: Word<V, O>,
+}
+
+/// Why an equation couldn't be oriented into a rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrientError {
+    /// Both sides are the same word, so there's nothing to rewrite.
+    Equal,
+    /// The ordering doesn't make either side greater.
+    Incomparable,
+}
+
+impl Display for OrientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrientError::Equal => write!(f, "the sides of the equation are equal"),
+            OrientError::Incomparable => write!(f, "the sides of the equation are incomparable"),
+        }
+    }
+}
+
+impl Error for OrientError {}
+
+impl<V: Variable, O: Operator> Equation<V, O> {
+    /// Orient the equation so its left side is the greater in order.
+    pub fn orient(&self, order: &impl ReductionOrder<V, O>) -> Result<Rule<V, O>, OrientError> {
+        let (left, right) = match order.compare(&self.left, &self.right) {
+            Some(Ordering::Greater) => (&self.left, &self.right),
+            Some(Ordering::Less) => (&self.right, &self.left),
+            Some(Ordering::Equal) => return Err(OrientError::Equal),
+            None => return Err(OrientError::Incomparable),
+        };
+        Ok(Rule {
+            left: left.clone(),
+            right: right.clone(),
+        })
+    }
+}
+
+impl<V: Variable, O: Operator> Rule<V, O> {
+    /// Whether the rules are the same up to renaming their variables.
+    pub fn is_variant(&self, other: &Rule<V, O>) -> bool {
+        let joined = |r: &Rule<V, O>| {
+            let syms = [&r.left.syms[..], &r.right.syms[..]].concat();
+            Word { syms }.canonicalize_vars()
+        };
+        self.left.syms.len() == other.left.syms.len() && joined(self) == joined(other)
+    }
+}
+
+impl<V: Variable, O: Operator> PartialEq for Equation<V, O> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.left == other.left && self.right == other.right)
+            || (self.left == other.right && self.right == other.left)
+    }
+}
+
+impl<V: Variable, O: Operator> Eq for Equation<V, O> {}
+
+impl<V: Variable, O: Operator> From<Rule<V, O>> for Equation<V, O> {
+    fn from(rule: Rule<V, O>) -> Equation<V, O> {
+        Equation {
+            left: rule.left,
+            right: rule.right,
+        }
+    }
+}
+
+impl<V: Variable, O: Operator> Display for Equation<V, O>
+where
+    Word<V, O>: Display,
+{
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{} = {}", self.left, self.right)
     }
 }
-*/
+
+impl<V: Variable, O: Operator> Display for Rule<V, O>
+where
+    Word<V, O>: Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} → {}", self.left, self.right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::{inv, one, var};
+    use crate::Kbo;
+
+    #[test]
+    fn orient() {
+        let x = var("x");
+        let eq = Equation {
+            left: x.clone(),
+            right: inv(&x) * one(),
+        };
+        assert_eq!(eq.to_string(), "x = x⁻¹ * 1");
+        let rule = eq.orient(&Kbo).unwrap();
+        assert_eq!(rule.left, inv(&x) * one());
+        assert_eq!(rule.to_string(), "x⁻¹ * 1 → x");
+        assert_eq!(Equation::from(rule), eq);
+
+        let same = Equation {
+            left: x.clone(),
+            right: x.clone(),
+        };
+        assert_eq!(same.orient(&Kbo), Err(OrientError::Equal));
+        let xy = Equation {
+            left: x,
+            right: var("y"),
+        };
+        assert_eq!(xy.orient(&Kbo), Err(OrientError::Incomparable));
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::BTreeSet;
+
+use crate::completion::{Completion, Outcome};
+use crate::relation::{Axiom, Rule};
+use crate::trs::Trs;
+use crate::word::{Operator, Symbol, Variable, Word};
+
+/// Whether the two sides of a goal are equal in a theory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Equal,
+    NotEqual,
+    Unknown,
+}
+
+/// The procedure that settled a goal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+    Completion,
+    Saturation,
+}
+
+#[derive(Clone, Debug)]
+pub struct GoalResult<V: Variable, O: Operator> {
+    pub goal: Axiom<V, O>,
+    pub verdict: Verdict,
+    pub method: Method,
+}
+
+/*
+ * Bounded equality saturation: apply the axioms in both directions, at every position, to grow
+ * the set of words provably equal to each side of a goal, until the two sets meet. This needs no
+ * ordering, so it can settle goals in theories that completion can't orient, such as ones with a
+ * commutative operator, but it only explores words of at most max_size symbols and at most
+ * max_words words in all.
+ *
+ * If the sets meet, the sides are equal. If the set for one side is closed without meeting the
+ * other, and nothing was left out along the way, the sides are not equal. Words are left out when
+ * they are too big, when the limit on words is reached, and when an axiom can't be used in one
+ * direction because that side has variables the other lacks; the verdict is then Unknown.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Saturation {
+    pub max_size: usize,
+    pub max_words: usize,
+}
+
+impl Default for Saturation {
+    fn default() -> Saturation {
+        Saturation {
+            max_size: 16,
+            max_words: 10_000,
+        }
+    }
+}
+
+// The set of words found equal to one side, and the ones whose rewrites haven't been added yet.
+struct Class<V: Variable, O: Operator> {
+    seen: BTreeSet<Vec<Symbol<V, O>>>,
+    frontier: Vec<Word<V, O>>,
+}
+
+impl Saturation {
+    pub fn prove<V: Variable, O: Operator>(
+        &self,
+        axioms: &[Axiom<V, O>],
+        goal: &Axiom<V, O>,
+    ) -> Verdict {
+        let mut complete = true;
+        let mut rules = Vec::new();
+        for a in axioms {
+            for (l, r) in [(&a.left, &a.right), (&a.right, &a.left)] {
+                if r.vars().is_subset(&l.vars()) {
+                    rules.push(Rule {
+                        left: l.clone(),
+                        right: r.clone(),
+                    });
+                } else {
+                    complete = false;
+                }
+            }
+        }
+        let trs = Trs::new(rules);
+        let class = |w: &Word<V, O>| Class {
+            seen: BTreeSet::from([w.syms.clone()]),
+            frontier: vec![w.clone()],
+        };
+        let mut sides = [class(&goal.left), class(&goal.right)];
+        if goal.left.syms == goal.right.syms {
+            return Verdict::Equal;
+        }
+        loop {
+            // Grow the smaller side by one round of rewriting.
+            let k = if sides[0].seen.len() <= sides[1].seen.len() {
+                0
+            } else {
+                1
+            };
+            if sides[k].frontier.is_empty() {
+                return if complete {
+                    Verdict::NotEqual
+                } else {
+                    Verdict::Unknown
+                };
+            }
+            let frontier = std::mem::take(&mut sides[k].frontier);
+            for w in frontier {
+                for v in trs.rewrites(&w) {
+                    if v.size() > self.max_size {
+                        complete = false;
+                        continue;
+                    }
+                    if sides[1 - k].seen.contains(&v.syms) {
+                        return Verdict::Equal;
+                    }
+                    if sides[0].seen.len() + sides[1].seen.len() >= self.max_words {
+                        return Verdict::Unknown;
+                    }
+                    if sides[k].seen.insert(v.syms.clone()) {
+                        sides[k].frontier.push(v);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/*
+ * Decide each goal in the theory of the axioms. Completion is tried first, stopping at max_rules
+ * rules: if it converges, a goal holds exactly when both sides have the same normal form. If it
+ * doesn't, the rules it found are still consequences of the axioms, so a goal whose sides they
+ * join holds, and the goals left are tried with saturation.
+ */
+pub fn decide<V: Variable, O: Operator>(
+    axioms: &[Axiom<V, O>],
+    goals: &[Axiom<V, O>],
+    max_rules: usize,
+    saturation: &Saturation,
+) -> Vec<GoalResult<V, O>> {
+    let mut completion = Completion::new(axioms);
+    completion.max_rules = Some(max_rules);
+    let outcome = completion.run();
+    let trs = completion.trs();
+    goals
+        .iter()
+        .map(|goal| {
+            let joined = trs.normalize(&goal.left).syms == trs.normalize(&goal.right).syms;
+            let (verdict, method) = match outcome {
+                Outcome::Trivial(_) => (Verdict::Equal, Method::Completion),
+                Outcome::Convergent if !joined => (Verdict::NotEqual, Method::Completion),
+                _ if joined => (Verdict::Equal, Method::Completion),
+                _ => (saturation.prove(axioms, goal), Method::Saturation),
+            };
+            GoalResult {
+                goal: goal.clone(),
+                verdict,
+                method,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sum::{var, zero, Sum, Word};
+
+    fn eq(left: Word, right: Word) -> Axiom<String, Sum> {
+        Axiom { left, right }
+    }
+
+    // Commutative monoids, which completion can't orient because of x + y = y + x.
+    fn commutative_monoid() -> Vec<Axiom<String, Sum>> {
+        let (x, y, z) = (var("x"), var("y"), var("z"));
+        vec![
+            eq(&zero() + &x, x.clone()),
+            eq(&x + &y, &y + &x),
+            eq((&x + &y) + &z, &x + (&y + &z)),
+        ]
+    }
+
+    #[test]
+    fn saturation() {
+        let (a, b, c) = (var("a"), var("b"), var("c"));
+        let sat = Saturation::default();
+        let axioms = commutative_monoid();
+        let goal = eq((&a + &b) + &c, &c + (&b + &a));
+        assert_eq!(sat.prove(&axioms, &goal), Verdict::Equal);
+        let goal = eq(&a + &zero(), a.clone());
+        assert_eq!(sat.prove(&axioms, &goal), Verdict::Equal);
+        // x = 0 + x can't be used left to right, so a failure to meet isn't a disproof.
+        let goal = eq(&a + &b, &a + &a);
+        assert_eq!(sat.prove(&axioms, &goal), Verdict::Unknown);
+
+        // Without identity, every equation preserves size, so the classes are finite and closed.
+        let axioms = &commutative_monoid()[1..];
+        assert_eq!(sat.prove(axioms, &eq(&a + &b, &a + &a)), Verdict::NotEqual);
+    }
+
+    #[test]
+    fn decide_goals() {
+        let (a, b, c) = (var("a"), var("b"), var("c"));
+        let goals = [
+            eq(&zero() + (&zero() + &a), a.clone()),
+            eq((&a + &b) + &c, &b + (&c + &a)),
+        ];
+        let results = decide(&commutative_monoid(), &goals, 20, &Saturation::default());
+        assert_eq!(results[0].verdict, Verdict::Equal);
+        assert_eq!(results[0].method, Method::Completion);
+        assert_eq!(results[1].verdict, Verdict::Equal);
+        assert_eq!(results[1].method, Method::Saturation);
+
+        // Completion settles everything in a theory it can complete.
+        let x = var("x");
+        let axioms = [eq(&zero() + &x, x.clone())];
+        let results = decide(
+            &axioms,
+            &[eq(&a + &zero(), a.clone())],
+            20,
+            &Saturation::default(),
+        );
+        assert_eq!(results[0].verdict, Verdict::NotEqual);
+        assert_eq!(results[0].method, Method::Completion);
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt::{Display, Write};
+
+use crate::parse::ParseError;
+use crate::term::NamedOps;
+use crate::word::{Op, Operator, Var, Variable, Word, WordRef};
+
+/*
+ * Words as s-expressions, (f x (g 0)), which is the prefix order of syms with the arguments of
+ * each operator in parentheses. An operator is written by its Display without surrounding spaces
+ * and a constant without parentheses. Reading one back, an atom is a constant if there's an
+ * operator by that name and a variable otherwise, as with parse_term, and (c) is a constant too.
+ * Names can't contain whitespace or parentheses.
+ */
+impl<V: Variable + Display, O: Operator + Display> WordRef<'_, V, O> {
+    pub fn to_sexpr(&self) -> String {
+        let mut out = String::new();
+        write_sexpr(&mut out, *self);
+        out
+    }
+}
+
+impl<V: Variable + Display, O: Operator + Display> Word<V, O> {
+    pub fn to_sexpr(&self) -> String {
+        self.view().to_sexpr()
+    }
+}
+
+fn write_sexpr<V: Variable + Display, O: Operator + Display>(
+    out: &mut String,
+    w: WordRef<'_, V, O>,
+) {
+    match &w.syms[0] {
+        Var(v) => write!(out, "{}", v).unwrap(),
+        Op(o) if w.syms.len() == 1 => write!(out, "{}", o.to_string().trim()).unwrap(),
+        Op(o) => {
+            write!(out, "({}", o.to_string().trim()).unwrap();
+            for a in w.subwords() {
+                out.push(' ');
+                write_sexpr(out, a);
+            }
+            out.push(')');
+        }
+    }
+}
+
+impl<O: Operator> Word<String, O> {
+    pub fn from_sexpr(s: &str, ops: &impl NamedOps<O>) -> Result<Word<String, O>, ParseError> {
+        let mut tokens = tokenize(s).into_iter().peekable();
+        let w = read(&mut tokens, ops)?;
+        match tokens.next() {
+            None => Ok(w),
+            Some((pos, t)) => Err(ParseError::Unexpected {
+                pos,
+                found: t.to_string(),
+            }),
+        }
+    }
+}
+
+// Split s into parentheses and atoms, with their byte offsets.
+fn tokenize(s: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            if let Some(j) = start.take() {
+                tokens.push((j, &s[j..i]));
+            }
+            if !c.is_whitespace() {
+                tokens.push((i, &s[i..i + 1]));
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(j) = start {
+        tokens.push((j, &s[j..]));
+    }
+    tokens
+}
+
+fn read<'a, O: Operator>(
+    tokens: &mut std::iter::Peekable<impl Iterator<Item = (usize, &'a str)>>,
+    ops: &impl NamedOps<O>,
+) -> Result<Word<String, O>, ParseError> {
+    let unexpected = |pos: usize, t: &str| ParseError::Unexpected {
+        pos,
+        found: t.to_string(),
+    };
+    let apply = |pos: usize, name: &str, o: O, args: Vec<Word<String, O>>| {
+        let o = o.with_arity(args.len()).ok_or_else(|| ParseError::Arity {
+            pos,
+            name: name.to_string(),
+            found: args.len(),
+        })?;
+        Ok(Word::op_owned(o, args))
+    };
+    match tokens.next().ok_or(ParseError::End)? {
+        (pos, ")") => Err(unexpected(pos, ")")),
+        (_, "(") => {
+            let (pos, name) = tokens.next().ok_or(ParseError::End)?;
+            if name == "(" || name == ")" {
+                return Err(unexpected(pos, name));
+            }
+            let o = ops.op_named(name).ok_or_else(|| ParseError::UnknownOp {
+                pos,
+                name: name.to_string(),
+            })?;
+            let mut args = Vec::new();
+            loop {
+                match tokens.peek() {
+                    None => return Err(ParseError::End),
+                    Some((_, ")")) => {
+                        tokens.next();
+                        break;
+                    }
+                    Some(_) => args.push(read(tokens, ops)?),
+                }
+            }
+            apply(pos, name, o, args)
+        }
+        (pos, name) => match ops.op_named(name) {
+            Some(o) => apply(pos, name, o, vec![]),
+            None => Ok(Word::var(name)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::{inv, one, var};
+    use crate::Signature;
+
+    #[test]
+    fn round_trip() {
+        let (x, y) = (var("x"), var("y"));
+        let w = inv(&(&x * &y)) * one();
+        assert_eq!(w.to_sexpr(), "(* (⁻¹ (* x y)) 1)");
+
+        let mut sig = Signature::new();
+        sig.declare("0", 0, 1).unwrap();
+        sig.declare("f", 2, 1).unwrap();
+        sig.declare("g", 1, 1).unwrap();
+        let w = Word::from_sexpr(" (f x\n  (g (0)))", &sig).unwrap();
+        assert_eq!(w, crate::term!(sig, f(x, g(0))));
+        assert_eq!(w.to_sexpr(), "(f x (g 0))");
+        assert_eq!(Word::from_sexpr(&w.to_sexpr(), &sig).unwrap(), w);
+
+        let err = |s: &str| Word::from_sexpr(s, &sig).unwrap_err().to_string();
+        assert_eq!(
+            err("(f x)"),
+            "operator f at offset 1 can't take 1 arguments"
+        );
+        assert_eq!(err("(h x)"), "no operator named h at offset 1");
+        assert_eq!(err("(g x"), "unexpected end of input");
+        assert_eq!(err("(g x) y"), "unexpected y at offset 6");
+        assert_eq!(err("()"), "unexpected ) at offset 1");
+    }
+}
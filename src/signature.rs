@@ -0,0 +1,295 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::rc::Rc;
+
+use crate::notation::{Assoc, Fixity, Notation};
+use crate::sort::Sort;
+use crate::word::{Operator, Word};
+
+/*
+ * Operators declared at runtime, for theories read from a file or built by a program rather than
+ * written as an enum. A Signature holds the declarations; each DynOp carries its own name, arity,
+ * and weight, and its precedence is the order of declaration, later operators being greater,
+ * except that a unary operator of weight zero is above the rest, as kbo::derived_precedence has it.
+ * Variables weigh 1, as with Prod and Sum, so constants should weigh at least 1. Operators are
+ * written as functions unless declared with another notation; an infix operator is displayed with
+ * a space on either side of its name.
+ */
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DynOp {
+    pub precedence: usize,
+    pub name: Rc<str>,
+    pub arity: usize,
+    pub weight: u64,
+    /// The argument and result sorts, if the operator is sorted.
+    pub sorts: Option<(Vec<Sort>, Sort)>,
+    pub notation: Notation,
+}
+
+impl Display for DynOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.notation.fixity {
+            Fixity::Infix => write!(f, " {} ", self.name),
+            _ => self.name.fmt(f),
+        }
+    }
+}
+
+impl Operator for DynOp {
+    fn min_weight() -> u64 {
+        1
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn weight(&self) -> u64 {
+        self.weight
+    }
+
+    fn result_sort(&self) -> Option<Sort> {
+        self.sorts.as_ref().map(|(_, s)| s.clone())
+    }
+
+    fn arg_sort(&self, i: usize) -> Option<Sort> {
+        self.sorts
+            .as_ref()
+            .and_then(|(args, _)| args.get(i).cloned())
+    }
+
+    fn notation(&self) -> Notation {
+        self.notation
+    }
+}
+
+pub type DynWord = Word<String, DynOp>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignatureError {
+    /// An operator was declared twice.
+    Duplicate(String),
+    /// An operator was used without being declared.
+    Unknown(String),
+    /// An operator was applied to the wrong number of arguments.
+    Arity {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignatureError::Duplicate(name) => write!(f, "operator {} is already declared", name),
+            SignatureError::Unknown(name) => write!(f, "operator {} is not declared", name),
+            SignatureError::Arity {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "operator {} takes {} arguments but was given {}",
+                name, expected, found
+            ),
+        }
+    }
+}
+
+impl Error for SignatureError {}
+
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Signature {
+    ops: Vec<DynOp>,
+    by_name: BTreeMap<Rc<str>, usize>,
+    var_sorts: BTreeMap<String, Sort>,
+    // The sorts declared directly above each sort.
+    supersorts: BTreeMap<Sort, BTreeSet<Sort>>,
+}
+
+impl Signature {
+    pub fn new() -> Signature {
+        Signature::default()
+    }
+
+    /// Declare an operator, above every operator declared so far in precedence.
+    pub fn declare(
+        &mut self,
+        name: &str,
+        arity: usize,
+        weight: u64,
+    ) -> Result<DynOp, SignatureError> {
+        self.insert(name, arity, weight, None, Notation::FUNCTION)
+    }
+
+    /// Declare an operator written with notation rather than as a function.
+    pub fn declare_with_notation(
+        &mut self,
+        name: &str,
+        arity: usize,
+        weight: u64,
+        notation: Notation,
+    ) -> Result<DynOp, SignatureError> {
+        self.insert(name, arity, weight, None, notation)
+    }
+
+    /// Declare a binary infix operator, such as * binding tighter than + at a higher precedence.
+    pub fn declare_infix(
+        &mut self,
+        name: &str,
+        precedence: u32,
+        assoc: Assoc,
+        weight: u64,
+    ) -> Result<DynOp, SignatureError> {
+        self.declare_with_notation(name, 2, weight, Notation::infix(precedence, assoc))
+    }
+
+    /// Declare a sorted operator taking arguments of sorts args to a word of sort result.
+    pub fn declare_sorted(
+        &mut self,
+        name: &str,
+        args: &[&str],
+        result: &str,
+        weight: u64,
+    ) -> Result<DynOp, SignatureError> {
+        let sorts = (
+            args.iter().map(|s| Sort::new(s)).collect(),
+            Sort::new(result),
+        );
+        self.insert(name, args.len(), weight, Some(sorts), Notation::FUNCTION)
+    }
+
+    fn insert(
+        &mut self,
+        name: &str,
+        arity: usize,
+        weight: u64,
+        sorts: Option<(Vec<Sort>, Sort)>,
+        notation: Notation,
+    ) -> Result<DynOp, SignatureError> {
+        if self.by_name.contains_key(name) {
+            return Err(SignatureError::Duplicate(name.to_string()));
+        }
+        let op = DynOp {
+            precedence: self.ops.len(),
+            name: name.into(),
+            arity,
+            weight,
+            sorts,
+            notation,
+        };
+        self.by_name.insert(op.name.clone(), self.ops.len());
+        self.ops.push(op.clone());
+        Ok(op)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DynOp> {
+        self.by_name.get(name).map(|&i| &self.ops[i])
+    }
+
+    /// The operators from lowest to highest precedence.
+    pub fn ops(&self) -> &[DynOp] {
+        &self.ops
+    }
+
+    /// Apply the operator called name to args, checking that it is declared with that arity.
+    pub fn app(&self, name: &str, args: &[DynWord]) -> Result<DynWord, SignatureError> {
+        let op = self
+            .get(name)
+            .ok_or_else(|| SignatureError::Unknown(name.to_string()))?;
+        if op.arity != args.len() {
+            return Err(SignatureError::Arity {
+                name: name.to_string(),
+                expected: op.arity,
+                found: args.len(),
+            });
+        }
+        Ok(Word::op(op.clone(), args))
+    }
+
+    pub fn var(&self, name: &str) -> DynWord {
+        Word::var(name)
+    }
+
+    /// Give the variable called name a sort, for sort_check.
+    pub fn declare_var(&mut self, name: &str, sort: &str) {
+        self.var_sorts.insert(name.to_string(), Sort::new(sort));
+    }
+
+    pub fn var_sort(&self, name: &str) -> Option<Sort> {
+        self.var_sorts.get(name).cloned()
+    }
+
+    /// Declare sub a subsort of sup, as in NzNat < Nat, so a word of sort sub can be used
+    /// wherever one of sort sup can.
+    pub fn declare_subsort(&mut self, sub: &str, sup: &str) {
+        self.supersorts
+            .entry(Sort::new(sub))
+            .or_default()
+            .insert(Sort::new(sup));
+    }
+
+    /// Whether a is b or below it in the declared subsorts.
+    pub fn is_subsort(&self, a: &Sort, b: &Sort) -> bool {
+        let mut seen = BTreeSet::from([a.clone()]);
+        let mut todo = vec![a.clone()];
+        while let Some(s) = todo.pop() {
+            if &s == b {
+                return true;
+            }
+            for sup in self.supersorts.get(&s).into_iter().flatten() {
+                if seen.insert(sup.clone()) {
+                    todo.push(sup.clone());
+                }
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{knuth_bendix, term, Axiom};
+
+    #[test]
+    fn declared_groups() {
+        let mut sig = Signature::new();
+        sig.declare("e", 0, 1).unwrap();
+        sig.declare("*", 2, 0).unwrap();
+        sig.declare("i", 1, 0).unwrap();
+        assert_eq!(
+            sig.declare("e", 0, 1),
+            Err(SignatureError::Duplicate("e".to_string()))
+        );
+        assert_eq!(
+            sig.app("f", &[]),
+            Err(SignatureError::Unknown("f".to_string()))
+        );
+        let x = sig.var("x");
+        assert_eq!(
+            sig.app("i", &[]).unwrap_err().to_string(),
+            "operator i takes 1 arguments but was given 0"
+        );
+
+        let axioms = [
+            Axiom {
+                left: term!(sig, *(e, x)),
+                right: x,
+            },
+            Axiom {
+                left: term!(sig, *(i(x), x)),
+                right: term!(sig, e),
+            },
+            Axiom {
+                left: term!(sig, *(*(x, y), z)),
+                right: term!(sig, *(x, *(y, z))),
+            },
+        ];
+        assert_eq!(knuth_bendix(&axioms).unwrap().len(), 10);
+    }
+}
@@ -0,0 +1,308 @@
+/*
+ * Export to SMT-LIB 2, for checking a theory or the rules completion found for it with an SMT
+ * solver such as Z3 or cvc5. Operators become uninterpreted functions and each equation a
+ * universally quantified assertion:
+ *
+ *     (set-logic UF)
+ *     (declare-sort U 0)
+ *     (declare-fun e () U)
+ *     (declare-fun |·| (U U) U)
+ *     (assert (forall ((x0 U)) (= (|·| e x0) x0)))
+ *
+ * Unsorted operators are over the one sort U; sorted ones keep their sorts, though SMT-LIB has no
+ * subsorts, so with subsorts declared the export is stricter than the signature. A variable gets
+ * the sort of the first argument position it appears in, and variables are renamed x0, x1, ... in
+ * each equation. An operator is written by its Display without surrounding spaces, quoted between
+ * bars unless it's a simple symbol. One whose name SMT-LIB already defines, such as = or and, or
+ * that can't be quoted, or that another operator already wrote as, is renamed f0, f1, ..., a
+ * sort that can't keep its name S0, S1, ..., and the renaming listed in a comment at the top.
+ */
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Write};
+
+use crate::relation::Equation;
+use crate::sort::{var_sorts, Sort};
+use crate::word::{Op, Operator, Var, Variable, WordRef};
+
+// Names SMT-LIB reserves or defines in the core theory, which an operator can't be declared as.
+const RESERVED: &[&str] = &[
+    "!", "_", "as", "let", "exists", "forall", "match", "par", "and", "or", "not", "xor", "=>",
+    "=", "distinct", "ite", "true", "false", "Bool",
+];
+
+fn is_simple(s: &str) -> bool {
+    !s.is_empty()
+        && !s.starts_with(|c: char| c.is_ascii_digit())
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "~!@$%^&*_-+=<>.?/".contains(c))
+}
+
+// A symbol for s, or None if it can't be written as one.
+fn symbol(s: &str) -> Option<String> {
+    if RESERVED.contains(&s) {
+        None
+    } else if is_simple(s) {
+        Some(s.to_string())
+    } else if !s.contains(['|', '\\']) && !s.chars().any(char::is_control) {
+        Some(format!("|{}|", s))
+    } else {
+        None
+    }
+}
+
+struct Names<O> {
+    ops: BTreeMap<O, String>,
+    renamed: Vec<(String, String)>,
+    sorts: BTreeMap<Option<Sort>, String>,
+}
+
+impl<O: Operator + Display> Names<O> {
+    fn new<V: Variable>(equations: &[&Equation<V, O>]) -> Names<O> {
+        let ops: BTreeSet<&O> = equations
+            .iter()
+            .flat_map(|eq| eq.left.syms.iter().chain(&eq.right.syms))
+            .filter_map(|s| match s {
+                Op(o) => Some(o),
+                Var(_) => None,
+            })
+            .collect();
+        let mut names = Names {
+            ops: BTreeMap::new(),
+            renamed: Vec::new(),
+            sorts: BTreeMap::new(),
+        };
+        let displayed: Vec<(&O, String)> = ops
+            .into_iter()
+            .map(|o| (o, o.to_string().trim().to_string()))
+            .collect();
+        let mut used: BTreeSet<String> = BTreeSet::new();
+        let mut renamed = Vec::new();
+        for (o, s) in displayed {
+            match symbol(&s) {
+                Some(name) if used.insert(name.clone()) => {
+                    names.ops.insert(o.clone(), name);
+                }
+                _ => renamed.push((o, s)),
+            }
+        }
+        let mut fresh = 0;
+        for (o, s) in renamed {
+            let name = loop {
+                let name = format!("f{}", fresh);
+                fresh += 1;
+                if used.insert(name.clone()) {
+                    break name;
+                }
+            };
+            names.renamed.push((name.clone(), s));
+            names.ops.insert(o.clone(), name);
+        }
+
+        let mut sorts: BTreeSet<Option<Sort>> = names
+            .ops
+            .keys()
+            .flat_map(|o| {
+                (0..o.arity())
+                    .map(|i| o.arg_sort(i))
+                    .chain([o.result_sort()])
+            })
+            .collect();
+        for eq in equations {
+            sorts.extend(
+                var_sorts(eq.left.view(), eq.right.view())
+                    .into_iter()
+                    .map(|v| v.1),
+            );
+        }
+        let mut fresh = 0;
+        for sort in sorts {
+            let written = sort.as_ref().map_or("U", |s| &s.0);
+            let name = match symbol(written) {
+                Some(name) if used.insert(name.clone()) => name,
+                _ => {
+                    let name = loop {
+                        let name = format!("S{}", fresh);
+                        fresh += 1;
+                        if used.insert(name.clone()) {
+                            break name;
+                        }
+                    };
+                    names.renamed.push((name.clone(), written.to_string()));
+                    name
+                }
+            };
+            names.sorts.insert(sort, name);
+        }
+        names
+    }
+
+    fn sort(&self, sort: Option<Sort>) -> &str {
+        &self.sorts[&sort]
+    }
+
+    fn write<V: Variable>(
+        &self,
+        out: &mut String,
+        w: WordRef<'_, V, O>,
+        vars: &[(&V, Option<Sort>)],
+    ) {
+        match &w.syms[0] {
+            Var(v) => {
+                let i = vars.iter().position(|u| u.0 == v).unwrap();
+                write!(out, "x{}", i).unwrap();
+            }
+            Op(o) if o.arity() == 0 => out.push_str(&self.ops[o]),
+            Op(o) => {
+                write!(out, "({}", self.ops[o]).unwrap();
+                for a in w.subwords() {
+                    out.push(' ');
+                    self.write(out, a, vars);
+                }
+                out.push(')');
+            }
+        }
+    }
+
+    // Write eq as a formula, quantifying over its variables.
+    fn equation<V: Variable>(&self, out: &mut String, eq: &Equation<V, O>) {
+        let vars = var_sorts(eq.left.view(), eq.right.view());
+        if !vars.is_empty() {
+            out.push_str("(forall (");
+            for (i, (_, sort)) in vars.iter().enumerate() {
+                if i > 0 {
+                    out.push(' ');
+                }
+                write!(out, "(x{} {})", i, self.sort(sort.clone())).unwrap();
+            }
+            out.push_str(") ");
+        }
+        out.push_str("(= ");
+        self.write(out, eq.left.view(), &vars);
+        out.push(' ');
+        self.write(out, eq.right.view(), &vars);
+        out.push(')');
+        if !vars.is_empty() {
+            out.push(')');
+        }
+    }
+}
+
+fn problem<V: Variable, O: Operator + Display>(
+    axioms: &[Equation<V, O>],
+    goal: Option<&Equation<V, O>>,
+) -> String {
+    let all: Vec<&Equation<V, O>> = axioms.iter().chain(goal).collect();
+    let names = Names::new(&all);
+    let mut out = String::new();
+    for (name, s) in &names.renamed {
+        writeln!(out, "; {} is {}", name, s).unwrap();
+    }
+    out.push_str("(set-logic UF)\n");
+    for name in names.sorts.values() {
+        writeln!(out, "(declare-sort {} 0)", name).unwrap();
+    }
+    for (o, name) in &names.ops {
+        let args: Vec<&str> = (0..o.arity()).map(|i| names.sort(o.arg_sort(i))).collect();
+        writeln!(
+            out,
+            "(declare-fun {} ({}) {})",
+            name,
+            args.join(" "),
+            names.sort(o.result_sort())
+        )
+        .unwrap();
+    }
+    for eq in axioms {
+        out.push_str("(assert ");
+        names.equation(&mut out, eq);
+        out.push_str(")\n");
+    }
+    if let Some(goal) = goal {
+        out.push_str("(assert (not ");
+        names.equation(&mut out, goal);
+        out.push_str("))\n(check-sat)\n");
+    }
+    out
+}
+
+/// Write equations, or rules from completion, as SMT-LIB assertions over uninterpreted functions.
+pub fn export<V: Variable, O: Operator + Display, E: Clone + Into<Equation<V, O>>>(
+    equations: &[E],
+) -> String {
+    let axioms: Vec<Equation<V, O>> = equations.iter().cloned().map(Into::into).collect();
+    problem(&axioms, None)
+}
+
+/// Write an SMT-LIB problem asserting the equations and the negation of the goal, which is unsat
+/// when the goal follows from them.
+pub fn export_problem<V: Variable, O: Operator + Display, E: Clone + Into<Equation<V, O>>>(
+    equations: &[E],
+    goal: &Equation<V, O>,
+) -> String {
+    let axioms: Vec<Equation<V, O>> = equations.iter().cloned().map(Into::into).collect();
+    problem(&axioms, Some(goal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems;
+    use crate::{knuth_bendix, Signature};
+
+    #[test]
+    fn export_groups() {
+        let groups = problems::groups();
+        let smt = export(&groups.axioms);
+        assert_eq!(
+            smt,
+            "(set-logic UF)\n\
+             (declare-sort U 0)\n\
+             (declare-fun e () U)\n\
+             (declare-fun |·| (U U) U)\n\
+             (declare-fun |⁻| (U) U)\n\
+             (assert (forall ((x0 U)) (= (|·| e x0) x0)))\n\
+             (assert (forall ((x0 U)) (= (|·| (|⁻| x0) x0) e)))\n\
+             (assert (forall ((x0 U) (x1 U) (x2 U)) (= (|·| (|·| x0 x1) x2) (|·| x0 (|·| x1 x2)))))\n"
+        );
+        let rules = knuth_bendix(&groups.axioms).unwrap();
+        let goal: Equation<_, _> = rules[0].clone().into();
+        let smt = export_problem(&groups.axioms, &goal);
+        assert!(smt.ends_with("))\n(check-sat)\n"), "{}", smt);
+        assert!(smt.contains("(assert (not "));
+    }
+
+    #[test]
+    fn sorts_and_names() {
+        let mut sig = Signature::new();
+        sig.declare_sorted("0", &[], "Nat", 1).unwrap();
+        sig.declare_sorted("s", &["Nat"], "Nat", 1).unwrap();
+        sig.declare_sorted("=", &["Nat", "Nat"], "Bool", 1).unwrap();
+        sig.declare_sorted("true", &[], "Bool", 1).unwrap();
+        let eq = Equation {
+            left: crate::term!(sig, s(x)),
+            right: crate::term!(sig, s(s(0))),
+        };
+        let eq2 = Equation {
+            left: crate::Word::op_owned(
+                sig.get("=").unwrap().clone(),
+                vec![crate::term!(sig, y), crate::term!(sig, y)],
+            ),
+            right: crate::term!(sig, true),
+        };
+        assert_eq!(
+            export(&[eq, eq2]),
+            "; f0 is =\n\
+             ; f1 is true\n\
+             ; S0 is Bool\n\
+             (set-logic UF)\n\
+             (declare-sort S0 0)\n\
+             (declare-sort Nat 0)\n\
+             (declare-fun |0| () Nat)\n\
+             (declare-fun s (Nat) Nat)\n\
+             (declare-fun f0 (Nat Nat) S0)\n\
+             (declare-fun f1 () S0)\n\
+             (assert (forall ((x0 Nat)) (= (s x0) (s (s |0|)))))\n\
+             (assert (forall ((x0 Nat)) (= (f0 x0 x0) f1)))\n"
+        );
+    }
+}
@@ -0,0 +1,417 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::rc::Rc;
+
+use crate::signature::Signature;
+use crate::word::{Op, Operator, Var, Variable, Word, WordError, WordRef};
+
+/*
+ * Sorts for many-sorted signatures, such as scalars and vectors of a module over a ring, or
+ * elements and lists in a theory of typed lists. An operator with sorts takes arguments of given
+ * sorts and has a result sort; Operator::result_sort returns None for an unsorted operator, which
+ * is every operator unless it says otherwise. A variable has the sort the signature declares for
+ * it, or else the smallest sort of the argument positions it appears in.
+ *
+ * With subsorts declared, as in NzNat < Nat, a word of a subsort can stand wherever the larger sort
+ * is expected, so a partial operation such as the predecessor can take only NzNat arguments instead
+ * of needing an error element for p(0). Sorted matching and unification only bind a variable to a
+ * word whose sort is at or below the variable's.
+ */
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sort(pub Rc<str>);
+
+impl Sort {
+    pub fn new(name: &str) -> Sort {
+        Sort(name.into())
+    }
+}
+
+impl Display for Sort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Why a word doesn't check against a sorted signature. Indexes are into syms.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SortError {
+    Malformed(WordError),
+    /// The subword at index has sort found where sort expected is needed.
+    Mismatch {
+        index: usize,
+        expected: Sort,
+        found: Sort,
+    },
+    /// The operator at index has no sorts.
+    Unsorted {
+        index: usize,
+    },
+    /// A variable is used at two different sorts.
+    VarConflict {
+        var: String,
+        first: Sort,
+        second: Sort,
+    },
+    /// A variable's sort is neither declared nor implied by where it appears.
+    UnknownVar(String),
+}
+
+impl Display for SortError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SortError::Malformed(e) => e.fmt(f),
+            SortError::Mismatch {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "the subword at index {} has sort {} but should have sort {}",
+                index, found, expected
+            ),
+            SortError::Unsorted { index } => {
+                write!(f, "the operator at index {} has no sorts", index)
+            }
+            SortError::VarConflict { var, first, second } => {
+                write!(f, "{} is used at sorts {} and {}", var, first, second)
+            }
+            SortError::UnknownVar(v) => write!(f, "the sort of {} is unknown", v),
+        }
+    }
+}
+
+impl Error for SortError {}
+
+impl From<WordError> for SortError {
+    fn from(e: WordError) -> SortError {
+        SortError::Malformed(e)
+    }
+}
+
+// The sorts given to the variables of a word so far, by declaration or by use.
+struct SortChecker<'a> {
+    sig: &'a Signature,
+    vars: BTreeMap<String, Sort>,
+}
+
+impl SortChecker<'_> {
+    fn sort_of(&self, v: &str) -> Option<Sort> {
+        self.sig.var_sort(v).or_else(|| self.vars.get(v).cloned())
+    }
+
+    // Check the subword of w starting at i against the expected sort, returning its sort and the
+    // index where it ends.
+    fn check<O: Operator>(
+        &mut self,
+        w: WordRef<'_, String, O>,
+        i: usize,
+        expected: Option<Sort>,
+    ) -> Result<(Sort, usize), SortError> {
+        let found = match &w.syms[i] {
+            Var(v) => match (self.sort_of(v), expected.clone()) {
+                (Some(s), _) if self.sig.var_sort(v).is_some() => s,
+                (Some(s), Some(e)) if self.sig.is_subsort(&s, &e) => s,
+                // Used below where it was first seen, so narrow it.
+                (Some(s), Some(e)) if self.sig.is_subsort(&e, &s) => {
+                    self.vars.insert(v.clone(), e.clone());
+                    e
+                }
+                (Some(s), Some(e)) => {
+                    return Err(SortError::VarConflict {
+                        var: v.clone(),
+                        first: s,
+                        second: e,
+                    })
+                }
+                (Some(s), None) => s,
+                (None, Some(e)) => {
+                    self.vars.insert(v.clone(), e.clone());
+                    e
+                }
+                (None, None) => return Err(SortError::UnknownVar(v.clone())),
+            },
+            Op(f) => f.result_sort().ok_or(SortError::Unsorted { index: i })?,
+        };
+        if let Some(e) = expected {
+            if !self.sig.is_subsort(&found, &e) {
+                return Err(SortError::Mismatch {
+                    index: i,
+                    expected: e,
+                    found,
+                });
+            }
+        }
+        let mut j = i + 1;
+        if let Op(f) = &w.syms[i] {
+            for k in 0..f.arity() {
+                let arg = f.arg_sort(k).ok_or(SortError::Unsorted { index: i })?;
+                j = self.check(w, j, Some(arg))?.1;
+            }
+        }
+        Ok((found, j))
+    }
+}
+
+impl<O: Operator> Word<String, O> {
+    /// Check that every operator is applied to arguments of the sorts it takes, and return the
+    /// sort of the word. Variables take their sorts from sig.
+    pub fn sort_check(&self, sig: &Signature) -> Result<Sort, SortError> {
+        self.validate()?;
+        let mut checker = SortChecker {
+            sig,
+            vars: BTreeMap::new(),
+        };
+        Ok(checker.check(self.view(), 0, None)?.0)
+    }
+}
+
+impl Signature {
+    // A checker that has checked each of words, so it knows the sorts of all their variables.
+    fn checker<O: Operator>(
+        &self,
+        words: &[&Word<String, O>],
+    ) -> Result<SortChecker<'_>, SortError> {
+        let mut checker = SortChecker {
+            sig: self,
+            vars: BTreeMap::new(),
+        };
+        for w in words {
+            w.validate()?;
+            checker.check(w.view(), 0, None)?;
+        }
+        Ok(checker)
+    }
+
+    // Whether every binding in vmap is to a word whose sort is at or below its variable's.
+    fn respects_sorts<O: Operator>(
+        &self,
+        checker: &mut SortChecker<'_>,
+        vmap: &BTreeMap<String, Word<String, O>>,
+    ) -> bool {
+        vmap.iter().all(|(v, w)| {
+            let expected = checker.sort_of(v);
+            expected.is_some() && checker.check(w.view(), 0, expected).is_ok()
+        })
+    }
+
+    /// The sort of each variable in w, declared or inferred as sort_check does.
+    pub fn var_sorts<O: Operator>(
+        &self,
+        w: &Word<String, O>,
+    ) -> Result<BTreeMap<String, Sort>, SortError> {
+        let checker = self.checker(&[w])?;
+        Ok(w.vars()
+            .into_iter()
+            .filter_map(|v| Some((v.clone(), checker.sort_of(&v)?)))
+            .collect())
+    }
+
+    /// Match pattern against w as Word::unify does, but only binding each variable to a word of
+    /// its sort or a subsort.
+    pub fn sorted_match<O: Operator>(
+        &self,
+        pattern: &Word<String, O>,
+        w: &Word<String, O>,
+    ) -> Option<BTreeMap<String, Word<String, O>>> {
+        let mut checker = self.checker(&[pattern, w]).ok()?;
+        let vmap = pattern.unify(w)?;
+        self.respects_sorts(&mut checker, &vmap).then_some(vmap)
+    }
+
+    /*
+     * Unify s and t as Word::mgu does, keeping only a unifier that respects sorts. Where the
+     * unifier binds a variable to another of a larger sort, the binding is turned around so the
+     * variable of the smaller sort survives. This doesn't introduce fresh variables at the
+     * intersection of two incomparable sorts, so it can miss unifiers when variables of such sorts
+     * meet.
+     */
+    pub fn sorted_unify<O: Operator>(
+        &self,
+        s: &Word<String, O>,
+        t: &Word<String, O>,
+    ) -> Option<BTreeMap<String, Word<String, O>>> {
+        let mut checker = self.checker(&[s, t]).ok()?;
+        let mut vmap = s.mgu(t)?;
+        let keys: Vec<String> = vmap.keys().cloned().collect();
+        for v in keys {
+            let u = match vmap.get(&v).map(|w| w.syms.as_slice()) {
+                Some([Var(u)]) => u.clone(),
+                _ => continue,
+            };
+            let (sv, su) = (checker.sort_of(&v)?, checker.sort_of(&u)?);
+            if sv != su && self.is_subsort(&sv, &su) {
+                let flip = BTreeMap::from([(u.clone(), Word::var(v.as_str()))]);
+                vmap.remove(&v);
+                for b in vmap.values_mut() {
+                    *b = b.subst(&flip);
+                }
+                vmap.insert(u, Word::var(v.as_str()));
+            }
+        }
+        self.respects_sorts(&mut checker, &vmap).then_some(vmap)
+    }
+}
+
+/// The variables of the equation or rule between left and right in the order they appear, each
+/// with the sort of the first position it's in, for exporting to systems with sorted variables.
+/// This doesn't check the sorts; a variable at the root, with the other side unsorted or a
+/// variable too, has no sort.
+pub(crate) fn var_sorts<'a, V: Variable, O: Operator>(
+    left: WordRef<'a, V, O>,
+    right: WordRef<'a, V, O>,
+) -> Vec<(&'a V, Option<Sort>)> {
+    fn walk<'a, V: Variable, O: Operator>(
+        w: WordRef<'a, V, O>,
+        expected: Option<Sort>,
+        vars: &mut Vec<(&'a V, Option<Sort>)>,
+    ) {
+        match &w.syms[0] {
+            Var(v) => {
+                if !vars.iter().any(|u| u.0 == v) {
+                    vars.push((v, expected));
+                }
+            }
+            Op(o) => {
+                for (i, a) in w.subwords().enumerate() {
+                    walk(a, o.arg_sort(i), vars);
+                }
+            }
+        }
+    }
+    let root = |w: WordRef<'_, V, O>| match &w.syms[0] {
+        Op(o) => o.result_sort(),
+        Var(_) => None,
+    };
+    let mut vars = Vec::new();
+    walk(left, root(right), &mut vars);
+    walk(right, root(left), &mut vars);
+    vars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::term;
+
+    // Lists of naturals: nil, cons(n, l), and zero and succ for the elements.
+    fn lists() -> Signature {
+        let mut sig = Signature::new();
+        sig.declare_sorted("0", &[], "Nat", 1).unwrap();
+        sig.declare_sorted("s", &["Nat"], "Nat", 1).unwrap();
+        sig.declare_sorted("nil", &[], "List", 1).unwrap();
+        sig.declare_sorted("cons", &["Nat", "List"], "List", 1)
+            .unwrap();
+        sig.declare_var("l", "List");
+        sig
+    }
+
+    #[test]
+    fn sort_check() {
+        let sig = lists();
+        let nat = Sort::new("Nat");
+        let list = Sort::new("List");
+        assert_eq!(
+            term!(sig, cons(s(0), cons(n, l))).sort_check(&sig),
+            Ok(list)
+        );
+        assert_eq!(term!(sig, s(n)).sort_check(&sig), Ok(nat.clone()));
+        assert_eq!(
+            term!(sig, cons(nil, nil)).sort_check(&sig),
+            Err(SortError::Mismatch {
+                index: 1,
+                expected: nat.clone(),
+                found: Sort::new("List"),
+            })
+        );
+        assert_eq!(
+            term!(sig, cons(l, cons(0, nil))).sort_check(&sig),
+            Err(SortError::Mismatch {
+                index: 1,
+                expected: nat.clone(),
+                found: Sort::new("List"),
+            })
+        );
+        assert_eq!(
+            term!(sig, cons(n, cons(0, n))).sort_check(&sig),
+            Err(SortError::VarConflict {
+                var: "n".to_string(),
+                first: nat,
+                second: Sort::new("List"),
+            })
+        );
+        assert_eq!(
+            term!(sig, n).sort_check(&sig),
+            Err(SortError::UnknownVar("n".to_string()))
+        );
+    }
+
+    // Naturals with the nonzero ones as a subsort, so the predecessor can be partial.
+    fn naturals() -> Signature {
+        let mut sig = Signature::new();
+        sig.declare_subsort("NzNat", "Nat");
+        sig.declare_sorted("0", &[], "Nat", 1).unwrap();
+        sig.declare_sorted("s", &["Nat"], "NzNat", 1).unwrap();
+        sig.declare_sorted("p", &["NzNat"], "Nat", 1).unwrap();
+        sig.declare_sorted("f", &["Nat", "NzNat"], "Nat", 1)
+            .unwrap();
+        sig.declare_var("n", "Nat");
+        sig.declare_var("m", "NzNat");
+        sig
+    }
+
+    #[test]
+    fn subsorts() {
+        let sig = naturals();
+        let nat = Sort::new("Nat");
+        let nz = Sort::new("NzNat");
+        assert!(sig.is_subsort(&nz, &nat));
+        assert!(!sig.is_subsort(&nat, &nz));
+        assert_eq!(term!(sig, s(s(0))).sort_check(&sig), Ok(nz.clone()));
+        assert_eq!(term!(sig, p(s(n))).sort_check(&sig), Ok(nat.clone()));
+        assert_eq!(
+            term!(sig, p(0)).sort_check(&sig),
+            Err(SortError::Mismatch {
+                index: 1,
+                expected: nz.clone(),
+                found: nat.clone(),
+            })
+        );
+        // x is first seen at Nat, then narrowed to NzNat.
+        let w = term!(sig, f(x, x));
+        assert_eq!(w.sort_check(&sig), Ok(nat));
+        assert_eq!(
+            sig.var_sorts(&w),
+            Ok(BTreeMap::from([("x".to_string(), nz)]))
+        );
+    }
+
+    #[test]
+    fn sorted_match_and_unify() {
+        let sig = naturals();
+        let (n, m) = (term!(sig, n), term!(sig, m));
+        let one = term!(sig, s(0));
+        assert_eq!(
+            sig.sorted_match(&term!(sig, p(m)), &term!(sig, p(s(0)))),
+            Some(BTreeMap::from([("m".to_string(), one.clone())]))
+        );
+        assert_eq!(sig.sorted_match(&m, &term!(sig, 0)), None);
+        assert_eq!(sig.sorted_match(&m, &n), None);
+        assert!(sig.sorted_match(&n, &one).is_some());
+
+        // The mgu binds m to n, which is turned around to keep the NzNat variable.
+        assert_eq!(
+            m.mgu(&n),
+            Some(BTreeMap::from([("m".to_string(), n.clone())]))
+        );
+        let flipped = Some(BTreeMap::from([("n".to_string(), m.clone())]));
+        assert_eq!(sig.sorted_unify(&m, &n), flipped);
+        assert_eq!(sig.sorted_unify(&n, &m), flipped);
+        assert_eq!(sig.sorted_unify(&m, &term!(sig, 0)), None);
+        assert_eq!(
+            sig.sorted_unify(&term!(sig, s(n)), &m),
+            Some(BTreeMap::from([("m".to_string(), term!(sig, s(n)))]))
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::notation::{Assoc, Notation};
+use crate::parse::{parse_equation, ParseError};
+use crate::relation::Equation;
+use crate::signature::{DynOp, Signature, SignatureError};
+
+/*
+ * A problem file: a signature, the axioms over it, and optionally a goal to prove. One
+ * declaration per line, with lines starting with # ignored:
+ *
+ *     op e 0 1
+ *     op * 2 0 infix 1
+ *     op i 1 0
+ *     vars x y z
+ *     axiom e * x = x
+ *     axiom i(x) * x = e
+ *     axiom (x * y) * z = x * (y * z)
+ *     goal x * i(x) = e
+ *
+ * An op line gives a name, an arity, and optionally a weight, which is 1 if it's left out, and a
+ * notation: prefix, postfix, or infix with a precedence and then left, right, or neither for
+ * associativity. Operators are declared in increasing precedence, as with Signature::declare.
+ * Equations are read by parse_equation. Without a vars line any name that isn't an operator is a
+ * variable; with one, only the names it lists are, so a misspelled operator is caught.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Spec {
+    pub signature: Signature,
+    pub vars: Option<Vec<String>>,
+    pub axioms: Vec<Equation<String, DynOp>>,
+    pub goal: Option<Equation<String, DynOp>>,
+}
+
+#[derive(Debug)]
+pub enum SpecError {
+    Io(io::Error),
+    /// A line that doesn't fit the format, by its number from 1.
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    Signature {
+        line: usize,
+        error: SignatureError,
+    },
+    Parse {
+        line: usize,
+        error: ParseError,
+    },
+    /// A name used as a variable that the vars line doesn't list.
+    UndeclaredVar {
+        line: usize,
+        name: String,
+    },
+}
+
+impl Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpecError::Io(e) => e.fmt(f),
+            SpecError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            SpecError::Signature { line, error } => write!(f, "line {}: {}", line, error),
+            SpecError::Parse { line, error } => write!(f, "line {}: {}", line, error),
+            SpecError::UndeclaredVar { line, name } => {
+                write!(
+                    f,
+                    "line {}: {} is not an operator or a variable",
+                    line, name
+                )
+            }
+        }
+    }
+}
+
+impl Error for SpecError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SpecError::Io(e) => Some(e),
+            SpecError::Signature { error, .. } => Some(error),
+            SpecError::Parse { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SpecError {
+    fn from(e: io::Error) -> SpecError {
+        SpecError::Io(e)
+    }
+}
+
+fn syntax(line: usize, message: &str) -> SpecError {
+    SpecError::Syntax {
+        line,
+        message: message.to_string(),
+    }
+}
+
+impl Spec {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Spec, SpecError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// Add one line of a problem file, numbered line for errors. Blank lines and comments are
+    /// ignored.
+    pub fn add_line(&mut self, line: usize, text: &str) -> Result<(), SpecError> {
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            return Ok(());
+        }
+        let (keyword, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        match keyword {
+            "op" => self.declare(line, &rest.split_whitespace().collect::<Vec<_>>())?,
+            "vars" => self
+                .vars
+                .get_or_insert_with(Vec::new)
+                .extend(rest.split_whitespace().map(str::to_string)),
+            "axiom" => {
+                let eq = self.equation(line, rest)?;
+                self.axioms.push(eq);
+            }
+            "goal" if self.goal.is_some() => return Err(syntax(line, "more than one goal")),
+            "goal" => self.goal = Some(self.equation(line, rest)?),
+            k => return Err(syntax(line, &format!("unknown declaration {}", k))),
+        }
+        Ok(())
+    }
+
+    // Declare the operator described by the words after op.
+    fn declare(&mut self, line: usize, words: &[&str]) -> Result<(), SpecError> {
+        let number = |w: &str, what: &str| -> Result<u64, SpecError> {
+            w.parse()
+                .map_err(|_| syntax(line, &format!("expected {}, found {}", what, w)))
+        };
+        let (name, arity, rest) = match words {
+            [name, arity, rest @ ..] => (*name, number(arity, "an arity")? as usize, rest),
+            _ => return Err(syntax(line, "expected op name arity [weight] [notation]")),
+        };
+        let (weight, rest) = match rest {
+            [w, rest @ ..] if w.starts_with(|c: char| c.is_ascii_digit()) => {
+                (number(w, "a weight")?, rest)
+            }
+            rest => (1, rest),
+        };
+        let notation = match rest {
+            [] => Notation::FUNCTION,
+            ["prefix"] => Notation::PREFIX,
+            ["postfix"] => Notation::POSTFIX,
+            ["infix", p, assoc @ ..] => {
+                let assoc = match assoc {
+                    [] => Assoc::None,
+                    ["left"] => Assoc::Left,
+                    ["right"] => Assoc::Right,
+                    _ => return Err(syntax(line, "expected left or right after infix")),
+                };
+                Notation::infix(number(p, "a precedence")? as u32, assoc)
+            }
+            _ => return Err(syntax(line, "expected prefix, postfix, or infix")),
+        };
+        self.signature
+            .declare_with_notation(name, arity, weight, notation)
+            .map_err(|error| SpecError::Signature { line, error })?;
+        Ok(())
+    }
+
+    fn equation(&self, line: usize, s: &str) -> Result<Equation<String, DynOp>, SpecError> {
+        let eq =
+            parse_equation(s, &self.signature).map_err(|error| SpecError::Parse { line, error })?;
+        match self.undeclared_var(&eq) {
+            Some(name) => Err(SpecError::UndeclaredVar { line, name }),
+            None => Ok(eq),
+        }
+    }
+
+    /// A variable of eq that the vars line doesn't list, if there is a vars line. Equations from
+    /// elsewhere, such as a goal given on the command line, are checked with this too.
+    pub fn undeclared_var(&self, eq: &Equation<String, DynOp>) -> Option<String> {
+        let vars = self.vars.as_ref()?;
+        let used = eq.left.vars().into_iter().chain(eq.right.vars());
+        used.into_iter().find(|v| !vars.contains(v))
+    }
+}
+
+impl FromStr for Spec {
+    type Err = SpecError;
+
+    fn from_str(s: &str) -> Result<Spec, SpecError> {
+        let mut spec = Spec::default();
+        for (i, text) in s.lines().enumerate() {
+            spec.add_line(i + 1, text)?;
+        }
+        Ok(spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{knuth_bendix, Trs};
+
+    #[test]
+    fn groups_file() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/specs/groups.eq");
+        let spec = Spec::from_file(path).unwrap();
+        assert_eq!(spec.signature.ops().len(), 3);
+        assert_eq!(spec.axioms.len(), 3);
+        assert_eq!(spec.axioms[2].to_string(), "(x * y) * z = x * (y * z)");
+        let trs = Trs::new(knuth_bendix(&spec.axioms).unwrap());
+        let goal = spec.goal.as_ref().unwrap();
+        assert_eq!(trs.normalize(&goal.left), trs.normalize(&goal.right));
+        let eq = |s| parse_equation(s, &spec.signature).unwrap();
+        assert_eq!(spec.undeclared_var(&eq("x * e = x")), None);
+        assert_eq!(spec.undeclared_var(&eq("x * foo = x")), Some("foo".into()));
+    }
+
+    #[test]
+    fn errors() {
+        let err = |s: &str| s.parse::<Spec>().unwrap_err().to_string();
+        assert_eq!(err("op f two"), "line 1: expected an arity, found two");
+        assert_eq!(
+            err("op f 1\nop f 2"),
+            "line 2: operator f is already declared"
+        );
+        assert_eq!(
+            err("op + 2 infix 1 up"),
+            "line 1: expected left or right after infix"
+        );
+        assert_eq!(
+            err("op f 1\nvars x\naxiom f(x) = g(x)"),
+            "line 3: no operator named g at offset 7"
+        );
+        assert_eq!(
+            err("op f 1\nvars x\naxiom f(x) = y"),
+            "line 3: y is not an operator or a variable"
+        );
+        assert_eq!(err("constant c"), "line 1: unknown declaration constant");
+        assert!(matches!(
+            Spec::from_file("/nonexistent.eq"),
+            Err(SpecError::Io(_))
+        ));
+    }
+}
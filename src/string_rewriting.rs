@@ -0,0 +1,273 @@
+/*
+ * String rewriting, or semi-Thue systems: rules u → v between strings over an alphabet, which
+ * rewrite any string containing u as a substring. A string is a word whose operators are all
+ * unary, so this is a special case of term rewriting, but it's simpler and faster to work with the
+ * letters directly. Presentations of monoids and groups are naturally written this way: each
+ * relation u = v between products of generators is an equation, and completion under the
+ * shortlex ordering, when it succeeds, gives a normal form for every element.
+ *
+ * Any type with Ord and Clone serves as the alphabet, with its Ord ordering the letters for
+ * shortlex. Strings are Vec<A>, or &[A] when borrowed.
+ */
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StringRule<A> {
+    pub left: Vec<A>,
+    pub right: Vec<A>,
+}
+
+// A string with its letters run together, or ε if it's empty.
+fn fmt_string<A: Display>(s: &[A], f: &mut fmt::Formatter) -> fmt::Result {
+    if s.is_empty() {
+        return "ε".fmt(f);
+    }
+    s.iter().try_for_each(|a| a.fmt(f))
+}
+
+impl<A: Display> Display for StringRule<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_string(&self.left, f)?;
+        " → ".fmt(f)?;
+        fmt_string(&self.right, f)
+    }
+}
+
+/// Compare strings by length, then lexicographically. Shortlex is a well-order in which every
+/// string is comparable with every other, so completion under it never meets an equation it can't
+/// orient.
+pub fn shortlex<A: Ord>(u: &[A], v: &[A]) -> Ordering {
+    u.len().cmp(&v.len()).then_with(|| u.cmp(v))
+}
+
+// The first index at which needle occurs in s.
+fn find<A: PartialEq>(s: &[A], needle: &[A]) -> Option<usize> {
+    if needle.len() > s.len() {
+        return None;
+    }
+    (0..=s.len() - needle.len()).find(|&i| s[i..].starts_with(needle))
+}
+
+fn splice<A: Clone>(s: &[A], i: usize, len: usize, with: &[A]) -> Vec<A> {
+    [&s[..i], with, &s[i + len..]].concat()
+}
+
+/// A string rewriting system. A rule with an empty left side applies everywhere, so the system
+/// doesn't terminate.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Srs<A> {
+    pub rules: Vec<StringRule<A>>,
+}
+
+impl<A: Clone + Ord> Srs<A> {
+    pub fn new(rules: Vec<StringRule<A>>) -> Srs<A> {
+        Srs { rules }
+    }
+
+    /// Rewrite s once at the leftmost position where a rule applies, with the first such rule,
+    /// or return None if s is in normal form.
+    pub fn rewrite_step(&self, s: &[A]) -> Option<Vec<A>> {
+        (0..s.len()).find_map(|i| {
+            self.rules
+                .iter()
+                .find(|r| s[i..].starts_with(&r.left))
+                .map(|r| splice(s, i, r.left.len(), &r.right))
+        })
+    }
+
+    /// Rewrite s until no rule applies. This only terminates if the system does.
+    pub fn normalize(&self, s: &[A]) -> Vec<A> {
+        let mut s = s.to_vec();
+        while let Some(t) = self.rewrite_step(&s) {
+            s = t;
+        }
+        s
+    }
+
+    /*
+     * The critical pairs between every two rules, including a rule and itself: where a proper
+     * suffix of one left side is a prefix of the other, the string covering both rewritten by
+     * each rule, and where one left side contains the other, that left side rewritten by each.
+     */
+    pub fn critical_pairs(&self) -> Vec<(Vec<A>, Vec<A>)> {
+        let mut pairs = Vec::new();
+        for r1 in &self.rules {
+            for r2 in &self.rules {
+                pairs.extend(critical_pairs(r1, r2));
+            }
+        }
+        pairs
+    }
+
+    /// Whether every critical pair joins, which for a terminating system means it's confluent.
+    pub fn is_locally_confluent(&self) -> bool {
+        self.critical_pairs()
+            .iter()
+            .all(|(u, v)| self.normalize(u) == self.normalize(v))
+    }
+}
+
+fn critical_pairs<A: Clone + Ord>(r1: &StringRule<A>, r2: &StringRule<A>) -> Vec<(Vec<A>, Vec<A>)> {
+    let (l1, l2) = (&r1.left, &r2.left);
+    let mut pairs = Vec::new();
+    for k in 1..l1.len().min(l2.len()) {
+        if l1[l1.len() - k..] == l2[..k] {
+            let left = [&r1.right[..], &l2[k..]].concat();
+            let right = [&l1[..l1.len() - k], &r2.right[..]].concat();
+            pairs.push((left, right));
+        }
+    }
+    if r1 != r2 {
+        let mut i = 0;
+        while let Some(j) = find(&l1[i..], l2) {
+            pairs.push((r1.right.clone(), splice(l1, i + j, l2.len(), &r2.right)));
+            i += j + 1;
+        }
+    }
+    pairs
+}
+
+/*
+ * Run Knuth-Bendix completion on the equations under shortlex, returning the convergent system
+ * it reaches, or None if that would need more than max_rules rules. As in Completion, the
+ * shortest pending equation is taken first and normalized, and if its sides differ it's oriented
+ * into a rule; rules whose left side it applies to turn back into equations, the others have
+ * their right sides normalized, and its critical pairs with every rule become pending.
+ */
+pub fn knuth_bendix<A: Clone + Ord>(
+    equations: &[(Vec<A>, Vec<A>)],
+    max_rules: usize,
+) -> Option<Srs<A>> {
+    let mut srs = Srs::new(Vec::new());
+    let mut pending = equations.to_vec();
+    while let Some(i) = (0..pending.len()).min_by_key(|&i| pending[i].0.len() + pending[i].1.len())
+    {
+        let (u, v) = pending.swap_remove(i);
+        let (u, v) = (srs.normalize(&u), srs.normalize(&v));
+        let rule = match shortlex(&u, &v) {
+            Ordering::Equal => continue,
+            Ordering::Greater => StringRule { left: u, right: v },
+            Ordering::Less => StringRule { left: v, right: u },
+        };
+        let (reducible, kept): (Vec<_>, Vec<_>) = srs
+            .rules
+            .drain(..)
+            .partition(|r| find(&r.left, &rule.left).is_some());
+        pending.extend(reducible.into_iter().map(|r| (r.left, r.right)));
+        srs.rules = kept;
+        srs.rules.push(rule.clone());
+        for j in 0..srs.rules.len() {
+            srs.rules[j].right = srs.normalize(&srs.rules[j].right);
+        }
+        if srs.rules.len() > max_rules {
+            return None;
+        }
+        for r in &srs.rules {
+            pending.extend(critical_pairs(&rule, r));
+            pending.extend(critical_pairs(r, &rule));
+        }
+    }
+    Some(srs)
+}
+
+/*
+ * The equations of a group presentation over letters, where a lowercase letter is a generator
+ * and the same letter in uppercase is its inverse: aA = ε and Aa = ε for each generator in the
+ * relators, and r = ε for each relator r.
+ */
+pub fn group_presentation(relators: &[&str]) -> Vec<(Vec<char>, Vec<char>)> {
+    let mut generators: Vec<char> = relators
+        .iter()
+        .flat_map(|r| r.chars())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    generators.sort();
+    generators.dedup();
+    let mut eqs = Vec::new();
+    for a in generators {
+        let inv = a.to_ascii_uppercase();
+        eqs.push((vec![a, inv], Vec::new()));
+        eqs.push((vec![inv, a], Vec::new()));
+    }
+    eqs.extend(relators.iter().map(|r| (r.chars().collect(), Vec::new())));
+    eqs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    // The number of strings over alphabet in normal form, if it's finite and at most max_len
+    // letters long.
+    fn count_normal_forms(srs: &Srs<char>, alphabet: &[char], max_len: usize) -> Option<usize> {
+        let mut level = vec![Vec::new()];
+        let mut count = 1;
+        for _ in 0..max_len {
+            level = level
+                .iter()
+                .flat_map(|s: &Vec<char>| alphabet.iter().map(move |&a| [&s[..], &[a]].concat()))
+                .filter(|s| srs.rewrite_step(s).is_none())
+                .collect();
+            if level.is_empty() {
+                return Some(count);
+            }
+            count += level.len();
+        }
+        None
+    }
+
+    #[test]
+    fn rewriting() {
+        let srs = Srs::new(vec![
+            StringRule {
+                left: chars("ba"),
+                right: chars("ab"),
+            },
+            StringRule {
+                left: chars("aa"),
+                right: vec![],
+            },
+        ]);
+        assert_eq!(srs.normalize(&chars("babab")), chars("bbb"));
+        assert_eq!(srs.normalize(&chars("bab")), chars("abb"));
+        assert_eq!(srs.rewrite_step(&chars("abb")), None);
+        assert_eq!(srs.rules[0].to_string(), "ba → ab");
+        assert_eq!(srs.rules[1].to_string(), "aa → ε");
+        assert_eq!(shortlex(&chars("b"), &chars("aa")), Ordering::Less);
+        assert_eq!(shortlex(&chars("ab"), &chars("ba")), Ordering::Less);
+        // ba and aa overlap in baa, which rewrites to aba and to b, and both go on to b.
+        assert!(srs.critical_pairs().contains(&(chars("aba"), chars("b"))));
+        assert!(srs.is_locally_confluent());
+    }
+
+    #[test]
+    fn presentations() {
+        // The free commutative monoid on a and b.
+        let srs = knuth_bendix(&[(chars("ab"), chars("ba"))], 10).unwrap();
+        assert_eq!(
+            srs.rules,
+            [StringRule {
+                left: chars("ba"),
+                right: chars("ab")
+            }]
+        );
+
+        // The symmetric group on three letters, and the cyclic group of order five.
+        let s3 = knuth_bendix(&group_presentation(&["aaa", "bb", "abab"]), 20).unwrap();
+        assert!(s3.is_locally_confluent());
+        assert_eq!(count_normal_forms(&s3, &['a', 'A', 'b', 'B'], 10), Some(6));
+        let z5 = knuth_bendix(&group_presentation(&["aaaaa"]), 20).unwrap();
+        assert_eq!(count_normal_forms(&z5, &['a', 'A'], 10), Some(5));
+        assert_eq!(z5.normalize(&chars("AAA")), chars("aa"));
+
+        // Kapur and Narendran showed this monoid has no finite convergent system on a and b.
+        assert!(knuth_bendix(&[(chars("aba"), chars("bab"))], 20).is_none());
+    }
+}
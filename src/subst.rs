@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::slice;
+
+use crate::word::{Operator, Symbol, Var, Variable, Word, WordRef};
+
+/*
+ * A word with a substitution applied lazily: the bound variables are replaced as the symbols are
+ * traversed, and nothing is copied until to_word. The substitution may be triangular, its
+ * bindings mentioning variables it binds itself, as the unifier does while it's being built, and
+ * those are replaced in turn. It must not be cyclic, which the occurs check in unification rules
+ * out, or traversal never ends.
+ */
+#[derive(Debug)]
+pub struct SubstWord<'a, V: Variable, O: Operator> {
+    pub word: WordRef<'a, V, O>,
+    pub subst: &'a BTreeMap<V, Word<V, O>>,
+}
+
+impl<V: Variable, O: Operator> Clone for SubstWord<'_, V, O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V: Variable, O: Operator> Copy for SubstWord<'_, V, O> {}
+
+/// The symbols of a SubstWord, in the order of syms.
+pub struct SubstSyms<'a, V: Variable, O: Operator> {
+    subst: &'a BTreeMap<V, Word<V, O>>,
+    // The rest of the word, then of each binding being traversed, innermost last.
+    stack: Vec<slice::Iter<'a, Symbol<V, O>>>,
+}
+
+impl<'a, V: Variable, O: Operator> Iterator for SubstSyms<'a, V, O> {
+    type Item = &'a Symbol<V, O>;
+
+    fn next(&mut self) -> Option<&'a Symbol<V, O>> {
+        loop {
+            let s = match self.stack.last_mut()?.next() {
+                Some(s) => s,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            match s {
+                Var(v) if self.subst.contains_key(v) => self.stack.push(self.subst[v].syms.iter()),
+                s => return Some(s),
+            }
+        }
+    }
+}
+
+impl<'a, V: Variable, O: Operator> SubstWord<'a, V, O> {
+    pub fn syms(self) -> SubstSyms<'a, V, O> {
+        SubstSyms {
+            subst: self.subst,
+            stack: vec![self.word.syms.iter()],
+        }
+    }
+
+    /// The word with its bound variables replaced, resolving bound variables at the head until
+    /// it starts with an operator or an unbound variable. This copies nothing.
+    pub fn resolve(self) -> SubstWord<'a, V, O> {
+        let mut word = self.word;
+        while let Some(b) = word
+            .syms
+            .first()
+            .and_then(|s| s.var())
+            .and_then(|v| self.subst.get(v))
+        {
+            word = b.view();
+        }
+        SubstWord { word, ..self }
+    }
+
+    pub fn head(self) -> Option<&'a Symbol<V, O>> {
+        self.resolve().word.syms.first()
+    }
+
+    /// The arguments of the resolved word, each with the same substitution.
+    pub fn subwords(self) -> impl Iterator<Item = SubstWord<'a, V, O>> {
+        let subst = self.subst;
+        self.resolve()
+            .word
+            .subwords()
+            .map(move |word| SubstWord { word, subst })
+    }
+
+    pub fn contains_var(self, v: &V) -> bool {
+        self.syms().any(|s| s.var() == Some(v))
+    }
+
+    pub fn to_word(self) -> Word<V, O> {
+        Word {
+            syms: self.syms().cloned().collect(),
+        }
+    }
+}
+
+impl<V: Variable, O: Operator> PartialEq for SubstWord<'_, V, O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.syms().eq(other.syms())
+    }
+}
+
+impl<V: Variable, O: Operator> Eq for SubstWord<'_, V, O> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::{inv, one, var};
+
+    #[test]
+    fn triangular() {
+        let (x, y, z) = (var("x"), var("y"), var("z"));
+        // x is bound to a word with y, which is bound in turn.
+        let subst = BTreeMap::from([("x".to_string(), inv(&y)), ("y".to_string(), &z * one())]);
+        let w = &x * &y;
+        let lazy = w.subst_lazy(&subst);
+        let eager = w.subst(&subst).subst(&subst);
+        assert_eq!(lazy.to_word(), eager);
+        assert_eq!(lazy.to_word(), inv(&(&z * one())) * (&z * one()));
+        assert!(lazy.contains_var(&"z".to_string()));
+        assert!(!lazy.contains_var(&"x".to_string()));
+
+        let head = x.subst_lazy(&subst);
+        assert_eq!(head.head(), inv(&y).syms.first());
+        let args: Vec<_> = head.subwords().map(SubstWord::to_word).collect();
+        assert_eq!(args, vec![&z * one()]);
+    }
+}
@@ -1,92 +1,72 @@
-use std::fmt::{self, Display};
 use std::ops;
 use std::slice;
+use std::str::FromStr;
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub enum Sum {
-    Zero,
-    Negate,
-    Add,
-}
-use Sum::*;
+use crate::parse::ParseError;
+use crate::word;
 
-impl Display for Sum {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Zero => "0".fmt(f),
-            Negate => "−".fmt(f),
-            Add => " + ".fmt(f),
-        }
+crate::theory! {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum Sum {
+        Zero = "0",
+        Negate(1) = "−" | "-" [weight(0), prefix],
+        Add(2) = " + " [infix(1), commutative, associative],
     }
 }
-
-
-use crate::word::{self, Op, Var};
+use Sum::*;
 
 pub type Symbol = word::Symbol<String, Sum>;
 pub type Word = word::Word<String, Sum>;
-
-impl word::Operator for Sum {
-    fn min_weight() -> u64 {
-        1
-    }
-
-    fn arity(&self) -> usize {
-        match self {
-            Zero => 0,
-            Negate => 1,
-            Add => 2,
-        }
-    }
-
-    fn weight(&self) -> u64 {
-        match self {
-            Zero => 1,
-            Negate => 0,
-            Add => 1,
-        }
-    }
-}
+pub type WordRef<'a> = word::WordRef<'a, String, Sum>;
 
 impl ops::Add for &Word {
     type Output = Word;
     fn add(self, rhs: &Word) -> Word {
-        Word::op(Add, &[self.clone(), rhs.clone()])
+        Word::op_owned(Add, vec![self.clone(), rhs.clone()])
     }
 }
 
 impl ops::Add<Word> for &Word {
     type Output = Word;
     fn add(self, rhs: Word) -> Word {
-        Word::op(Add, &[self.clone(), rhs])
+        Word::op_owned(Add, vec![self.clone(), rhs])
     }
 }
 
 impl ops::Add<&Word> for Word {
     type Output = Word;
     fn add(self, rhs: &Word) -> Word {
-        Word::op(Add, &[self, rhs.clone()])
+        Word::op_owned(Add, vec![self, rhs.clone()])
     }
 }
 
 impl ops::Add for Word {
     type Output = Word;
     fn add(self, rhs: Word) -> Word {
-        Word::op(Add, &[self, rhs])
+        Word::op_owned(Add, vec![self, rhs])
     }
 }
 
 impl ops::Neg for Word {
     type Output = Word;
     fn neg(self) -> Word {
-        Word::op(Negate, &[self])
+        Word::op_owned(Negate, vec![self])
     }
 }
 
 impl ops::Neg for &Word {
     type Output = Word;
     fn neg(self) -> Word {
-        Word::op(Negate, &[self.clone()])
+        Word::op(Negate, slice::from_ref(self))
+    }
+}
+
+/// Parse a sum as it's displayed, such as (a + b) + −(b + 0).
+impl FromStr for Word {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Word, ParseError> {
+        Sum::parse_word(s)
     }
 }
 
@@ -102,59 +82,10 @@ pub fn negate(w: &Word) -> Word {
     Word::op(Negate, slice::from_ref(w))
 }
 
-fn fmt_with_parens(w: &Word, f: &mut fmt::Formatter) -> fmt::Result {
-    match w.syms.first() {
-        Some(Var(v)) => v.fmt(f),
-        Some(Op(Zero)) => Zero.fmt(f),
-        Some(Op(Negate)) => {
-            if let Some(arg) = w.subwords().next() {
-                Negate.fmt(f).and(fmt_with_parens(&arg, f))
-            } else {
-                fmt::Result::Err(fmt::Error::default())
-            }
-        }
-        Some(Op(Add)) => {
-            let mut sw = w.subwords();
-            if let (Some(left), Some(right)) = (sw.next(), sw.next()) {
-                "(".fmt(f)
-                    .and(fmt_with_parens(&left, f))
-                    .and(Add.fmt(f))
-                    .and(fmt_with_parens(&right, f))
-                    .and(")".fmt(f))
-            } else {
-                fmt::Result::Err(fmt::Error::default())
-            }
-        }
-        None => fmt::Result::Err(fmt::Error::default()),
-    }
-}
-
-impl Display for Word {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.syms.first() {
-            /*
-             * Format a top-level Add without surrounding parentheses; otherwise, call into
-             * fmt_with_parens.
-             */
-            Some(Op(Add)) => {
-                let mut sw = self.subwords();
-                if let (Some(left), Some(right)) = (sw.next(), sw.next()) {
-                    fmt_with_parens(&left, f)
-                        .and(Add.fmt(f))
-                        .and(fmt_with_parens(&right, f))
-                } else {
-                    fmt::Result::Err(fmt::Error::default())
-                }
-            },
-            _ => fmt_with_parens(self, f),
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
-    use crate::print_subs;
     use crate::critical_term;
+    use crate::print_subs;
     use crate::sum::*;
     use std::cmp::*;
     use std::collections::BTreeMap;
@@ -196,6 +127,17 @@ mod tests {
         println!("{}", negatezero);
     }
 
+    #[test]
+    fn sort_commutative() {
+        let a = var("a");
+        let b = var("b");
+        let p = (&b + &a) + -(&zero() + &b);
+        let q = -(&b + &zero()) + (&a + &b);
+        assert_ne!(p, q);
+        assert_eq!(p.sort_commutative(), q.sort_commutative());
+        assert_eq!(p.sort_commutative().to_string(), "(a + b) + −(b + 0)");
+    }
+
     #[test]
     fn eq() {
         let a = var("a");
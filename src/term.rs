@@ -0,0 +1,89 @@
+use crate::signature::{DynOp, Signature};
+use crate::word::{Operator, Word};
+
+/// A way to look up operators by name, for term!. Signature is one, and so is any function from
+/// names to operators.
+pub trait NamedOps<O: Operator> {
+    fn op_named(&self, name: &str) -> Option<O>;
+}
+
+impl NamedOps<DynOp> for Signature {
+    fn op_named(&self, name: &str) -> Option<DynOp> {
+        self.get(name).cloned()
+    }
+}
+
+impl<O: Operator, F: Fn(&str) -> Option<O>> NamedOps<O> for F {
+    fn op_named(&self, name: &str) -> Option<O> {
+        self(name)
+    }
+}
+
+/*
+ * Build a word from a term written as it would be on paper, against anything implementing
+ * NamedOps: term!(sig, f(x, g(y, 0))). A name applied to arguments must be an operator, and a
+ * name or number on its own is a constant if the signature has an operator by that name and a
+ * variable otherwise. Panics if an operator isn't found.
+ */
+#[macro_export]
+macro_rules! term {
+    ($sig:expr, $f:tt ( $( $a:tt $( ( $($inner:tt)* ) )? ),* $(,)? )) => {
+        $crate::term_app(
+            &$sig,
+            stringify!($f),
+            vec![ $( $crate::term!($sig, $a $( ( $($inner)* ) )? ) ),* ],
+        )
+    };
+    ($sig:expr, $x:tt) => {
+        $crate::term_leaf(&$sig, stringify!($x))
+    };
+}
+
+#[doc(hidden)]
+pub fn term_app<O: Operator>(
+    sig: &impl NamedOps<O>,
+    name: &str,
+    args: Vec<Word<String, O>>,
+) -> Word<String, O> {
+    let f = sig
+        .op_named(name)
+        .unwrap_or_else(|| panic!("term!: no operator named {}", name));
+    Word::op_owned(f, args)
+}
+
+#[doc(hidden)]
+pub fn term_leaf<O: Operator>(sig: &impl NamedOps<O>, name: &str) -> Word<String, O> {
+    match sig.op_named(name) {
+        Some(f) if f.arity() == 0 => Word::op(f, &[]),
+        _ => Word::var(name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prod::{inv, one, var, Prod};
+    use crate::Signature;
+
+    #[test]
+    fn build() {
+        let mut sig = Signature::new();
+        sig.declare("0", 0, 1).unwrap();
+        let g = sig.declare("g", 2, 1).unwrap();
+        let f = sig.declare("f", 2, 1).unwrap();
+        let (x, y) = (sig.var("x"), sig.var("y"));
+        let zero = sig.app("0", &[]).unwrap();
+        let expected = crate::word::Word::op(f, &[x, crate::word::Word::op(g, &[y, zero])]);
+        assert_eq!(term!(sig, f(x, g(y, 0))), expected);
+
+        let prod = |name: &str| match name {
+            "one" => Some(Prod::One),
+            "mul" => Some(Prod::Mul),
+            "inv" => Some(Prod::Inv),
+            _ => None,
+        };
+        assert_eq!(
+            term!(prod, mul(inv(x), mul(one, y))),
+            inv(&var("x")) * (one() * var("y"))
+        );
+    }
+}
@@ -0,0 +1,22 @@
+/*
+ * Ready-made theories to feed into completion, for tests and examples. Each is a module with
+ * its axioms, a signature of its own where the crate's Sum and Prod don't fit, and the canonical
+ * system completion reaches, where there is one.
+ *
+ * abelian_group, boolean, kleene, and lattice are axioms only. Each has an operator that is
+ * associative and commutative, and deciding such a theory by completion takes completion modulo
+ * AC, which rests on AC matching and AC unification; the crate has neither. Plain completion
+ * can't orient x + y = y + x, so it never finishes on these, and the canonical AC systems some of
+ * them are known for aren't given, since nothing here derives them.
+ */
+pub mod abelian_group;
+pub mod boolean;
+pub mod central_groupoid;
+pub mod cl;
+pub mod group;
+pub mod kleene;
+pub mod lattice;
+pub mod list;
+pub mod peano;
+pub mod quasigroup;
+pub mod ring;
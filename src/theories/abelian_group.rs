@@ -0,0 +1,60 @@
+/*
+ * Abelian groups, over the Sum signature: the group axioms written additively, with + also
+ * commutative. No ordering can orient x + y = y + x, so plain completion stops with it
+ * unorientable. Completion modulo associativity and commutativity would build them into the
+ * matching of + instead, and reach the five rules of Peterson and Stickel's "Complete sets of
+ * reductions for some equational theories" (1981). This module is axioms only, for the reason in
+ * the theories module docs.
+ */
+use crate::relation::Axiom;
+use crate::sum::{var, zero, Sum};
+
+/// The axioms, on which plain completion stops with commutativity unorientable.
+pub fn axioms() -> Vec<Axiom<String, Sum>> {
+    let (x, y, z) = (var("x"), var("y"), var("z"));
+    vec![
+        Axiom {
+            left: zero() + &x,
+            right: x.clone(),
+        },
+        Axiom {
+            left: -&x + &x,
+            right: zero(),
+        },
+        Axiom {
+            left: (&x + &y) + &z,
+            right: &x + (&y + &z),
+        },
+        Axiom {
+            left: &x + &y,
+            right: &y + &x,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::saturation::{Saturation, Verdict};
+    use crate::{Completion, Outcome};
+
+    #[test]
+    fn no_ac_completion() {
+        let axioms = axioms();
+        let Outcome::Unorientable(eqs) = Completion::new(&axioms).run() else {
+            panic!("commutativity was oriented");
+        };
+        assert!(eqs.contains(&axioms[3]));
+
+        // Peterson and Stickel's rules, which saturation can reach within its bounds for the
+        // first three. Nothing here derives them.
+        let sat = Saturation::default();
+        for (l, r) in [("x + 0", "x"), ("x + −x", "0"), ("−0", "0")] {
+            let goal = Axiom {
+                left: l.parse().unwrap(),
+                right: r.parse().unwrap(),
+            };
+            assert_eq!(sat.prove(&axioms, &goal), Verdict::Equal, "{}", goal);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+/*
+ * Boolean algebra as a Boolean ring, the axiomatization Hsiang used for theorem proving by
+ * rewriting: ⊕ (exclusive or) and ∧ make a commutative ring with 0 and 1 in which
+ * every element is idempotent under ∧ and its own inverse under ⊕, and ∨ and ¬ are
+ * defined in terms of them. Modulo associativity and commutativity of ⊕ and ∧,
+ * completion of these axioms gives the eight rules of Hsiang's system, under which every
+ * Boolean formula has a unique normal form: a sum of products of its variables, which is 0
+ * exactly when the formula is a contradiction.
+ *
+ * This module is axioms only, for the reason in the theories module docs. Plain completion never
+ * finishes: it can't orient the commutativity axioms, and keeps generating rules around them.
+ */
+use std::str::FromStr;
+
+use crate::parse::ParseError;
+use crate::word;
+
+crate::theory! {
+    /// The operators in order of precedence, with negation highest as it has weight zero.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum Bool {
+        False = "0",
+        True = "1",
+        Xor(2) = " ⊕ " [infix(2), commutative, associative],
+        And(2) = " ∧ " [infix(3), commutative, associative],
+        Or(2) = " ∨ " [infix(1), commutative, associative],
+        Not(1) = "¬" [weight(0), prefix],
+    }
+
+    /// The axioms, on which plain completion never finishes.
+    pub fn axioms {
+        "x ⊕ 0" = "x",
+        "x ⊕ x" = "0",
+        "(x ⊕ y) ⊕ z" = "x ⊕ (y ⊕ z)",
+        "x ⊕ y" = "y ⊕ x",
+        "x ∧ 1" = "x",
+        "x ∧ x" = "x",
+        "(x ∧ y) ∧ z" = "x ∧ (y ∧ z)",
+        "x ∧ y" = "y ∧ x",
+        "x ∧ (y ⊕ z)" = "x ∧ y ⊕ x ∧ z",
+        "x ∨ y" = "x ∧ y ⊕ (x ⊕ y)",
+        "¬x" = "x ⊕ 1",
+    }
+}
+
+pub type Word = word::Word<String, Bool>;
+
+/// Parse a formula as it's displayed, such as ¬(x ∨ y) ⊕ x ∧ 1.
+impl FromStr for Word {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Word, ParseError> {
+        Bool::parse_word(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::{Completion, Outcome, Var};
+    use Bool::*;
+
+    // Hsiang's system, which completion modulo AC reaches and nothing here derives.
+    const HSIANG: [(&str, &str); 8] = [
+        ("x ⊕ 0", "x"),
+        ("x ⊕ x", "0"),
+        ("x ∧ 0", "0"),
+        ("x ∧ 1", "x"),
+        ("x ∧ x", "x"),
+        ("x ∧ (y ⊕ z)", "x ∧ y ⊕ x ∧ z"),
+        ("x ∨ y", "x ∧ y ⊕ (x ⊕ y)"),
+        ("¬x", "x ⊕ 1"),
+    ];
+
+    fn parse(s: &str) -> Word {
+        s.parse().unwrap()
+    }
+
+    // The value of w in the two-element Boolean algebra, with the variables given by env.
+    fn eval(w: &Word, env: &BTreeMap<String, bool>) -> bool {
+        let mut stack: Vec<bool> = Vec::new();
+        for s in w.syms.iter().rev() {
+            let v = match s {
+                Var(v) => env[v],
+                word::Op(False) => false,
+                word::Op(True) => true,
+                word::Op(Not) => !stack.pop().unwrap(),
+                word::Op(f) => {
+                    let (a, b) = (stack.pop().unwrap(), stack.pop().unwrap());
+                    match f {
+                        Xor => a != b,
+                        And => a && b,
+                        _ => a || b,
+                    }
+                }
+            };
+            stack.push(v);
+        }
+        stack[0]
+    }
+
+    #[test]
+    fn truth_tables() {
+        let sides = axioms().into_iter().map(|a| (a.left, a.right));
+        let rules = HSIANG.iter().map(|(l, r)| (parse(l), parse(r)));
+        for (l, r) in sides.chain(rules) {
+            for bits in 0..8 {
+                let env = ["x", "y", "z"]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| (v.to_string(), bits & (1 << i) != 0))
+                    .collect();
+                assert_eq!(eval(&l, &env), eval(&r, &env), "{} = {}", l, r);
+            }
+        }
+    }
+
+    #[test]
+    fn no_ac_completion() {
+        let axioms = axioms();
+        let mut c = Completion::new(&axioms);
+        c.max_steps = Some(200);
+        assert!(matches!(c.run(), Outcome::Limit));
+        assert!(c.pending().any(|e| *e == axioms[3]));
+        assert!(c.pending().any(|e| *e == axioms[7]));
+    }
+}
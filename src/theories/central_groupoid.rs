@@ -0,0 +1,102 @@
+/*
+ * Central groupoids: one binary operation with (x · y) · (y · z) = y, the example Knuth and
+ * Bendix used to show completion at work. From the single axiom it derives two more rules and
+ * stops, and the three decide the word problem. problems::central_groupoids is the same problem
+ * over the signature of the paper's other examples.
+ */
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::notation::{Assoc, Notation};
+use crate::parse::{parse_term, ParseError};
+use crate::relation::{Axiom, Rule};
+use crate::word::{self, Operator};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mul;
+
+pub type Word = word::Word<String, Mul>;
+
+impl Display for Mul {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        " · ".fmt(f)
+    }
+}
+
+impl Operator for Mul {
+    fn min_weight() -> u64 {
+        1
+    }
+
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn weight(&self) -> u64 {
+        1
+    }
+
+    fn notation(&self) -> Notation {
+        Notation::infix(1, Assoc::None)
+    }
+}
+
+// The operator by the name it's displayed with, and . for ·, for parsing.
+fn named(name: &str) -> Option<Mul> {
+    matches!(name, "·" | ".").then_some(Mul)
+}
+
+/// Parse a product as it's displayed, such as (x · y) · z.
+impl FromStr for Word {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Word, ParseError> {
+        parse_term(s, &named)
+    }
+}
+
+pub fn axioms() -> Vec<Axiom<String, Mul>> {
+    vec![Axiom {
+        left: "(x · y) · (y · z)".parse().unwrap(),
+        right: "y".parse().unwrap(),
+    }]
+}
+
+/// The canonical system for central groupoids, each rule's sides as words are displayed.
+pub const RULES: [(&str, &str); 3] = [
+    ("(x · y) · (y · z)", "y"),
+    ("x · ((x · y) · z)", "x · y"),
+    ("(x · (y · z)) · z", "y · z"),
+];
+
+pub fn rules() -> Vec<Rule<String, Mul>> {
+    RULES
+        .iter()
+        .map(|(l, r)| Rule {
+            left: l.parse().unwrap(),
+            right: r.parse().unwrap(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Completion, Outcome};
+
+    #[test]
+    fn completion() {
+        let mut c = Completion::new(&axioms());
+        assert!(matches!(c.run(), Outcome::Convergent));
+        let canonical = rules();
+        assert_eq!(c.rules().len(), canonical.len());
+        for r in &canonical {
+            assert!(c.rules().iter().any(|s| s.is_variant(r)), "{}", r);
+        }
+        for (r, (l, rhs)) in canonical.iter().zip(RULES) {
+            assert_eq!(r.to_string(), format!("{} → {}", l, rhs));
+        }
+        assert!(c.trs().is_locally_confluent().is_locally_confluent());
+    }
+}
@@ -0,0 +1,88 @@
+/*
+ * Combinatory logic: constants S, K, and I, and application, written x · y and associating to
+ * the left, with the contraction rules
+ *
+ *     I · x → x
+ *     K · x · y → x
+ *     S · x · y · z → x · z · (y · z)
+ *
+ * The system is orthogonal, so confluent, but doesn't terminate: S · I · I · (S · I · I)
+ * contracts to itself. Normalize it with Trs::normalize_bounded. Its leftmost outermost steps
+ * find the normal form of every word that has one, even K · I · Ω, whose argument Ω has none.
+ */
+use std::str::FromStr;
+
+use crate::parse::ParseError;
+use crate::relation::Rule;
+use crate::trs::Trs;
+use crate::word;
+
+crate::theory! {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum Cl {
+        S = "S",
+        K = "K",
+        I = "I",
+        App(2) = " · " | "." [infix(1, Left)],
+    }
+}
+
+pub type Word = word::Word<String, Cl>;
+
+/// Parse a combinator as it's displayed, such as S · K · (K · x).
+impl FromStr for Word {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Word, ParseError> {
+        Cl::parse_word(s)
+    }
+}
+
+pub fn rules() -> Trs<String, Cl> {
+    let rule = |l: &str, r: &str| Rule {
+        left: l.parse().unwrap(),
+        right: r.parse().unwrap(),
+    };
+    Trs::new(vec![
+        rule("I · x", "x"),
+        rule("K · x · y", "x"),
+        rule("S · x · y · z", "x · z · (y · z)"),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(s: &str) -> Word {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn contraction() {
+        let trs = rules();
+        // Orthogonal: left-linear, with no critical pairs.
+        assert!(trs.critical_pairs().is_empty());
+        assert_eq!(trs.normalize(&word("S · K · K · a")), word("a"));
+        assert_eq!(word("S . (K . a) . b").to_string(), "S · (K · a) · b");
+        // B = S(KS)K composes: B f g x → f (g x).
+        let b = "S · (K · S) · K";
+        let w = word(&format!("{} · f · g · x", b));
+        assert_eq!(trs.normalize_bounded(&w, 10), Ok(word("f · (g · x)")));
+        assert_eq!(trs.normalize_bounded(&word("a"), 0), Ok(word("a")));
+    }
+
+    #[test]
+    fn fuel() {
+        let trs = rules();
+        let omega = "S · I · I · (S · I · I)";
+        let Err(w) = trs.normalize_bounded(&word(omega), 100) else {
+            panic!("Ω has a normal form");
+        };
+        assert!(trs.rewrite_step(&w).is_some());
+        // Outermost first, the diverging argument is discarded before it's contracted.
+        let w = word(&format!("K · I · ({})", omega));
+        assert_eq!(trs.normalize_bounded(&w, 1), Ok(word("I")));
+        assert_eq!(trs.normalize_bounded(&w, 0), Err(w.clone()));
+    }
+}
@@ -0,0 +1,77 @@
+/*
+ * Groups, over the Prod signature: a left identity, left inverses, and associativity. From these
+ * completion reaches the ten rules of Knuth and Bendix's paper, which decide the word problem for
+ * free groups.
+ */
+use crate::prod::{inv, one, var, Prod, Word};
+use crate::relation::{Axiom, Rule};
+
+pub fn axioms() -> Vec<Axiom<String, Prod>> {
+    let (x, y, z) = (var("x"), var("y"), var("z"));
+    vec![
+        Axiom {
+            left: one() * &x,
+            right: x.clone(),
+        },
+        Axiom {
+            left: inv(&x) * &x,
+            right: one(),
+        },
+        Axiom {
+            left: (&x * &y) * &z,
+            right: &x * (&y * &z),
+        },
+    ]
+}
+
+/// The canonical system for groups, each rule's sides as Prod words are displayed.
+pub const RULES: [(&str, &str); 10] = [
+    ("1 * x", "x"),
+    ("x⁻¹ * x", "1"),
+    ("(x * y) * z", "x * (y * z)"),
+    ("x⁻¹ * (x * y)", "y"),
+    ("1⁻¹", "1"),
+    ("x * x⁻¹", "1"),
+    ("x * 1", "x"),
+    ("x⁻¹⁻¹", "x"),
+    ("x * (x⁻¹ * y)", "y"),
+    ("(x * y)⁻¹", "y⁻¹ * x⁻¹"),
+];
+
+pub fn rules() -> Vec<Rule<String, Prod>> {
+    let word = |s: &str| s.parse::<Word>().unwrap();
+    RULES
+        .iter()
+        .map(|(l, r)| Rule {
+            left: word(l),
+            right: word(r),
+        })
+        .collect()
+}
+
+/// Whether rules are the canonical system, in any order and up to renaming variables.
+pub fn is_canonical(rules: &[Rule<String, Prod>]) -> bool {
+    let canonical = self::rules();
+    rules.len() == canonical.len()
+        && canonical
+            .iter()
+            .all(|c| rules.iter().any(|r| r.is_variant(c)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{knuth_bendix, Trs};
+
+    #[test]
+    fn completion() {
+        let rules = knuth_bendix(&axioms()).unwrap();
+        assert!(is_canonical(&rules));
+        let trs = Trs::new(super::rules());
+        assert!(trs.is_locally_confluent().is_locally_confluent());
+        assert!(!is_canonical(&trs.rules()[1..]));
+        let mut wrong = trs.rules().to_vec();
+        wrong[9].right = "y1⁻¹ * x⁻¹".parse().unwrap();
+        assert!(!is_canonical(&wrong));
+    }
+}
@@ -0,0 +1,150 @@
+/*
+ * Idempotent semirings and Kleene algebra, over + (choice), · (sequencing), 0, 1, and the
+ * postfix star. An idempotent semiring has + associative, commutative, and idempotent with unit
+ * 0, and · associative with unit 1 and zero 0, distributing over + on both sides. Kleene algebra
+ * adds x* as the least solution of 1 + x · y ≤ y; only the unfolding equations of star are
+ * equational, and the induction rules that make it least are implications, which are left out.
+ *
+ * This module is axioms only, for the reason in the theories module docs. Unfailing completion
+ * would get past x + y = y + x too, and the crate doesn't have that either: plain completion
+ * can't orient commutativity and runs on. Saturation settles goals between small words.
+ */
+use std::str::FromStr;
+
+use crate::parse::ParseError;
+use crate::relation::Axiom;
+use crate::word;
+
+crate::theory! {
+    /// The operators in order of precedence.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum Kleene {
+        Zero = "0",
+        One = "1",
+        Plus(2) = " + " [infix(1), commutative, associative],
+        Times(2) = " · " [infix(2), associative],
+        Star(1) = "*" [postfix],
+    }
+
+    /// The axioms of an idempotent semiring, on which completion can't finish.
+    pub fn axioms {
+        "(x + y) + z" = "x + (y + z)",
+        "x + y" = "y + x",
+        "x + x" = "x",
+        "x + 0" = "x",
+        "(x · y) · z" = "x · (y · z)",
+        "1 · x" = "x",
+        "x · 1" = "x",
+        "0 · x" = "0",
+        "x · 0" = "0",
+        "x · (y + z)" = "x · y + x · z",
+        "(x + y) · z" = "x · z + y · z",
+    }
+
+    // The unfoldings of star.
+    fn unfoldings {
+        "1 + x · x*" = "x*",
+        "1 + x* · x" = "x*",
+    }
+}
+
+pub type Word = word::Word<String, Kleene>;
+
+/// Parse a word as it's displayed, such as (x + 1) · y*.
+impl FromStr for Word {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Word, ParseError> {
+        Kleene::parse_word(s)
+    }
+}
+
+/// The idempotent semiring axioms with the two unfoldings of star, on which completion can't
+/// finish either.
+pub fn star_axioms() -> Vec<Axiom<String, Kleene>> {
+    let mut axioms = axioms();
+    axioms.extend(unfoldings());
+    axioms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::saturation::{Saturation, Verdict};
+    use crate::{Completion, Outcome, Var};
+    use Kleene::*;
+
+    // Relations on {0, 1} as 2×2 Boolean matrices, bit 2i + j for i related to j.
+    fn compose(a: u8, b: u8) -> u8 {
+        let mut c = 0;
+        for i in 0..2 {
+            for j in 0..2 {
+                if (0..2).any(|k| a >> (2 * i + k) & 1 == 1 && b >> (2 * k + j) & 1 == 1) {
+                    c |= 1 << (2 * i + j);
+                }
+            }
+        }
+        c
+    }
+
+    // The value of w in the Kleene algebra of relations on {0, 1}, with x, y, and z as in env.
+    fn eval(w: &Word, env: [u8; 3]) -> u8 {
+        let mut stack: Vec<u8> = Vec::new();
+        for s in w.syms.iter().rev() {
+            let v = match s {
+                Var(v) => env[["x", "y", "z"].iter().position(|n| n == v).unwrap()],
+                word::Op(Zero) => 0,
+                word::Op(One) => 0b1001,
+                word::Op(Star) => {
+                    let a = stack.pop().unwrap() | 0b1001;
+                    compose(a, a)
+                }
+                word::Op(f) => {
+                    let (a, b) = (stack.pop().unwrap(), stack.pop().unwrap());
+                    if *f == Plus {
+                        a | b
+                    } else {
+                        compose(a, b)
+                    }
+                }
+            };
+            stack.push(v);
+        }
+        stack[0]
+    }
+
+    #[test]
+    fn relations() {
+        for a in star_axioms() {
+            for bits in 0..1u16 << 12 {
+                let env = [0, 4, 8].map(|i| (bits >> i & 15) as u8);
+                assert_eq!(eval(&a.left, env), eval(&a.right, env), "{}", a);
+            }
+        }
+        let w: Word = "(x + 1) · y*".parse().unwrap();
+        assert_eq!(w.to_string(), "(x + 1) · y*");
+    }
+
+    #[test]
+    fn saturation() {
+        let sat = Saturation::default();
+        let goal = |l: &str, r: &str| Axiom {
+            left: l.parse().unwrap(),
+            right: r.parse().unwrap(),
+        };
+        let axioms = axioms();
+        assert_eq!(
+            sat.prove(&axioms, &goal("a · (b + 1)", "a + a · b")),
+            Verdict::Equal
+        );
+        assert_eq!(
+            sat.prove(&axioms, &goal("a · b", "b · a")),
+            Verdict::Unknown
+        );
+
+        let mut c = Completion::new(&axioms);
+        c.max_steps = Some(100);
+        assert!(matches!(c.run(), Outcome::Limit));
+        assert!(c.pending().any(|e| *e == axioms[1]));
+    }
+}
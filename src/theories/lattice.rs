@@ -0,0 +1,116 @@
+/*
+ * Lattices and semilattices, over ∧ (meet) and ∨ (join). A semilattice has one operator that is
+ * associative, commutative, and idempotent; a lattice has two, tied together by absorption.
+ *
+ * This module is axioms only, for the reason in the theories module docs: no ordering can orient
+ * x ∧ y = y ∧ x, so completion can't finish on these. Saturation, which needs no ordering,
+ * settles goals between small words.
+ */
+use std::str::FromStr;
+
+use crate::parse::ParseError;
+use crate::word;
+
+crate::theory! {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum Lattice {
+        Meet(2) = " ∧ " [infix(2), commutative, associative],
+        Join(2) = " ∨ " [infix(1), commutative, associative],
+    }
+
+    /// The axioms of a meet semilattice, on which completion can't finish.
+    pub fn semilattice_axioms {
+        "(x ∧ y) ∧ z" = "x ∧ (y ∧ z)",
+        "x ∧ y" = "y ∧ x",
+        "x ∧ x" = "x",
+    }
+
+    /// The axioms of a lattice, on which completion can't finish.
+    pub fn axioms {
+        "(x ∧ y) ∧ z" = "x ∧ (y ∧ z)",
+        "x ∧ y" = "y ∧ x",
+        "x ∧ x" = "x",
+        "(x ∨ y) ∨ z" = "x ∨ (y ∨ z)",
+        "x ∨ y" = "y ∨ x",
+        "x ∨ x" = "x",
+        "x ∧ (x ∨ y)" = "x",
+        "x ∨ x ∧ y" = "x",
+    }
+}
+
+pub type Word = word::Word<String, Lattice>;
+
+/// Parse a lattice word as it's displayed, such as x ∧ (y ∨ z).
+impl FromStr for Word {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Word, ParseError> {
+        Lattice::parse_word(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relation::Axiom;
+    use crate::saturation::{Saturation, Verdict};
+    use crate::{Completion, Outcome, Var};
+    use Lattice::*;
+
+    // The value of w in the lattice of subsets of {0, 1, 2}, with x, y, and z as given by env.
+    fn eval(w: &Word, env: [u8; 3]) -> u8 {
+        let mut stack: Vec<u8> = Vec::new();
+        for s in w.syms.iter().rev() {
+            let v = match s {
+                Var(v) => env[["x", "y", "z"].iter().position(|n| n == v).unwrap()],
+                word::Op(f) => {
+                    let (a, b) = (stack.pop().unwrap(), stack.pop().unwrap());
+                    match f {
+                        Meet => a & b,
+                        Join => a | b,
+                    }
+                }
+            };
+            stack.push(v);
+        }
+        stack[0]
+    }
+
+    #[test]
+    fn sets() {
+        for a in axioms() {
+            for bits in 0..1u16 << 9 {
+                let env = [0, 3, 6].map(|i| (bits >> i & 7) as u8);
+                assert_eq!(eval(&a.left, env), eval(&a.right, env), "{}", a);
+            }
+        }
+    }
+
+    #[test]
+    fn saturation() {
+        let sat = Saturation::default();
+        let goal = |l: &str, r: &str| Axiom {
+            left: l.parse().unwrap(),
+            right: r.parse().unwrap(),
+        };
+        let semilattice = semilattice_axioms();
+        let axioms = axioms();
+        assert_eq!(
+            sat.prove(&semilattice, &goal("(a ∧ b) ∧ a", "b ∧ a")),
+            Verdict::Equal
+        );
+        assert_eq!(
+            sat.prove(&semilattice, &goal("a ∧ b", "a")),
+            Verdict::Unknown
+        );
+        assert_eq!(
+            sat.prove(&axioms, &goal("(a ∨ b) ∧ a", "a")),
+            Verdict::Equal
+        );
+
+        let mut c = Completion::new(&axioms);
+        c.max_steps = Some(100);
+        assert!(matches!(c.run(), Outcome::Limit));
+        assert!(c.pending().any(|e| *e == axioms[1]));
+    }
+}
@@ -0,0 +1,139 @@
+/*
+ * Lists, built from nil and cons, with append, reverse, and length defined by recursion on
+ * their first argument, and lengths as numerals s(...s(0)). Reverse's definition has fewer
+ * symbols on its left side, so it takes the lexicographic path ordering, with the defined
+ * operators above the constructors, to orient it. The definitions are then already complete.
+ *
+ * The lemma reverse(append(x, y)) = append(reverse(y), reverse(x)) holds for every list, but
+ * only by induction: it isn't an equational consequence of the definitions, so the two sides
+ * have different normal forms, though every ground instance joins. Adding it to the axioms
+ * makes completion diverge, generating append(append(...), nil) → append(...) for ever larger
+ * words, as it tries to derive append(x, nil) = x and associativity of append one instance at a
+ * time. Given associativity as well, completion converges, deriving append(reverse(x), nil) →
+ * reverse(x) and no equation between constructors, which is the inductionless induction proof of
+ * both lemmas.
+ */
+use std::str::FromStr;
+
+use crate::lpo::Lpo;
+use crate::parse::ParseError;
+use crate::relation::Axiom;
+use crate::word;
+
+crate::theory! {
+    /// The operators in order of precedence.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum List {
+        Zero = "0",
+        Succ(1) = "s",
+        Nil = "nil",
+        Cons(2) = "cons",
+        Append(2) = "append",
+        Reverse(1) = "reverse",
+        Length(1) = "length",
+    }
+
+    pub fn axioms {
+        "append(nil, y)" = "y",
+        "append(cons(x, xs), y)" = "cons(x, append(xs, y))",
+        "reverse(nil)" = "nil",
+        "reverse(cons(x, xs))" = "append(reverse(xs), cons(x, nil))",
+        "length(nil)" = "0",
+        "length(cons(x, xs))" = "s(length(xs))",
+    }
+
+    // The lemmas that hold by induction.
+    fn lemmas {
+        "reverse(append(x, y))" = "append(reverse(y), reverse(x))",
+        "append(append(x, y), z)" = "append(x, append(y, z))",
+    }
+}
+
+pub type Word = word::Word<String, List>;
+
+/// Parse a word as it's displayed, such as append(cons(x, nil), reverse(y)).
+impl FromStr for Word {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Word, ParseError> {
+        List::parse_word(s)
+    }
+}
+
+/// The ordering that orients the axioms as definitions.
+pub fn order() -> Lpo {
+    Lpo
+}
+
+/// reverse(append(x, y)) = append(reverse(y), reverse(x)), which holds by induction.
+pub fn reverse_append() -> Axiom<String, List> {
+    lemmas().remove(0)
+}
+
+/// append(append(x, y), z) = append(x, append(y, z)), which holds by induction.
+pub fn append_associative() -> Axiom<String, List> {
+    lemmas().remove(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Completion, Outcome, Rule, Trs};
+
+    fn complete(
+        axioms: &[Axiom<String, List>],
+        max_steps: usize,
+    ) -> (Outcome<String, List>, Trs<String, List>) {
+        let mut c = Completion::with_order(axioms, order());
+        c.max_steps = Some(max_steps);
+        (c.run(), c.trs())
+    }
+
+    fn word(s: &str) -> Word {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn definitions() {
+        let (outcome, trs) = complete(&axioms(), 100);
+        assert!(matches!(outcome, Outcome::Convergent));
+        assert_eq!(trs.rules().len(), axioms().len());
+        for a in axioms() {
+            assert!(trs.rules().contains(&Rule {
+                left: a.left,
+                right: a.right
+            }));
+        }
+        let w = word("length(reverse(append(cons(a, nil), cons(b, cons(c, nil)))))");
+        assert_eq!(trs.normalize(&w), word("s(s(s(0)))"));
+
+        // The lemma's sides are distinct normal forms, but its ground instances join.
+        let lemma = reverse_append();
+        assert_ne!(trs.normalize(&lemma.left), trs.normalize(&lemma.right));
+        let lists = ["nil", "cons(a, nil)", "cons(a, cons(b, nil))"];
+        for x in lists {
+            for y in ["nil", "cons(c, nil)", "cons(c, cons(d, nil))"] {
+                let l = word(&format!("reverse(append({}, {}))", x, y));
+                let r = word(&format!("append(reverse({}), reverse({}))", y, x));
+                assert_eq!(trs.normalize(&l), trs.normalize(&r));
+            }
+        }
+    }
+
+    #[test]
+    fn lemmas() {
+        let (outcome, trs) = complete(&[axioms(), vec![reverse_append()]].concat(), 200);
+        assert!(matches!(outcome, Outcome::Limit));
+        assert!(trs.rules().len() > 20);
+
+        let axioms = [axioms(), vec![append_associative(), reverse_append()]].concat();
+        let (outcome, trs) = complete(&axioms, 200);
+        assert!(matches!(outcome, Outcome::Convergent));
+        assert_eq!(trs.rules().len(), 9);
+        let derived = Rule {
+            left: word("append(reverse(x), nil)"),
+            right: word("reverse(x)"),
+        };
+        assert!(trs.rules().iter().any(|r| r.is_variant(&derived)));
+    }
+}
@@ -0,0 +1,120 @@
+/*
+ * Peano arithmetic: zero, successor, addition, and multiplication, with the equations that
+ * define + and * by recursion on their second argument. x * s(y) = x * y + x copies x, so no
+ * Knuth-Bendix ordering can orient it left to right, but the lexicographic path ordering with
+ * the precedence 0 < s < + < * orients every equation as a definition. Completion then adds
+ * nothing: the system is orthogonal, and every ground word normalizes to a numeral s(...s(0)).
+ */
+use std::str::FromStr;
+
+use crate::lpo::Lpo;
+use crate::parse::ParseError;
+use crate::relation::Rule;
+use crate::trs::Trs;
+use crate::word::{self, Op};
+
+crate::theory! {
+    /// The operators in order of precedence.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum Peano {
+        Zero = "0",
+        Succ(1) = "s",
+        Add(2) = " + " [infix(1), commutative, associative],
+        Mul(2) = " * " [infix(2), commutative, associative],
+    }
+
+    pub fn axioms {
+        "x + 0" = "x",
+        "x + s(y)" = "s(x + y)",
+        "x * 0" = "0",
+        "x * s(y)" = "x * y + x",
+    }
+}
+use Peano::*;
+
+pub type Word = word::Word<String, Peano>;
+
+/// Parse a word as it's displayed, such as s(0) * (x + s(y)).
+impl FromStr for Word {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Word, ParseError> {
+        Peano::parse_word(s)
+    }
+}
+
+/// The ordering that orients the axioms as definitions.
+pub fn order() -> Lpo {
+    Lpo
+}
+
+/// The rules the axioms complete to, which are the axioms oriented left to right.
+pub fn rules() -> Trs<String, Peano> {
+    Trs::new(
+        axioms()
+            .into_iter()
+            .map(|a| Rule {
+                left: a.left,
+                right: a.right,
+            })
+            .collect(),
+    )
+}
+
+/// The numeral for n: s applied n times to 0.
+pub fn numeral(n: u64) -> Word {
+    let mut syms = vec![Op(Succ); n as usize];
+    syms.push(Op(Zero));
+    Word { syms }
+}
+
+/// The number w is the numeral for, if it is one.
+pub fn value(w: &Word) -> Option<u64> {
+    match w.syms.split_last() {
+        Some((Op(Zero), succs)) if succs.iter().all(|s| *s == Op(Succ)) => Some(succs.len() as u64),
+        _ => None,
+    }
+}
+
+/// Evaluate a ground word by normalizing it with the rules.
+pub fn eval(w: &Word) -> Option<u64> {
+    value(&rules().normalize(w))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    use crate::{Completion, Kbo, Outcome, ReductionOrder};
+
+    #[test]
+    fn completion() {
+        let mut c = Completion::with_order(&axioms(), order());
+        assert!(matches!(c.run(), Outcome::Convergent));
+        let rules = rules();
+        assert_eq!(c.rules().len(), rules.rules().len());
+        assert!(rules
+            .rules()
+            .iter()
+            .all(|r| c.rules().iter().any(|s| s.is_variant(r))));
+        // The Knuth-Bendix ordering would orient x * s(y) = x * y + x the other way.
+        let a = &axioms()[3];
+        assert_eq!(Kbo.compare(&a.left, &a.right), Some(Ordering::Less));
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(numeral(3).to_string(), "s(s(s(0)))");
+        assert_eq!(value(&numeral(3)), Some(3));
+        assert_eq!(value(&"s(x)".parse().unwrap()), None);
+        let w = format!("{} * {} + {}", numeral(2), numeral(3), numeral(1));
+        assert_eq!(eval(&w.parse().unwrap()), Some(7));
+        let w = &numeral(4).to_string();
+        assert_eq!(
+            eval(&format!("{} * ({} + {})", w, w, w).parse().unwrap()),
+            Some(32)
+        );
+        assert_eq!(eval(&"s(0) + x".parse().unwrap()), None);
+    }
+}
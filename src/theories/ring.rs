@@ -0,0 +1,141 @@
+/*
+ * Rings, over one signature with both the operators of Sum and those of Prod: an abelian group
+ * under +, a monoid under *, and * distributing over + on both sides. Sum and Prod words convert
+ * into Ring words with Word::map_ops(Ring::from), so the two can be mixed.
+ *
+ * Completion runs on the axioms but doesn't finish. x * (y + z) has x once and its expansion
+ * has it twice, so the Knuth-Bendix ordering orients distributivity the other way, to factor,
+ * and commutativity of + can't be oriented at all. Completion keeps generating rules around the
+ * two; a bounded run still finds enough of them to prove identities such as 0 * x = 0 and
+ * −(x * y) = x * −y.
+ */
+use std::ops;
+use std::str::FromStr;
+
+use crate::parse::ParseError;
+use crate::prod::Prod;
+use crate::sum::Sum;
+use crate::word;
+
+crate::theory! {
+    /// The operators in order of precedence, with negation highest as it has weight zero.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum Ring {
+        Zero = "0",
+        One = "1",
+        Add(2) = " + " [infix(1), commutative, associative],
+        Mul(2) = " * " [infix(2), associative],
+        Negate(1) = "−" | "-" [weight(0), prefix],
+    }
+
+    pub fn axioms {
+        "0 + x" = "x",
+        "−x + x" = "0",
+        "(x + y) + z" = "x + (y + z)",
+        "x + y" = "y + x",
+        "1 * x" = "x",
+        "x * 1" = "x",
+        "(x * y) * z" = "x * (y * z)",
+        "x * (y + z)" = "x * y + x * z",
+        "(x + y) * z" = "x * z + y * z",
+    }
+}
+use Ring::*;
+
+pub type Word = word::Word<String, Ring>;
+
+impl From<Sum> for Ring {
+    fn from(s: Sum) -> Ring {
+        match s {
+            Sum::Zero => Zero,
+            Sum::Add => Add,
+            Sum::Negate => Negate,
+        }
+    }
+}
+
+/// Panics on Inv, as elements of a ring needn't have inverses under *.
+impl From<Prod> for Ring {
+    fn from(p: Prod) -> Ring {
+        match p {
+            Prod::One => One,
+            Prod::Mul => Mul,
+            Prod::Inv => panic!("rings have no multiplicative inverse"),
+        }
+    }
+}
+
+impl ops::Add for &Word {
+    type Output = Word;
+    fn add(self, rhs: &Word) -> Word {
+        Word::op_owned(Add, vec![self.clone(), rhs.clone()])
+    }
+}
+
+impl ops::Mul for &Word {
+    type Output = Word;
+    fn mul(self, rhs: &Word) -> Word {
+        Word::op_owned(Mul, vec![self.clone(), rhs.clone()])
+    }
+}
+
+impl ops::Neg for &Word {
+    type Output = Word;
+    fn neg(self) -> Word {
+        Word::op_owned(Negate, vec![self.clone()])
+    }
+}
+
+/// Parse a ring word as it's displayed, such as x * (y + −1).
+impl FromStr for Word {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Word, ParseError> {
+        Ring::parse_word(s)
+    }
+}
+
+pub fn var<VF: Into<String>>(v: VF) -> Word {
+    Word::var(v)
+}
+
+pub fn zero() -> Word {
+    Word::op(Zero, &[])
+}
+
+pub fn one() -> Word {
+    Word::op(One, &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prod, sum, Completion, Outcome};
+
+    #[test]
+    fn mixed() {
+        let s = "(a + −b) + 0".parse::<sum::Word>().unwrap();
+        let p = "a * (b * 1)".parse::<prod::Word>().unwrap();
+        let w = &s.map_ops(|f| Ring::from(f.clone())) * &p.map_ops(|f| Ring::from(f.clone()));
+        assert_eq!(w.to_string(), "((a + −b) + 0) * (a * (b * 1))");
+        assert_eq!(w.to_string().parse::<Word>().unwrap(), w);
+    }
+
+    #[test]
+    fn completion() {
+        let axioms = axioms();
+        let mut c = Completion::new(&axioms);
+        c.max_steps = Some(800);
+        assert!(matches!(c.run(), Outcome::Limit));
+        assert!(c.pending().any(|e| *e == axioms[3]));
+        let trs = c.trs();
+        let joined = |l: &str, r: &str| {
+            trs.normalize(&l.parse().unwrap()) == trs.normalize(&r.parse().unwrap())
+        };
+        assert!(joined("0 * a", "0"));
+        assert!(joined("a * 0", "0"));
+        assert!(joined("−(a * b)", "a * −b"));
+        assert!(joined("−a * b", "a * −b"));
+        assert!(joined("a * −1", "−a"));
+    }
+}
@@ -0,0 +1,224 @@
+use crate::completion::{Completion, Equivalence};
+use crate::relation::{Axiom, Rule};
+use crate::trs::Trs;
+use crate::word::{Operator, Variable, Word};
+
+/// The role of an equation in a theory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EquationKind {
+    /// An ordinary equation of the theory, oriented by the ordering.
+    Axiom,
+    /// A definition of the operator at the root of the left side, always used left to right.
+    Definition,
+    /// An assumption made only while proving goals, and not part of the theory itself.
+    Hypothesis,
+}
+
+#[derive(Clone, Debug)]
+pub struct TaggedEquation<V: Variable, O: Operator> {
+    pub kind: EquationKind,
+    pub left: Word<V, O>,
+    pub right: Word<V, O>,
+}
+
+/// When definitions are expanded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Expansion {
+    /// Expand every defined operator in the axioms, hypotheses, and goals before completion, so
+    /// completion never sees them. The definitions must not be recursive.
+    #[default]
+    Eager,
+    /// Add the definitions to completion as rules oriented left to right, so defined operators
+    /// are only expanded where rewriting meets them.
+    Lazy,
+}
+
+/*
+ * A theory made of axioms, definitions, and hypotheses, structured the way specification
+ * languages present one. The axioms and definitions make up the theory; hypotheses are
+ * only assumed while proving goals, with their variables universally quantified like those of
+ * any equation.
+ */
+#[derive(Clone, Debug)]
+pub struct Theory<V: Variable, O: Operator> {
+    pub equations: Vec<TaggedEquation<V, O>>,
+    pub expansion: Expansion,
+    /// The limit on rules for each completion run.
+    pub max_rules: usize,
+}
+
+impl<V: Variable, O: Operator> Theory<V, O> {
+    pub fn new(equations: Vec<TaggedEquation<V, O>>) -> Theory<V, O> {
+        Theory {
+            equations,
+            expansion: Expansion::default(),
+            max_rules: 100,
+        }
+    }
+
+    fn of_kind(&self, kind: EquationKind) -> impl Iterator<Item = &TaggedEquation<V, O>> {
+        self.equations.iter().filter(move |e| e.kind == kind)
+    }
+
+    fn definitions(&self) -> Trs<V, O> {
+        Trs::new(
+            self.of_kind(EquationKind::Definition)
+                .map(|e| Rule {
+                    left: e.left.clone(),
+                    right: e.right.clone(),
+                })
+                .collect(),
+        )
+    }
+
+    // Expand the definitions in w, if they are expanded eagerly.
+    fn expand(&self, defs: &Trs<V, O>, w: &Word<V, O>) -> Word<V, O> {
+        match self.expansion {
+            Expansion::Eager => defs.normalize(w),
+            Expansion::Lazy => w.clone(),
+        }
+    }
+
+    /// Set up completion of the theory, including the hypotheses if asked.
+    pub fn completion(&self, with_hypotheses: bool) -> Completion<V, O> {
+        let defs = self.definitions();
+        let axioms: Vec<Axiom<V, O>> = self
+            .equations
+            .iter()
+            .filter(|e| match e.kind {
+                EquationKind::Axiom => true,
+                EquationKind::Hypothesis => with_hypotheses,
+                EquationKind::Definition => false,
+            })
+            .map(|e| Axiom {
+                left: self.expand(&defs, &e.left),
+                right: self.expand(&defs, &e.right),
+            })
+            .collect();
+        let mut c = Completion::new(&axioms);
+        c.max_rules = Some(self.max_rules);
+        if self.expansion == Expansion::Lazy {
+            for rule in defs.into_rules() {
+                c.add_fixed_rule(rule);
+            }
+        }
+        c
+    }
+
+    /*
+     * Decide whether the goal holds in the theory extended with the hypotheses, by completing it
+     * and comparing normal forms, or None if completion doesn't converge within max_rules rules
+     * and the rules it found don't join the goal.
+     */
+    pub fn prove(&self, goal: &Axiom<V, O>) -> Option<Equivalence> {
+        let defs = self.definitions();
+        let mut c = self.completion(true);
+        c.run();
+        c.equivalent(
+            &self.expand(&defs, &goal.left),
+            &self.expand(&defs, &goal.right),
+            None,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::word;
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    enum Nat {
+        Zero,
+        Succ,
+        Plus,
+        Double,
+    }
+    use Nat::*;
+
+    impl Operator for Nat {
+        fn min_weight() -> u64 {
+            1
+        }
+
+        fn arity(&self) -> usize {
+            match self {
+                Zero => 0,
+                Succ | Double => 1,
+                Plus => 2,
+            }
+        }
+
+        fn weight(&self) -> u64 {
+            1
+        }
+    }
+
+    type W = word::Word<String, Nat>;
+
+    fn tag(kind: EquationKind, left: W, right: W) -> TaggedEquation<String, Nat> {
+        TaggedEquation { kind, left, right }
+    }
+
+    // Addition on successor numerals, with double(x) = x + x as a definition. The definition's
+    // right side is heavier, so the ordering alone would orient it the other way.
+    fn theory() -> Theory<String, Nat> {
+        let (x, y) = (W::var("x"), W::var("y"));
+        let plus = |a: &W, b: &W| W::op(Plus, &[a.clone(), b.clone()]);
+        let s = |a: &W| W::op(Succ, std::slice::from_ref(a));
+        let zero = W::op(Zero, &[]);
+        Theory::new(vec![
+            tag(EquationKind::Axiom, plus(&zero, &y), y.clone()),
+            tag(EquationKind::Axiom, plus(&s(&x), &y), s(&plus(&x, &y))),
+            tag(
+                EquationKind::Definition,
+                W::op(Double, std::slice::from_ref(&x)),
+                plus(&x, &x),
+            ),
+        ])
+    }
+
+    #[test]
+    fn definitions() {
+        let zero = W::op(Zero, &[]);
+        let s = |a: &W| W::op(Succ, std::slice::from_ref(a));
+        let two = s(&s(&zero));
+        let four = s(&s(&two));
+        let goal = Axiom {
+            left: W::op(Double, std::slice::from_ref(&two)),
+            right: four.clone(),
+        };
+        for expansion in [Expansion::Eager, Expansion::Lazy] {
+            let mut t = theory();
+            t.expansion = expansion;
+            let eq = t.prove(&goal).unwrap();
+            assert!(eq.equal && eq.definitive, "{:?}", expansion);
+            let rules = t.completion(false).rules().to_vec();
+            let defined = rules.iter().any(|r| r.left.op_count(&Double) > 0);
+            assert_eq!(defined, expansion == Expansion::Lazy);
+        }
+    }
+
+    #[test]
+    fn hypotheses() {
+        let (a, b) = (W::var("a"), W::var("b"));
+        let zero = W::op(Zero, &[]);
+        let s = |a: &W| W::op(Succ, std::slice::from_ref(a));
+        let plus = |a: &W, b: &W| W::op(Plus, &[a.clone(), b.clone()]);
+        let mut t = theory();
+        let goal = Axiom {
+            left: plus(&s(&a), &zero),
+            right: s(&a),
+        };
+        // a + 0 = a doesn't follow from the axioms, but it does with the hypothesis.
+        assert!(!t.prove(&goal).unwrap().equal);
+        t.equations
+            .push(tag(EquationKind::Hypothesis, plus(&b, &zero), b.clone()));
+        assert!(t.prove(&goal).unwrap().equal);
+        assert!(t
+            .completion(false)
+            .rules()
+            .iter()
+            .all(|r| r.left.syms != plus(&b, &zero).syms));
+    }
+}
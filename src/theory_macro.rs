@@ -0,0 +1,262 @@
+/*
+ * Declare a theory's signature and axioms in one block:
+ *
+ *     theory! {
+ *         pub enum Lattice {
+ *             Meet(2) = " ∧ " [infix(2), commutative, associative],
+ *             Join(2) = " ∨ " [infix(1), commutative, associative],
+ *         }
+ *
+ *         pub fn axioms {
+ *             "x ∧ (x ∨ y)" = "x",
+ *             "x ∨ x ∧ y" = "x",
+ *         }
+ *     }
+ *
+ * The enum gets the derives an operator needs and the attributes written on it, and variants are
+ * in order of precedence as Ord has them. A variant's arity is in parentheses, 0 if they're left
+ * out, and its name after =, as Display writes it; parsing takes the name with its spaces
+ * trimmed, and any other names after it separated by |, such as "−" | "-". In brackets,
+ * optionally, go weight(n), which is 1 otherwise, commutative, associative, and prefix, postfix,
+ * infix(precedence), or infix(precedence, Left or Right) for its notation, which is a function's
+ * otherwise. Anything else in brackets is a compile error. min_weight is 1, so an operator of
+ * weight zero must be unary and come last.
+ *
+ * The enum also gets named, looking an operator up by name, and parse_word, parsing a word with
+ * the variants' names. Each fn block becomes a function returning its equations as axioms,
+ * parsed with parse_word; it panics if one doesn't parse.
+ */
+#[macro_export]
+macro_rules! theory {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $op:ident $(($arity:literal))? = $display:literal $(| $alias:literal)*
+                    $([ $($opt:tt)* ])?
+            ),* $(,)?
+        }
+        $( $(#[$fmeta:meta])* $fvis:vis fn $axioms:ident { $( $l:literal = $r:literal ),* $(,)? } )*
+    ) => {
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        $(#[$meta])*
+        $vis enum $name {
+            $($op),*
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                let name = match self {
+                    $($name::$op => $display),*
+                };
+                f.write_str(name)
+            }
+        }
+
+        impl $crate::Operator for $name {
+            fn min_weight() -> u64 {
+                1
+            }
+
+            fn arity(&self) -> usize {
+                match self {
+                    $($name::$op => 0 $(+ $arity)?),*
+                }
+            }
+
+            fn weight(&self) -> u64 {
+                match self {
+                    $($name::$op => {
+                        let mut _w = 1;
+                        $($crate::__theory_opts!(weight _w; $($opt)*);)?
+                        _w
+                    })*
+                }
+            }
+
+            fn is_commutative(&self) -> bool {
+                match self {
+                    $($name::$op => {
+                        let mut _c = false;
+                        $($crate::__theory_opts!(commutative _c; $($opt)*);)?
+                        _c
+                    })*
+                }
+            }
+
+            fn is_associative(&self) -> bool {
+                match self {
+                    $($name::$op => {
+                        let mut _a = false;
+                        $($crate::__theory_opts!(associative _a; $($opt)*);)?
+                        _a
+                    })*
+                }
+            }
+
+            fn notation(&self) -> $crate::Notation {
+                match self {
+                    $($name::$op => {
+                        let mut _n = $crate::Notation::FUNCTION;
+                        $($crate::__theory_opts!(notation _n; $($opt)*);)?
+                        _n
+                    })*
+                }
+            }
+        }
+
+        impl $name {
+            /// The operator with this name, ignoring the spaces around it in its display, or one
+            /// of its other names.
+            #[allow(dead_code)]
+            $vis fn named(name: &str) -> ::std::option::Option<$name> {
+                $(if name == $display.trim() $(|| name == $alias)* {
+                    return ::std::option::Option::Some($name::$op);
+                })*
+                ::std::option::Option::None
+            }
+
+            /// Parse a word as it's displayed, with these operators.
+            #[allow(dead_code)]
+            $vis fn parse_word(
+                s: &str,
+            ) -> ::std::result::Result<$crate::Word<String, $name>, $crate::ParseError> {
+                $crate::parse_term(s, &$name::named)
+            }
+        }
+
+        $(
+            $(#[$fmeta])*
+            $fvis fn $axioms() -> ::std::vec::Vec<$crate::Axiom<String, $name>> {
+                ::std::vec![$(
+                    $crate::Axiom {
+                        left: $name::parse_word($l).expect($l),
+                        right: $name::parse_word($r).expect($r),
+                    }
+                ),*]
+            }
+        )*
+    };
+}
+
+// Apply each of a variant's options that sets the property named first to the variable after it.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __theory_opts {
+    ($prop:ident $v:ident;) => {};
+    ($prop:ident $v:ident; $opt:ident $(($($arg:tt)*))? $(, $($rest:tt)*)?) => {
+        $crate::__theory_opt!($prop $v; $opt $(($($arg)*))?);
+        $crate::__theory_opts!($prop $v; $($($rest)*)?);
+    };
+}
+
+/*
+ * Every property's pass applies the options that set it and skips the others, and the weight
+ * pass, which every variant with options goes through, rejects anything that isn't an option.
+ */
+/// Misspelled options don't compile:
+///
+/// ```
+/// knuth_bendix::theory! {
+///     pub enum Semigroup {
+///         Mul(2) = " * " [infix(1), associative],
+///     }
+/// }
+/// ```
+///
+/// ```compile_fail
+/// knuth_bendix::theory! {
+///     pub enum Semigroup {
+///         Mul(2) = " * " [infix(1), asociative],
+///     }
+/// }
+/// ```
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __theory_opt {
+    (weight $v:ident; weight($n:literal)) => {
+        $v = $n;
+    };
+    (commutative $v:ident; commutative) => {
+        $v = true;
+    };
+    (associative $v:ident; associative) => {
+        $v = true;
+    };
+    (notation $v:ident; prefix) => {
+        $v = $crate::Notation::PREFIX;
+    };
+    (notation $v:ident; postfix) => {
+        $v = $crate::Notation::POSTFIX;
+    };
+    (notation $v:ident; infix($p:literal)) => {
+        $v = $crate::Notation::infix($p, $crate::Assoc::None);
+    };
+    (notation $v:ident; infix($p:literal, $assoc:ident)) => {
+        $v = $crate::Notation::infix($p, $crate::Assoc::$assoc);
+    };
+    (weight $v:ident; commutative) => {};
+    (weight $v:ident; associative) => {};
+    (weight $v:ident; prefix) => {};
+    (weight $v:ident; postfix) => {};
+    (weight $v:ident; infix($p:literal)) => {};
+    (weight $v:ident; infix($p:literal, $assoc:ident)) => {};
+    (weight $v:ident; $($other:tt)*) => {
+        ::std::compile_error!(::std::concat!(
+            "unknown theory option ",
+            ::std::stringify!($($other)*)
+        ));
+    };
+    ($prop:ident $v:ident; $($other:tt)*) => {};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Notation, Operator};
+
+    theory! {
+        /// Abelian groups written additively.
+        pub enum Additive {
+            Zero = "0",
+            Add(2) = " + " [infix(1, Left), commutative, associative],
+            Negate(1) = "−" | "-" [weight(0), prefix],
+        }
+
+        pub fn axioms {
+            "0 + x" = "x",
+            "−x + x" = "0",
+            "(x + y) + z" = "x + (y + z)",
+        }
+
+        /// The axiom left out of axioms.
+        fn commutativity {
+            "x + y" = "y + x",
+        }
+    }
+
+    #[test]
+    fn declared() {
+        use Additive::*;
+        assert_eq!(
+            [Zero, Add, Negate].map(|f| (f.arity(), f.weight())),
+            [(0, 1), (2, 1), (1, 0)]
+        );
+        assert!(Add.is_commutative() && Add.is_associative() && !Negate.is_associative());
+        assert_eq!(Zero.notation(), Notation::FUNCTION);
+        assert_eq!(Negate.notation(), Notation::PREFIX);
+        assert_eq!(Additive::named("+"), Some(Add));
+        assert_eq!(Additive::named("-"), Some(Negate));
+        assert_eq!(Additive::named("*"), None);
+
+        let axioms = axioms();
+        assert_eq!(axioms.len(), 3);
+        assert_eq!(axioms[1].to_string(), "−x + x = 0");
+        assert_eq!(commutativity()[0].to_string(), "x + y = y + x");
+        let w = Additive::parse_word("a + b + −c").unwrap();
+        assert_eq!(w.to_string(), "a + b + −c");
+
+        let mut c = crate::Completion::new(&axioms);
+        assert!(matches!(c.run(), crate::Outcome::Convergent));
+        assert_eq!(c.rules().len(), 10);
+    }
+}
@@ -0,0 +1,308 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::ops;
+
+use crate::kbo;
+use crate::order::ReductionOrder;
+use crate::word::{Op, Operator, Symbol, Var, Variable, Word, WordRef};
+
+/// An ordinal below ω^ω, written as a polynomial in ω with natural number coefficients. The
+/// coefficients are stored lowest power first, without trailing zeros.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ordinal {
+    coeffs: Vec<u64>,
+}
+
+impl Ordinal {
+    /// The ordinal with coefficient coeffs[i] on ω^i.
+    pub fn new(coeffs: Vec<u64>) -> Ordinal {
+        let mut o = Ordinal { coeffs };
+        while o.coeffs.last() == Some(&0) {
+            o.coeffs.pop();
+        }
+        o
+    }
+
+    pub fn finite(n: u64) -> Ordinal {
+        Ordinal::new(vec![n])
+    }
+
+    /// The ordinal ω^k.
+    pub fn omega_pow(k: usize) -> Ordinal {
+        let mut coeffs = vec![0; k + 1];
+        coeffs[k] = 1;
+        Ordinal::new(coeffs)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+}
+
+/*
+ * Ordinal weights are added with the natural (Hessenberg) sum, coefficient by coefficient. Unlike
+ * ordinal addition it is commutative and strictly monotone in both arguments, which is what the
+ * ordering needs.
+ */
+impl ops::Add for &Ordinal {
+    type Output = Ordinal;
+    fn add(self, rhs: &Ordinal) -> Ordinal {
+        let n = self.coeffs.len().max(rhs.coeffs.len());
+        let get = |o: &Ordinal, i: usize| o.coeffs.get(i).copied().unwrap_or(0);
+        Ordinal::new((0..n).map(|i| get(self, i) + get(rhs, i)).collect())
+    }
+}
+
+impl PartialOrd for Ordinal {
+    fn partial_cmp(&self, other: &Ordinal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ordinal {
+    fn cmp(&self, other: &Ordinal) -> Ordering {
+        self.coeffs
+            .len()
+            .cmp(&other.coeffs.len())
+            .then_with(|| self.coeffs.iter().rev().cmp(other.coeffs.iter().rev()))
+    }
+}
+
+/// The parameters of a transfinite Knuth-Bendix ordering.
+#[derive(Clone, Debug)]
+pub struct TkboParams<O: Operator> {
+    /// The weight of each operator. Operators without an entry have their finite
+    /// Operator::weight.
+    pub weights: BTreeMap<O, Ordinal>,
+    /// The weight of every variable, which must be positive.
+    pub var_weight: u64,
+    /// Operators from lowest to highest precedence. Listed operators are greater than unlisted
+    /// ones, which are compared by kbo::derived_precedence.
+    pub precedence: Vec<O>,
+}
+
+impl<O: Operator> Default for TkboParams<O> {
+    fn default() -> TkboParams<O> {
+        TkboParams {
+            weights: BTreeMap::new(),
+            var_weight: O::min_weight(),
+            precedence: Vec::new(),
+        }
+    }
+}
+
+/*
+ * The transfinite Knuth-Bendix ordering: the Knuth-Bendix ordering with operator weights taken
+ * from the ordinals below ω^ω instead of the natural numbers. An operator of weight ω outweighs
+ * any finite number of operators of finite weight, so it can be oriented above words that no
+ * fixed natural weight would bound, as happens when completion generates an infinite family of
+ * rules such as f(x) → g^n(x).
+ *
+ * As with natural weights, the ordering is only well founded when the parameters are
+ * admissible, which is_admissible checks.
+ */
+#[derive(Clone, Debug)]
+pub struct Tkbo<O: Operator> {
+    pub params: TkboParams<O>,
+}
+
+impl<O: Operator> Tkbo<O> {
+    pub fn new(params: TkboParams<O>) -> Tkbo<O> {
+        Tkbo { params }
+    }
+
+    pub fn op_weight(&self, f: &O) -> Ordinal {
+        self.params
+            .weights
+            .get(f)
+            .cloned()
+            .unwrap_or_else(|| Ordinal::finite(f.weight()))
+    }
+
+    pub fn weight<V: Variable>(&self, w: WordRef<'_, V, O>) -> Ordinal {
+        let var = Ordinal::finite(self.params.var_weight);
+        w.syms.iter().fold(Ordinal::default(), |acc, s| match s {
+            Var(_) => &acc + &var,
+            Op(f) => &acc + &self.op_weight(f),
+        })
+    }
+
+    /// Compare operators by precedence.
+    pub fn precedence(&self, f: &O, g: &O) -> Ordering {
+        let rank = |h: &O| self.params.precedence.iter().position(|p| p == h);
+        match (rank(f), rank(g)) {
+            (Some(i), Some(j)) => i.cmp(&j),
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (None, None) => kbo::derived_precedence(f, g, |h| self.op_weight(h).is_zero()),
+        }
+    }
+
+    /// Check that the parameters make the ordering well founded on words over the given
+    /// operators: the variable weight is positive, every constant weighs at least as much as a
+    /// variable, and a unary operator of weight zero has the highest precedence.
+    pub fn is_admissible(&self, ops: &[O]) -> bool {
+        let var = Ordinal::finite(self.params.var_weight);
+        self.params.var_weight > 0
+            && ops.iter().all(|f| {
+                let w = self.op_weight(f);
+                match f.arity() {
+                    0 => w >= var,
+                    1 if w.is_zero() => ops
+                        .iter()
+                        .all(|g| g == f || self.precedence(f, g) == Ordering::Greater),
+                    _ => true,
+                }
+            })
+    }
+
+    fn greater<V: Variable>(&self, s: WordRef<'_, V, O>, t: WordRef<'_, V, O>) -> bool {
+        let counts = |w: WordRef<'_, V, O>| {
+            let mut m: BTreeMap<V, usize> = BTreeMap::new();
+            for v in w.syms.iter().filter_map(Symbol::var) {
+                *m.entry(v.clone()).or_insert(0) += 1;
+            }
+            m
+        };
+        let cs = counts(s);
+        if counts(t)
+            .iter()
+            .any(|(v, n)| cs.get(v).copied().unwrap_or(0) < *n)
+        {
+            return false;
+        }
+        match self.weight(s).cmp(&self.weight(t)) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => match (s.syms.first(), t.syms.first()) {
+                // s = f^k(x) with weight-zero f, since the variable counts and weights agree.
+                (Some(Op(_)), Some(Var(_))) => true,
+                (Some(Op(f)), Some(Op(g))) => match self.precedence(f, g) {
+                    Ordering::Greater => true,
+                    Ordering::Less => false,
+                    Ordering::Equal => {
+                        for (a, b) in s.subwords().zip(t.subwords()) {
+                            if a.syms != b.syms {
+                                return self.greater(a, b);
+                            }
+                        }
+                        false
+                    }
+                },
+                _ => false,
+            },
+        }
+    }
+}
+
+impl<V: Variable, O: Operator> ReductionOrder<V, O> for Tkbo<O> {
+    fn compare(&self, s: &Word<V, O>, t: &Word<V, O>) -> Option<Ordering> {
+        if s.syms == t.syms {
+            Some(Ordering::Equal)
+        } else if self.greater(s.view(), t.view()) {
+            Some(Ordering::Greater)
+        } else if self.greater(t.view(), s.view()) {
+            Some(Ordering::Less)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::*;
+    use crate::{Kbo, ReductionOrder};
+
+    #[test]
+    fn ordinals() {
+        let omega = Ordinal::omega_pow(1);
+        let big = Ordinal::finite(1_000_000);
+        assert!(omega > big);
+        assert!(&omega + &Ordinal::finite(1) > omega);
+        assert!(Ordinal::omega_pow(2) > &omega + &omega);
+        assert_eq!(Ordinal::new(vec![3, 0, 0]), Ordinal::finite(3));
+        assert_eq!(&big + &omega, &omega + &big);
+    }
+
+    #[test]
+    fn omega_weight() {
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        enum Fg {
+            F,
+            G,
+        }
+
+        impl Operator for Fg {
+            fn min_weight() -> u64 {
+                1
+            }
+
+            fn arity(&self) -> usize {
+                1
+            }
+
+            fn weight(&self) -> u64 {
+                1
+            }
+        }
+
+        // No natural weight of F orients f(x) → g^n(x) for every n, but weight ω does.
+        let x = crate::word::Word::<String, Fg>::var("x");
+        let f = crate::word::Word::op(Fg::F, std::slice::from_ref(&x));
+        let tkbo = Tkbo::new(TkboParams {
+            weights: BTreeMap::from([(Fg::F, Ordinal::omega_pow(1))]),
+            ..TkboParams::default()
+        });
+        assert!(tkbo.is_admissible(&[Fg::F, Fg::G]));
+        let mut g = x.clone();
+        for n in 1..50 {
+            g = crate::word::Word::op(Fg::G, std::slice::from_ref(&g));
+            assert_eq!(tkbo.compare(&f, &g), Some(Ordering::Greater));
+            if n > 1 {
+                assert!(!Kbo.greater(&f, &g));
+            }
+        }
+    }
+
+    #[test]
+    fn matches_kbo_with_finite_weights() {
+        let tkbo = Tkbo::new(TkboParams::default());
+        let a = var("a");
+        let b = var("b");
+        let words = [
+            one(),
+            a.clone(),
+            inv(&a),
+            &a * &b,
+            &b * &a,
+            inv(&(&a * &b)),
+            inv(&b) * inv(&a),
+            (&a * &b) * &a,
+        ];
+        // Word's own comparison gives up on equal weights unless the variable counts agree
+        // exactly, so it's only checked where it has an answer.
+        for s in words.iter() {
+            for t in words.iter() {
+                if let Some(o) = s.partial_cmp(t) {
+                    assert_eq!(tkbo.compare(s, t), Some(o), "{} vs {}", s, t);
+                }
+            }
+        }
+        assert_eq!(tkbo.compare(&inv(&a), &one()), Some(Ordering::Greater));
+    }
+
+    #[test]
+    fn admissibility() {
+        let ops = [Prod::One, Prod::Mul, Prod::Inv];
+        // Inv has weight zero, so it must come last in the precedence.
+        let tkbo = Tkbo::new(TkboParams {
+            precedence: vec![Prod::Inv, Prod::One, Prod::Mul],
+            ..TkboParams::default()
+        });
+        assert!(!tkbo.is_admissible(&ops));
+        let tkbo = Tkbo::new(TkboParams::<Prod>::default());
+        assert!(tkbo.is_admissible(&ops));
+    }
+}
@@ -0,0 +1,426 @@
+/*
+ * The .trs format of the Termination Problems Data Base and the Confluence Problems collection:
+ *
+ *     (VAR x y)
+ *     (RULES
+ *       plus(0, y) -> y
+ *       plus(s(x), y) -> s(plus(x, y))
+ *     )
+ *     (COMMENT addition on unary naturals)
+ *
+ * Names listed in VAR are variables, and every other name is a function symbol whose arity is
+ * given by its uses, which have to agree. A constant is written a or a(). Symbols are declared in
+ * a Signature as their first use is read, arguments before the symbol applied to them, each
+ * weighing 1. COMMENT sections are skipped; a
+ * THEORY or STRATEGY section or a conditional rule changes what the rules mean, so reading one
+ * is an error rather than something to quietly drop.
+ */
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt::{self, Display, Write};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::relation::Rule;
+use crate::signature::{DynOp, Signature};
+use crate::trs::Trs;
+use crate::word::{Op, Operator, Var, Variable, Word, WordRef};
+
+#[derive(Debug)]
+pub enum TpdbError {
+    Io(io::Error),
+    /// Something the format doesn't allow, on this line from 1.
+    Syntax {
+        line: usize,
+        found: String,
+    },
+    /// The input ended inside a section.
+    End,
+    /// A symbol used with two arities.
+    Arity {
+        line: usize,
+        name: String,
+        first: usize,
+        second: usize,
+    },
+    /// A section or rule form this reader doesn't support.
+    Unsupported {
+        line: usize,
+        what: String,
+    },
+}
+
+impl Display for TpdbError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TpdbError::Io(e) => e.fmt(f),
+            TpdbError::Syntax { line, found } => write!(f, "line {}: unexpected {}", line, found),
+            TpdbError::End => write!(f, "unexpected end of input"),
+            TpdbError::Arity {
+                line,
+                name,
+                first,
+                second,
+            } => write!(
+                f,
+                "line {}: {} is used with {} arguments after {}",
+                line, name, second, first
+            ),
+            TpdbError::Unsupported { line, what } => {
+                write!(f, "line {}: {} is not supported", line, what)
+            }
+        }
+    }
+}
+
+impl Error for TpdbError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TpdbError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TpdbError {
+    fn from(e: io::Error) -> TpdbError {
+        TpdbError::Io(e)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    Comma,
+    Arrow,
+    Name(String),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Open => "(".fmt(f),
+            Token::Close => ")".fmt(f),
+            Token::Comma => ",".fmt(f),
+            Token::Arrow => "->".fmt(f),
+            Token::Name(n) => n.fmt(f),
+        }
+    }
+}
+
+// Split s into tokens with their line numbers. A quoted string is one name, for comments.
+fn tokenize(s: &str) -> Vec<(usize, Token)> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\n' => {
+                line += 1;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            '-' if chars.peek() == Some(&'>') => {
+                chars.next();
+                Token::Arrow
+            }
+            '"' => {
+                let mut name = String::new();
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        break;
+                    }
+                    line += usize::from(c == '\n');
+                    name.push(c);
+                }
+                Token::Name(name)
+            }
+            c => {
+                let mut name = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "(),\"".contains(c) || (c == '-' && name.ends_with('-'))
+                    {
+                        break;
+                    }
+                    name.push(c);
+                    chars.next();
+                }
+                Token::Name(name)
+            }
+        };
+        tokens.push((line, token));
+    }
+    tokens
+}
+
+struct Reader {
+    tokens: Vec<(usize, Token)>,
+    i: usize,
+    vars: BTreeSet<String>,
+    signature: Signature,
+}
+
+impl Reader {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.i).map(|t| &t.1)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.i)
+            .or(self.tokens.last())
+            .map_or(1, |t| t.0)
+    }
+
+    fn next(&mut self) -> Result<Token, TpdbError> {
+        let t = self.peek().cloned().ok_or(TpdbError::End)?;
+        self.i += 1;
+        Ok(t)
+    }
+
+    fn unexpected(&self, t: &Token) -> TpdbError {
+        TpdbError::Syntax {
+            line: self.tokens[self.i - 1].0,
+            found: t.to_string(),
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), TpdbError> {
+        match self.next()? {
+            t if t == token => Ok(()),
+            t => Err(self.unexpected(&t)),
+        }
+    }
+
+    fn term(&mut self) -> Result<Word<String, DynOp>, TpdbError> {
+        let line = self.line();
+        let name = match self.next()? {
+            Token::Name(n) => n,
+            t => return Err(self.unexpected(&t)),
+        };
+        let mut args = Vec::new();
+        if self.peek() == Some(&Token::Open) {
+            self.i += 1;
+            if self.peek() == Some(&Token::Close) {
+                self.i += 1;
+            } else {
+                loop {
+                    args.push(self.term()?);
+                    match self.next()? {
+                        Token::Comma => {}
+                        Token::Close => break,
+                        t => return Err(self.unexpected(&t)),
+                    }
+                }
+            }
+        } else if self.vars.contains(&name) {
+            return Ok(Word::var(name));
+        }
+        let f = match self.signature.get(&name) {
+            Some(f) if f.arity != args.len() => {
+                return Err(TpdbError::Arity {
+                    line,
+                    name,
+                    first: f.arity,
+                    second: args.len(),
+                })
+            }
+            Some(f) => f.clone(),
+            None => self
+                .signature
+                .declare(&name, args.len(), 1)
+                .expect("an undeclared name can be declared"),
+        };
+        Ok(Word::op_owned(f, args))
+    }
+
+    // Skip the rest of a section, up to and including its closing parenthesis.
+    fn skip(&mut self) -> Result<(), TpdbError> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next()? {
+                Token::Open => depth += 1,
+                Token::Close => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn section(&mut self, rules: &mut Vec<Rule<String, DynOp>>) -> Result<(), TpdbError> {
+        self.expect(Token::Open)?;
+        let line = self.line();
+        match self.next()? {
+            Token::Name(k) if k == "VAR" => loop {
+                match self.next()? {
+                    Token::Name(v) => {
+                        self.vars.insert(v);
+                    }
+                    Token::Close => return Ok(()),
+                    t => return Err(self.unexpected(&t)),
+                }
+            },
+            Token::Name(k) if k == "RULES" => loop {
+                if self.peek() == Some(&Token::Close) {
+                    self.i += 1;
+                    return Ok(());
+                }
+                let left = self.term()?;
+                self.expect(Token::Arrow)?;
+                let right = self.term()?;
+                if self.peek() == Some(&Token::Name("|".to_string())) {
+                    return Err(TpdbError::Unsupported {
+                        line: self.line(),
+                        what: "a conditional rule".to_string(),
+                    });
+                }
+                rules.push(Rule { left, right });
+            },
+            Token::Name(k) if k == "COMMENT" => self.skip(),
+            Token::Name(k) => Err(TpdbError::Unsupported {
+                line,
+                what: format!("a {} section", k),
+            }),
+            t => Err(self.unexpected(&t)),
+        }
+    }
+}
+
+/// Read a system in the .trs format, with the signature its symbols are declared in.
+pub fn parse(s: &str) -> Result<(Signature, Trs<String, DynOp>), TpdbError> {
+    let mut reader = Reader {
+        tokens: tokenize(s),
+        i: 0,
+        vars: BTreeSet::new(),
+        signature: Signature::new(),
+    };
+    let mut rules = Vec::new();
+    while reader.peek().is_some() {
+        reader.section(&mut rules)?;
+    }
+    Ok((reader.signature, Trs::new(rules)))
+}
+
+pub fn from_file(path: impl AsRef<Path>) -> Result<(Signature, Trs<String, DynOp>), TpdbError> {
+    parse(&fs::read_to_string(path)?)
+}
+
+// Write w in prefix form, with each operator as its Display without surrounding spaces, since
+// the format has no infix operators.
+fn write_word<V: Variable + Display, O: Operator + Display>(
+    out: &mut String,
+    w: WordRef<'_, V, O>,
+) -> fmt::Result {
+    match w.syms.first() {
+        Some(Var(v)) => write!(out, "{}", v),
+        Some(Op(f)) => {
+            write!(out, "{}", f.to_string().trim())?;
+            let mut args = w.subwords().peekable();
+            if args.peek().is_some() {
+                out.push('(');
+                for (i, a) in args.enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    write_word(out, a)?;
+                }
+                out.push(')');
+            }
+            Ok(())
+        }
+        None => Err(fmt::Error),
+    }
+}
+
+/// Write trs in the .trs format, declaring every variable its rules use.
+pub fn to_string<V: Variable + Display, O: Operator + Display>(trs: &Trs<V, O>) -> String {
+    let vars: BTreeSet<V> = trs
+        .rules()
+        .iter()
+        .flat_map(|r| r.left.vars().into_iter().chain(r.right.vars()))
+        .collect();
+    let mut out = String::from("(VAR");
+    for v in &vars {
+        write!(out, " {}", v).unwrap();
+    }
+    out.push_str(")\n(RULES\n");
+    for r in trs.rules() {
+        out.push_str("  ");
+        write_word(&mut out, r.left.view()).unwrap();
+        out.push_str(" -> ");
+        write_word(&mut out, r.right.view()).unwrap();
+        out.push('\n');
+    }
+    out.push_str(")\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::{inv, one, var};
+
+    const PLUS: &str = "
+        (VAR x y)
+        (RULES
+          plus(0, y) -> y
+          plus(s(x), y) -> s(plus(x, y))
+          double(x) -> plus(x,x)
+        )
+        (COMMENT \"unary (naturals)\" doubling)
+    ";
+
+    #[test]
+    fn read_and_write() {
+        let (sig, trs) = parse(PLUS).unwrap();
+        let names: Vec<&str> = sig.ops().iter().map(|f| &*f.name).collect();
+        assert_eq!(names, ["0", "plus", "s", "double"]);
+        assert_eq!(trs.rules().len(), 3);
+        let four = crate::term!(sig, s(s(s(s(0)))));
+        assert_eq!(trs.normalize(&crate::term!(sig, double(s(s(0))))), four);
+
+        let written = to_string(&trs);
+        assert_eq!(
+            written,
+            "(VAR x y)\n(RULES\n  plus(0, y) -> y\n  plus(s(x), y) -> s(plus(x, y))\n  \
+             double(x) -> plus(x, x)\n)\n"
+        );
+        let (_, again) = parse(&written).unwrap();
+        assert_eq!(again.rules(), trs.rules());
+
+        // Operators are written by name, even infix ones.
+        let x = var("x");
+        let groups = Trs::new(vec![Rule {
+            left: inv(&x) * &x,
+            right: one(),
+        }]);
+        assert_eq!(
+            to_string(&groups),
+            "(VAR x)\n(RULES\n  *(⁻¹(x), x) -> 1\n)\n"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = |s: &str| parse(s).unwrap_err().to_string();
+        assert_eq!(
+            err("(VAR x)\n(RULES\n f(x) -> f(x, x)\n)"),
+            "line 3: f is used with 2 arguments after 1"
+        );
+        assert_eq!(
+            err("(RULES f(x) -> x | x == a)"),
+            "line 1: a conditional rule is not supported"
+        );
+        assert_eq!(
+            err("(VAR x)\n(STRATEGY INNERMOST)"),
+            "line 2: a STRATEGY section is not supported"
+        );
+        assert_eq!(err("(RULES a -> )"), "line 1: unexpected )");
+        assert_eq!(err("(RULES a -> b"), "unexpected end of input");
+    }
+}
@@ -0,0 +1,213 @@
+/*
+ * Export to TPTP, the input language of first-order provers such as E and Vampire, so a theory
+ * or the rules completion found for it can be checked by one of them. Each equation becomes a
+ * unit clause,
+ *
+ *     cnf(eq0, axiom, '*'(X0, '*'(X1, X2)) = '*'('*'(X0, X1), X2)).
+ *
+ * and a goal a universally quantified conjecture. Variables are renamed X0, X1, ... in the order
+ * they appear in each clause. An operator is written by its Display without surrounding spaces,
+ * as is when that's a lower-case identifier and quoted otherwise. TPTP only allows printable
+ * ASCII, so an operator whose name has anything else, like ⁻¹, is renamed f0, f1, ..., and the
+ * renaming listed in a comment at the top.
+ */
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Write};
+
+use crate::relation::Equation;
+use crate::word::{Op, Operator, Var, Variable, WordRef};
+
+// The TPTP name of each operator used.
+struct Names<O> {
+    names: BTreeMap<O, String>,
+    renamed: Vec<(String, String)>,
+}
+
+fn is_lower_word(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_lowercase())
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl<O: Operator + Display> Names<O> {
+    fn new<'a, V: Variable + 'a>(words: impl IntoIterator<Item = WordRef<'a, V, O>>) -> Names<O>
+    where
+        O: 'a,
+    {
+        let ops: BTreeSet<&O> = words
+            .into_iter()
+            .flat_map(|w| w.syms)
+            .filter_map(|s| match s {
+                Op(o) => Some(o),
+                Var(_) => None,
+            })
+            .collect();
+        let displayed: Vec<(&O, String)> = ops
+            .into_iter()
+            .map(|o| (o, o.to_string().trim().to_string()))
+            .collect();
+        let mut used: BTreeSet<String> = displayed.iter().map(|(_, s)| s.clone()).collect();
+        let mut names = Names {
+            names: BTreeMap::new(),
+            renamed: Vec::new(),
+        };
+        let mut fresh = 0;
+        for (o, s) in displayed {
+            let name = if is_lower_word(&s) {
+                s
+            } else if !s.is_empty() && s.chars().all(|c| (' '..='~').contains(&c)) {
+                format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+            } else {
+                let name = loop {
+                    let name = format!("f{}", fresh);
+                    fresh += 1;
+                    if used.insert(name.clone()) {
+                        break name;
+                    }
+                };
+                names.renamed.push((name.clone(), s));
+                name
+            };
+            names.names.insert(o.clone(), name);
+        }
+        names
+    }
+
+    fn write<V: Variable>(&self, out: &mut String, w: WordRef<'_, V, O>, vars: &[&V]) {
+        match &w.syms[0] {
+            Var(v) => {
+                let i = vars.iter().position(|u| *u == v).unwrap();
+                write!(out, "X{}", i).unwrap();
+            }
+            Op(o) => {
+                out.push_str(&self.names[o]);
+                let mut args = w.subwords().peekable();
+                if args.peek().is_some() {
+                    out.push('(');
+                    for (i, a) in args.enumerate() {
+                        if i > 0 {
+                            out.push_str(", ");
+                        }
+                        self.write(out, a, vars);
+                    }
+                    out.push(')');
+                }
+            }
+        }
+    }
+
+    fn equation<V: Variable>(&self, out: &mut String, eq: &Equation<V, O>) -> usize {
+        let mut vars: Vec<&V> = Vec::new();
+        for s in eq.left.syms.iter().chain(&eq.right.syms) {
+            if let Var(v) = s {
+                if !vars.contains(&v) {
+                    vars.push(v);
+                }
+            }
+        }
+        self.write(out, eq.left.view(), &vars);
+        out.push_str(" = ");
+        self.write(out, eq.right.view(), &vars);
+        vars.len()
+    }
+}
+
+fn problem<V: Variable, O: Operator + Display>(
+    axioms: &[Equation<V, O>],
+    goal: Option<&Equation<V, O>>,
+) -> String {
+    let names = Names::new(
+        axioms
+            .iter()
+            .chain(goal)
+            .flat_map(|eq| [eq.left.view(), eq.right.view()]),
+    );
+    let mut out = String::new();
+    for (name, s) in &names.renamed {
+        writeln!(out, "% {} is {}", name, s).unwrap();
+    }
+    for (i, eq) in axioms.iter().enumerate() {
+        write!(out, "cnf(eq{}, axiom, ", i).unwrap();
+        names.equation(&mut out, eq);
+        out.push_str(").\n");
+    }
+    if let Some(goal) = goal {
+        let mut body = String::new();
+        let n = names.equation(&mut body, goal);
+        out.push_str("fof(goal, conjecture, ");
+        if n > 0 {
+            let vars: Vec<String> = (0..n).map(|i| format!("X{}", i)).collect();
+            write!(out, "![{}] : ({})", vars.join(", "), body).unwrap();
+        } else {
+            out.push_str(&body);
+        }
+        out.push_str(").\n");
+    }
+    out
+}
+
+/// Write equations, or rules from completion, as TPTP axioms.
+pub fn export<V: Variable, O: Operator + Display, E: Clone + Into<Equation<V, O>>>(
+    equations: &[E],
+) -> String {
+    let axioms: Vec<Equation<V, O>> = equations.iter().cloned().map(Into::into).collect();
+    problem(&axioms, None)
+}
+
+/// Write a TPTP problem: the equations as axioms, and the goal as a conjecture to prove from them.
+pub fn export_problem<V: Variable, O: Operator + Display, E: Clone + Into<Equation<V, O>>>(
+    equations: &[E],
+    goal: &Equation<V, O>,
+) -> String {
+    let axioms: Vec<Equation<V, O>> = equations.iter().cloned().map(Into::into).collect();
+    problem(&axioms, Some(goal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::{self, Word};
+    use crate::{knuth_bendix, Signature};
+
+    #[test]
+    fn export_groups() {
+        let groups = problems::groups();
+        let rules = knuth_bendix(&groups.axioms).unwrap();
+        let tptp = export(&rules);
+        assert!(tptp.starts_with("% f0 is ·\n% f1 is ⁻\n"), "{}", tptp);
+        assert_eq!(tptp.lines().filter(|l| l.starts_with("cnf(")).count(), 10);
+        assert!(tptp.contains(", axiom, f0(f1(X0), X0) = e).\n"), "{}", tptp);
+
+        // x · x⁻ = e, from the operators of the axioms.
+        let left = &groups.axioms[1].left;
+        let (mul, inv) = (&left.syms[0], &left.syms[1]);
+        let x = crate::word::Var("x".to_string());
+        let goal = Equation {
+            left: Word {
+                syms: vec![mul.clone(), x.clone(), inv.clone(), x],
+            },
+            right: groups.axioms[1].right.clone(),
+        };
+        let tptp = export_problem(&groups.axioms, &goal);
+        assert!(tptp.ends_with("fof(goal, conjecture, ![X0] : (f0(X0, f1(X0)) = e)).\n"));
+    }
+
+    #[test]
+    fn names() {
+        let mut sig = Signature::new();
+        sig.declare("plus", 2, 1).unwrap();
+        sig.declare("Zero", 0, 1).unwrap();
+        sig.declare("'", 0, 1).unwrap();
+        let quote = crate::Word::op_owned(sig.get("'").unwrap().clone(), vec![]);
+        let eq = Equation {
+            left: crate::term!(sig, plus(x, plus(y, Zero))),
+            right: crate::Word::op_owned(
+                sig.get("plus").unwrap().clone(),
+                vec![crate::Word::var("y".to_string()), quote],
+            ),
+        };
+        assert_eq!(
+            export(&[eq]),
+            "cnf(eq0, axiom, plus(X0, plus(X1, 'Zero')) = plus(X1, '\\'')).\n"
+        );
+    }
+}
@@ -0,0 +1,1123 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt::{self, Display};
+use std::sync::{Mutex, OnceLock};
+
+use crate::arena::TermArena;
+use crate::cache::{CacheStats, NormalFormCache};
+use crate::discrimination::DiscriminationTree;
+use crate::flatterm::Flatterm;
+use crate::order::ReductionOrder;
+use crate::relation::Rule;
+use crate::word::{FreshVars, Op, Operator, Position, VarGen, Variable, Word};
+
+/// A critical pair: two words that the same peak word rewrites to in one step each, by two
+/// overlapping rule applications.
+#[derive(Clone, Debug)]
+pub struct CriticalPair<V: Variable, O: Operator> {
+    pub peak: Word<V, O>,
+    pub left: Word<V, O>,
+    pub right: Word<V, O>,
+}
+
+/// The result of checking a system for local confluence.
+#[derive(Clone, Debug)]
+pub struct ConfluenceReport<V: Variable, O: Operator> {
+    /// The number of critical pairs that were checked.
+    pub n_critical_pairs: usize,
+    /// The critical pairs whose two sides are not joinable.
+    pub counterexamples: Vec<CriticalPair<V, O>>,
+}
+
+impl<V: Variable, O: Operator> ConfluenceReport<V, O> {
+    pub fn is_locally_confluent(&self) -> bool {
+        self.counterexamples.is_empty()
+    }
+}
+
+/*
+ * A critical pair that shows a terminating system isn't confluent: the peak rewrites by rule
+ * outer at the root to the pair's left side and by rule inner inside to its right side, and the
+ * two sides have different normal forms.
+ */
+#[derive(Clone, Debug)]
+pub struct Counterexample<V: Variable, O: Operator> {
+    pub pair: CriticalPair<V, O>,
+    pub outer: RuleId,
+    pub inner: RuleId,
+    pub left_normal: Word<V, O>,
+    pub right_normal: Word<V, O>,
+}
+
+impl<V: Variable, O: Operator> Display for Counterexample<V, O>
+where
+    Word<V, O>: Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ← {} → {} by rules {} and {}, with normal forms {} and {}",
+            self.pair.left,
+            self.pair.peak,
+            self.pair.right,
+            self.outer,
+            self.inner,
+            self.left_normal,
+            self.right_normal
+        )
+    }
+}
+
+/// Whether a system is confluent, as Trs::is_confluent decides it, and why not.
+#[derive(Clone, Debug)]
+pub enum ConfluenceResult<V: Variable, O: Operator> {
+    /// Every rule decreases in the ordering, so the system terminates, and every one of this many
+    /// critical pairs joins, so by Newman's lemma it's confluent.
+    Confluent { n_critical_pairs: usize },
+    /// This rule doesn't decrease in the ordering, so termination isn't shown and confluence
+    /// wasn't checked.
+    NotDecreasing(Rule<V, O>),
+    /// The system terminates but these critical pairs don't join, so it isn't confluent.
+    NotConfluent(Vec<Counterexample<V, O>>),
+}
+
+impl<V: Variable, O: Operator> ConfluenceResult<V, O> {
+    pub fn is_confluent(&self) -> bool {
+        matches!(self, ConfluenceResult::Confluent { .. })
+    }
+}
+
+impl<V: Variable, O: Operator> Display for ConfluenceResult<V, O>
+where
+    Word<V, O>: Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfluenceResult::Confluent { n_critical_pairs } => write!(
+                f,
+                "confluent: terminating, and all {} critical pairs join",
+                n_critical_pairs
+            ),
+            ConfluenceResult::NotDecreasing(rule) => write!(
+                f,
+                "termination not shown: {} doesn't decrease in the ordering",
+                rule
+            ),
+            ConfluenceResult::NotConfluent(pairs) => {
+                write!(
+                    f,
+                    "not confluent: {} critical pairs don't join",
+                    pairs.len()
+                )?;
+                for c in pairs {
+                    write!(f, "\n  {}", c)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Whether two systems present the same equational theory, as Trs::equivalent_to decides it.
+#[derive(Clone, Debug)]
+pub enum EquivalenceResult<V: Variable, O: Operator> {
+    /// The sides of every rule of each system have the same normal form in the other, so each
+    /// system's equations follow from the other's.
+    Equivalent,
+    /// The sides of this rule of one system have different normal forms in the other, which is
+    /// convergent, so the rule's equation doesn't hold there.
+    NotEquivalent(Rule<V, O>),
+    /// Neither was shown: a system doesn't decrease in its ordering, so normal forms may not
+    /// exist, or a rule's sides have different normal forms in a system that isn't confluent.
+    Unknown,
+}
+
+impl<V: Variable, O: Operator> EquivalenceResult<V, O> {
+    pub fn is_equivalent(&self) -> bool {
+        matches!(self, EquivalenceResult::Equivalent)
+    }
+}
+
+/// The index of a rule in a list of rules.
+pub type RuleId = usize;
+
+/// Two rules whose left sides overlap: inner's unifies with the subword of outer's at position,
+/// which isn't a variable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Overlap {
+    pub outer: RuleId,
+    pub inner: RuleId,
+    pub position: Position,
+}
+
+/*
+ * The syntactic properties of a system, as Trs::analysis finds them, each with the rules or
+ * overlaps that keep it from holding. An orthogonal system, left-linear with no overlaps, is
+ * confluent whether or not it terminates.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Analysis {
+    /// The rules whose left sides have a variable more than once.
+    pub left_nonlinear: Vec<RuleId>,
+    /// The rules whose right sides have a variable more than once.
+    pub right_nonlinear: Vec<RuleId>,
+    /// The rules whose right sides have a variable more times than their left sides.
+    pub duplicating: Vec<RuleId>,
+    pub overlaps: Vec<Overlap>,
+}
+
+impl Analysis {
+    pub fn is_left_linear(&self) -> bool {
+        self.left_nonlinear.is_empty()
+    }
+
+    pub fn is_right_linear(&self) -> bool {
+        self.right_nonlinear.is_empty()
+    }
+
+    pub fn is_duplicating(&self) -> bool {
+        !self.duplicating.is_empty()
+    }
+
+    pub fn is_non_overlapping(&self) -> bool {
+        self.overlaps.is_empty()
+    }
+
+    pub fn is_orthogonal(&self) -> bool {
+        self.is_left_linear() && self.is_non_overlapping()
+    }
+}
+
+// The number of times each variable occurs in w.
+fn var_counts<V: Variable, O: Operator>(w: &Word<V, O>) -> BTreeMap<&V, usize> {
+    let mut counts = BTreeMap::new();
+    for v in w.syms.iter().filter_map(|s| s.var()) {
+        *counts.entry(v).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// One step of a derivation: the rule applied, the position it was applied at, and the word that
+/// resulted.
+#[derive(Clone, Debug)]
+pub struct RewriteStep<V: Variable, O: Operator> {
+    pub rule: RuleId,
+    pub position: Position,
+    pub word: Word<V, O>,
+}
+
+/*
+ * Rename the variables of a rule so that none of them are in avoid.
+ */
+fn rename_apart<V: Variable, O: Operator>(rule: &Rule<V, O>, avoid: &BTreeSet<V>) -> Rule<V, O> {
+    let mut gen = FreshVars::new();
+    gen.avoid_word(&rule.left);
+    gen.avoid_word(&rule.right);
+    avoid.iter().for_each(|v| gen.avoid(v.clone()));
+    let vmap: BTreeMap<V, Word<V, O>> = rule
+        .left
+        .vars()
+        .union(&rule.right.vars())
+        .filter(|v| avoid.contains(v))
+        .map(|v| (v.clone(), Word::var(gen.fresh())))
+        .collect();
+    Rule {
+        left: rule.left.subst(&vmap),
+        right: rule.right.subst(&vmap),
+    }
+}
+
+/*
+ * Compute the critical pairs from overlapping inner's left side onto the subwords of outer's left
+ * side. same says whether the two are the same rule, in which case the root overlap is skipped.
+ */
+pub(crate) fn overlaps<V: Variable, O: Operator>(
+    outer: &Rule<V, O>,
+    inner: &Rule<V, O>,
+    same: bool,
+) -> Vec<CriticalPair<V, O>> {
+    overlaps_at(outer, inner, same, 0..outer.left.syms.len())
+}
+
+/// The same as overlaps, but only trying the subwords at the given indices of outer's left side,
+/// in order.
+pub(crate) fn overlaps_at<V: Variable, O: Operator>(
+    outer: &Rule<V, O>,
+    inner: &Rule<V, O>,
+    same: bool,
+    indices: impl IntoIterator<Item = usize>,
+) -> Vec<CriticalPair<V, O>> {
+    let outer_vars = outer
+        .left
+        .vars()
+        .union(&outer.right.vars())
+        .cloned()
+        .collect();
+    let inner = rename_apart(inner, &outer_vars);
+    let mut out = Vec::new();
+    for p in indices {
+        // Overlaps at variables are never critical, and overlapping a rule with itself at the
+        // root just gives the same rewrite twice.
+        if outer.left.syms[p].var().is_some() || (same && p == 0) {
+            continue;
+        }
+        // Only a subword with the same root as inner's left side can unify with it.
+        if let (Op(f), Some(Op(g))) = (&outer.left.syms[p], inner.left.syms.first()) {
+            if f != g {
+                continue;
+            }
+        }
+        let sw = Word {
+            syms: outer.left.syms[p..outer.left.subword_end(p)].to_vec(),
+        };
+        if let Some(vmap) = sw.mgu(&inner.left) {
+            let peak = outer.left.subst(&vmap);
+            // The substitution moves the overlap to the right by the growth of every variable
+            // before it.
+            let q = outer.left.syms[..p]
+                .iter()
+                .map(|s| {
+                    s.var()
+                        .and_then(|v| vmap.get(v))
+                        .map_or(1, |w| w.syms.len())
+                })
+                .sum();
+            out.push(CriticalPair {
+                left: outer.right.subst(&vmap),
+                right: peak.splice(q, &inner.right.subst(&vmap)),
+                peak,
+            });
+        }
+    }
+    out
+}
+
+/// A term rewriting system: a collection of rules, each applied left to right.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trs<V: Variable, O: Operator> {
+    rules: Vec<Rule<V, O>>,
+    // An index of the rules, built when it's first needed after they change.
+    #[cfg_attr(feature = "serde", serde(skip, default = "OnceLock::new"))]
+    index: OnceLock<DiscriminationTree<O>>,
+    /// The normal forms normalize has found, if caching them is turned on with with_cache.
+    #[cfg_attr(feature = "serde", serde(skip, default = "Option::default"))]
+    cache: Option<Mutex<NormalFormCache<V, O>>>,
+}
+
+impl<V: Variable, O: Operator> Clone for Trs<V, O> {
+    fn clone(&self) -> Self {
+        Trs {
+            rules: self.rules.clone(),
+            index: self.index.clone(),
+            cache: self
+                .cache
+                .as_ref()
+                .map(|c| Mutex::new(c.lock().unwrap().clone())),
+        }
+    }
+}
+
+impl<V: Variable, O: Operator> Trs<V, O> {
+    pub fn new(rules: Vec<Rule<V, O>>) -> Trs<V, O> {
+        Trs {
+            rules,
+            index: OnceLock::new(),
+            cache: None,
+        }
+    }
+
+    pub fn rules(&self) -> &[Rule<V, O>] {
+        &self.rules
+    }
+
+    /// The rules to change, which drops the index and empties the cache, if there is one.
+    pub fn rules_mut(&mut self) -> &mut Vec<Rule<V, O>> {
+        self.index.take();
+        if let Some(cache) = &mut self.cache {
+            cache.get_mut().unwrap().clear();
+        }
+        &mut self.rules
+    }
+
+    pub fn into_rules(self) -> Vec<Rule<V, O>> {
+        self.rules
+    }
+
+    // The discrimination tree of the left sides, which finds the rules that might apply to a
+    // subword.
+    fn index(&self) -> &DiscriminationTree<O> {
+        self.index
+            .get_or_init(|| DiscriminationTree::new(&self.rules))
+    }
+
+    /// The index of the rules, for a Completion to keep using after normalizing with them.
+    pub(crate) fn into_index(self) -> DiscriminationTree<O> {
+        let Trs { rules, index, .. } = self;
+        index
+            .into_inner()
+            .unwrap_or_else(|| DiscriminationTree::new(&rules))
+    }
+
+    /*
+     * Have normalize remember the normal forms it finds and reuse them for the same word, or one
+     * that differs only in the names of its variables. Only whole words are looked up: a lookup
+     * costs about as much as a pass over the word, so looking up its subwords too costs more than
+     * normalizing them again, even when each word has the ones before it as subwords. Completion
+     * doesn't use a cache, since its rules change with every rule it adds. The cache is emptied
+     * when the rules are changed with rules_mut.
+     */
+    pub fn with_cache(mut self) -> Trs<V, O> {
+        self.cache = Some(Mutex::new(NormalFormCache::new()));
+        self
+    }
+
+    /// How much normalize has used the cache, if it has one.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|c| c.lock().unwrap().stats())
+    }
+
+    /// Return every word reachable from w in exactly one rewrite step, using any rule at any
+    /// position.
+    pub fn rewrites(&self, w: &Word<V, O>) -> Vec<Word<V, O>> {
+        let mut out = Vec::new();
+        for i in 0..w.syms.len() {
+            let candidates = self.index().candidates(w, i);
+            if candidates.is_empty() {
+                continue;
+            }
+            let sw = Word {
+                syms: w.syms[i..w.subword_end(i)].to_vec(),
+            };
+            for id in candidates {
+                let rule = &self.rules[id];
+                if let Some(vmap) = rule.left.unify(&sw) {
+                    out.push(w.splice(i, &rule.right.subst(&vmap)));
+                }
+            }
+        }
+        out
+    }
+
+    // The index of the leftmost outermost redex in w, the rule that applies there, and the word
+    // the redex rewrites to.
+    fn redex(&self, w: &Word<V, O>) -> Option<(usize, RuleId, Word<V, O>)> {
+        for i in 0..w.syms.len() {
+            let candidates = self.index().candidates(w, i);
+            if candidates.is_empty() {
+                continue;
+            }
+            let sw = Word {
+                syms: w.syms[i..w.subword_end(i)].to_vec(),
+            };
+            for id in candidates {
+                let rule = &self.rules[id];
+                if let Some(vmap) = rule.left.unify(&sw) {
+                    return Some((i, id, rule.right.subst(&vmap)));
+                }
+            }
+        }
+        None
+    }
+
+    /// Rewrite w once, at the leftmost outermost position where some rule applies, or return None
+    /// if w is in normal form.
+    pub fn rewrite_step(&self, w: &Word<V, O>) -> Option<Word<V, O>> {
+        self.redex(w).map(|(i, _, r)| w.splice(i, &r))
+    }
+
+    /// Rewrite w to normal form one leftmost outermost step at a time, returning each step. This
+    /// only terminates if the system does.
+    pub fn derivation(&self, w: &Word<V, O>) -> Vec<RewriteStep<V, O>> {
+        let mut steps: Vec<RewriteStep<V, O>> = Vec::new();
+        loop {
+            let w = steps.last().map_or(w, |s| &s.word);
+            let Some((i, rule, r)) = self.redex(w) else {
+                return steps;
+            };
+            let step = RewriteStep {
+                rule,
+                position: w.position_of(i),
+                word: w.splice(i, &r),
+            };
+            steps.push(step);
+        }
+    }
+
+    /// Rewrite w until no rule applies. This only terminates if the system does.
+    pub fn normalize(&self, w: &Word<V, O>) -> Word<V, O> {
+        let Some(cache) = &self.cache else {
+            return self.normalize_in(w, &mut TermArena::new());
+        };
+        if let Some(normal) = cache.lock().unwrap().get(w) {
+            return normal;
+        }
+        // Other threads can use the cache while this one normalizes.
+        let normal = self.normalize_in(w, &mut TermArena::new());
+        cache.lock().unwrap().insert(w, &normal);
+        normal
+    }
+
+    /*
+     * Normalize w, building each reduct in arena. The rules that might apply at each subword are
+     * found with a DiscriminationTree of their left sides, built on the first call after the
+     * rules change. Subwords are matched where they lie in a Flatterm, which skips each bound
+     * subword by its offset instead of measuring it, and reducts are spliced straight from the
+     * arena, so a step allocates nothing once the arena and the word have grown large enough;
+     * reusing one arena across calls saves even that. A rule with a ground left side, as in
+     * congruence closure, is matched by the tree alone and its right side is spliced in as it
+     * is, without going through the arena.
+     */
+    pub fn normalize_in(&self, w: &Word<V, O>, arena: &mut TermArena<V, O>) -> Word<V, O> {
+        self.normalize_steps(w, arena, None).unwrap_or_else(|w| w)
+    }
+
+    /// Normalize w, but give up after max_steps rewrite steps, returning the word reached as the
+    /// error. Steps are leftmost outermost, as with normalize, which finds the normal form of any
+    /// word that has one in a left-normal orthogonal system such as combinatory logic, even if the
+    /// system doesn't terminate.
+    pub fn normalize_bounded(
+        &self,
+        w: &Word<V, O>,
+        max_steps: usize,
+    ) -> Result<Word<V, O>, Word<V, O>> {
+        self.normalize_steps(w, &mut TermArena::new(), Some(max_steps))
+    }
+
+    fn normalize_steps(
+        &self,
+        w: &Word<V, O>,
+        arena: &mut TermArena<V, O>,
+        max_steps: Option<usize>,
+    ) -> Result<Word<V, O>, Word<V, O>> {
+        normalize_steps(&self.rules, self.index(), w, arena, max_steps)
+    }
+
+    /// Search for a rewrite sequence from s to t of at most max_steps steps. If one exists, return
+    /// the sequence of words visited, starting with s and ending with t.
+    pub fn reachable(
+        &self,
+        s: &Word<V, O>,
+        t: &Word<V, O>,
+        max_steps: usize,
+    ) -> Option<Vec<Word<V, O>>> {
+        // Breadth-first, so the sequence found is a shortest one. Each visited word maps to the
+        // word it was first reached from.
+        let mut parents = BTreeMap::from([(s.syms.clone(), None::<Word<V, O>>)]);
+        let mut queue = VecDeque::from([(s.clone(), 0)]);
+        while let Some((w, depth)) = queue.pop_front() {
+            if w.syms == t.syms {
+                let mut path = vec![w];
+                while let Some(Some(p)) = path.last().and_then(|w| parents.get(&w.syms)) {
+                    path.push(p.clone());
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if depth == max_steps {
+                continue;
+            }
+            for r in self.rewrites(&w) {
+                if !parents.contains_key(&r.syms) {
+                    parents.insert(r.syms.clone(), Some(w.clone()));
+                    queue.push_back((r, depth + 1));
+                }
+            }
+        }
+        None
+    }
+
+    /// Decide whether s and t rewrite to a common word.
+    ///
+    /// The search expands the reducts of both words in lockstep and stops as soon as they meet,
+    /// so it always terminates when s and t are joinable. When they are not, it terminates only
+    /// if each word has finitely many reducts, which is the case for any terminating system.
+    pub fn joinable(&self, s: &Word<V, O>, t: &Word<V, O>) -> bool {
+        let mut seen = [
+            BTreeSet::from([s.syms.clone()]),
+            BTreeSet::from([t.syms.clone()]),
+        ];
+        let mut frontiers = [vec![s.clone()], vec![t.clone()]];
+        if s.syms == t.syms {
+            return true;
+        }
+        while !frontiers[0].is_empty() || !frontiers[1].is_empty() {
+            for side in 0..2 {
+                let mut next = Vec::new();
+                for w in frontiers[side].iter() {
+                    for r in self.rewrites(w) {
+                        if seen[1 - side].contains(&r.syms) {
+                            return true;
+                        }
+                        if seen[side].insert(r.syms.clone()) {
+                            next.push(r);
+                        }
+                    }
+                }
+                frontiers[side] = next;
+            }
+        }
+        false
+    }
+
+    /// Check that every rule is strictly decreasing in the given ordering, which proves that the
+    /// system terminates. Otherwise, return the first rule that isn't.
+    pub fn terminates_by(&self, order: &impl ReductionOrder<V, O>) -> Result<(), Rule<V, O>> {
+        match self
+            .rules
+            .iter()
+            .find(|rule| !order.greater(&rule.left, &rule.right))
+        {
+            Some(rule) => Err(rule.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// Compute every critical pair of the system: for each rule whose left side unifies with a
+    /// non-variable subword of another rule's left side (or its own, below the root), the two
+    /// results of rewriting the unified word with each rule.
+    pub fn critical_pairs(&self) -> Vec<CriticalPair<V, O>> {
+        let mut out = Vec::new();
+        for (i, outer) in self.rules.iter().enumerate() {
+            for (j, inner) in self.rules.iter().enumerate() {
+                out.extend(overlaps(outer, inner, i == j));
+            }
+        }
+        out
+    }
+
+    /// The critical pairs whose sides have different normal forms, with the rules that make
+    /// them. Normalizing relies on the system terminating.
+    pub fn counterexamples(&self) -> Vec<Counterexample<V, O>> {
+        self.unjoinable().1
+    }
+
+    // The number of critical pairs, and the counterexamples among them.
+    fn unjoinable(&self) -> (usize, Vec<Counterexample<V, O>>) {
+        let mut n = 0;
+        let mut out = Vec::new();
+        for (outer, r) in self.rules.iter().enumerate() {
+            for (inner, s) in self.rules.iter().enumerate() {
+                for pair in overlaps(r, s, outer == inner) {
+                    n += 1;
+                    let left_normal = self.normalize(&pair.left);
+                    let right_normal = self.normalize(&pair.right);
+                    if left_normal != right_normal {
+                        out.push(Counterexample {
+                            pair,
+                            outer,
+                            inner,
+                            left_normal,
+                            right_normal,
+                        });
+                    }
+                }
+            }
+        }
+        (n, out)
+    }
+
+    /// Check whether the system is locally confluent by checking that every critical pair is
+    /// joinable. For a terminating system this also decides confluence. The check relies on
+    /// joinable, so it may not terminate if the system doesn't.
+    pub fn is_locally_confluent(&self) -> ConfluenceReport<V, O> {
+        let cps = self.critical_pairs();
+        ConfluenceReport {
+            n_critical_pairs: cps.len(),
+            counterexamples: cps
+                .into_iter()
+                .filter(|cp| !self.joinable(&cp.left, &cp.right))
+                .collect(),
+        }
+    }
+
+    /// Find which rules are left-linear, right-linear, and duplicating, and every overlap
+    /// between left sides, the same ones critical_pairs finds.
+    pub fn analysis(&self) -> Analysis {
+        let mut a = Analysis::default();
+        for (i, rule) in self.rules.iter().enumerate() {
+            if !rule.left.is_linear() {
+                a.left_nonlinear.push(i);
+            }
+            if !rule.right.is_linear() {
+                a.right_nonlinear.push(i);
+            }
+            let left = var_counts(&rule.left);
+            if var_counts(&rule.right)
+                .iter()
+                .any(|(v, &n)| n > left.get(v).copied().unwrap_or(0))
+            {
+                a.duplicating.push(i);
+            }
+        }
+        for (i, outer) in self.rules.iter().enumerate() {
+            let outer_vars = outer.left.vars();
+            for (j, inner) in self.rules.iter().enumerate() {
+                let inner = rename_apart(inner, &outer_vars);
+                for (p, (position, sw)) in outer.left.positions().enumerate() {
+                    if outer.left.syms[p].var().is_some() || (i == j && p == 0) {
+                        continue;
+                    }
+                    if sw.mgu(&inner.left).is_some() {
+                        a.overlaps.push(Overlap {
+                            outer: i,
+                            inner: j,
+                            position,
+                        });
+                    }
+                }
+            }
+        }
+        a
+    }
+
+    /*
+     * Whether the two systems present the same equational theory, each shown to terminate by its
+     * own ordering so that normal forms exist: every rule of each should have sides with the same
+     * normal form in the other. A rule whose sides don't is a counterexample only if the system
+     * it fails in is confluent, which is then checked with that system's ordering.
+     */
+    pub fn equivalent_to(
+        &self,
+        order: &impl ReductionOrder<V, O>,
+        other: &Trs<V, O>,
+        other_order: &impl ReductionOrder<V, O>,
+    ) -> EquivalenceResult<V, O> {
+        if self.terminates_by(order).is_err() || other.terminates_by(other_order).is_err() {
+            return EquivalenceResult::Unknown;
+        }
+        let unjoined = |a: &Trs<V, O>, b: &Trs<V, O>| {
+            a.rules
+                .iter()
+                .find(|r| b.normalize(&r.left) != b.normalize(&r.right))
+                .cloned()
+        };
+        let (rule, confluent) = match unjoined(self, other) {
+            Some(rule) => (rule, other.is_confluent(other_order)),
+            None => match unjoined(other, self) {
+                Some(rule) => (rule, self.is_confluent(order)),
+                None => return EquivalenceResult::Equivalent,
+            },
+        };
+        match confluent.is_confluent() {
+            true => EquivalenceResult::NotEquivalent(rule),
+            false => EquivalenceResult::Unknown,
+        }
+    }
+
+    /*
+     * Decide confluence of a system that the ordering shows terminates: check that every rule
+     * decreases, then that every critical pair's sides have the same normal form, which by
+     * Newman's lemma is enough. Unlike is_locally_confluent, this always terminates.
+     */
+    pub fn is_confluent(&self, order: &impl ReductionOrder<V, O>) -> ConfluenceResult<V, O> {
+        if let Err(rule) = self.terminates_by(order) {
+            return ConfluenceResult::NotDecreasing(rule);
+        }
+        let (n_critical_pairs, unjoinable) = self.unjoinable();
+        if unjoinable.is_empty() {
+            ConfluenceResult::Confluent { n_critical_pairs }
+        } else {
+            ConfluenceResult::NotConfluent(unjoinable)
+        }
+    }
+}
+
+// Normalize w with rules, indexed by tree, giving up after max_steps steps if there's a limit.
+pub(crate) fn normalize_steps<V: Variable, O: Operator>(
+    rules: &[Rule<V, O>],
+    tree: &DiscriminationTree<O>,
+    w: &Word<V, O>,
+    arena: &mut TermArena<V, O>,
+    mut max_steps: Option<usize>,
+) -> Result<Word<V, O>, Word<V, O>> {
+    let mut w = Flatterm::new(w);
+    let (mut stack, mut candidates) = (Vec::new(), Vec::new());
+    'steps: loop {
+        for i in 0..w.syms.len() {
+            tree.candidates_flat(&w, i, &mut stack, &mut candidates);
+            if candidates.is_empty() {
+                continue;
+            }
+            let end = w.end(i);
+            for &id in &candidates {
+                let rule = &rules[id];
+                let ground = tree.is_ground(id);
+                if !ground && !arena.match_flat(&rule.left.syms, &w, i) {
+                    continue;
+                }
+                match max_steps.as_mut() {
+                    Some(0) => return Err(w.to_word()),
+                    Some(n) => *n -= 1,
+                    None => {}
+                }
+                if ground {
+                    w.splice(i..end, &rule.right.syms);
+                } else {
+                    let r = arena.subst(&rule.right.syms, &w.syms);
+                    w.splice(i..end, arena.get(r));
+                    arena.clear();
+                }
+                continue 'steps;
+            }
+        }
+        return Ok(w.to_word());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prod::*;
+    use crate::{ConfluenceResult, EquivalenceResult, Kbo, Overlap, Position, Rule, Trs};
+
+    fn group_fragment() -> Trs<String, Prod> {
+        let x = var("x");
+        Trs::new(vec![
+            Rule {
+                left: one() * &x,
+                right: x.clone(),
+            },
+            Rule {
+                left: inv(&x) * &x,
+                right: one(),
+            },
+        ])
+    }
+
+    #[test]
+    fn rewrites() {
+        let trs = group_fragment();
+        let a = var("a");
+        let w = one() * (inv(&a) * &a);
+        let rs = trs.rewrites(&w);
+        assert_eq!(rs.len(), 2);
+        assert!(rs.contains(&(inv(&a) * &a)));
+        assert!(rs.contains(&(one() * one())));
+    }
+
+    #[test]
+    fn normalize() {
+        let trs = group_fragment();
+        let a = var("a");
+        let b = var("b");
+        let w = one() * (inv(&(one() * &a)) * (one() * &a));
+        assert_eq!(trs.normalize(&w), one());
+        assert_eq!(trs.normalize(&(&a * &b)), &a * &b);
+        assert!(trs.rewrite_step(&(&a * &b)).is_none());
+    }
+
+    #[test]
+    fn derivation() {
+        let trs = group_fragment();
+        let a = var("a");
+        let w = one() * (inv(&a) * (one() * &a));
+        let steps: Vec<_> = trs
+            .derivation(&w)
+            .into_iter()
+            .map(|s| (s.rule, s.position.to_string(), s.word))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (0, "ε".to_string(), inv(&a) * (one() * &a)),
+                (0, "1".to_string(), inv(&a) * &a),
+                (1, "ε".to_string(), one()),
+            ]
+        );
+        assert!(trs.derivation(&a).is_empty());
+        assert_eq!(w.position_of(7), crate::word::Position(vec![1, 1, 1]));
+        assert_eq!(w.position_of(0), crate::word::Position::root());
+    }
+
+    // Ground rules are matched by comparing symbols, and mix with the others.
+    #[test]
+    fn normalize_ground() {
+        let mut trs = group_fragment();
+        trs.rules_mut().push(Rule {
+            left: inv(&one()),
+            right: one(),
+        });
+        let w = inv(&(inv(&one()) * inv(&inv(&one())))) * (one() * var("a"));
+        let mut stepwise = w.clone();
+        while let Some(v) = trs.rewrite_step(&stepwise) {
+            stepwise = v;
+        }
+        assert_eq!(trs.normalize(&w), var("a"));
+        assert_eq!(stepwise, var("a"));
+    }
+
+    #[test]
+    fn reachable() {
+        let trs = group_fragment();
+        let a = var("a");
+        let w = one() * (inv(&a) * &a);
+        let path = trs.reachable(&w, &one(), 2).unwrap();
+        assert_eq!(path.len(), 3);
+        assert_eq!(path[0], w);
+        assert_eq!(path[2], one());
+        assert!(trs.reachable(&w, &one(), 1).is_none());
+        assert!(trs.reachable(&w, &a, 10).is_none());
+        assert_eq!(trs.reachable(&a, &a, 0), Some(vec![a.clone()]));
+    }
+
+    #[test]
+    fn joinable() {
+        let trs = group_fragment();
+        let a = var("a");
+        let b = var("b");
+        assert!(trs.joinable(&(one() * &a), &a));
+        assert!(trs.joinable(&(inv(&a) * &a), &(one() * one())));
+        assert!(!trs.joinable(&a, &b));
+        assert!(!trs.joinable(&(a.clone() * &b), &(b.clone() * &a)));
+    }
+
+    #[test]
+    fn index() {
+        let x = var("x");
+        let mut trs = Trs::new(vec![
+            Rule {
+                left: one() * &x,
+                right: x.clone(),
+            },
+            Rule {
+                left: inv(&one()),
+                right: one(),
+            },
+            Rule {
+                left: &x * one(),
+                right: x.clone(),
+            },
+        ]);
+        assert_eq!(trs.normalize(&(inv(&one()) * one())), one());
+        assert_eq!(trs.rewrites(&(one() * one())), [one(), one()]);
+        // Changing the rules drops the index of the old ones.
+        trs.rules_mut().remove(1);
+        assert_eq!(trs.normalize(&(inv(&one()) * one())), inv(&one()));
+        assert_eq!(trs.rewrite_step(&inv(&one())), None);
+    }
+
+    #[test]
+    fn critical_pairs() {
+        let x = var("x");
+        let y = var("y");
+        let z = var("z");
+        let assoc = Rule {
+            left: (&x * &y) * &z,
+            right: &x * (&y * &z),
+        };
+        let trs = Trs::new(vec![assoc]);
+        // Associativity overlaps with itself once, at the left argument of its left side.
+        let cps = trs.critical_pairs();
+        assert_eq!(cps.len(), 1);
+        for cp in cps.iter() {
+            assert!(trs.rewrites(&cp.peak).contains(&cp.left));
+            assert!(trs.rewrites(&cp.peak).contains(&cp.right));
+        }
+        let report = trs.is_locally_confluent();
+        assert_eq!(report.n_critical_pairs, 1);
+        assert!(report.is_locally_confluent());
+    }
+
+    #[test]
+    fn not_locally_confluent() {
+        let x = var("x");
+        let y = var("y");
+        let z = var("z");
+        let trs = Trs::new(vec![
+            Rule {
+                left: inv(&x) * &x,
+                right: one(),
+            },
+            Rule {
+                left: (&x * &y) * &z,
+                right: &x * (&y * &z),
+            },
+        ]);
+        // The overlap (x⁻¹ * x) * z gives the critical pair 1 * z = x⁻¹ * (x * z), which is not
+        // joinable without a rule for 1 * z.
+        let report = trs.is_locally_confluent();
+        assert!(report.n_critical_pairs >= 1);
+        assert!(!report.is_locally_confluent());
+        let cp = &report.counterexamples[0];
+        assert!(!trs.joinable(&cp.left, &cp.right));
+        assert!(trs.joinable(&cp.peak, &cp.left));
+        assert!(trs.joinable(&cp.peak, &cp.right));
+    }
+
+    #[test]
+    fn terminates_by() {
+        let x = var("x");
+        let y = var("y");
+        assert!(group_fragment().terminates_by(&Kbo).is_ok());
+
+        // Commutativity can't be oriented, so the system isn't shown to terminate.
+        let comm = Rule {
+            left: &x * &y,
+            right: &y * &x,
+        };
+        let mut trs = group_fragment();
+        trs.rules_mut().push(comm.clone());
+        let err = trs.terminates_by(&Kbo).unwrap_err();
+        assert_eq!((err.left, err.right), (comm.left, comm.right));
+
+        // Neither can a rule that is increasing.
+        let trs = Trs::new(vec![Rule {
+            left: x.clone(),
+            right: one() * &x,
+        }]);
+        assert!(trs.terminates_by(&Kbo).is_err());
+    }
+
+    #[test]
+    fn is_confluent() {
+        let mut trs = Trs::new(crate::theories::group::rules());
+        let result = trs.is_confluent(&Kbo);
+        assert!(result.is_confluent(), "{}", result);
+
+        // Without (x * y)⁻¹ → y⁻¹ * x⁻¹, the inverse of a product has no normal form to agree on.
+        trs.rules_mut()
+            .retain(|r| r.to_string() != "(x * y)⁻¹ → y⁻¹ * x⁻¹");
+        assert_eq!(trs.rules.len(), 9);
+        let ConfluenceResult::NotConfluent(pairs) = trs.is_confluent(&Kbo) else {
+            panic!("expected critical pairs that don't join");
+        };
+        for u in &pairs {
+            assert_eq!(trs.normalize(&u.pair.left), u.left_normal);
+            assert_ne!(u.left_normal, u.right_normal);
+            let by = |i: usize| Trs::new(vec![trs.rules[i].clone()]).rewrites(&u.pair.peak);
+            assert!(by(u.outer).contains(&u.pair.left));
+            assert!(by(u.inner).contains(&u.pair.right));
+        }
+        assert_eq!(trs.counterexamples().len(), pairs.len());
+        let shown = pairs[0].to_string();
+        assert!(shown.contains(&format!(
+            "by rules {} and {}",
+            pairs[0].outer, pairs[0].inner
+        )));
+        assert!(shown.ends_with(&format!(
+            "{} and {}",
+            pairs[0].left_normal, pairs[0].right_normal
+        )));
+
+        let (x, y) = (var("x"), var("y"));
+        trs.rules_mut().push(Rule {
+            left: &x * &y,
+            right: &y * &x,
+        });
+        let result = trs.is_confluent(&Kbo);
+        assert!(matches!(result, ConfluenceResult::NotDecreasing(_)));
+        assert_eq!(
+            result.to_string(),
+            "termination not shown: x * y → y * x doesn't decrease in the ordering"
+        );
+    }
+
+    #[test]
+    fn analysis() {
+        let a = crate::theories::cl::rules().analysis();
+        assert!(a.is_orthogonal());
+        // S · x · y · z → x · z · (y · z) copies z.
+        assert_eq!(a.right_nonlinear, [2]);
+        assert_eq!(a.duplicating, [2]);
+
+        let a = group_fragment().analysis();
+        assert_eq!(a.left_nonlinear, [1]);
+        assert!(!a.is_duplicating());
+        assert!(a.is_non_overlapping());
+        assert!(!a.is_orthogonal());
+
+        let trs = Trs::new(crate::theories::group::rules());
+        let a = trs.analysis();
+        assert!(!a.is_non_overlapping());
+        // (x * y) * z overlaps itself at its left argument.
+        assert!(a.overlaps.contains(&Overlap {
+            outer: 2,
+            inner: 2,
+            position: Position(vec![0]),
+        }));
+        assert_eq!(a.overlaps.len(), trs.critical_pairs().len());
+    }
+
+    #[test]
+    fn equivalent_to() {
+        let mut c = crate::Completion::new(&crate::theories::group::axioms());
+        assert!(matches!(c.run(), crate::Outcome::Convergent));
+        let completed = c.trs();
+        let mut rules = crate::theories::group::rules();
+        rules.reverse();
+        let by_hand = Trs::new(rules);
+        assert!(by_hand
+            .equivalent_to(&Kbo, &completed, &Kbo)
+            .is_equivalent());
+
+        // Groups of exponent two are a different theory.
+        let x = var("x");
+        let mut exponent_two = by_hand.clone();
+        exponent_two.rules_mut().push(Rule {
+            left: &x * &x,
+            right: one(),
+        });
+        assert!(matches!(
+            by_hand.equivalent_to(&Kbo, &exponent_two, &Kbo),
+            EquivalenceResult::NotEquivalent(_)
+        ));
+        assert!(matches!(
+            exponent_two.equivalent_to(&Kbo, &by_hand, &Kbo),
+            EquivalenceResult::NotEquivalent(r) if r.left == &x * &x
+        ));
+
+        // The axioms as rules terminate but aren't confluent, so a rule of the completed system
+        // not joining in them shows nothing.
+        let axioms = Trs::new(
+            crate::theories::group::axioms()
+                .into_iter()
+                .map(|a| Rule {
+                    left: a.left,
+                    right: a.right,
+                })
+                .collect(),
+        );
+        assert!(matches!(
+            completed.equivalent_to(&Kbo, &axioms, &Kbo),
+            EquivalenceResult::Unknown
+        ));
+        let mut reversed = axioms.clone();
+        reversed.rules_mut().reverse();
+        assert!(axioms.equivalent_to(&Kbo, &reversed, &Kbo).is_equivalent());
+        // Nor does a system its ordering doesn't show terminates.
+        let mut looping = by_hand.clone();
+        looping.rules_mut().push(Rule {
+            left: &x * &x,
+            right: &x * &x,
+        });
+        assert!(matches!(
+            by_hand.equivalent_to(&Kbo, &looping, &Kbo),
+            EquivalenceResult::Unknown
+        ));
+    }
+
+    #[test]
+    fn cache() {
+        let trs = Trs::new(crate::theories::group::rules()).with_cache();
+        let (x, y) = (var("x"), var("y"));
+        let w = inv(&(&x * &y)) * &x;
+        assert_eq!(trs.normalize(&w), inv(&y));
+        assert_eq!(trs.normalize(&w), inv(&y));
+        // A renaming of w shares its entry, with the normal form renamed to match.
+        let (a, b) = (var("a"), var("b"));
+        assert_eq!(trs.normalize(&(inv(&(&a * &b)) * &a)), inv(&b));
+        let stats = trs.cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 1, 1));
+
+        // Changing the rules empties it.
+        let mut exponent_two = trs.clone();
+        exponent_two.rules_mut().push(Rule {
+            left: &x * &x,
+            right: one(),
+        });
+        assert_eq!(exponent_two.cache_stats().unwrap().entries, 0);
+        assert_eq!(exponent_two.normalize(&(&a * &a)), one());
+        let stats = exponent_two.cache_stats().unwrap();
+        assert_eq!((stats.misses, stats.entries, stats.clears), (2, 1, 1));
+        assert!(Trs::<String, Prod>::new(Vec::new()).cache_stats().is_none());
+    }
+}
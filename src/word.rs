@@ -1,19 +1,153 @@
 use std::cmp::*;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Debug, Display};
+use std::hash::{Hash, Hasher};
 use std::slice;
 
-pub trait Variable: Eq + Ord + Clone + Debug {}
+use crate::kbo::{self, KboParams};
+use crate::notation::Notation;
+use crate::order::GroundKbo;
+use crate::relation::Axiom;
+use crate::sort::Sort;
+use crate::subst::SubstWord;
 
-impl Variable for String {}
+/*
+ * The type of a word's variables. Renaming words apart and giving them canonical names has to
+ * make new variables, and nothing else in the bounds can, so prime and nth have no defaults.
+ * Variable was once a marker trait with no methods, and these two are a breaking change for an
+ * implementation on another type: it needs both, as String's implementation below shows.
+ */
+pub trait Variable: Eq + Ord + Clone + Debug {
+    /// Return a variable distinct from this one, keeping a visible link to it. Repeated priming
+    /// must never return to a variable seen before. FreshVars is the way to mint unrelated
+    /// variables.
+    fn prime(&self) -> Self;
+
+    /// The nth variable of a fixed sequence of distinct variables, used for canonical names.
+    fn nth(n: usize) -> Self;
+}
+
+impl Variable for String {
+    fn prime(&self) -> String {
+        format!("{}'", self)
+    }
+
+    fn nth(n: usize) -> String {
+        format!("x{}", n)
+    }
+}
+
+/// A source of fresh variables.
+pub trait VarGen<V: Variable> {
+    /// Return a variable that this generator hasn't returned before and that isn't in use.
+    fn fresh(&mut self) -> V;
+}
+
+/// Generates Variable::nth(0), nth(1), ... in turn, skipping any variable it was told to avoid.
+/// For String these are x0, x1, ....
+#[derive(Clone, Debug)]
+pub struct FreshVars<V: Variable> {
+    next: usize,
+    avoid: BTreeSet<V>,
+}
+
+impl<V: Variable> Default for FreshVars<V> {
+    fn default() -> FreshVars<V> {
+        FreshVars {
+            next: 0,
+            avoid: BTreeSet::new(),
+        }
+    }
+}
+
+impl<V: Variable> FreshVars<V> {
+    pub fn new() -> FreshVars<V> {
+        FreshVars::default()
+    }
+
+    /// Never generate v.
+    pub fn avoid(&mut self, v: V) {
+        self.avoid.insert(v);
+    }
+
+    /// Never generate a variable of w.
+    pub fn avoid_word<O: Operator>(&mut self, w: &Word<V, O>) {
+        self.avoid
+            .extend(w.syms.iter().filter_map(Symbol::var).cloned());
+    }
+}
 
+impl<V: Variable> VarGen<V> for FreshVars<V> {
+    fn fresh(&mut self) -> V {
+        loop {
+            let v = V::nth(self.next);
+            self.next += 1;
+            if !self.avoid.contains(&v) {
+                return v;
+            }
+        }
+    }
+}
+
+/*
+ * Words are stored flat, so every operator symbol must know its own arity. An operator with
+ * variable arity records the arity of each occurrence in its value (e.g. Tuple(3)), declares the
+ * range of arities it accepts with min_arity and max_arity, and produces an occurrence of a given
+ * arity with with_arity. The defaults describe an operator of fixed arity.
+ */
 pub trait Operator: Eq + Ord + Clone + Debug {
     fn min_weight() -> u64;
     fn arity(&self) -> usize;
     fn weight(&self) -> u64;
+
+    fn min_arity(&self) -> usize {
+        self.arity()
+    }
+
+    /// The largest arity allowed, or None if there is no limit.
+    fn max_arity(&self) -> Option<usize> {
+        Some(self.arity())
+    }
+
+    /// Whether the order of this operator's arguments doesn't matter. This is only used to put
+    /// words in a canonical form; rewriting still treats the arguments as ordered.
+    fn is_commutative(&self) -> bool {
+        false
+    }
+
+    /// Whether the operator is associative. Nothing here works modulo associativity; it's only
+    /// passed on when exporting to systems that do, such as Maude.
+    fn is_associative(&self) -> bool {
+        false
+    }
+
+    /// The sort of words with this operator at the root, or None if the operator is unsorted.
+    fn result_sort(&self) -> Option<Sort> {
+        None
+    }
+
+    /// The sort of argument i, for a sorted operator.
+    fn arg_sort(&self, _i: usize) -> Option<Sort> {
+        None
+    }
+
+    /// How the operator is written when words are displayed. The default is as a function.
+    fn notation(&self) -> Notation {
+        Notation::FUNCTION
+    }
+
+    /// Return this operator as an occurrence with n arguments, if n is in its arity range.
+    fn with_arity(&self, n: usize) -> Option<Self> {
+        if n == self.arity() {
+            Some(self.clone())
+        } else {
+            None
+        }
+    }
 }
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Symbol<V: Variable, O: Operator> {
     Var(V),
     Op(O),
@@ -63,126 +197,700 @@ impl<V: Variable + Display, O: Operator + Display> Display for Symbol<V, O> {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Word<V: Variable, O: Operator> {
     pub syms: Vec<Symbol<V, O>>,
 }
 
-#[derive(Clone, Debug)]
-pub struct Axiom<V: Variable, O: Operator> {
-    pub left: Word<V, O>,
-    pub right: Word<V, O>,
+// Deserializing checks that the symbols make up a word, since everything else assumes they do.
+#[cfg(feature = "serde")]
+impl<'de, V, O> serde::Deserialize<'de> for Word<V, O>
+where
+    V: Variable + serde::Deserialize<'de>,
+    O: Operator + serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Word<V, O>, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "Word")]
+        struct Syms<V: Variable, O: Operator> {
+            syms: Vec<Symbol<V, O>>,
+        }
+        let w = Word {
+            syms: Syms::deserialize(d)?.syms,
+        };
+        w.validate().map_err(serde::de::Error::custom)?;
+        Ok(w)
+    }
 }
 
-#[derive(Clone, Debug)]
-pub struct Rule<V: Variable, O: Operator> {
-    pub left: Word<V, O>,
-    pub right: Word<V, O>,
+/// Why a sequence of symbols isn't a word, found by Word::validate. Indexes are into syms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WordError {
+    Empty,
+    /// The symbols from this index on come after a complete word.
+    Trailing(usize),
+    /// The operator at index is missing this many of its last arguments.
+    MissingArguments {
+        index: usize,
+        missing: usize,
+    },
+    /// The operator at index is used at an arity outside its arity range.
+    BadArity {
+        index: usize,
+        arity: usize,
+    },
 }
 
-impl<V: Variable, O: Operator> Word<V, O> {
-    pub fn from_sym(s: Symbol<V, O>) -> Word<V, O> {
-        Word { syms: vec![s] }
+impl Display for WordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WordError::Empty => write!(f, "the word is empty"),
+            WordError::Trailing(i) => write!(f, "trailing symbols from index {}", i),
+            WordError::MissingArguments { index, missing } => write!(
+                f,
+                "the operator at index {} is missing {} argument(s)",
+                index, missing
+            ),
+            WordError::BadArity { index, arity } => write!(
+                f,
+                "the operator at index {} can't take {} argument(s)",
+                index, arity
+            ),
+        }
     }
+}
 
-    pub fn var<VF: Into<V>>(v: VF) -> Word<V, O> {
-        Word::from_sym(Var(v.into()))
+impl std::error::Error for WordError {}
+
+/*
+ * A borrowed view of a word, or of a subword of one, as a slice of its symbols. The read-only
+ * operations all work on views, and Subwords yields them, so walking the arguments of a word
+ * copies nothing; to_word makes an owned copy when one is needed.
+ */
+#[derive(Debug)]
+pub struct WordRef<'a, V: Variable, O: Operator> {
+    pub syms: &'a [Symbol<V, O>],
+}
+
+impl<V: Variable, O: Operator> Clone for WordRef<'_, V, O> {
+    fn clone(&self) -> Self {
+        *self
     }
+}
 
-    pub fn op<OF: Into<O>>(f: OF, args: &[Word<V, O>]) -> Word<V, O> {
-        let mut out = Word::from_sym(Op(f.into()));
-        for a in args {
-            out.syms.extend(a.syms.clone());
+impl<V: Variable, O: Operator> Copy for WordRef<'_, V, O> {}
+
+impl<'a, V: Variable, O: Operator> From<&'a Word<V, O>> for WordRef<'a, V, O> {
+    fn from(w: &'a Word<V, O>) -> Self {
+        w.view()
+    }
+}
+
+impl<'a, V: Variable, O: Operator> WordRef<'a, V, O> {
+    pub fn to_word(self) -> Word<V, O> {
+        Word {
+            syms: self.syms.to_vec(),
         }
-        out
     }
 
-    fn weight(&self) -> u64 {
+    pub fn size(self) -> usize {
+        self.syms.len()
+    }
+
+    pub(crate) fn weight(self) -> u64 {
         self.syms.iter().map(Symbol::weight).sum()
     }
 
-    fn n_of_var(&self, var: &V) -> usize {
-        let v = Var(var.clone());
-        self.syms.iter().filter(|s| **s == v).count()
+    pub fn vars(self) -> BTreeSet<V> {
+        self.syms.iter().filter_map(Symbol::var).cloned().collect()
+    }
+
+    /// Whether the word has no variables.
+    pub fn is_ground(self) -> bool {
+        self.syms.iter().all(|s| s.var().is_none())
     }
 
-    fn vars(&self) -> BTreeSet<V> {
+    /// A variable that occurs more than once in the word, if there is one.
+    pub fn repeated_var(self) -> Option<&'a V> {
+        let mut seen = BTreeSet::new();
         self.syms
             .iter()
-            .filter_map(|s| match s {
-                Var(v) => Some(v),
-                Op(_) => None,
-            })
-            .cloned()
-            .collect()
+            .filter_map(Symbol::var)
+            .find(|v| !seen.insert(*v))
     }
 
-    pub fn is_well_formed(&self) -> bool {
-        let mut nsyms: isize = 1;
-        for s in self.syms.iter() {
-            nsyms += (s.arity() as isize) - 1;
+    pub(crate) fn subword_end(self, i: usize) -> usize {
+        let mut nsyms: usize = 1;
+        let mut j = i;
+        while nsyms > 0 {
+            nsyms -= 1;
+            nsyms += self.syms.get(j).map_or(0, Symbol::arity);
+            j += 1;
         }
-        nsyms == 0
+        j
     }
 
-    pub fn subwords(&self) -> Subwords<'_, V, O> {
+    pub fn subwords(self) -> Subwords<'a, V, O> {
         Subwords {
-            syms: &self.syms,
+            syms: self.syms,
             i: 1,
             nargs: self.syms.first().map_or(0, Symbol::arity),
         }
     }
 
-    pub fn subst(&self, vars: &BTreeMap<V, Word<V, O>>) -> Word<V, O> {
+    /// The word with vars applied lazily, as a view.
+    pub fn subst_lazy<'b>(self, vars: &'b BTreeMap<V, Word<V, O>>) -> SubstWord<'b, V, O>
+    where
+        'a: 'b,
+    {
+        SubstWord {
+            word: self,
+            subst: vars,
+        }
+    }
+
+    pub fn subst(self, vars: &BTreeMap<V, Word<V, O>>) -> Word<V, O> {
         Word {
             syms: self
                 .syms
                 .iter()
-                .map(|s| {
+                .flat_map(|s| {
                     s.var()
                         .and_then(|v| vars.get(v))
                         .map_or(slice::from_ref(s), |w| w.syms.as_slice())
                 })
-                .flatten()
                 .cloned()
                 .collect(),
         }
     }
 
+    /*
+     * Match this word against another one way, as unify does, but bind each variable to a view
+     * into the other word instead of a copy.
+     */
+    pub fn matches<'b>(self, other: WordRef<'b, V, O>) -> Option<BTreeMap<V, WordRef<'b, V, O>>> {
+        let mut vmap = BTreeMap::new();
+        if self.is_ground() {
+            // Nothing to bind, so only an equal word matches.
+            return (self == other).then_some(vmap);
+        }
+        let matched = match_syms(
+            self.syms,
+            other.syms,
+            0,
+            |j| other.subword_end(j),
+            |v, r| {
+                let w = WordRef {
+                    syms: &other.syms[r],
+                };
+                // A different substitution for this variable may already exist.
+                vmap.insert(v.clone(), w).is_none_or(|ow| ow == w)
+            },
+        );
+        matched.then_some(vmap)
+    }
+
     /*
      * Compute the substitutions of the variables in this word such that it is equal to another
      * word, or return None if this is not possible.
      */
-    pub fn unify(&self, other: &Word<V, O>) -> Option<BTreeMap<V, Word<V, O>>> {
-        match (self.syms.first(), other.syms.first()) {
-            (Some(Var(v)), Some(_)) => {
-                // If self is just a variable, we can just substitute the entire other word.
-                let vmap = BTreeMap::from([(v.clone(), other.clone())]);
-                Some(vmap)
+    pub fn unify(self, other: WordRef<'_, V, O>) -> Option<BTreeMap<V, Word<V, O>>> {
+        Some(
+            self.matches(other)?
+                .into_iter()
+                .map(|(v, w)| (v, w.to_word()))
+                .collect(),
+        )
+    }
+}
+
+/*
+ * Match pattern one way against the subword of syms starting at start, the walk shared by
+ * WordRef::matches and TermArena. end gives the index one past the subword of syms at an index,
+ * and bind is called with each variable of pattern and the range of syms it lines up with,
+ * returning whether that binding is consistent with the earlier ones.
+ */
+pub(crate) fn match_syms<V: Variable, O: Operator>(
+    pattern: &[Symbol<V, O>],
+    syms: &[Symbol<V, O>],
+    start: usize,
+    end: impl Fn(usize) -> usize,
+    mut bind: impl FnMut(&V, std::ops::Range<usize>) -> bool,
+) -> bool {
+    let mut j = start;
+    for p in pattern {
+        let Some(s) = syms.get(j) else {
+            return false;
+        };
+        match p {
+            Var(v) => {
+                let e = end(j);
+                if !bind(v, j..e) {
+                    return false;
+                }
+                j = e;
+            }
+            p if p == s => j += 1,
+            // A different operator, or an operator in pattern where syms has a variable.
+            _ => return false,
+        }
+    }
+    true
+}
+
+impl<V: Variable, O: Operator> PartialEq for WordRef<'_, V, O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.syms == other.syms
+    }
+}
+
+impl<V: Variable, O: Operator> Eq for WordRef<'_, V, O> {}
+
+impl<V: Variable, O: Operator> PartialOrd for WordRef<'_, V, O> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        kbo::compare_ref(*self, *other, &KboParams::default())
+    }
+}
+
+/// A position in a word: the argument indices on the path from the root to a subword, counting
+/// from zero. The root is the empty path.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position(pub Vec<usize>);
+
+impl Position {
+    pub fn root() -> Position {
+        Position(Vec::new())
+    }
+
+    /// The position of argument i of the subword at this position.
+    pub fn child(&self, i: usize) -> Position {
+        let mut p = self.0.clone();
+        p.push(i);
+        Position(p)
+    }
+
+    /// Whether the subword at other is inside the subword at self.
+    pub fn is_prefix_of(&self, other: &Position) -> bool {
+        other.0.starts_with(&self.0)
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "ε");
+        }
+        for (k, i) in self.0.iter().enumerate() {
+            if k > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{}", i)?;
+        }
+        Ok(())
+    }
+}
+
+impl<V: Variable, O: Operator> Word<V, O> {
+    pub fn from_sym(s: Symbol<V, O>) -> Word<V, O> {
+        Word { syms: vec![s] }
+    }
+
+    pub fn var<VF: Into<V>>(v: VF) -> Word<V, O> {
+        Word::from_sym(Var(v.into()))
+    }
+
+    /*
+     * Apply an operator to arguments. A variable-arity operator takes on the number of arguments
+     * given; otherwise the operator is used as is, so the result may not be well formed.
+     */
+    pub fn op<OF: Into<O>>(f: OF, args: &[Word<V, O>]) -> Word<V, O> {
+        let f = f.into();
+        let f = f.with_arity(args.len()).unwrap_or(f);
+        let mut syms = Vec::with_capacity(1 + args.iter().map(Word::size).sum::<usize>());
+        syms.push(Op(f));
+        for a in args {
+            syms.extend_from_slice(&a.syms);
+        }
+        Word { syms }
+    }
+
+    /// Word::op taking ownership of the arguments, so their symbols are moved rather than cloned.
+    pub fn op_owned<OF: Into<O>>(f: OF, args: Vec<Word<V, O>>) -> Word<V, O> {
+        let f = f.into();
+        let f = f.with_arity(args.len()).unwrap_or(f);
+        let mut syms = Vec::with_capacity(1 + args.iter().map(Word::size).sum::<usize>());
+        syms.push(Op(f));
+        for a in args {
+            syms.extend(a.syms);
+        }
+        Word { syms }
+    }
+
+    /*
+     * Append rhs as the next argument of a word being built root first, as in
+     * Word::from_sym(Op(f)).apply(a).apply(b). The root's arity isn't adjusted, so a
+     * variable-arity operator should be applied with op or op_owned instead.
+     */
+    pub fn apply(mut self, rhs: Word<V, O>) -> Word<V, O> {
+        self.syms.extend(rhs.syms);
+        self
+    }
+
+    pub fn view(&self) -> WordRef<'_, V, O> {
+        WordRef { syms: &self.syms }
+    }
+
+    pub(crate) fn weight(&self) -> u64 {
+        self.view().weight()
+    }
+
+    pub fn vars(&self) -> BTreeSet<V> {
+        self.view().vars()
+    }
+
+    pub fn is_ground(&self) -> bool {
+        self.view().is_ground()
+    }
+
+    /// Whether no variable occurs more than once.
+    pub fn is_linear(&self) -> bool {
+        self.view().repeated_var().is_none()
+    }
+
+    /*
+     * Rename the variables to Variable::nth(0), nth(1), ... in order of first occurrence. Two
+     * words are variants, equal up to renaming variables, exactly when this gives the same word.
+     */
+    pub fn canonicalize_vars(&self) -> Word<V, O> {
+        let mut names: BTreeMap<V, V> = BTreeMap::new();
+        let syms = self
+            .syms
+            .iter()
+            .map(|s| match s {
+                Var(v) => {
+                    let n = names.len();
+                    Var(names.entry(v.clone()).or_insert_with(|| V::nth(n)).clone())
+                }
+                Op(f) => Op(f.clone()),
+            })
+            .collect();
+        Word { syms }
+    }
+
+    pub fn is_variant(&self, other: &Word<V, O>) -> bool {
+        self.syms.len() == other.syms.len()
+            && self.canonicalize_vars().syms == other.canonicalize_vars().syms
+    }
+
+    /// Rename every variable with f, which may change the variable type.
+    pub fn map_vars<V2: Variable>(&self, f: impl Fn(&V) -> V2) -> Word<V2, O> {
+        Word {
+            syms: self
+                .syms
+                .iter()
+                .map(|s| match s {
+                    Var(v) => Var(f(v)),
+                    Op(g) => Op(g.clone()),
+                })
+                .collect(),
+        }
+    }
+
+    /// Replace every operator with f of it, which may change the operator type. The new operator
+    /// must have the same arity, or the result won't be well formed.
+    pub fn map_ops<O2: Operator>(&self, f: impl Fn(&O) -> O2) -> Word<V, O2> {
+        Word {
+            syms: self
+                .syms
+                .iter()
+                .map(|s| match s {
+                    Var(v) => Var(v.clone()),
+                    Op(g) => Op(f(g)),
+                })
+                .collect(),
+        }
+    }
+
+    /// The number of symbols in the word.
+    pub fn size(&self) -> usize {
+        self.syms.len()
+    }
+
+    /// The length of the longest position in the word, so a variable or constant has depth 0.
+    pub fn depth(&self) -> usize {
+        self.fold(|_| 0, |_, args| args.into_iter().max().map_or(0, |d| d + 1))
+    }
+
+    /*
+     * Compute a value bottom up over the tree structure of the word: leaf gives the value of a
+     * variable, and node the value of an operator applied to the values of its arguments. This
+     * works from the end of syms with a stack of argument values, so it doesn't recurse.
+     */
+    pub fn fold<A>(&self, leaf: impl Fn(&V) -> A, node: impl Fn(&O, Vec<A>) -> A) -> A {
+        let mut stack: Vec<A> = Vec::new();
+        for s in self.syms.iter().rev() {
+            match s {
+                Var(v) => stack.push(leaf(v)),
+                Op(f) => {
+                    // The first argument is on top of the stack.
+                    let args: Vec<A> = stack.drain(stack.len() - f.arity()..).rev().collect();
+                    stack.push(node(f, args));
+                }
             }
-            (Some(Op(f)), Some(Op(g))) if f == g => {
-                // If self and other are both the same operator, we can unify recursively.
-                let mut vmap = BTreeMap::new();
-                for (s, t) in self.subwords().zip(other.subwords()) {
-                    if let Some(sub) = s.unify(&t) {
-                        for (v, w) in sub.iter() {
-                            if let Some(ow) = vmap.insert(v.clone(), w.clone()) {
-                                if &ow != w {
-                                    // A different substitution for this variable already exists.
-                                    return None;
-                                }
-                            }
-                        }
-                    } else {
-                        return None;
-                    }
+        }
+        stack.pop().expect("fold of an empty word")
+    }
+
+    /// The number of occurrences of f in the word.
+    pub fn op_count(&self, f: &O) -> usize {
+        self.syms.iter().filter(|s| s.op() == Some(f)).count()
+    }
+
+    /*
+     * Return the index one past the end of the subword that starts at index i. The flat encoding
+     * means a subword is always a contiguous run of symbols, so this is found by counting off
+     * arguments the same way as validate.
+     */
+    pub(crate) fn subword_end(&self, i: usize) -> usize {
+        self.view().subword_end(i)
+    }
+
+    /*
+     * Return a copy of this word with the subword starting at index i replaced by w.
+     */
+    pub(crate) fn splice(&self, i: usize, w: &Word<V, O>) -> Word<V, O> {
+        let end = self.subword_end(i);
+        let mut syms = Vec::with_capacity(self.syms.len() - (end - i) + w.syms.len());
+        syms.extend_from_slice(&self.syms[..i]);
+        syms.extend_from_slice(&w.syms);
+        syms.extend_from_slice(&self.syms[end..]);
+        Word { syms }
+    }
+
+    /*
+     * Return the index in syms of the subword at position p, by skipping over the earlier
+     * arguments at each step down, or None if the path leaves the word.
+     */
+    pub(crate) fn index_at(&self, p: &Position) -> Option<usize> {
+        let mut i = 0;
+        for &k in p.0.iter() {
+            if k >= self.syms.get(i)?.arity() {
+                return None;
+            }
+            i += 1;
+            for _ in 0..k {
+                i = self.subword_end(i);
+            }
+        }
+        (i < self.syms.len()).then_some(i)
+    }
+
+    /// The position of the subword starting at index i of syms, the inverse of index_at.
+    pub(crate) fn position_of(&self, i: usize) -> Position {
+        let mut p = Position::root();
+        let mut j = 0;
+        while j < i {
+            // Step into the argument of the subword at j that contains i.
+            let mut k = 0;
+            j += 1;
+            loop {
+                let end = self.subword_end(j);
+                if i < end {
+                    break;
                 }
-                return Some(vmap);
+                j = end;
+                k += 1;
+            }
+            p.0.push(k);
+        }
+        p
+    }
+
+    pub fn subterm_at(&self, p: &Position) -> Option<Word<V, O>> {
+        let i = self.index_at(p)?;
+        Some(Word {
+            syms: self.syms[i..self.subword_end(i)].to_vec(),
+        })
+    }
+
+    /// Return a copy of this word with the subword at position p replaced by w, or None if
+    /// there is no subword at p.
+    pub fn replace_at(&self, p: &Position, w: Word<V, O>) -> Option<Word<V, O>> {
+        Some(self.splice(self.index_at(p)?, &w))
+    }
+
+    /// Iterate over every subword with its position, in the order of syms: the word itself
+    /// first, then each argument's subwords in turn.
+    pub fn positions(&self) -> impl Iterator<Item = (Position, Word<V, O>)> + '_ {
+        let mut paths = Vec::with_capacity(self.syms.len());
+        let mut path = Vec::new();
+        // The number of arguments still to come of each operator on the path.
+        let mut remaining: Vec<usize> = Vec::new();
+        for s in self.syms.iter() {
+            paths.push(Position(path.clone()));
+            if s.arity() > 0 {
+                remaining.push(s.arity());
+                path.push(0);
+                continue;
+            }
+            while let Some(r) = remaining.last_mut() {
+                *r -= 1;
+                if *r > 0 {
+                    *path.last_mut().unwrap() += 1;
+                    break;
+                }
+                remaining.pop();
+                path.pop();
             }
-            // All other cases result in no possible unification. (Different operator, an operator
-            // in self when other is just a variable, or missing symbols.)
-            _ => None,
         }
+        paths.into_iter().zip(self.subterms().with_self())
     }
+
+    /*
+     * Return this word with the arguments of every commutative operator sorted by GroundKbo, so
+     * that words equal up to commuting arguments become identical. Display and hash the result
+     * when they should agree on such words.
+     */
+    pub fn sort_commutative(&self) -> Word<V, O> {
+        match self.syms.first() {
+            Some(Op(f)) => {
+                let mut args: Vec<Word<V, O>> = self
+                    .subwords()
+                    .map(|a| a.to_word().sort_commutative())
+                    .collect();
+                if f.is_commutative() {
+                    args.sort_by(|a, b| GroundKbo.cmp(a, b));
+                }
+                Word::op(f.clone(), &args)
+            }
+            _ => self.clone(),
+        }
+    }
+
+    pub fn is_well_formed(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    /// Check that the symbols form exactly one word, with every operator used at an arity it
+    /// allows, and say where they don't.
+    pub fn validate(&self) -> Result<(), WordError> {
+        if self.syms.is_empty() {
+            return Err(WordError::Empty);
+        }
+        // The index of each operator still waiting for arguments, and how many it's waiting for.
+        let mut pending: Vec<(usize, usize)> = Vec::new();
+        for (i, s) in self.syms.iter().enumerate() {
+            if let Op(f) = s {
+                let n = f.arity();
+                if n < f.min_arity() || f.max_arity().is_some_and(|max| n > max) {
+                    return Err(WordError::BadArity { index: i, arity: n });
+                }
+            }
+            // Every symbol must be needed as an argument of an earlier one, or be the root.
+            if i > 0 && pending.is_empty() {
+                return Err(WordError::Trailing(i));
+            }
+            if let Some((_, k)) = pending.last_mut() {
+                *k -= 1;
+            }
+            while pending.last().is_some_and(|&(_, k)| k == 0) {
+                pending.pop();
+            }
+            if s.arity() > 0 {
+                pending.push((i, s.arity()));
+            }
+        }
+        match pending.last() {
+            Some(&(index, missing)) => Err(WordError::MissingArguments { index, missing }),
+            None => Ok(()),
+        }
+    }
+
+    pub fn subwords(&self) -> Subwords<'_, V, O> {
+        self.view().subwords()
+    }
+
+    /// Iterate over every proper subword at every depth, in the order of syms.
+    pub fn subterms(&self) -> Subterms<'_, V, O> {
+        Subterms { word: self, i: 1 }
+    }
+
+    pub fn subst(&self, vars: &BTreeMap<V, Word<V, O>>) -> Word<V, O> {
+        self.view().subst(vars)
+    }
+
+    pub fn subst_lazy<'a>(&'a self, vars: &'a BTreeMap<V, Word<V, O>>) -> SubstWord<'a, V, O> {
+        self.view().subst_lazy(vars)
+    }
+
+    /*
+     * Compute the substitutions of the variables in this word such that it is equal to another
+     * word, or return None if this is not possible.
+     */
+    pub fn unify(&self, other: &Word<V, O>) -> Option<BTreeMap<V, Word<V, O>>> {
+        self.view().unify(other.view())
+    }
+
+    /*
+     * Compute the most general unifier of this word and another: substitutions of the variables
+     * in both words such that they become equal. Unlike unify, variables on either side may be
+     * bound, so the two words should not share variables unless that is intended.
+     */
+    pub fn mgu(&self, other: &Word<V, O>) -> Option<BTreeMap<V, Word<V, O>>> {
+        let mut vmap = BTreeMap::new();
+        if !mgu_into(self.view(), other.view(), &mut vmap) {
+            return None;
+        }
+        // Resolve the triangular unifier once, so a single subst applies it fully.
+        Some(
+            vmap.iter()
+                .map(|(v, w)| (v.clone(), w.subst_lazy(&vmap).to_word()))
+                .collect(),
+        )
+    }
+}
+
+/*
+ * Extend vmap so that it also unifies s and t, both seen through vmap. vmap is kept triangular: a
+ * binding may mention variables bound after it, so binding a variable copies only the word it's
+ * bound to, rather than applying vmap to both words at every step and the new binding to every
+ * value.
+ */
+fn mgu_into<V: Variable, O: Operator>(
+    s: WordRef<'_, V, O>,
+    t: WordRef<'_, V, O>,
+    vmap: &mut BTreeMap<V, Word<V, O>>,
+) -> bool {
+    // A bound variable at the head is replaced by its binding, which has to be copied out of vmap
+    // before vmap can grow.
+    let resolve = |w: WordRef<'_, V, O>, vmap: &BTreeMap<V, Word<V, O>>| {
+        w.syms
+            .first()
+            .and_then(Symbol::var)
+            .filter(|v| vmap.contains_key(v))
+            .map(|_| w.subst_lazy(vmap).resolve().word.to_word())
+    };
+    let (rs, rt) = (resolve(s, vmap), resolve(t, vmap));
+    let s = rs.as_ref().map_or(s, Word::view);
+    let t = rt.as_ref().map_or(t, Word::view);
+    let (v, w) = match (s.syms.first(), t.syms.first()) {
+        (Some(Var(u)), Some(Var(v))) if u == v => return true,
+        (Some(Var(v)), Some(_)) => (v, t),
+        (Some(_), Some(Var(v))) => (v, s),
+        (Some(Op(f)), Some(Op(g))) => {
+            return f == g
+                && s.subwords()
+                    .zip(t.subwords())
+                    .all(|(a, b)| mgu_into(a, b, vmap));
+        }
+        _ => return false,
+    };
+    if w.subst_lazy(vmap).contains_var(v) {
+        // Occurs check: v can't be bound to a word that contains it.
+        return false;
+    }
+    vmap.insert(v.clone(), w.to_word());
+    true
 }
 
 pub fn print_subs<V: Variable + Display, O: Operator>(subs: &BTreeMap<V, Word<V, O>>)
@@ -194,14 +902,37 @@ where
     }
 }
 
+/*
+ * The operators and variables that occur in a set of axioms. This is the part of the signature
+ * that completion can ever see, so it's what a precedence or weight assignment has to cover, and
+ * anything else in a signature can be dropped. Variable-arity operators appear once for each
+ * arity they're used at.
+ */
+pub fn used_signature<V: Variable, O: Operator>(
+    axioms: &[Axiom<V, O>],
+) -> (BTreeSet<O>, BTreeSet<V>) {
+    let mut ops = BTreeSet::new();
+    let mut vars = BTreeSet::new();
+    for s in axioms
+        .iter()
+        .flat_map(|a| a.left.syms.iter().chain(a.right.syms.iter()))
+    {
+        match s {
+            Var(v) => vars.insert(v.clone()),
+            Op(f) => ops.insert(f.clone()),
+        };
+    }
+    (ops, vars)
+}
+
 pub struct Subwords<'a, V: Variable, O: Operator> {
-    syms: &'a Vec<Symbol<V, O>>,
+    syms: &'a [Symbol<V, O>],
     i: usize,
     nargs: usize,
 }
 
 impl<'a, V: Variable, O: Operator> Iterator for Subwords<'a, V, O> {
-    type Item = Word<V, O>;
+    type Item = WordRef<'a, V, O>;
     fn next(&mut self) -> Option<Self::Item> {
         if self.nargs > 0 {
             self.nargs -= 1;
@@ -212,86 +943,62 @@ impl<'a, V: Variable, O: Operator> Iterator for Subwords<'a, V, O> {
                 nsyms += self.syms.get(self.i).map_or(0, Symbol::arity);
                 self.i += 1;
             }
-            self.syms.get(swstart..self.i).map(|subsyms| Word {
-                syms: Vec::from(subsyms),
-            })
+            self.syms.get(swstart..self.i).map(|syms| WordRef { syms })
         } else {
             None
         }
     }
 }
 
+pub struct Subterms<'a, V: Variable, O: Operator> {
+    word: &'a Word<V, O>,
+    i: usize,
+}
+
+impl<V: Variable, O: Operator> Subterms<'_, V, O> {
+    /// Yield the word itself before its proper subwords.
+    pub fn with_self(mut self) -> Self {
+        self.i = 0;
+        self
+    }
+}
+
+impl<'a, V: Variable, O: Operator> Iterator for Subterms<'a, V, O> {
+    type Item = Word<V, O>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let i = self.i;
+        if i >= self.word.syms.len() {
+            return None;
+        }
+        self.i += 1;
+        Some(Word {
+            syms: self.word.syms[i..self.word.subword_end(i)].to_vec(),
+        })
+    }
+}
+
+/*
+ * The Knuth-Bendix ordering only finds two words equal when they are the same symbol for symbol,
+ * so equality compares the symbols directly, and hashing them is consistent with it.
+ */
 impl<V: Variable, O: Operator> PartialEq for Word<V, O> {
     fn eq(&self, other: &Self) -> bool {
-        self.partial_cmp(other) == Some(Ordering::Equal)
+        self.syms == other.syms
+    }
+}
+
+impl<V: Variable, O: Operator> Eq for Word<V, O> {}
+
+impl<V: Variable + Hash, O: Operator + Hash> Hash for Word<V, O> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.syms.hash(state);
     }
 }
 
 impl<V: Variable, O: Operator> PartialOrd for Word<V, O> {
+    /// The Knuth-Bendix ordering with the operators' own weights and precedence; see kbo.
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        // Case 1
-        // w(alpha) > w(beta) and n_of_var(vi, alpha) >= n_of_var(vi, beta) for all vi
-        // Each variable must occur at least as often in alpha as in beta.
-        let sw = self.weight();
-        let ow = other.weight();
-        let vars: BTreeSet<V> = self.vars().union(&other.vars()).cloned().collect();
-        if sw > ow {
-            for v in vars.iter() {
-                if self.n_of_var(v) < other.n_of_var(v) {
-                    return None;
-                }
-            }
-            Some(Ordering::Greater)
-        // Case 2 from Knuth-Bendix
-        // w(alpha) == w(beta) and n_of_var(vi, alpha) == n_of_var(vi, beta) for all vi
-        // Each variable must occur exactly as often in alpha as in beta, otherwise equal
-        // weight words can't be compared.
-        } else if sw == ow {
-            for v in vars.iter() {
-                if self.n_of_var(v) != other.n_of_var(v) {
-                    return None;
-                }
-            }
-            match (self.syms.first(), other.syms.first()) {
-                // This covers the case where self is f^N x and other is x. Each word has exactly
-                // the same variables here, so if one side is just a variable, then the other side
-                // also only has one of that same variable. They also have the same weight, which
-                // means that there are no operators which have positive weight, otherwise the only
-                // side with any operators would have larger weight. Operators with arity 2 or more
-                // may have zero weight, but this would ultimately require additional variables or
-                // nullary operators on one side, which would contribute to a larger weight, and so
-                // can't happen here. Therefore the only operator in play here is a unary operator
-                // of zero weight, and the ordering defines this to mean that the one with an
-                // operator is greater than the one without.
-                (Some(Op(_)), Some(Var(_))) => Some(Ordering::Greater),
-                (Some(Var(_)), Some(Op(_))) => Some(Ordering::Less),
-
-                // We already know these are the same variable from comparing n_of_var(v) for all
-                // variables appearing in either word. If they are different variables then
-                // None is returned in that loop.
-                (Some(Var(_)), Some(Var(_))) => Some(Ordering::Equal),
-
-                (Some(Op(f)), Some(Op(g))) => {
-                    if f == g {
-                        // If the operators are the same, must compare subwords lexicographically.
-                        self.subwords().partial_cmp(other.subwords())
-                    } else {
-                        // Otherwise compare the operators directly.
-                        Some(f.cmp(g))
-                    }
-                }
-                // If either syms is empty. Shouldn't happen.
-                _ => None,
-            }
-        // Case 1 but in the opposite direction.
-        } else {
-            for v in vars.iter() {
-                if self.n_of_var(v) > other.n_of_var(v) {
-                    return None;
-                }
-            }
-            Some(Ordering::Less)
-        }
+        kbo::compare(self, other, &KboParams::default())
     }
 }
 
@@ -316,10 +1023,10 @@ pub fn critical_term<V: Variable, O: Operator>(
             if let Some(Var(_)) = ts.syms.first() {
                 continue;
             }
-            if let Some(vmap) = ts.unify(u) {
+            if let Some(vmap) = ts.unify(u.view()) {
                 let ct = t.subst(&vmap);
                 return Some(ct);
-            } else if let Some(vmap) = u.unify(&ts) {
+            } else if let Some(vmap) = u.view().unify(ts) {
                 let ct = u.subst(&vmap);
                 return Some(ct);
             }
@@ -329,10 +1036,10 @@ pub fn critical_term<V: Variable, O: Operator>(
             if let Some(Var(_)) = us.syms.first() {
                 continue;
             }
-            if let Some(vmap) = us.unify(t) {
+            if let Some(vmap) = us.unify(t.view()) {
                 let ct = u.subst(&vmap);
                 return Some(ct);
-            } else if let Some(vmap) = t.unify(&us) {
+            } else if let Some(vmap) = t.view().unify(us) {
                 let ct = t.subst(&vmap);
                 return Some(ct);
             }
@@ -341,37 +1048,316 @@ pub fn critical_term<V: Variable, O: Operator>(
     }
 }
 
-pub fn knuth_bendix<V: Variable, O: Operator>(
-    axioms: &Vec<Axiom<V, O>>,
-) -> Option<Vec<Rule<V, O>>> {
-    let mut axioms: Vec<Axiom<V, O>> = axioms.clone();
-    let mut rules = Vec::new();
-    while let Some(axiom) = axioms.pop() {
-        // apply all rules to each side of axiom
-        //
-        // if axiom is x = x, continue
-        //
-        // flip axiom based on reduction ordering and add it to rules
-        // if the two sides of the axiom aren't comparable, return None
-        //
-        // superpose new rule's LHS onto all LHS's (including itself)
-        // introduce newly found critical pairs as axioms
-        //
-        // TODO: termination condition for divergence?
-        rules.push(Rule {
-            left: axiom.left,
-            right: axiom.right,
-        });
-    }
-    Some(rules)
-}
+// TODO: implement common-subterm search
 
-/*
- * Knuth-Bendix algorithm:
- *
- * For all pairs of reductions (a -> b, c -> d) in R:
- * check...
- *
- */
+#[cfg(test)]
+mod tests {
+    use crate::word::{self, Operator};
 
-// TODO: implement common-subterm search
+    // A signature with an n-ary tuple operator, carrying the arity of each occurrence.
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    enum Tup {
+        Unit,
+        Tuple(usize),
+    }
+    use Tup::*;
+
+    impl Operator for Tup {
+        fn min_weight() -> u64 {
+            1
+        }
+
+        fn arity(&self) -> usize {
+            match self {
+                Unit => 0,
+                Tuple(n) => *n,
+            }
+        }
+
+        fn weight(&self) -> u64 {
+            1
+        }
+
+        fn min_arity(&self) -> usize {
+            match self {
+                Unit => 0,
+                Tuple(_) => 2,
+            }
+        }
+
+        fn max_arity(&self) -> Option<usize> {
+            match self {
+                Unit => Some(0),
+                Tuple(_) => None,
+            }
+        }
+
+        fn with_arity(&self, n: usize) -> Option<Tup> {
+            match self {
+                Unit if n == 0 => Some(Unit),
+                Tuple(_) if n >= 2 => Some(Tuple(n)),
+                _ => None,
+            }
+        }
+    }
+
+    type Word = word::Word<String, Tup>;
+
+    #[test]
+    fn variadic() {
+        let x = Word::var("x");
+        let y = Word::var("y");
+        let unit = Word::op(Unit, &[]);
+        let t3 = Word::op(Tuple(0), &[x.clone(), unit.clone(), y.clone()]);
+        let t2 = Word::op(Tuple(0), &[t3.clone(), x.clone()]);
+        assert_eq!(t3.syms[0], word::Op(Tuple(3)));
+        assert!(t3.is_well_formed());
+        assert!(t2.is_well_formed());
+        assert_eq!(t2.subwords().collect::<Vec<_>>(), vec![t3.view(), x.view()]);
+        assert_eq!(t3.subwords().count(), 3);
+        assert_eq!(Word::op_owned(Tuple(0), vec![t3.clone(), x.clone()]), t2);
+        assert_eq!(
+            Word::from_sym(word::Op(Tuple(2)))
+                .apply(t3.clone())
+                .apply(x.clone()),
+            t2
+        );
+
+        // Tuples are at least pairs, and a tuple of a given arity only matches the same arity.
+        assert!(!Word::op(Tuple(0), std::slice::from_ref(&x)).is_well_formed());
+        let pat = Word::op(Tuple(0), &[Word::var("a"), Word::var("b")]);
+        assert!(pat.unify(&t2).is_some());
+        assert!(pat.unify(&t3).is_none());
+    }
+
+    #[test]
+    fn well_formed() {
+        let x = Word::var("x");
+        let mut w = Word::op(Tuple(0), &[x.clone(), x.clone()]);
+        assert!(w.is_well_formed());
+        w.syms.push(word::Var("y".to_string()));
+        assert!(!w.is_well_formed());
+        assert_eq!(w.validate(), Err(word::WordError::Trailing(3)));
+        w.syms.truncate(2);
+        assert!(!w.is_well_formed());
+        assert_eq!(
+            w.validate(),
+            Err(word::WordError::MissingArguments {
+                index: 0,
+                missing: 1
+            })
+        );
+
+        let u = Word::op(Tuple(0), &[x.clone(), w.clone()]);
+        assert_eq!(
+            u.validate(),
+            Err(word::WordError::MissingArguments {
+                index: 2,
+                missing: 1
+            })
+        );
+        let one = Word::op(Tuple(0), std::slice::from_ref(&x));
+        assert_eq!(
+            one.validate(),
+            Err(word::WordError::BadArity { index: 0, arity: 0 })
+        );
+        assert_eq!(
+            Word { syms: vec![] }.validate(),
+            Err(word::WordError::Empty)
+        );
+        assert_eq!(
+            word::WordError::Trailing(3).to_string(),
+            "trailing symbols from index 3"
+        );
+    }
+
+    #[test]
+    fn positions() {
+        let x = Word::var("x");
+        let y = Word::var("y");
+        let unit = Word::op(Unit, &[]);
+        let inner = Word::op(Tuple(0), &[x.clone(), unit.clone(), y.clone()]);
+        let w = Word::op(Tuple(0), &[inner.clone(), x.clone()]);
+        let p = |path: &[usize]| word::Position(path.to_vec());
+
+        assert_eq!(w.subterm_at(&p(&[])), Some(w.clone()));
+        assert_eq!(w.subterm_at(&p(&[0])), Some(inner.clone()));
+        assert_eq!(w.subterm_at(&p(&[0, 2])), Some(y.clone()));
+        assert_eq!(w.subterm_at(&p(&[1])), Some(x.clone()));
+        assert_eq!(w.subterm_at(&p(&[2])), None);
+        assert_eq!(w.subterm_at(&p(&[1, 0])), None);
+
+        let v = w.replace_at(&p(&[0, 1]), y.clone()).unwrap();
+        assert_eq!(
+            v,
+            Word::op(
+                Tuple(0),
+                &[Word::op(Tuple(0), &[x.clone(), y.clone(), y.clone()]), x]
+            )
+        );
+        assert!(w.replace_at(&p(&[0, 3]), unit).is_none());
+
+        let all: Vec<_> = w.positions().collect();
+        assert_eq!(all.len(), w.syms.len());
+        let paths: Vec<String> = all.iter().map(|(p, _)| p.to_string()).collect();
+        assert_eq!(paths, ["ε", "0", "0.0", "0.1", "0.2", "1"]);
+        for (p, sub) in all {
+            assert_eq!(w.subterm_at(&p), Some(sub));
+        }
+    }
+
+    #[test]
+    fn subterms() {
+        let x = Word::var("x");
+        let unit = Word::op(Unit, &[]);
+        let pair = Word::op(Tuple(0), &[x.clone(), unit.clone()]);
+        let w = Word::op(Tuple(0), &[pair.clone(), x.clone(), pair.clone()]);
+        let subs: Vec<_> = w.subterms().collect();
+        assert_eq!(
+            subs,
+            [
+                pair.clone(),
+                x.clone(),
+                unit.clone(),
+                x.clone(),
+                pair,
+                x.clone(),
+                unit
+            ]
+        );
+        assert_eq!(w.subterms().with_self().next(), Some(w.clone()));
+        assert_eq!(w.subterms().with_self().count(), w.syms.len());
+        assert_eq!(x.subterms().count(), 0);
+    }
+
+    #[test]
+    fn size_and_depth() {
+        let x = Word::var("x");
+        let unit = Word::op(Unit, &[]);
+        let pair = Word::op(Tuple(0), &[x.clone(), unit.clone()]);
+        let w = Word::op(Tuple(0), &[unit.clone(), pair.clone(), x.clone()]);
+        assert_eq!((x.size(), x.depth()), (1, 0));
+        assert_eq!((pair.size(), pair.depth()), (3, 1));
+        assert_eq!((w.size(), w.depth()), (6, 2));
+        assert_eq!(w.op_count(&Unit), 2);
+        assert_eq!(w.op_count(&Tuple(3)), 1);
+        assert_eq!(w.op_count(&Tuple(2)), 1);
+        assert_eq!(w.op_count(&Tuple(4)), 0);
+        for (p, sub) in w.positions() {
+            assert!(p.0.len() + sub.depth() <= w.depth());
+        }
+    }
+
+    #[test]
+    fn canonicalize_vars() {
+        let (x, y, z) = (Word::var("x"), Word::var("y"), Word::var("z"));
+        let w = Word::op(Tuple(0), &[y.clone(), x.clone(), y.clone()]);
+        let v = Word::op(Tuple(0), &[z.clone(), y.clone(), z.clone()]);
+        let c = w.canonicalize_vars();
+        let names: Vec<_> = c.syms.iter().filter_map(|s| s.var().cloned()).collect();
+        assert_eq!(names, ["x0", "x1", "x0"]);
+        assert!(w.is_variant(&v));
+        assert!(!w.is_variant(&Word::op(Tuple(0), &[y.clone(), y.clone(), x.clone()])));
+        assert_eq!(c.canonicalize_vars(), c);
+    }
+
+    #[test]
+    fn fresh_vars() {
+        let w = Word::op(
+            Tuple(0),
+            &[Word::var("x1"), Word::var("y"), Word::var("x2")],
+        );
+        let mut gen = word::FreshVars::new();
+        gen.avoid_word(&w);
+        gen.avoid("x3".to_string());
+        let fresh: Vec<String> = (0..3).map(|_| word::VarGen::fresh(&mut gen)).collect();
+        assert_eq!(fresh, ["x0", "x4", "x5"]);
+    }
+
+    #[test]
+    fn map_vars_and_ops() {
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        enum Ext {
+            Base(Tup),
+            Skolem(usize),
+        }
+
+        impl Operator for Ext {
+            fn min_weight() -> u64 {
+                1
+            }
+
+            fn arity(&self) -> usize {
+                match self {
+                    Ext::Base(f) => f.arity(),
+                    Ext::Skolem(_) => 0,
+                }
+            }
+
+            fn weight(&self) -> u64 {
+                1
+            }
+        }
+
+        let w = Word::op(
+            Tuple(0),
+            &[Word::var("x"), Word::op(Unit, &[]), Word::var("y")],
+        );
+        let renamed = w.map_vars(|v| v.to_uppercase());
+        assert_eq!(renamed.vars().into_iter().collect::<Vec<_>>(), ["X", "Y"]);
+        let ext = w.map_ops(|f| Ext::Base(f.clone()));
+        assert!(ext.is_well_formed());
+        assert_eq!(ext.syms[0], word::Op(Ext::Base(Tuple(3))));
+        let c = word::Word::<String, Ext>::op(Ext::Skolem(0), &[]);
+        let vmap = std::collections::BTreeMap::from([("x".to_string(), c.clone())]);
+        assert_eq!(ext.subst(&vmap).syms[1], c.syms[0]);
+    }
+
+    #[test]
+    fn fold() {
+        let w = Word::op(
+            Tuple(0),
+            &[
+                Word::var("x"),
+                Word::op(Unit, &[]),
+                Word::op(Tuple(0), &[Word::var("y"), Word::var("z")]),
+            ],
+        );
+        let shown = w.fold(
+            |v| v.clone(),
+            |f, args| match f {
+                Unit => "()".to_string(),
+                Tuple(_) => format!("({})", args.join(", ")),
+            },
+        );
+        assert_eq!(shown, "(x, (), (y, z))");
+        assert_eq!(
+            w.fold(|_| 1, |_, args| 1 + args.iter().sum::<usize>()),
+            w.size()
+        );
+    }
+
+    #[test]
+    fn used_signature() {
+        let x = Word::var("x");
+        let y = Word::var("y");
+        let unit = Word::op(Unit, &[]);
+        let axioms = [
+            crate::Axiom {
+                left: Word::op(Tuple(0), &[x.clone(), unit.clone()]),
+                right: x.clone(),
+            },
+            crate::Axiom {
+                left: Word::op(Tuple(0), &[x.clone(), y.clone(), unit.clone()]),
+                right: Word::op(Tuple(0), &[x.clone(), y.clone()]),
+            },
+        ];
+        let (ops, vars) = word::used_signature(&axioms);
+        assert_eq!(
+            ops.into_iter().collect::<Vec<_>>(),
+            [Unit, Tuple(2), Tuple(3)]
+        );
+        assert_eq!(vars.into_iter().collect::<Vec<_>>(), ["x", "y"]);
+        assert!(word::used_signature::<String, Tup>(&[]).0.is_empty());
+    }
+}
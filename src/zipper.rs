@@ -0,0 +1,137 @@
+use crate::word::{Operator, Position, Symbol, Variable, Word};
+
+/*
+ * A cursor into a word, for editing it in place. The focus is a subword, which can be moved down
+ * into an argument, across to the next argument, or back up, and replaced. Since a subword is a
+ * contiguous run of syms, replacing one only shifts the symbols after it, and moving around only
+ * counts off arguments, so no step copies the whole word.
+ */
+#[derive(Clone, Debug)]
+pub struct WordZipper<V: Variable, O: Operator> {
+    word: Word<V, O>,
+    // The index in syms where the focus starts.
+    focus: usize,
+    // For each operator above the focus, its index in syms and which of its arguments holds the
+    // focus.
+    path: Vec<(usize, usize)>,
+}
+
+impl<V: Variable, O: Operator> WordZipper<V, O> {
+    /// Start with the whole word in focus.
+    pub fn new(word: Word<V, O>) -> WordZipper<V, O> {
+        WordZipper {
+            word,
+            focus: 0,
+            path: Vec::new(),
+        }
+    }
+
+    pub fn focus(&self) -> &[Symbol<V, O>] {
+        &self.word.syms[self.focus..self.word.subword_end(self.focus)]
+    }
+
+    pub fn focus_word(&self) -> Word<V, O> {
+        Word {
+            syms: self.focus().to_vec(),
+        }
+    }
+
+    pub fn position(&self) -> Position {
+        Position(self.path.iter().map(|&(_, k)| k).collect())
+    }
+
+    /// Move to argument i of the focus, returning false if it has no such argument.
+    pub fn down(&mut self, i: usize) -> bool {
+        if i >= self.word.syms.get(self.focus).map_or(0, Symbol::arity) {
+            return false;
+        }
+        let mut j = self.focus + 1;
+        for _ in 0..i {
+            j = self.word.subword_end(j);
+        }
+        self.path.push((self.focus, i));
+        self.focus = j;
+        true
+    }
+
+    /// Move to the next argument of the focus's parent, returning false if it's the last one.
+    pub fn right(&mut self) -> bool {
+        match self.path.last_mut() {
+            Some((parent, k)) if *k + 1 < self.word.syms[*parent].arity() => {
+                *k += 1;
+                self.focus = self.word.subword_end(self.focus);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Move to the parent of the focus, returning false at the root.
+    pub fn up(&mut self) -> bool {
+        match self.path.pop() {
+            Some((parent, _)) => {
+                self.focus = parent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn top(&mut self) {
+        self.focus = 0;
+        self.path.clear();
+    }
+
+    /// Replace the focus with w, which becomes the new focus.
+    pub fn replace(&mut self, w: &Word<V, O>) {
+        let end = self.word.subword_end(self.focus);
+        self.word
+            .syms
+            .splice(self.focus..end, w.syms.iter().cloned());
+    }
+
+    /// The word as edited so far.
+    pub fn word(&self) -> &Word<V, O> {
+        &self.word
+    }
+
+    pub fn into_word(self) -> Word<V, O> {
+        self.word
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prod::{inv, one, var};
+
+    #[test]
+    fn navigate_and_replace() {
+        let (a, b, c) = (var("a"), var("b"), var("c"));
+        let w = (&a * &b) * inv(&c);
+        let mut z = WordZipper::new(w.clone());
+        assert!(z.down(0));
+        assert!(z.down(1));
+        assert_eq!(z.focus_word(), b);
+        assert!(!z.right());
+        assert!(!z.down(0));
+        z.replace(&(&c * &c));
+        assert_eq!(z.focus_word(), &c * &c);
+        assert!(z.up());
+        assert!(z.right());
+        assert_eq!(z.position(), Position(vec![1]));
+        assert_eq!(z.focus_word(), inv(&c));
+        assert!(z.down(0));
+        z.replace(&one());
+        z.top();
+        assert!(!z.up());
+        assert_eq!(z.focus_word(), z.word().clone());
+
+        let expected = (&a * (&c * &c)) * inv(&one());
+        assert_eq!(z.into_word(), expected);
+        let mut z = WordZipper::new(w.clone());
+        assert!(!z.down(2));
+        assert!(z.down(1));
+        assert_eq!(w.subterm_at(&z.position()), Some(z.focus_word()));
+    }
+}
@@ -0,0 +1,308 @@
+/*
+ * The kb binary, run on the specs and on the rule systems in tests/data, checking what each
+ * subcommand prints, as text and as JSON, and the status it exits with.
+ */
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// Run kb from the crate's root with the given arguments and standard input, returning its stdout,
+// stderr, and exit status.
+fn run(args: &[&str], input: &str) -> (String, String, i32) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_kb"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    (
+        String::from_utf8(out.stdout).unwrap(),
+        String::from_utf8(out.stderr).unwrap(),
+        out.status.code().unwrap(),
+    )
+}
+
+fn kb(args: &[&str]) -> (String, i32) {
+    let (out, _, status) = run(args, "");
+    (out, status)
+}
+
+// A path for kb to write to, unique to this test process.
+fn temp(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("kb-{}-{}", std::process::id(), name));
+    path.to_str().unwrap().to_string()
+}
+
+const GROUP_RULES: [&str; 10] = [
+    "e * x → x",
+    "i(x) * x → e",
+    "(x * y) * z → x * (y * z)",
+    "i(x0) * (x0 * z) → z",
+    "i(e) → e",
+    "x1 * i(x1) → e",
+    "x * e → x",
+    "i(i(x1)) → x1",
+    "x1 * (i(x1) * z) → z",
+    "i(x1 * x2) → i(x2) * i(x1)",
+];
+
+#[test]
+fn usage() {
+    let (out, err, status) = run(&[], "");
+    assert_eq!(status, 1);
+    assert!(out.is_empty());
+    assert!(err.starts_with("kb: usage: kb complete"));
+
+    let (out, status) = kb(&["frobnicate", "--json"]);
+    assert_eq!(status, 1);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert!(json["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("unknown command frobnicate"));
+}
+
+#[test]
+fn complete() {
+    let (out, status) = kb(&["complete", "specs/groups.eq"]);
+    assert_eq!(status, 0);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], "convergent, 10 rules");
+    assert!(lines[1].starts_with("187 equations, 174 critical pairs"));
+    let rules: Vec<&str> = lines[3..].iter().map(|l| l.trim()).collect();
+    assert_eq!(rules, GROUP_RULES);
+
+    let output = temp("groups.trs");
+    let (out, status) = kb(&["complete", "specs/groups.eq", "--output", &output]);
+    assert_eq!(status, 0);
+    assert_eq!(out.lines().count(), 3);
+    let trs = std::fs::read_to_string(&output).unwrap();
+    std::fs::remove_file(&output).unwrap();
+    assert!(trs.starts_with("(VAR x x0 x1 x2 y z)\n(RULES\n  *(e, x) -> x\n"));
+    assert_eq!(trs.matches(" -> ").count(), 10);
+
+    let (out, status) = kb(&["complete", "specs/groups.eq", "--max-steps", "5"]);
+    assert_eq!(status, 2);
+    assert!(out.starts_with("stopped after 5 steps with 4 rules\n"));
+
+    let (_, err, status) = run(&["complete", "specs/groups.eq", "--max-steps"], "");
+    assert_eq!(status, 1);
+    assert_eq!(err, "kb: --max-steps needs a number\n");
+}
+
+#[test]
+fn complete_json() {
+    let (out, status) = kb(&["complete", "specs/groups.eq", "--json"]);
+    assert_eq!(status, 0);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(json["status"], "convergent");
+    assert_eq!(json["n_rules"], 10);
+    assert_eq!(json["rules"], serde_json::json!(GROUP_RULES));
+    assert_eq!(json["stats"]["critical_pairs"], 174);
+    assert_eq!(json["properties"][0]["property"], "associative");
+    assert_eq!(json["output"], serde_json::Value::Null);
+
+    // The summary file has the object --json prints, alongside the usual output.
+    let summary = temp("summary.json");
+    let args = [
+        "complete",
+        "specs/groups.eq",
+        "--max-steps",
+        "5",
+        "--summary",
+        &summary,
+    ];
+    let (out, status) = kb(&args);
+    assert_eq!(status, 2);
+    assert!(out.starts_with("stopped after 5 steps"));
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&summary).unwrap()).unwrap();
+    std::fs::remove_file(&summary).unwrap();
+    assert_eq!(json["status"], "limit");
+    assert_eq!(json["n_rules"], 4);
+    assert_eq!(json["rules"][3], "i(x0) * (x0 * z) → z");
+}
+
+#[test]
+fn normalize() {
+    let (out, status) = kb(&["normalize", "tests/data/plus.trs", "plus(s(0), s(0))"]);
+    assert_eq!(status, 0);
+    assert_eq!(out, "s(s(0))\n");
+
+    let (out, status) = kb(&[
+        "normalize",
+        "tests/data/plus.trs",
+        "plus(s(0), y)",
+        "--steps",
+    ]);
+    assert_eq!(status, 0);
+    assert_eq!(
+        out,
+        "  plus(s(0), y)\n\
+         → s(plus(0, y))    by rule 1 (plus(s(x), y) → s(plus(x, y))) at ε\n\
+         → s(y)    by rule 0 (plus(0, y) → y) at 0\n"
+    );
+
+    let (out, status) = kb(&[
+        "normalize",
+        "tests/data/plus.trs",
+        "plus(s(0), y)",
+        "--json",
+    ]);
+    assert_eq!(status, 0);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(json["normal_form"], "s(y)");
+    assert_eq!(json["steps"][1]["rule"], 0);
+    assert_eq!(json["steps"][1]["position"], serde_json::json!([0]));
+
+    let (_, err, status) = run(&["normalize", "tests/data/plus.trs", "plus(0)"], "");
+    assert_eq!(status, 1);
+    assert!(err.starts_with("kb: plus(0): "));
+}
+
+#[test]
+fn prove() {
+    // Without a goal on the command line the spec's own is proved.
+    let (out, status) = kb(&["prove", "specs/groups.eq"]);
+    assert_eq!(status, 0);
+    assert!(out.starts_with("x * i(x) = e holds:\n"));
+
+    let (out, status) = kb(&["prove", "specs/groups.eq", "i(i(x)) = x"]);
+    assert_eq!(status, 0);
+    assert!(out.starts_with("i(i(x)) = x holds:\n"));
+
+    let (out, status) = kb(&[
+        "prove",
+        "specs/groups.eq",
+        "x * y = y * x",
+        "--max-steps",
+        "100",
+    ]);
+    assert_eq!(status, 2);
+    assert_eq!(out, "unknown: completion stopped after 100 steps\n");
+
+    // w isn't among the spec's vars, so the goal is rejected rather than read as a constant.
+    let (out, err, status) = run(&["prove", "specs/groups.eq", "x * w = w"], "");
+    assert_eq!(status, 1);
+    assert!(out.is_empty());
+    assert_eq!(err, "kb: x * w = w: w is not an operator or a variable\n");
+}
+
+#[test]
+fn prove_json() {
+    let (out, status) = kb(&["prove", "specs/groups.eq", "--json"]);
+    assert_eq!(status, 0);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(json["goal"], "x * i(x) = e");
+    assert_eq!(json["status"], "holds");
+    assert_eq!(
+        json["proof"]["left"].as_array().unwrap().last().unwrap()["word"],
+        "e"
+    );
+
+    let (out, status) = kb(&["prove", "specs/groups.eq", "x * w = w", "--json"]);
+    assert_eq!(status, 1);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(
+        json["error"],
+        "x * w = w: w is not an operator or a variable"
+    );
+}
+
+#[test]
+fn repl() {
+    let input = "load specs/groups.eq\ncomplete\nnf i(x * y) * x\nrules\nquit\n";
+    let (out, _, status) = run(&["repl"], input);
+    assert_eq!(status, 0);
+    assert!(out.contains("3 operators, 3 axioms\n"));
+    assert!(out.contains("convergent, 10 rules\n"));
+    assert!(out.contains("i(y)\n"));
+    assert!(out.contains("i(x1 * x2) → i(x2) * i(x1)"));
+
+    // Each command is answered with one line of JSON.
+    let input = "load specs/groups.eq\ncomplete\nnf i(x * y) * x\n";
+    let (out, _, status) = run(&["repl", "--json"], input);
+    assert_eq!(status, 0);
+    let lines: Vec<serde_json::Value> = out
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["n_axioms"], 3);
+    assert_eq!(lines[1]["status"], "convergent");
+    assert_eq!(lines[2]["normal_form"], "i(y)");
+}
+
+#[test]
+fn bench() {
+    let (out, status) = kb(&["bench", "specs", "--timeout", "10s"]);
+    assert_eq!(status, 0);
+    let lines: Vec<&str> = out.lines().collect();
+    assert!(lines[0].starts_with("problem    result     rules"));
+    assert!(lines[1].starts_with("groups.eq  completed     10"));
+    assert_eq!(lines[2], "1 completed, 0 failed, 0 timed out, of 1");
+
+    let (out, status) = kb(&["bench", "specs", "--json"]);
+    assert_eq!(status, 0);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(json["completed"], 1);
+    assert_eq!(json["results"][0]["problem"], "groups.eq");
+    assert_eq!(json["results"][0]["rules"], 10);
+
+    let (_, err, status) = run(&["bench", "specs", "--timeout", "soon"], "");
+    assert_eq!(status, 1);
+    assert!(err.starts_with("kb: "));
+}
+
+#[test]
+fn check() {
+    let (out, status) = kb(&["check", "tests/data/plus.trs"]);
+    assert_eq!(status, 0);
+    assert_eq!(
+        out,
+        "2 rules\n\
+         left-linear: yes\n\
+         critical pairs: 0\n\
+         termination: yes, by KBO with precedence 0 < s < plus\n\
+         joinable: all\n\
+         convergent: confluent and terminating\n"
+    );
+
+    // f(x) → f(s(x)) doesn't terminate, but it's orthogonal.
+    let (out, status) = kb(&["check", "tests/data/loop.trs"]);
+    assert_eq!(status, 2);
+    assert!(out.contains("termination: unknown\n"));
+    assert!(out.ends_with("confluent, being orthogonal, but not known to terminate\n"));
+
+    let (out, status) = kb(&["check", "tests/data/peak.trs"]);
+    assert_eq!(status, 2);
+    assert!(out.contains("left-linear: no, rule 2 (eq(x, x) → true) repeats a variable\n"));
+    assert!(out.contains("critical pairs: 1\n"));
+    assert!(out.ends_with("not confluent\n"));
+}
+
+#[test]
+fn check_json() {
+    let (out, status) = kb(&["check", "tests/data/peak.trs", "--json"]);
+    assert_eq!(status, 2);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(json["left_linear"], false);
+    assert_eq!(json["nonlinear_rule"], 2);
+    assert_eq!(json["critical_pairs"], 1);
+    assert_eq!(json["termination"]["method"], "kbo");
+    assert_eq!(json["counterexamples"][0]["peak"], "f(a)");
+    assert_eq!(json["status"], "not_confluent");
+
+    let (out, _) = kb(&["check", "tests/data/loop.trs", "--json"]);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(json["joinable"], serde_json::Value::Null);
+    assert_eq!(json["status"], "orthogonal");
+}